@@ -0,0 +1,418 @@
+//! self-service account deletion, data export and settings
+//!
+//! users ask for their account to be deleted with [`request_deletion`], it's only deleted once
+//! [`AuthConfig::account_deletion_grace_period`] has passed, and until then [`cancel_deletion`] undoes it.
+//!
+//! the deletion itself is done by [`complete_deletions`] (or [`complete_deletions_with_attachments`]
+//! when the storage plugin is enabled), which has to run periodically: with the tasks plugin, call
+//! `create_rust_app::tasks::account_deletions::schedule()` once at startup, otherwise run it from a cron job.
+//! it deletes everything the auth plugin stores about the user, and keeps an emptied `users` row
+//! so the ids referenced by the app's own tables stay valid.
+//!
+//! [`set_locale`] changes the language the user's emails are written in.
+
+use crate::auth::{
+    controller::{hash_password, verify_password},
+    error::AuthError,
+    permissions::UserRole,
+    utc_now, Auth, AuthConfig, FailedLogin, User, UserApiKey, UserApiKeyJson, UserLockout,
+    UserPasskey, UserPasskeyJson, UserPermission, UserResourcePermission, UserSession,
+    UserSessionJson, UserTotp, Utc, ID,
+};
+use crate::{Connection, Database};
+
+use diesel::{OptionalExtension, QueryResult};
+use serde::{Deserialize, Serialize};
+
+/// the `record_type` of the storage plugin's attachments which belong to a user (whose id is the `record_id`),
+/// they are detached by [`complete_deletions_with_attachments`] and listed by [`export`]
+pub const ATTACHMENT_RECORD_TYPE: &str = "users";
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the .../account/deletion endpoint
+pub struct DeleteAccountInput {
+    /// the user's current password, so a stolen access token isn't enough to delete the account
+    password: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the .../account/locale endpoint
+pub struct SetLocaleInput {
+    /// the language tag (like `fr-CA`) the user's emails should be written in, `None` for the default templates
+    #[serde(default)]
+    locale: Option<String>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// when an account will be deleted, see [`request_deletion`]
+pub struct AccountDeletionResponse {
+    pub deletion_scheduled_at: Utc,
+}
+
+#[derive(Debug)]
+/// why an account could not be deleted, see [`complete_deletion`]
+pub enum DeletionError {
+    /// a query failed
+    Query(diesel::result::Error),
+    /// the random password replacing the user's could not be hashed
+    Password(AuthError),
+    /// the user's attachments could not be detached
+    #[cfg(feature = "plugin_storage")]
+    Attachments(String),
+}
+
+impl std::fmt::Display for DeletionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Query(error) => write!(f, "a query failed: {error}"),
+            Self::Password(error) => write!(f, "could not hash the password: {error}"),
+            #[cfg(feature = "plugin_storage")]
+            Self::Attachments(error) => write!(f, "could not detach the attachments: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for DeletionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Query(error) => Some(error),
+            Self::Password(error) => Some(error),
+            #[cfg(feature = "plugin_storage")]
+            Self::Attachments(_) => None,
+        }
+    }
+}
+
+impl From<diesel::result::Error> for DeletionError {
+    fn from(error: diesel::result::Error) -> Self {
+        Self::Query(error)
+    }
+}
+
+#[derive(Debug, Default)]
+/// the accounts [`complete_deletions`] deleted, and the ones it could not delete
+pub struct CompletedDeletions {
+    pub deleted: Vec<ID>,
+    pub failed: Vec<(ID, DeletionError)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// the `users` entry of an [`AccountExport`], without the password hash
+pub struct AccountJson {
+    pub id: ID,
+    pub email: String,
+    pub activated: bool,
+    pub deletion_scheduled_at: Option<Utc>,
+    pub locale: Option<String>,
+    pub created_at: Utc,
+}
+
+#[cfg(feature = "plugin_storage")]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// an attachment of an [`AccountExport`], without the file itself
+pub struct AttachmentJson {
+    pub id: ID,
+    pub name: String,
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub byte_size: i64,
+    pub created_at: Utc,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// everything the library stores about a user, see [`export`]
+///
+/// secrets (password hash, tokens, keys) are left out
+pub struct AccountExport {
+    pub account: AccountJson,
+    /// roles assigned directly to the user
+    pub roles: Vec<String>,
+    /// permissions granted directly to the user
+    pub permissions: Vec<String>,
+    pub resource_permissions: Vec<UserResourcePermission>,
+    pub sessions: Vec<UserSessionJson>,
+    pub two_factor_enabled: bool,
+    pub passkeys: Vec<UserPasskeyJson>,
+    pub api_keys: Vec<UserApiKeyJson>,
+    pub failed_logins: Vec<FailedLogin>,
+    pub lockout: Option<UserLockout>,
+    #[cfg(feature = "plugin_auth-oidc")]
+    pub oidc_links: Vec<crate::auth::oidc::UserOauth2LinkJson>,
+    /// attachments with the [`ATTACHMENT_RECORD_TYPE`] record type
+    #[cfg(feature = "plugin_storage")]
+    pub attachments: Vec<AttachmentJson>,
+}
+
+/// /account/deletion
+///
+/// schedules the deletion of the account of the User associated with [`auth`](`Auth`),
+/// [`auth_config.account_deletion_grace_period`](`AuthConfig::account_deletion_grace_period`) from now
+///
+/// asking again doesn't postpone an already scheduled deletion
+///
+/// # Errors
+/// - 401: [`AuthError::InvalidCredentials`]
+/// - 500: [`AuthError::CouldNotFindUser`]
+/// - 500: [`AuthError::CouldNotScheduleDeletion`]
+/// - 500: [`AuthError::Internal`]
+pub fn request_deletion(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    item: &DeleteAccountInput,
+) -> Result<AccountDeletionResponse, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let Ok(user) = User::read(&mut db, auth.user_id) else {
+        return Err(AuthError::CouldNotFindUser);
+    };
+
+    if !verify_password(&user.hash_password, &item.password)? {
+        return Err(AuthError::InvalidCredentials);
+    }
+
+    if let Some(deletion_scheduled_at) = user.deletion_scheduled_at {
+        return Ok(AccountDeletionResponse {
+            deletion_scheduled_at,
+        });
+    }
+
+    let user = User::schedule_deletion(
+        &mut db,
+        user.id,
+        Some(utc_now() + auth_config.account_deletion_grace_period),
+    )
+    .map_err(|_| AuthError::CouldNotScheduleDeletion)?;
+
+    user.deletion_scheduled_at
+        .map(|deletion_scheduled_at| AccountDeletionResponse {
+            deletion_scheduled_at,
+        })
+        .ok_or(AuthError::CouldNotScheduleDeletion)
+}
+
+/// /account/deletion
+///
+/// cancels the scheduled deletion of the account of the User associated with [`auth`](`Auth`)
+///
+/// # Errors
+/// - 400: [`AuthError::DeletionNotScheduled`]
+/// - 500: [`AuthError::CouldNotCancelDeletion`]
+pub fn cancel_deletion(db: &Database, auth: &Auth) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    match User::read(&mut db, auth.user_id) {
+        Ok(user) if user.deletion_scheduled_at.is_some() => {}
+        Ok(_) => return Err(AuthError::DeletionNotScheduled),
+        Err(_) => return Err(AuthError::CouldNotCancelDeletion),
+    }
+
+    User::schedule_deletion(&mut db, auth.user_id, None)
+        .map_err(|_| AuthError::CouldNotCancelDeletion)?;
+
+    Ok(())
+}
+
+/// /account/locale
+///
+/// sets (or clears) the locale the emails of the User associated with [`auth`](`Auth`) are rendered in,
+/// see [`Mailer::render`](`crate::Mailer::render`)
+///
+/// # Errors
+/// - 400: [`AuthError::InvalidLocale`]
+/// - 500: [`AuthError::CouldNotFindUser`]
+pub fn set_locale(db: &Database, auth: &Auth, item: &SetLocaleInput) -> Result<(), AuthError> {
+    if item
+        .locale
+        .as_deref()
+        .is_some_and(|locale| !crate::is_valid_locale(locale))
+    {
+        return Err(AuthError::InvalidLocale);
+    }
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    User::set_locale(&mut db, auth.user_id, item.locale.as_deref())
+        .map_err(|_| AuthError::CouldNotFindUser)?;
+
+    Ok(())
+}
+
+/// deletes the account of the User whose id is [`user_id`](`ID`) right away, see [`User::erase`]
+///
+/// the storage plugin's attachments aren't detached, see [`complete_deletions_with_attachments`]
+///
+/// # Errors
+/// * [`DeletionError::Password`] if the random password replacing the user's could not be hashed
+/// * [`DeletionError::Query`] if a query fails
+pub fn complete_deletion(db: &mut Connection, user_id: ID) -> Result<User, DeletionError> {
+    use rand::{distributions::Alphanumeric, Rng};
+
+    // nobody knows this password, so nobody can log in anymore
+    let password = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect::<String>();
+    let hash_password = hash_password(&password).map_err(DeletionError::Password)?;
+
+    Ok(User::erase(db, user_id, &hash_password)?)
+}
+
+/// deletes every account whose grace period is over
+///
+/// an account that could not be deleted doesn't stop the others from being deleted,
+/// it's listed in [`CompletedDeletions::failed`] and tried again on the next call
+///
+/// # Errors
+/// * [`diesel::result::Error`](`diesel::result::Error`) if the accounts to delete could not be read
+pub fn complete_deletions(db: &mut Connection) -> QueryResult<CompletedDeletions> {
+    let mut deletions = CompletedDeletions::default();
+
+    for user_id in User::read_all_due_for_deletion(db, utc_now())? {
+        match complete_deletion(db, user_id) {
+            Ok(_) => deletions.deleted.push(user_id),
+            Err(error) => deletions.failed.push((user_id, error)),
+        }
+    }
+
+    Ok(deletions)
+}
+
+/// like [`complete_deletions`], but also detaches the users' attachments (see [`ATTACHMENT_RECORD_TYPE`])
+///
+/// # Errors
+/// * [`diesel::result::Error`](`diesel::result::Error`) if the accounts to delete could not be read
+#[cfg(feature = "plugin_storage")]
+pub async fn complete_deletions_with_attachments(
+    db: &mut Connection,
+    storage: &crate::Storage,
+) -> QueryResult<CompletedDeletions> {
+    let mut deletions = CompletedDeletions::default();
+
+    for user_id in User::read_all_due_for_deletion(db, utc_now())? {
+        let deletion = match crate::Attachment::detach_all_for_record(
+            db,
+            storage,
+            ATTACHMENT_RECORD_TYPE.to_string(),
+            user_id,
+        )
+        .await
+        {
+            Ok(()) => complete_deletion(db, user_id),
+            Err(error) => Err(DeletionError::Attachments(error)),
+        };
+
+        match deletion {
+            Ok(_) => deletions.deleted.push(user_id),
+            Err(error) => deletions.failed.push((user_id, error)),
+        }
+    }
+
+    Ok(deletions)
+}
+
+/// /account/export
+///
+/// everything the library stores about the User associated with [`auth`](`Auth`), see [`AccountExport`]
+///
+/// # Errors
+/// - 500: [`AuthError::CouldNotExportAccount`]
+pub fn export(db: &Database, auth: &Auth) -> Result<AccountExport, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    read_export(&mut db, auth.user_id).map_err(|_| AuthError::CouldNotExportAccount)
+}
+
+fn read_export(db: &mut Connection, user_id: ID) -> QueryResult<AccountExport> {
+    let user = User::read(db, user_id)?;
+
+    Ok(AccountExport {
+        account: AccountJson {
+            id: user.id,
+            email: user.email,
+            activated: user.activated,
+            deletion_scheduled_at: user.deletion_scheduled_at,
+            locale: user.locale,
+            created_at: user.created_at,
+        },
+        roles: UserRole::read_all(db, user_id)?
+            .into_iter()
+            .map(|user_role| user_role.role)
+            .collect(),
+        permissions: UserPermission::read_all(db, user_id)?
+            .into_iter()
+            .map(|user_permission| user_permission.permission)
+            .collect(),
+        resource_permissions: UserResourcePermission::read_all(db, user_id)?,
+        sessions: UserSession::read_all_for_user(db, user_id)?
+            .iter()
+            .map(UserSessionJson::from)
+            .collect(),
+        two_factor_enabled: UserTotp::read(db, user_id)
+            .optional()?
+            .is_some_and(|totp| totp.confirmed),
+        passkeys: UserPasskey::read_all(db, user_id)?
+            .iter()
+            .map(UserPasskeyJson::from)
+            .collect(),
+        api_keys: UserApiKey::read_all(db, user_id)?
+            .iter()
+            .map(UserApiKeyJson::from)
+            .collect(),
+        failed_logins: FailedLogin::read_all_for_user(db, user_id)?,
+        lockout: UserLockout::read(db, user_id).optional()?,
+        #[cfg(feature = "plugin_auth-oidc")]
+        oidc_links: crate::auth::oidc::UserOauth2Link::read_all_linked(db, user_id)?
+            .iter()
+            .map(crate::auth::oidc::UserOauth2LinkJson::from)
+            .collect(),
+        #[cfg(feature = "plugin_storage")]
+        attachments: read_attachments(db, user_id)?,
+    })
+}
+
+#[cfg(feature = "plugin_storage")]
+fn read_attachments(db: &mut Connection, user_id: ID) -> QueryResult<Vec<AttachmentJson>> {
+    use crate::{Attachment, AttachmentBlob};
+
+    let attachments =
+        Attachment::find_all_for_record_any_name(db, ATTACHMENT_RECORD_TYPE.to_string(), user_id)?;
+    let blobs = AttachmentBlob::find_all_by_id(
+        db,
+        attachments
+            .iter()
+            .map(|attachment| attachment.blob_id)
+            .collect(),
+    )?;
+
+    Ok(attachments
+        .into_iter()
+        .filter_map(|attachment| {
+            let blob = blobs.iter().find(|blob| blob.id == attachment.blob_id)?;
+
+            Some(AttachmentJson {
+                id: attachment.id,
+                name: attachment.name,
+                file_name: blob.file_name.clone(),
+                content_type: blob.content_type.clone(),
+                byte_size: blob.byte_size,
+                created_at: attachment.created_at,
+            })
+        })
+        .collect())
+}
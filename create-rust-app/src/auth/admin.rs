@@ -0,0 +1,517 @@
+//! admin user-management
+//!
+//! lets users with [`AuthConfig::admin_role`] search users, (de)activate them,
+//! assign roles and permissions, and sign them out everywhere.
+//! every change is recorded in the `admin_audit_logs` table, see [`AdminAuditLog`]
+
+use crate::auth::{
+    error::AuthError, permissions::UserRole, AdminAuditLog, AdminAuditLogChangeset, Auth,
+    AuthConfig, PaginationParams, Permission, Role, User, UserChangeset, UserPermission,
+    UserSession, Utc, ID,
+};
+use crate::{Connection, Database};
+
+use diesel::Connection as _;
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::IntoParams))]
+/// query parameters for the admin user search
+pub struct UserSearchParams {
+    pub page: i64,
+    pub page_size: i64,
+    /// only return users whose email contains this (case-insensitive)
+    pub query: Option<String>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// a user, as seen by an admin
+pub struct AdminUserJson {
+    pub id: ID,
+    pub email: String,
+    pub activated: bool,
+    pub created_at: Utc,
+    /// roles assigned directly to the user (inherited roles are not included)
+    pub roles: Vec<String>,
+    /// permissions granted directly to the user (permissions granted through roles are not included)
+    pub permissions: Vec<String>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// a page of users matching an admin search
+pub struct AdminUserResponse {
+    pub users: Vec<AdminUserJson>,
+    pub num_pages: i64,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// a page of the admin audit trail, most recent first
+pub struct AdminAuditLogResponse {
+    pub entries: Vec<AdminAuditLog>,
+    pub num_pages: i64,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+pub struct ActivationInput {
+    pub activated: bool,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+pub struct RoleInput {
+    pub role: String,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+pub struct PermissionInput {
+    pub permission: String,
+}
+
+/// makes sure the admin endpoints are enabled and that the User associated with [`auth`](`Auth`) is an admin
+///
+/// # Errors
+/// - 404: [`AuthError::NotFound`] (the admin endpoints are disabled, see [`AuthConfig::admin_role`])
+/// - 403: [`AuthError::Forbidden`]
+fn authorize(auth_config: &AuthConfig, auth: &Auth) -> Result<(), AuthError> {
+    let Some(admin_role) = &auth_config.admin_role else {
+        return Err(AuthError::NotFound);
+    };
+
+    if !auth.has_role(admin_role) {
+        return Err(AuthError::Forbidden);
+    }
+
+    Ok(())
+}
+
+/// records an admin action in the audit trail
+fn audit(
+    db: &mut Connection,
+    auth: &Auth,
+    action: &str,
+    target_user_id: Option<ID>,
+    details: Option<String>,
+) -> Result<(), AuthError> {
+    AdminAuditLog::create(
+        db,
+        &AdminAuditLogChangeset {
+            admin_user_id: auth.user_id,
+            action: action.to_string(),
+            target_user_id,
+            details,
+        },
+    )
+    .map_err(|_| AuthError::CouldNotRecordAdminAction)?;
+
+    Ok(())
+}
+
+fn user_json(db: &mut Connection, user: User) -> Result<AdminUserJson, AuthError> {
+    let roles = UserRole::read_all(db, user.id).map_err(|_| AuthError::CouldNotFetchUser)?;
+    let permissions =
+        UserPermission::read_all(db, user.id).map_err(|_| AuthError::CouldNotFetchUser)?;
+
+    Ok(AdminUserJson {
+        id: user.id,
+        email: user.email,
+        activated: user.activated,
+        created_at: user.created_at,
+        roles: roles.into_iter().map(|r| r.role).collect(),
+        permissions: permissions.into_iter().map(|p| p.permission).collect(),
+    })
+}
+
+/// makes sure the pagination is valid, the database rejects negative offsets and limits
+fn check_pagination(pagination: &PaginationParams) -> Result<(), AuthError> {
+    if pagination.page < 0 || pagination.page_size < 1 {
+        return Err(AuthError::InvalidPagination);
+    }
+
+    Ok(())
+}
+
+fn read_user(db: &mut Connection, user_id: ID) -> Result<User, AuthError> {
+    match User::read(db, user_id) {
+        Ok(user) => Ok(user),
+        Err(diesel::result::Error::NotFound) => Err(AuthError::UserNotFound),
+        Err(_) => Err(AuthError::CouldNotFetchUser),
+    }
+}
+
+/// /admin/users
+///
+/// searches users by email, paginated according to [`params`](`UserSearchParams`)
+///
+/// # Errors
+/// - 400: [`AuthError::InvalidPagination`]
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 500: [`AuthError::CouldNotFetchUsers`]
+pub fn search_users(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    params: &UserSearchParams,
+) -> Result<AdminUserResponse, AuthError> {
+    authorize(auth_config, auth)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let query = params.query.as_deref().filter(|q| !q.is_empty());
+    let pagination = PaginationParams {
+        page: params.page,
+        page_size: params.page_size,
+    };
+    check_pagination(&pagination)?;
+
+    let users =
+        User::search(&mut db, query, &pagination).map_err(|_| AuthError::CouldNotFetchUsers)?;
+    let num_users =
+        User::count_search(&mut db, query).map_err(|_| AuthError::CouldNotFetchUsers)?;
+
+    let page_size = pagination
+        .page_size
+        .clamp(1, i64::from(PaginationParams::MAX_PAGE_SIZE));
+    let num_pages = (num_users / page_size) + i64::from(num_users % page_size != 0);
+
+    let users = users
+        .into_iter()
+        .map(|user| user_json(&mut db, user))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(AdminUserResponse { users, num_pages })
+}
+
+/// /admin/users/{id}
+///
+/// # Errors
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 404: [`AuthError::UserNotFound`]
+/// - 500: [`AuthError::CouldNotFetchUser`]
+pub fn get_user(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    user_id: ID,
+) -> Result<AdminUserJson, AuthError> {
+    authorize(auth_config, auth)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let user = read_user(&mut db, user_id)?;
+
+    user_json(&mut db, user)
+}
+
+/// /admin/users/{id}/activation
+///
+/// activates or deactivates the User whose id is [`user_id`](`ID`);
+/// deactivated users are also signed out of every session
+///
+/// # Errors
+/// - 400: [`AuthError::CannotDeactivateSelf`]
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 404: [`AuthError::UserNotFound`]
+/// - 500: [`AuthError::CouldNotUpdateUser`]
+pub fn set_activation(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    user_id: ID,
+    item: &ActivationInput,
+) -> Result<(), AuthError> {
+    authorize(auth_config, auth)?;
+
+    if !item.activated && user_id == auth.user_id {
+        return Err(AuthError::CannotDeactivateSelf);
+    }
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let user = read_user(&mut db, user_id)?;
+
+    db.transaction(|db| {
+        User::update(
+            db,
+            user_id,
+            &UserChangeset {
+                email: user.email,
+                hash_password: user.hash_password,
+                activated: item.activated,
+            },
+        )
+        .map_err(|_| AuthError::CouldNotUpdateUser)?;
+
+        if !item.activated {
+            UserSession::delete_all_for_user(db, user_id)
+                .map_err(|_| AuthError::CouldNotUpdateUser)?;
+        }
+
+        let action = if item.activated {
+            "user.activate"
+        } else {
+            "user.deactivate"
+        };
+        audit(db, auth, action, Some(user_id), None)
+    })
+}
+
+/// /admin/users/{id}/roles
+///
+/// assigns `item.role` to the User whose id is [`user_id`](`ID`),
+/// users who already have the role are left as they are (and nothing is recorded)
+///
+/// # Errors
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 404: [`AuthError::UserNotFound`]
+/// - 500: [`AuthError::CouldNotAssignRole`]
+pub fn assign_role(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    user_id: ID,
+    item: &RoleInput,
+) -> Result<(), AuthError> {
+    authorize(auth_config, auth)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    read_user(&mut db, user_id)?;
+
+    let assigned = UserRole::read_all(&mut db, user_id)
+        .map_err(|_| AuthError::CouldNotAssignRole)?
+        .into_iter()
+        .any(|user_role| user_role.role == item.role);
+    if assigned {
+        return Ok(());
+    }
+
+    db.transaction(|db| {
+        if !matches!(Role::assign(db, user_id, &item.role), Ok(true)) {
+            return Err(AuthError::CouldNotAssignRole);
+        }
+
+        audit(
+            db,
+            auth,
+            "role.assign",
+            Some(user_id),
+            Some(item.role.clone()),
+        )
+    })
+}
+
+/// /admin/users/{id}/roles/{role}
+///
+/// unassigns `role` from the User whose id is [`user_id`](`ID`)
+///
+/// # Errors
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 404: [`AuthError::UserNotFound`]
+/// - 500: [`AuthError::CouldNotUnassignRole`]
+pub fn unassign_role(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    user_id: ID,
+    role: &str,
+) -> Result<(), AuthError> {
+    authorize(auth_config, auth)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    read_user(&mut db, user_id)?;
+
+    db.transaction(|db| {
+        if !matches!(Role::unassign(db, user_id, role), Ok(true)) {
+            return Err(AuthError::CouldNotUnassignRole);
+        }
+
+        audit(
+            db,
+            auth,
+            "role.unassign",
+            Some(user_id),
+            Some(role.to_string()),
+        )
+    })
+}
+
+/// /admin/users/{id}/permissions
+///
+/// grants `item.permission` to the User whose id is [`user_id`](`ID`),
+/// users who were already granted the permission are left as they are (and nothing is recorded)
+///
+/// # Errors
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 404: [`AuthError::UserNotFound`]
+/// - 500: [`AuthError::CouldNotGrantPermission`]
+pub fn grant_permission(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    user_id: ID,
+    item: &PermissionInput,
+) -> Result<(), AuthError> {
+    authorize(auth_config, auth)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    read_user(&mut db, user_id)?;
+
+    let granted = UserPermission::read_all(&mut db, user_id)
+        .map_err(|_| AuthError::CouldNotGrantPermission)?
+        .into_iter()
+        .any(|user_permission| user_permission.permission == item.permission);
+    if granted {
+        return Ok(());
+    }
+
+    db.transaction(|db| {
+        Permission::grant_to_user(db, user_id, &item.permission)
+            .map_err(|_| AuthError::CouldNotGrantPermission)?;
+
+        audit(
+            db,
+            auth,
+            "permission.grant",
+            Some(user_id),
+            Some(item.permission.clone()),
+        )
+    })
+}
+
+/// /admin/users/{id}/permissions/{permission}
+///
+/// revokes `permission` from the User whose id is [`user_id`](`ID`)
+///
+/// # Errors
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 404: [`AuthError::UserNotFound`]
+/// - 500: [`AuthError::CouldNotRevokePermission`]
+pub fn revoke_permission(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    user_id: ID,
+    permission: &str,
+) -> Result<(), AuthError> {
+    authorize(auth_config, auth)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    read_user(&mut db, user_id)?;
+
+    db.transaction(|db| {
+        Permission::revoke_from_user(db, user_id, permission)
+            .map_err(|_| AuthError::CouldNotRevokePermission)?;
+
+        audit(
+            db,
+            auth,
+            "permission.revoke",
+            Some(user_id),
+            Some(permission.to_string()),
+        )
+    })
+}
+
+/// /admin/users/{id}/sessions
+///
+/// signs the User whose id is [`user_id`](`ID`) out of every session
+///
+/// note: access tokens which were already issued stay valid until they expire
+///
+/// # Errors
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 404: [`AuthError::UserNotFound`]
+/// - 500: [`AuthError::CouldNotDeleteSessions`]
+pub fn force_logout(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    user_id: ID,
+) -> Result<(), AuthError> {
+    authorize(auth_config, auth)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    read_user(&mut db, user_id)?;
+
+    db.transaction(|db| {
+        UserSession::delete_all_for_user(db, user_id)
+            .map_err(|_| AuthError::CouldNotDeleteSessions)?;
+
+        audit(db, auth, "sessions.revoke", Some(user_id), None)
+    })
+}
+
+/// /admin/audit
+///
+/// the audit trail of admin actions, most recent first, paginated according to [`info`](`PaginationParams`)
+///
+/// # Errors
+/// - 400: [`AuthError::InvalidPagination`]
+/// - 403: [`AuthError::Forbidden`]
+/// - 404: [`AuthError::NotFound`]
+/// - 500: [`AuthError::CouldNotFetchAuditLog`]
+pub fn get_audit_log(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    info: &PaginationParams,
+) -> Result<AdminAuditLogResponse, AuthError> {
+    authorize(auth_config, auth)?;
+    check_pagination(info)?;
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let entries =
+        AdminAuditLog::read_all(&mut db, info).map_err(|_| AuthError::CouldNotFetchAuditLog)?;
+    let num_entries =
+        AdminAuditLog::count_all(&mut db).map_err(|_| AuthError::CouldNotFetchAuditLog)?;
+
+    let page_size = info
+        .page_size
+        .clamp(1, i64::from(PaginationParams::MAX_PAGE_SIZE));
+    let num_pages = (num_entries / page_size) + i64::from(num_entries % page_size != 0);
+
+    Ok(AdminAuditLogResponse { entries, num_pages })
+}
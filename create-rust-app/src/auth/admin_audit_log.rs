@@ -0,0 +1,99 @@
+use super::schema::admin_audit_logs;
+use crate::diesel::{
+    insert_into, AsChangeset, ExpressionMethods, Identifiable, Insertable, QueryDsl, Queryable,
+    RunQueryDsl,
+};
+
+use super::{PaginationParams, Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, AsChangeset,
+)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+#[diesel(table_name=admin_audit_logs)]
+/// Rust struct modeling an entry in the `admin_audit_logs` table
+///
+/// every change an admin makes through the admin endpoints is recorded here
+pub struct AdminAuditLog {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// the admin who made the change
+    pub admin_user_id: ID,
+    /// what was done, for example `role.assign`
+    pub action: String,
+    /// the user the change was made to, if any
+    pub target_user_id: Option<ID>,
+    /// action specific details, for example the role that was assigned
+    pub details: Option<String>,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=admin_audit_logs)]
+/// Rust struct modeling mutable data in an entry in the `admin_audit_logs` table
+pub struct AdminAuditLogChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub admin_user_id: ID,
+    pub action: String,
+    pub target_user_id: Option<ID>,
+    pub details: Option<String>,
+}
+
+impl AdminAuditLog {
+    /// Create an entry in [`db`](`Connection`)'s `admin_audit_logs` table using the data in [`item`](`AdminAuditLogChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &AdminAuditLogChangeset) -> QueryResult<Self> {
+        use super::schema::admin_audit_logs::dsl::admin_audit_logs;
+
+        insert_into(admin_audit_logs)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), return entries of the `admin_audit_logs` table,
+    /// most recent first, paginated according to [`pagination`](`PaginationParams`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(db: &mut Connection, pagination: &PaginationParams) -> QueryResult<Vec<Self>> {
+        use super::schema::admin_audit_logs::dsl::{admin_audit_logs, id};
+
+        let page_size = std::cmp::min(
+            pagination.page_size,
+            i64::from(PaginationParams::MAX_PAGE_SIZE),
+        );
+
+        admin_audit_logs
+            .order(id.desc())
+            .limit(page_size)
+            .offset(pagination.page * page_size)
+            .load::<Self>(db)
+    }
+
+    /// Count the entries in [`db`](`Connection`)'s `admin_audit_logs` table
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn count_all(db: &mut Connection) -> QueryResult<i64> {
+        use super::schema::admin_audit_logs::dsl::admin_audit_logs;
+
+        admin_audit_logs.count().get_result(db)
+    }
+}
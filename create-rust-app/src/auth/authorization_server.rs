@@ -0,0 +1,614 @@
+//! an OAuth 2.0 / OpenID Connect authorization server
+//!
+//! when [`AuthConfig::authorization_server`] is enabled, the apps registered as [`OAuthClient`]s
+//! (see [`OAuthClient::register`]) can sign their users in through this backend,
+//! using the authorization code flow with PKCE:
+//!
+//! 1. the client sends the user to the `authorization_endpoint` of the [`discovery`] document,
+//!    a page of the frontend (`/oauth/authorize`) which makes sure the user is logged in
+//! 2. the page posts the query parameters to [`authorize`], and asks the user for their consent
+//!    if the client isn't first-party
+//! 3. the user is redirected back to the client with a code, which the client exchanges at [`token`]
+//!
+//! the client gets a refresh token for a new [`UserSession`](`crate::auth::UserSession`) which only it
+//! can refresh, an access token for [`userinfo`] (see [`ClientAccessTokenClaims`], the app's own
+//! endpoints don't accept it), and an ID token if it requested the `openid` scope. ID tokens are signed
+//! with [`AuthConfig::keys`], which should be asymmetric (RS256 or `EdDSA`) so clients can verify them
+//! with the published JWKS
+
+use crate::auth::{
+    controller::{create_client_session, rotate_session, ClientInfo},
+    error::AuthError,
+    keys::{decode_token, encode_token},
+    totp::percent_encode,
+    utc_now, Auth, AuthConfig, OAuthAuthorizationCode, OAuthAuthorizationCodeChangeset,
+    OAuthClient, User, ID,
+};
+use crate::{AppConfig, Connection, Database};
+
+use data_encoding::BASE64URL_NOPAD;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// how long clients have to exchange an authorization code
+const CODE_LIFETIME_SECONDS: i64 = 60;
+
+/// the scopes clients can be granted, others are ignored
+const SUPPORTED_SCOPES: [&str; 2] = ["openid", "email"];
+
+/// the `token_type` of [`ClientAccessTokenClaims`]
+const CLIENT_ACCESS_TOKEN_TYPE: &str = "client_access_token";
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// the query parameters of an authorization request, posted by the frontend on behalf of the user
+pub struct AuthorizeInput {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    /// the user's answer to [`AuthorizeOutcome::ConsentRequired`], not needed for first-party clients
+    pub consent: Option<bool>,
+}
+
+/// the result of [`authorize`]
+pub enum AuthorizeOutcome {
+    /// send the user to this url, it contains either a code or an error for the client
+    Redirect(String),
+    /// ask the user whether the client (named `client_name`) may access `scopes`,
+    /// then post the request again with their answer in [`AuthorizeInput::consent`]
+    ConsentRequired {
+        client_name: String,
+        scopes: Vec<String>,
+    },
+}
+
+#[tsync::tsync]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// the [RFC 6749](https://datatracker.ietf.org/doc/html/rfc6749#section-5.2) error codes of [`token`],
+/// see [`AuthError::InvalidTokenRequest`]
+pub enum TokenError {
+    InvalidClient,
+    InvalidGrant,
+    InvalidRequest,
+    UnsupportedGrantType,
+    ServerError,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// the form a client posts to [`token`]
+pub struct TokenInput {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    /// clients may authenticate with HTTP basic auth instead
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// the tokens issued by [`token`]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// the claims of the ID tokens issued to clients
+pub struct IdTokenClaims {
+    pub iss: String,
+    /// the user's id
+    pub sub: String,
+    /// the client's `client_id`
+    pub aud: String,
+    pub exp: usize,
+    pub iat: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// the claims of the access tokens issued to clients
+///
+/// their `token_type` isn't `access_token`, so the [`Auth`] extractors of the app's own endpoints
+/// reject them; they're only accepted by [`userinfo`]
+pub struct ClientAccessTokenClaims {
+    pub iss: String,
+    /// the user's id
+    pub sub: ID,
+    /// the client's `client_id`
+    pub aud: String,
+    pub exp: usize,
+    pub token_type: String,
+    /// the space separated scopes the client was granted
+    pub scope: String,
+}
+
+/// the issuer identifier, every other url of the authorization server is derived from it
+fn issuer(app_config: &AppConfig) -> String {
+    format!("{}/api/auth", app_config.app_url.trim_end_matches('/'))
+}
+
+/// /.well-known/openid-configuration
+///
+/// the discovery document clients configure themselves with, `None` if the authorization server is disabled
+#[must_use]
+pub fn discovery(app_config: &AppConfig, auth_config: &AuthConfig) -> Option<serde_json::Value> {
+    if !auth_config.authorization_server {
+        return None;
+    }
+
+    let issuer = issuer(app_config);
+    let app_url = app_config.app_url.trim_end_matches('/');
+
+    Some(serde_json::json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{app_url}/oauth/authorize"),
+        "token_endpoint": format!("{issuer}/oauth/token"),
+        "userinfo_endpoint": format!("{issuer}/oauth/userinfo"),
+        "jwks_uri": format!("{issuer}/.well-known/jwks.json"),
+        "response_types_supported": ["code"],
+        "grant_types_supported": ["authorization_code", "refresh_token"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": [auth_config.keys.signing_key().algorithm()],
+        "scopes_supported": SUPPORTED_SCOPES,
+        "claims_supported": ["iss", "sub", "aud", "exp", "iat", "nonce", "email", "email_verified"],
+        "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post", "none"],
+        "code_challenge_methods_supported": ["S256"],
+    }))
+}
+
+/// appends `params` (and the client's `state`) to the query of `redirect_uri`
+fn redirect_with(redirect_uri: &str, params: &[(&str, &str)], state: Option<&str>) -> String {
+    let query = params
+        .iter()
+        .copied()
+        .chain(state.map(|state| ("state", state)))
+        .map(|(name, value)| format!("{name}={}", percent_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let separator = if redirect_uri.contains('?') { '&' } else { '?' };
+    format!("{redirect_uri}{separator}{query}")
+}
+
+/// /oauth/authorize
+///
+/// issues an authorization code to the client in [`item`](`AuthorizeInput`) on behalf of the
+/// User associated with [`auth`](`Auth`)
+///
+/// errors which happen after the client and its redirect uri were verified are sent to the client,
+/// in [`AuthorizeOutcome::Redirect`]
+///
+/// # Errors
+/// - 404: [`AuthError::NotFound`] (the authorization server is disabled)
+/// - 403: [`AuthError::ApiKeyCannotAuthorize`]
+/// - 400: [`AuthError::UnknownClient`]
+/// - 400: [`AuthError::InvalidRedirectUri`]
+/// - 500: [`AuthError::CouldNotCreateAuthorizationCode`]
+pub fn authorize(
+    db: &Database,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    item: &AuthorizeInput,
+) -> Result<AuthorizeOutcome, AuthError> {
+    if !auth_config.authorization_server {
+        return Err(AuthError::NotFound);
+    }
+
+    if auth.api_key_id.is_some() {
+        return Err(AuthError::ApiKeyCannotAuthorize);
+    }
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let Ok(client) = OAuthClient::find_by_client_id(&mut db, &item.client_id) else {
+        return Err(AuthError::UnknownClient);
+    };
+
+    // never redirect to a url the client didn't register
+    if !client.allows_redirect_uri(&item.redirect_uri) {
+        return Err(AuthError::InvalidRedirectUri);
+    }
+
+    let state = item.state.as_deref();
+    let error = |error: &str, description: &str| {
+        Ok(AuthorizeOutcome::Redirect(redirect_with(
+            &item.redirect_uri,
+            &[("error", error), ("error_description", description)],
+            state,
+        )))
+    };
+
+    if item.response_type != "code" {
+        return error(
+            "unsupported_response_type",
+            "Only the authorization code flow is supported.",
+        );
+    }
+
+    let Some(code_challenge) = item
+        .code_challenge
+        .as_ref()
+        .filter(|_| item.code_challenge_method.as_deref() == Some("S256"))
+    else {
+        return error("invalid_request", "PKCE with S256 is required.");
+    };
+
+    let scopes = item
+        .scope
+        .as_deref()
+        .unwrap_or_default()
+        .split(' ')
+        .filter(|scope| SUPPORTED_SCOPES.contains(scope))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if !client.first_party {
+        match item.consent {
+            None => {
+                return Ok(AuthorizeOutcome::ConsentRequired {
+                    client_name: client.name,
+                    scopes,
+                })
+            }
+            Some(false) => return error("access_denied", "The user denied the request."),
+            Some(true) => {}
+        }
+    }
+
+    let _ = OAuthAuthorizationCode::delete_expired(&mut db, utc_now());
+
+    let code = OAuthAuthorizationCode::generate_code();
+    OAuthAuthorizationCode::create(
+        &mut db,
+        &OAuthAuthorizationCodeChangeset {
+            code_hash: OAuthAuthorizationCode::hash_code(&code),
+            oauth_client_id: client.id,
+            user_id: auth.user_id,
+            redirect_uri: item.redirect_uri.clone(),
+            scope: scopes.join(" "),
+            nonce: item.nonce.clone(),
+            code_challenge: code_challenge.clone(),
+            expires_at: utc_now() + chrono::Duration::seconds(CODE_LIFETIME_SECONDS),
+        },
+    )
+    .map_err(|_| AuthError::CouldNotCreateAuthorizationCode)?;
+
+    Ok(AuthorizeOutcome::Redirect(redirect_with(
+        &item.redirect_uri,
+        &[("code", &code)],
+        state,
+    )))
+}
+
+/// the client id and secret of an `Authorization: Basic ...` header
+#[must_use]
+pub fn basic_credentials(authorization: &str) -> Option<(String, String)> {
+    let encoded = authorization.strip_prefix("Basic ")?;
+    let decoded = data_encoding::BASE64
+        .decode(encoded.trim().as_bytes())
+        .ok()?;
+    let (client_id, client_secret) = String::from_utf8(decoded)
+        .ok()?
+        .split_once(':')
+        .map(|(client_id, client_secret)| (client_id.to_string(), client_secret.to_string()))?;
+
+    Some((client_id, client_secret))
+}
+
+/// the token of an `Authorization: Bearer ...` header
+#[must_use]
+pub fn bearer_token(authorization: &str) -> Option<&str> {
+    authorization.strip_prefix("Bearer ").map(str::trim)
+}
+
+/// the client identified by the basic auth `credentials` or the `client_id`/`client_secret` of
+/// [`item`](`TokenInput`), if its secret is correct
+fn authenticate_client(
+    db: &mut Connection,
+    item: &TokenInput,
+    credentials: Option<(String, String)>,
+) -> Option<OAuthClient> {
+    let (client_id, client_secret) = match credentials {
+        Some((client_id, client_secret)) => (client_id, Some(client_secret)),
+        None => (item.client_id.clone()?, item.client_secret.clone()),
+    };
+
+    OAuthClient::find_by_client_id(db, &client_id)
+        .ok()
+        .filter(|client| client.verify_secret(client_secret.as_deref()))
+}
+
+/// /oauth/token
+///
+/// exchanges an authorization code (or a refresh token) for tokens; `credentials` are the client's
+/// HTTP basic auth credentials, if it sent them
+///
+/// errors are reported as [`AuthError::InvalidTokenRequest`], with the error codes of
+/// [RFC 6749](https://datatracker.ietf.org/doc/html/rfc6749#section-5.2) (see [`TokenError`])
+///
+/// # Errors
+/// - 404: [`AuthError::NotFound`] (the authorization server is disabled)
+/// - 401: [`TokenError::InvalidClient`]
+/// - 400: [`TokenError::UnsupportedGrantType`]
+/// - 400: [`TokenError::InvalidRequest`]
+/// - 400: [`TokenError::InvalidGrant`]
+/// - 500: [`TokenError::ServerError`]
+pub fn token(
+    db: &Database,
+    app_config: &AppConfig,
+    auth_config: &AuthConfig,
+    item: &TokenInput,
+    credentials: Option<(String, String)>,
+) -> Result<TokenResponse, AuthError> {
+    if !auth_config.authorization_server {
+        return Err(AuthError::NotFound);
+    }
+
+    let fail = |error: TokenError| AuthError::InvalidTokenRequest { error };
+
+    let expires_in = auth_config.access_token_lifetime.num_seconds();
+
+    if !matches!(
+        item.grant_type.as_str(),
+        "authorization_code" | "refresh_token"
+    ) {
+        return Err(fail(TokenError::UnsupportedGrantType));
+    }
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| fail(TokenError::ServerError))?;
+
+    let Some(client) = authenticate_client(&mut db, item, credentials) else {
+        return Err(fail(TokenError::InvalidClient));
+    };
+
+    if item.grant_type == "refresh_token" {
+        // only the client the session was started for can refresh it
+        let (user_id, scope, refresh_token) = rotate_session(
+            &mut db,
+            auth_config,
+            item.refresh_token.as_deref(),
+            Some(client.id),
+            &ClientInfo::default(),
+        )
+        .map_err(|_| fail(TokenError::InvalidGrant))?;
+        let scope = scope.unwrap_or_default();
+
+        let access_token = client_access_token(app_config, auth_config, &client, &scope, user_id)
+            .map_err(|_| fail(TokenError::ServerError))?;
+
+        return Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in,
+            refresh_token,
+            id_token: None,
+            scope: Some(scope),
+        });
+    }
+
+    let (Some(code), Some(redirect_uri), Some(code_verifier)) =
+        (&item.code, &item.redirect_uri, &item.code_verifier)
+    else {
+        return Err(fail(TokenError::InvalidRequest));
+    };
+
+    let Ok(Some(authorization)) = OAuthAuthorizationCode::consume(&mut db, code) else {
+        return Err(fail(TokenError::InvalidGrant));
+    };
+
+    let challenge = BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()));
+    if authorization.oauth_client_id != client.id
+        || authorization.redirect_uri != *redirect_uri
+        || authorization.code_challenge != challenge
+    {
+        return Err(fail(TokenError::InvalidGrant));
+    }
+
+    let Ok(user) = User::read(&mut db, authorization.user_id) else {
+        return Err(fail(TokenError::InvalidGrant));
+    };
+
+    let refresh_token =
+        create_client_session(&mut db, auth_config, &client, &authorization.scope, user.id)
+            .map_err(|_| fail(TokenError::ServerError))?;
+    let access_token = client_access_token(
+        app_config,
+        auth_config,
+        &client,
+        &authorization.scope,
+        user.id,
+    )
+    .map_err(|_| fail(TokenError::ServerError))?;
+
+    let scopes = authorization.scope.split(' ').collect::<Vec<_>>();
+    let id_token = if scopes.contains(&"openid") {
+        let include_email = scopes.contains(&"email");
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let claims = IdTokenClaims {
+            iss: issuer(app_config),
+            sub: user.id.to_string(),
+            aud: client.client_id,
+            exp: (chrono::Utc::now() + auth_config.access_token_lifetime).timestamp() as usize,
+            iat: chrono::Utc::now().timestamp() as usize,
+            nonce: authorization.nonce,
+            email: include_email.then(|| user.email.clone()),
+            email_verified: include_email.then(|| email_verified(&mut db, &user)),
+        };
+
+        Some(
+            encode_token(auth_config.keys.as_ref(), &claims)
+                .map_err(|_| fail(TokenError::ServerError))?,
+        )
+    } else {
+        None
+    };
+
+    Ok(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in,
+        refresh_token,
+        id_token,
+        scope: Some(authorization.scope),
+    })
+}
+
+/// an access token for `client`, which was granted `scope`, see [`ClientAccessTokenClaims`]
+fn client_access_token(
+    app_config: &AppConfig,
+    auth_config: &AuthConfig,
+    client: &OAuthClient,
+    scope: &str,
+    user_id: ID,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let claims = ClientAccessTokenClaims {
+        iss: issuer(app_config),
+        sub: user_id,
+        aud: client.client_id.clone(),
+        exp: (chrono::Utc::now() + auth_config.access_token_lifetime).timestamp() as usize,
+        token_type: CLIENT_ACCESS_TOKEN_TYPE.to_string(),
+        scope: scope.to_string(),
+    };
+
+    encode_token(auth_config.keys.as_ref(), &claims)
+}
+
+/// whether `user`'s email was verified, either through the activation link sent to it or by the
+/// OIDC provider they signed up with (accounts created through a provider aren't activated)
+fn email_verified(db: &mut Connection, user: &User) -> bool {
+    #[cfg(feature = "plugin_auth-oidc")]
+    if !user.activated {
+        return crate::auth::oidc::UserOauth2Link::read_all_linked(db, user.id)
+            .is_ok_and(|links| !links.is_empty());
+    }
+
+    #[cfg(not(feature = "plugin_auth-oidc"))]
+    let _ = db;
+
+    user.activated
+}
+
+/// /oauth/userinfo
+///
+/// the claims about the User an access token issued by [`token`] was issued for, the token has to
+/// have been granted the `openid` scope, and `email` for the user's email to be included
+///
+/// # Errors
+/// - 404: [`AuthError::NotFound`] (the authorization server is disabled)
+/// - 401: [`AuthError::InvalidToken`] (also when the token's client was deleted)
+/// - 403: [`AuthError::Forbidden`] (the token wasn't granted the `openid` scope)
+/// - 500: [`AuthError::CouldNotFindUser`]
+pub fn userinfo(
+    db: &Database,
+    auth_config: &AuthConfig,
+    access_token: Option<&str>,
+) -> Result<serde_json::Value, AuthError> {
+    if !auth_config.authorization_server {
+        return Err(AuthError::NotFound);
+    }
+
+    let Some(claims) = access_token
+        .and_then(|token| {
+            decode_token::<ClientAccessTokenClaims>(auth_config.keys.as_ref(), token).ok()
+        })
+        .map(|token| token.claims)
+        .filter(|claims| claims.token_type == CLIENT_ACCESS_TOKEN_TYPE)
+    else {
+        return Err(AuthError::InvalidToken);
+    };
+
+    let scopes = claims.scope.split(' ').collect::<Vec<_>>();
+    if !scopes.contains(&"openid") {
+        return Err(AuthError::Forbidden);
+    }
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    if OAuthClient::find_by_client_id(&mut db, &claims.aud).is_err() {
+        return Err(AuthError::InvalidToken);
+    }
+
+    let Ok(user) = User::read(&mut db, claims.sub) else {
+        return Err(AuthError::CouldNotFindUser);
+    };
+
+    let mut userinfo = serde_json::json!({ "sub": user.id.to_string() });
+    if scopes.contains(&"email") {
+        userinfo["email_verified"] = email_verified(&mut db, &user).into();
+        userinfo["email"] = user.email.into();
+    }
+
+    Ok(userinfo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_with_appends_encoded_params() {
+        assert_eq!(
+            redirect_with(
+                "https://app.example.com/callback",
+                &[("code", "abc")],
+                Some("x y")
+            ),
+            "https://app.example.com/callback?code=abc&state=x%20y"
+        );
+        assert_eq!(
+            redirect_with(
+                "https://app.example.com/callback?tenant=1",
+                &[("error", "access_denied")],
+                None
+            ),
+            "https://app.example.com/callback?tenant=1&error=access_denied"
+        );
+    }
+
+    #[test]
+    fn test_parses_basic_credentials() {
+        assert_eq!(
+            basic_credentials("Basic Y2xpZW50OnNlY3JldA=="),
+            Some(("client".to_string(), "secret".to_string()))
+        );
+        assert_eq!(basic_credentials("Bearer Y2xpZW50OnNlY3JldA=="), None);
+        assert_eq!(basic_credentials("Basic bm9jb2xvbg=="), None);
+    }
+
+    #[test]
+    fn test_parses_bearer_tokens() {
+        assert_eq!(bearer_token("Bearer abc.def.ghi"), Some("abc.def.ghi"));
+        assert_eq!(bearer_token("Basic Y2xpZW50OnNlY3JldA=="), None);
+    }
+}
@@ -1,30 +1,76 @@
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-
 use crate::auth::{
-    AccessTokenClaims, Auth, PaginationParams, Permission, Role, User, UserChangeset, UserSession,
-    UserSessionChangeset, UserSessionJson, UserSessionResponse, ID,
+    error::AuthError,
+    keys::{decode_token, encode_token},
+    passkey, totp, user_agent, utc_now, AccessTokenClaims, Auth, AuthConfig, FailedLogin,
+    FailedLoginChangeset, OAuthClient, PaginationParams, PasskeyChallenge,
+    PasskeyChallengeChangeset, Permission, Role, User, UserApiKey, UserApiKeyChangeset,
+    UserApiKeyJson, UserApiKeyResponse, UserChangeset, UserLockout, UserLockoutChangeset,
+    UserLoginLink, UserLoginLinkChangeset, UserPasskey, UserPasskeyChangeset, UserPasskeyJson,
+    UserPasskeyResponse, UserSession, UserSessionChangeset, UserSessionJson, UserSessionResponse,
+    UserTotp, UserTotpChangeset, Utc, API_KEY_PREFIX, ID,
 };
-use crate::{Connection, Database, Mailer};
+use crate::{AppConfig, Connection, Database, Mailer};
 
+use diesel::OptionalExtension;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
 pub const COOKIE_NAME: &str = "refresh_token";
 
 lazy_static! {
-    pub static ref ARGON_CONFIG: argon2::Config<'static> = argon2::Config {
+    /// the `SECRET_KEY` environment variable, `None` if it isn't set
+    static ref SECRET_KEY: Option<&'static [u8]> = std::env::var("SECRET_KEY")
+        .ok()
+        .map(|s| &*Box::leak(s.into_boxed_str().into_boxed_bytes()));
+}
+
+/// the argon2 config used to hash passwords, with `SECRET_KEY` as its secret
+///
+/// # Errors
+/// - 500: [`AuthError::Internal`] if `SECRET_KEY` isn't set
+pub fn argon_config() -> Result<argon2::Config<'static>, AuthError> {
+    Ok(argon2::Config {
         variant: argon2::Variant::Argon2id,
         version: argon2::Version::Version13,
-        secret: std::env::var("SECRET_KEY").map_or_else(|_| panic!("No SECRET_KEY environment variable set!"), |s| Box::leak(s.into_boxed_str()).as_bytes()),
+        secret: SECRET_KEY.ok_or(AuthError::Internal)?,
         ..Default::default()
-    };
-    // TODO: instead of initializing EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()) repeatedly in the code, just initialize it here and use it everywhere
+    })
+}
+
+/// hashes `password` with a new salt
+pub(crate) fn hash_password(password: &str) -> Result<String, AuthError> {
+    argon2::hash_encoded(password.as_bytes(), &generate_salt(), &argon_config()?)
+        .map_err(|_| AuthError::Internal)
+}
+
+/// `mailer`, rendering emails in the recipient's stored `locale`
+fn localized(mailer: &Mailer, locale: Option<&str>) -> Mailer {
+    mailer.clone().with_locale(locale.map(ToString::to_string))
+}
+
+/// whether `password` is the one `hash` was made from, see [`hash_password`]
+pub(crate) fn verify_password(hash: &str, password: &str) -> Result<bool, AuthError> {
+    let argon_config = argon_config()?;
+
+    argon2::verify_encoded_ext(
+        hash,
+        password.as_bytes(),
+        argon_config.secret,
+        argon_config.ad,
+    )
+    .map_err(|_| AuthError::Internal)
 }
 
 #[cfg(not(debug_assertions))]
 type Seconds = i64;
-type StatusCode = u16;
-type Message = &'static str;
+#[derive(Debug, Clone, Default)]
+/// where a login or refresh request came from, recorded on the session it creates (see [`UserSession`])
+pub struct ClientInfo {
+    /// the address of the client making the request, if it's known
+    pub ip_address: Option<String>,
+    /// the request's `User-Agent` header
+    pub user_agent: Option<String>,
+}
 
 #[derive(Deserialize, Serialize)]
 #[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
@@ -46,6 +92,11 @@ pub struct RefreshTokenClaims {
     exp: usize,
     sub: ID,
     token_type: String,
+    /// random, so that two refresh tokens issued in the same second are still different
+    jti: String,
+    /// the scope granted to the [`OAuthClient`] the session belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -55,6 +106,9 @@ pub struct RefreshTokenClaims {
 pub struct RegisterInput {
     email: String,
     password: String,
+    /// the language tag (like `fr-CA`) the user's emails should be written in, see [`Mailer::render`]
+    #[serde(default)]
+    locale: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -81,6 +135,34 @@ pub struct ForgotInput {
     email: String,
 }
 
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the .../login/link endpoint
+pub struct LoginLinkInput {
+    email: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the .../login/link/verify endpoint
+pub struct LoginLinkVerifyInput {
+    /// the token from the emailed login link
+    login_token: String,
+    device: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// claims of the token in an emailed login link
+pub struct LoginLinkClaims {
+    exp: usize,
+    sub: ID,
+    token_type: String,
+    /// the primary key of the link in the `user_login_links` table, which is deleted once it's used
+    jti: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 /// TODO: documentation
 pub struct ResetTokenClaims {
@@ -98,6 +180,37 @@ pub struct ChangeInput {
     new_password: String,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the .../email/change endpoint
+pub struct ChangeEmailInput {
+    /// the user's current password, so a stolen access token isn't enough to take over the account
+    password: String,
+    new_email: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the .../email/change/confirm endpoint
+pub struct ConfirmEmailChangeInput {
+    /// the token from the link emailed to the new address
+    email_change_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// claims of the token in the link [`request_email_change`] sends to the new address
+pub struct EmailChangeClaims {
+    exp: usize,
+    sub: ID,
+    token_type: String,
+    /// the token is only valid while the user still has this email,
+    /// so it can't be used twice or after another change
+    old_email: String,
+    new_email: String,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
 /// Rust struct representing the Json body of
@@ -107,6 +220,132 @@ pub struct ResetInput {
     new_password: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+/// claims of the short-lived token handed out by [`login`] when the
+/// user still has to provide their second factor
+pub struct TwoFactorClaims {
+    exp: usize,
+    sub: ID,
+    token_type: String,
+    device: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /login/2fa endpoint
+pub struct TwoFactorLoginInput {
+    two_factor_token: String,
+    /// either a code from the authenticator app or one of the recovery codes
+    code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /2fa/confirm and /2fa/disable endpoints
+pub struct TwoFactorCodeInput {
+    /// either a code from the authenticator app or, when disabling, one of the recovery codes
+    code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json response of
+/// POST requests to the /2fa/enroll endpoint
+pub struct TwoFactorEnrollment {
+    /// base32 encoded secret, for manual entry into an authenticator app
+    pub secret: String,
+    /// `otpauth://` url, usually rendered as a QR code
+    pub otpauth_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json response of
+/// POST requests to the /2fa/confirm endpoint
+pub struct TwoFactorRecoveryCodes {
+    /// single-use codes which can be used in place of a TOTP code; they are only shown once
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json response of
+/// POST requests to the /passkeys/register/start and /passkeys/login/start endpoints
+pub struct PasskeyOptions {
+    /// has to be sent back along with the browser's response
+    pub challenge: String,
+    /// options for `navigator.credentials.create()` or `navigator.credentials.get()`;
+    /// binary fields (`challenge`, `user.id` and credential ids) are base64url encoded
+    #[cfg_attr(feature = "plugin_utoipa", schema(value_type = Object))]
+    pub public_key: serde_json::Value,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /api-keys endpoint
+pub struct ApiKeyInput {
+    name: String,
+    /// limits the key to these of the user's permissions; it gets all of them (and the user's roles) if unset
+    permissions: Option<Vec<String>>,
+    /// the key is valid forever if unset
+    #[cfg_attr(feature = "plugin_utoipa", schema(value_type = Option<String>))]
+    expires_at: Option<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json response of
+/// POST requests to the /api-keys endpoint
+pub struct ApiKeyCreated {
+    /// the key itself, which should be shown to the user once; only its hash is stored
+    pub key: String,
+    pub api_key: UserApiKeyJson,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /passkeys/register/finish endpoint
+pub struct PasskeyRegisterInput {
+    challenge: String,
+    /// base64url encoded `response.clientDataJSON` of the created credential
+    client_data_json: String,
+    /// base64url encoded `response.attestationObject` of the created credential
+    attestation_object: String,
+    /// a name for the passkey, so the user can tell them apart
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /passkeys/login/start endpoint
+pub struct PasskeyLoginStartInput {
+    /// ignored: the browser always lets the user pick any passkey they have for this site,
+    /// so the endpoint can't be used to find out which emails have passkeys
+    email: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representing the Json body of
+/// POST requests to the /passkeys/login/finish endpoint
+pub struct PasskeyLoginInput {
+    challenge: String,
+    /// base64url encoded `rawId` of the credential
+    credential_id: String,
+    /// base64url encoded `response.clientDataJSON` of the assertion
+    client_data_json: String,
+    /// base64url encoded `response.authenticatorData` of the assertion
+    authenticator_data: String,
+    /// base64url encoded `response.signature` of the assertion
+    signature: String,
+    device: Option<String>,
+}
+
 /// /sessions
 ///
 /// queries [`db`](`Database`) for all sessions owned by the User
@@ -117,39 +356,27 @@ pub struct ResetInput {
 /// # Returns [`Result`]
 /// - Ok([`UserSessionResponse`])
 ///     - the results of the query paginated according to [`info`](`PaginationParams`)
-/// - Err([`StatusCode`], [`Message`])
+/// - Err([`AuthError`])
 ///
 /// # Errors
-/// - 500: Could not fetch sessions
-///
-/// # Panics
-/// - could not connect to database
-///
-/// TODO: don't panic if db connection fails, just return an error
+/// - 500: [`AuthError::CouldNotFetchSessions`]
 pub fn get_sessions(
     db: &Database,
     auth: &Auth,
     info: &PaginationParams,
-) -> Result<UserSessionResponse, (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+) -> Result<UserSessionResponse, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
     let Ok(sessions) = UserSession::read_all(&mut db, info, auth.user_id) else {
-        return Err((500, "Could not fetch sessions."));
+        return Err(AuthError::CouldNotFetchSessions);
     };
 
-    let sessions_json: Vec<UserSessionJson> = sessions
-        .iter()
-        .map(|s| UserSessionJson {
-            id: s.id,
-            device: s.device.clone(),
-            created_at: s.created_at,
-            #[cfg(not(feature = "database_sqlite"))]
-            updated_at: s.updated_at,
-        })
-        .collect();
+    let sessions_json: Vec<UserSessionJson> = sessions.iter().map(UserSessionJson::from).collect();
 
     let Ok(num_sessions) = UserSession::count_all(&mut db, auth.user_id) else {
-        return Err((500, "Could not fetch sessions."));
+        return Err(AuthError::CouldNotFetchSessions);
     };
 
     let num_pages = (num_sessions / info.page_size) + i64::from(num_sessions % info.page_size != 0);
@@ -168,29 +395,22 @@ pub fn get_sessions(
 /// [`db`](`Database`) if it's owned by the User associated with [`auth`](`Auth`)
 ///
 /// # Errors
-/// - 404: Session not found
-/// - 500: Internal error
-/// - 500: Could not delete session
-///
-/// # Panics
-/// - could not connect to database
-///
-/// TODO: don't panic if db connection fails, just return an error
-pub fn destroy_session(
-    db: &Database,
-    auth: &Auth,
-    item_id: ID,
-) -> Result<(), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+/// - 404: [`AuthError::SessionNotFound`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotDeleteSession`]
+pub fn destroy_session(db: &Database, auth: &Auth, item_id: ID) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
     let user_session = match UserSession::read(&mut db, item_id) {
         Ok(user_session) if user_session.user_id == auth.user_id => user_session,
-        Ok(_) => return Err((404, "Session not found.")),
-        Err(_) => return Err((500, "Internal error.")),
+        Ok(_) => return Err(AuthError::SessionNotFound),
+        Err(_) => return Err(AuthError::Internal),
     };
 
-    UserSession::delete(&mut db, user_session.id)
-        .map_err(|_| (500, "Could not delete session."))?;
+    UserSession::delete_family(&mut db, &user_session.family)
+        .map_err(|_| AuthError::CouldNotDeleteSession)?;
 
     Ok(())
 }
@@ -201,120 +421,1184 @@ pub fn destroy_session(
 /// by the User associated with [`auth`](`Auth`)
 ///
 /// # Errors
-/// - 500: Could not delete sessions
+/// - 500: [`AuthError::CouldNotDeleteSessions`]
+pub fn destroy_sessions(db: &Database, auth: &Auth) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    UserSession::delete_all_for_user(&mut db, auth.user_id)
+        .map_err(|_| AuthError::CouldNotDeleteSessions)?;
+
+    Ok(())
+}
+
+type AccessToken = String;
+type RefreshToken = String;
+type TwoFactorToken = String;
+
+/// the result of a successful password check in [`login`]
+pub enum LoginOutcome {
+    /// the user doesn't use a second factor; a session was created
+    Session(AccessToken, RefreshToken),
+    /// the user has to send a code along with this token to [`login_two_factor`]
+    /// before a session is created
+    TwoFactorRequired(TwoFactorToken),
+}
+
+/// /login
+///
+/// creates a user session for the user associated with [`item`](`LoginInput`)
+/// in the request body (have the `content-type` header set to `application/json` and content that can be deserialized into [`LoginInput`])
+///
+/// # Returns [`Result`]
+/// - Ok([`LoginOutcome::Session`])
+///     - an access token that should be sent to the user in the response body,
+///     - a reset token that should be sent as a secure, http-only, and `same_site=strict` cookie.
+/// - Ok([`LoginOutcome::TwoFactorRequired`])
+///     - the user has two-factor authentication enabled, the token should be sent to the user
+///       in the response body so it can be exchanged for a session at [`login_two_factor`]
+/// - Err([`AuthError`])
+///
+/// failed attempts are throttled according to [`auth_config.login_throttle`](`crate::auth::LoginThrottleConfig`),
+/// `client` is where the request came from, see [`ClientInfo`]
+///
+/// # Errors
+/// - 400: [`AuthError::DeviceTooLong`]
+/// - 400: [`AuthError::AccountNotActivated`]
+/// - 401: [`AuthError::InvalidCredentials`]
+/// - 429: [`AuthError::TooManyFailedLogins`]
+/// - 500: [`AuthError::Internal`]
+pub fn login(
+    db: &Database,
+    auth_config: &AuthConfig,
+    mailer: &Mailer,
+    item: &LoginInput,
+    client: &ClientInfo,
+) -> Result<LoginOutcome, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    check_ip_throttle(&mut db, auth_config, client.ip_address.as_deref())?;
+
+    // verify device
+    let device = match item.device {
+        Some(ref device) if device.len() > 256 => {
+            return Err(AuthError::DeviceTooLong);
+        }
+        Some(ref device) => Some(device.clone()),
+        None => None,
+    };
+
+    let user = match User::find_by_email(&mut db, item.email.clone()) {
+        Ok(user) if user.activated => user,
+        Ok(_) => return Err(AuthError::AccountNotActivated),
+        Err(_) => {
+            register_failed_login(
+                &mut db,
+                auth_config,
+                mailer,
+                None,
+                client.ip_address.as_deref(),
+            )?;
+            return Err(AuthError::InvalidCredentials);
+        }
+    };
+
+    check_lockout(&mut db, user.id)?;
+
+    let is_valid = verify_password(&user.hash_password, &item.password)?;
+
+    if !is_valid {
+        register_failed_login(
+            &mut db,
+            auth_config,
+            mailer,
+            Some(&user),
+            client.ip_address.as_deref(),
+        )?;
+        return Err(AuthError::InvalidCredentials);
+    }
+
+    complete_login(&mut db, auth_config, mailer, client, user.id, device)
+}
+
+/// finishes a login once the User whose id is [`user_id`](`ID`) proved who they are
+/// (with their password, or a login link): creates a session, or asks for their second factor
+fn complete_login(
+    db: &mut Connection,
+    auth_config: &AuthConfig,
+    mailer: &Mailer,
+    client: &ClientInfo,
+    user_id: ID,
+    device: Option<String>,
+) -> Result<LoginOutcome, AuthError> {
+    match UserTotp::read(db, user_id).optional() {
+        Ok(Some(user_totp)) if user_totp.confirmed => {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let two_factor_claims = TwoFactorClaims {
+                exp: (chrono::Utc::now() + chrono::Duration::minutes(5)).timestamp() as usize,
+                sub: user_id,
+                token_type: "two_factor_token".to_string(),
+                device,
+            };
+
+            let two_factor_token = encode_token(auth_config.keys.as_ref(), &two_factor_claims)
+                .map_err(|_| AuthError::Internal)?;
+
+            Ok(LoginOutcome::TwoFactorRequired(two_factor_token))
+        }
+        Ok(_) => {
+            clear_failed_logins(db, user_id)?;
+
+            let (access_token, refresh_token) =
+                create_user_session(db, auth_config, Some(mailer), client, device, None, user_id)?;
+
+            Ok(LoginOutcome::Session(access_token, refresh_token))
+        }
+        Err(_) => Err(AuthError::Internal),
+    }
+}
+
+/// /login/link
 ///
-/// # Panics
-/// - could not connect to database
+/// emails a one-time login link to the User whose email is [`item.email`](`LoginLinkInput`),
+/// valid for [`auth_config.login_link_lifetime`](`AuthConfig::login_link_lifetime`)
+///
+/// the link points to `login/link?token=...` in the frontend, which should send
+/// the token to [`login_with_link`]
 ///
-/// TODO: don't panic if db connection fails, just return an error
-pub fn destroy_sessions(db: &Database, auth: &Auth) -> Result<(), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+/// always succeeds (unless something goes wrong on our end) so it can't be used
+/// to find out which emails have accounts; no email is sent if the user was sent a link
+/// less than [`auth_config.login_link_cooldown`](`AuthConfig::login_link_cooldown`) ago
+///
+/// # Errors
+/// - 429: [`AuthError::TooManyFailedLogins`]
+/// - 500: [`AuthError::Internal`]
+pub fn request_login_link(
+    db: &Database,
+    auth_config: &AuthConfig,
+    mailer: &Mailer,
+    item: &LoginLinkInput,
+    ip_address: Option<&str>,
+) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    check_ip_throttle(&mut db, auth_config, ip_address)?;
+
+    let Ok(user) = User::find_by_email(&mut db, item.email.clone()) else {
+        return Ok(());
+    };
+    if !user.activated {
+        return Ok(());
+    }
+
+    // links which were never used are forgotten once they expire
+    UserLoginLink::delete_expired(&mut db, utc_now()).map_err(|_| AuthError::Internal)?;
+
+    let last_sent_at =
+        UserLoginLink::last_created_at(&mut db, user.id).map_err(|_| AuthError::Internal)?;
+    if last_sent_at.is_some_and(|sent_at| sent_at + auth_config.login_link_cooldown > utc_now()) {
+        return Ok(());
+    }
+
+    let expires_at = chrono::Utc::now() + auth_config.login_link_lifetime;
+    let token_id = generate_token_id();
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let login_link_claims = LoginLinkClaims {
+        exp: expires_at.timestamp() as usize,
+        sub: user.id,
+        token_type: "login_link_token".to_string(),
+        jti: token_id.clone(),
+    };
+
+    let login_token = encode_token(auth_config.keys.as_ref(), &login_link_claims)
+        .map_err(|_| AuthError::Internal)?;
+
+    UserLoginLink::create(
+        &mut db,
+        &UserLoginLinkChangeset {
+            token_id,
+            user_id: user.id,
+            expires_at: utc_now() + auth_config.login_link_lifetime,
+        },
+    )
+    .map_err(|_| AuthError::Internal)?;
+
+    let link = &format!("login/link?token={login_token}");
+    let mailer = &localized(mailer, user.locale.as_deref());
+    mailer.templates.send_login_link(mailer, &user.email, link);
+
+    Ok(())
+}
+
+/// /login/link/verify
+///
+/// logs in the User a login link (see [`request_login_link`]) was sent to;
+/// each link can only be used once
+///
+/// # Returns [`Result`]
+/// - Ok([`LoginOutcome`]), just like [`login`]
+/// - Err([`AuthError`])
+///
+/// # Errors
+/// - 400: [`AuthError::DeviceTooLong`]
+/// - 400: [`AuthError::AccountNotActivated`]
+/// - 401: [`AuthError::InvalidToken`]
+/// - 429: [`AuthError::TooManyFailedLogins`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotCreateSession`]
+pub fn login_with_link(
+    db: &Database,
+    auth_config: &AuthConfig,
+    mailer: &Mailer,
+    item: &LoginLinkVerifyInput,
+    client: &ClientInfo,
+) -> Result<LoginOutcome, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    check_ip_throttle(&mut db, auth_config, client.ip_address.as_deref())?;
+
+    let device = match item.device {
+        Some(ref device) if device.len() > 256 => {
+            return Err(AuthError::DeviceTooLong);
+        }
+        Some(ref device) => Some(device.clone()),
+        None => None,
+    };
+
+    let token = match decode_token::<LoginLinkClaims>(auth_config.keys.as_ref(), &item.login_token)
+    {
+        Ok(token)
+            if token
+                .claims
+                .token_type
+                .eq_ignore_ascii_case("login_link_token") =>
+        {
+            token
+        }
+        _ => return Err(AuthError::InvalidToken),
+    };
+
+    match UserLoginLink::consume(&mut db, &token.claims.jti, token.claims.sub) {
+        Ok(1) => {}
+        Ok(_) => return Err(AuthError::InvalidToken),
+        Err(_) => return Err(AuthError::Internal),
+    }
+
+    let user = match User::read(&mut db, token.claims.sub) {
+        Ok(user) if user.activated => user,
+        Ok(_) => return Err(AuthError::AccountNotActivated),
+        Err(_) => return Err(AuthError::InvalidToken),
+    };
+
+    check_lockout(&mut db, user.id)?;
+
+    complete_login(&mut db, auth_config, mailer, client, user.id, device)
+}
+
+/// /login/2fa
+///
+/// completes a login started by [`login`] for a user with two-factor authentication enabled
+/// by checking the code in [`item`](`TwoFactorLoginInput`), which may also be an unused recovery code
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`], [`RefreshToken`])
+///     - an access token that should be sent to the user in the response body,
+///     - a reset token that should be sent as a secure, http-only, and `same_site=strict` cookie.
+/// - Err([`AuthError`])
+///
+/// wrong codes count as failed login attempts, see [`login`]
+///
+/// # Errors
+/// - 401: [`AuthError::InvalidToken`]
+/// - 401: [`AuthError::InvalidCode`]
+/// - 429: [`AuthError::TooManyFailedLogins`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotCreateSession`]
+pub fn login_two_factor(
+    db: &Database,
+    auth_config: &AuthConfig,
+    mailer: &Mailer,
+    item: &TwoFactorLoginInput,
+    client: &ClientInfo,
+) -> Result<(AccessToken, RefreshToken), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    check_ip_throttle(&mut db, auth_config, client.ip_address.as_deref())?;
+
+    let token =
+        match decode_token::<TwoFactorClaims>(auth_config.keys.as_ref(), &item.two_factor_token) {
+            Ok(token)
+                if token
+                    .claims
+                    .token_type
+                    .eq_ignore_ascii_case("two_factor_token") =>
+            {
+                token
+            }
+            _ => return Err(AuthError::InvalidToken),
+        };
+
+    let user_totp = match UserTotp::read(&mut db, token.claims.sub) {
+        Ok(user_totp) if user_totp.confirmed => user_totp,
+        _ => return Err(AuthError::InvalidToken),
+    };
+
+    check_lockout(&mut db, token.claims.sub)?;
+
+    if !verify_second_factor(&mut db, &user_totp, &item.code, true)? {
+        let user = User::read(&mut db, token.claims.sub).map_err(|_| AuthError::Internal)?;
+        register_failed_login(
+            &mut db,
+            auth_config,
+            mailer,
+            Some(&user),
+            client.ip_address.as_deref(),
+        )?;
+        return Err(AuthError::InvalidCode);
+    }
+
+    clear_failed_logins(&mut db, token.claims.sub)?;
+
+    create_user_session(
+        &mut db,
+        auth_config,
+        Some(mailer),
+        client,
+        token.claims.device,
+        None,
+        token.claims.sub,
+    )
+}
+
+/// rejects the attempt if too many logins from `ip_address` failed recently
+fn check_ip_throttle(
+    db: &mut Connection,
+    auth_config: &AuthConfig,
+    ip_address: Option<&str>,
+) -> Result<(), AuthError> {
+    let Some(ip_address) = ip_address else {
+        return Ok(());
+    };
+    let config = &auth_config.login_throttle;
+
+    let failed_attempts =
+        FailedLogin::count_for_ip_since(db, ip_address, utc_now() - config.window)
+            .map_err(|_| AuthError::Internal)?;
+
+    if failed_attempts >= config.max_failed_attempts_per_ip {
+        return Err(AuthError::TooManyFailedLogins);
+    }
+
+    Ok(())
+}
+
+/// rejects the attempt while the account of the User whose id is `user_id` is locked
+fn check_lockout(db: &mut Connection, user_id: ID) -> Result<(), AuthError> {
+    match UserLockout::read(db, user_id).optional() {
+        Ok(Some(lockout)) if lockout.locked_until > utc_now() => {
+            Err(AuthError::TooManyFailedLogins)
+        }
+        Ok(_) => Ok(()),
+        Err(_) => Err(AuthError::Internal),
+    }
+}
+
+/// records a failed login attempt for `user` (if the email belonged to an account) from `ip_address`
+///
+/// once the account reaches [`max_failed_attempts_per_account`](`crate::auth::LoginThrottleConfig`)
+/// it is locked, for longer after every consecutive lockout, and the user is notified by email
+fn register_failed_login(
+    db: &mut Connection,
+    auth_config: &AuthConfig,
+    mailer: &Mailer,
+    user: Option<&User>,
+    ip_address: Option<&str>,
+) -> Result<(), AuthError> {
+    let config = &auth_config.login_throttle;
+    let now = utc_now();
+
+    // attempts older than the window are never counted again
+    FailedLogin::delete_older_than(db, now - config.window).map_err(|_| AuthError::Internal)?;
+
+    FailedLogin::create(
+        db,
+        &FailedLoginChangeset {
+            user_id: user.map(|user| user.id),
+            ip_address: ip_address.map(ToString::to_string),
+        },
+    )
+    .map_err(|_| AuthError::Internal)?;
+
+    let Some(user) = user else {
+        return Ok(());
+    };
+
+    let lockout = UserLockout::read(db, user.id)
+        .optional()
+        .map_err(|_| AuthError::Internal)?;
+
+    // attempts made before the previous lockout expired have already been punished
+    let since = match lockout {
+        Some(ref lockout) if lockout.locked_until > now - config.window => lockout.locked_until,
+        _ => now - config.window,
+    };
+
+    let failed_attempts =
+        FailedLogin::count_for_user_since(db, user.id, since).map_err(|_| AuthError::Internal)?;
+
+    if failed_attempts < config.max_failed_attempts_per_account {
+        return Ok(());
+    }
+
+    let lockout_count = lockout.as_ref().map_or(0, |lockout| lockout.lockout_count) + 1;
+    let changeset = UserLockoutChangeset {
+        user_id: user.id,
+        lockout_count,
+        locked_until: now + config.lockout_duration(lockout_count),
+    };
+
+    let result = if lockout.is_some() {
+        UserLockout::update(db, user.id, &changeset)
+    } else {
+        UserLockout::create(db, &changeset)
+    };
+    let lockout = result.map_err(|_| AuthError::Internal)?;
+
+    let mailer = &localized(mailer, user.locale.as_deref());
+    mailer.templates.send_account_locked(
+        mailer,
+        &user.email,
+        &lockout
+            .locked_until
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string(),
+    );
+
+    Err(AuthError::TooManyFailedLogins)
+}
+
+/// forgets the failed login attempts and lockouts of the User whose id is `user_id`,
+/// called once they've logged in successfully
+fn clear_failed_logins(db: &mut Connection, user_id: ID) -> Result<(), AuthError> {
+    FailedLogin::delete_all_for_user(db, user_id)
+        .and_then(|_| UserLockout::delete(db, user_id))
+        .map_err(|_| AuthError::Internal)?;
+
+    Ok(())
+}
+
+/// checks `code` against the user's authenticator secret, or, if `allow_recovery_code`
+/// is set, against their unused recovery codes
+///
+/// accepted codes are recorded so they can't be used again
+fn verify_second_factor(
+    db: &mut Connection,
+    user_totp: &UserTotp,
+    code: &str,
+    allow_recovery_code: bool,
+) -> Result<bool, AuthError> {
+    #[allow(clippy::cast_sign_loss)]
+    let now = chrono::Utc::now().timestamp() as u64;
+
+    let mut changeset = UserTotpChangeset {
+        user_id: user_totp.user_id,
+        secret: user_totp.secret.clone(),
+        confirmed: user_totp.confirmed,
+        recovery_codes: user_totp.recovery_codes.clone(),
+        last_used_step: user_totp.last_used_step,
+    };
+
+    if let Some(step) = totp::verify(&user_totp.secret, code, now, user_totp.last_used_step) {
+        #[allow(clippy::cast_possible_wrap)]
+        let step = step as i64;
+        changeset.last_used_step = Some(step);
+    } else {
+        let code_hash = totp::hash_recovery_code(code);
+        let hashes = user_totp.recovery_code_hashes();
+
+        if !allow_recovery_code || !hashes.contains(&code_hash.as_str()) {
+            return Ok(false);
+        }
+
+        changeset.recovery_codes = hashes
+            .into_iter()
+            .filter(|hash| !hash.eq(&code_hash))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    UserTotp::update(db, user_totp.user_id, &changeset).map_err(|_| AuthError::Internal)?;
+
+    Ok(true)
+}
+
+/// /2fa/enroll
+///
+/// generates a new TOTP secret for the User associated with [`auth`](`Auth`)
+///
+/// two-factor authentication is not enabled until the user proves they've set up
+/// their authenticator app by submitting a code to [`confirm_two_factor`]
+///
+/// the account is listed under the host of [`app_config.app_url`](`AppConfig`) in authenticator apps
+///
+/// # Errors
+/// - 400: [`AuthError::TwoFactorAlreadyEnabled`]
+/// - 500: [`AuthError::CouldNotFindUser`]
+/// - 500: [`AuthError::CouldNotEnrollTwoFactor`]
+pub fn enroll_two_factor(
+    db: &Database,
+    app_config: &AppConfig,
+    auth: &Auth,
+) -> Result<TwoFactorEnrollment, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let Ok(user) = User::read(&mut db, auth.user_id) else {
+        return Err(AuthError::CouldNotFindUser);
+    };
+
+    let existing = match UserTotp::read(&mut db, auth.user_id).optional() {
+        Ok(Some(user_totp)) if user_totp.confirmed => {
+            return Err(AuthError::TwoFactorAlreadyEnabled)
+        }
+        Ok(existing) => existing,
+        Err(_) => return Err(AuthError::CouldNotEnrollTwoFactor),
+    };
+
+    let changeset = UserTotpChangeset {
+        user_id: auth.user_id,
+        secret: totp::generate_secret(),
+        confirmed: false,
+        recovery_codes: String::new(),
+        last_used_step: None,
+    };
+
+    let saved = if existing.is_some() {
+        UserTotp::update(&mut db, auth.user_id, &changeset)
+    } else {
+        UserTotp::create(&mut db, &changeset)
+    };
+
+    saved.map_err(|_| AuthError::CouldNotEnrollTwoFactor)?;
+
+    let issuer = app_config.app_url.rsplit("://").next().unwrap_or_default();
+
+    Ok(TwoFactorEnrollment {
+        otpauth_url: totp::otpauth_url(issuer, &user.email, &changeset.secret),
+        secret: changeset.secret,
+    })
+}
+
+/// /2fa/confirm
+///
+/// enables two-factor authentication for the User associated with [`auth`](`Auth`)
+/// if the code in [`item`](`TwoFactorCodeInput`) matches the secret from [`enroll_two_factor`]
+///
+/// # Returns [`Result`]
+/// - Ok([`TwoFactorRecoveryCodes`])
+///     - recovery codes which should be shown to the user once; only their hashes are stored
+/// - Err([`AuthError`])
+///
+/// # Errors
+/// - 400: [`AuthError::TwoFactorNotEnrolled`]
+/// - 400: [`AuthError::TwoFactorAlreadyEnabled`]
+/// - 401: [`AuthError::InvalidCode`]
+/// - 500: [`AuthError::CouldNotEnableTwoFactor`]
+pub fn confirm_two_factor(
+    db: &Database,
+    auth: &Auth,
+    item: &TwoFactorCodeInput,
+) -> Result<TwoFactorRecoveryCodes, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let user_totp = match UserTotp::read(&mut db, auth.user_id) {
+        Ok(user_totp) if user_totp.confirmed => return Err(AuthError::TwoFactorAlreadyEnabled),
+        Ok(user_totp) => user_totp,
+        Err(_) => return Err(AuthError::TwoFactorNotEnrolled),
+    };
+
+    #[allow(clippy::cast_sign_loss)]
+    let now = chrono::Utc::now().timestamp() as u64;
+
+    let Some(step) = totp::verify(&user_totp.secret, &item.code, now, user_totp.last_used_step)
+    else {
+        return Err(AuthError::InvalidCode);
+    };
+
+    let recovery_codes = totp::generate_recovery_codes();
+
+    #[allow(clippy::cast_possible_wrap)]
+    UserTotp::update(
+        &mut db,
+        auth.user_id,
+        &UserTotpChangeset {
+            user_id: auth.user_id,
+            secret: user_totp.secret,
+            confirmed: true,
+            recovery_codes: recovery_codes
+                .iter()
+                .map(|code| totp::hash_recovery_code(code))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            last_used_step: Some(step as i64),
+        },
+    )
+    .map_err(|_| AuthError::CouldNotEnableTwoFactor)?;
+
+    Ok(TwoFactorRecoveryCodes { recovery_codes })
+}
+
+/// /2fa/disable
+///
+/// disables two-factor authentication for the User associated with [`auth`](`Auth`)
+/// if the code in [`item`](`TwoFactorCodeInput`) is valid (recovery codes are accepted)
+///
+/// # Errors
+/// - 400: [`AuthError::TwoFactorNotEnabled`]
+/// - 401: [`AuthError::InvalidCode`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotDisableTwoFactor`]
+pub fn disable_two_factor(
+    db: &Database,
+    auth: &Auth,
+    item: &TwoFactorCodeInput,
+) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let user_totp = match UserTotp::read(&mut db, auth.user_id) {
+        Ok(user_totp) if user_totp.confirmed => user_totp,
+        _ => return Err(AuthError::TwoFactorNotEnabled),
+    };
+
+    if !verify_second_factor(&mut db, &user_totp, &item.code, true)? {
+        return Err(AuthError::InvalidCode);
+    }
+
+    UserTotp::delete(&mut db, auth.user_id).map_err(|_| AuthError::CouldNotDisableTwoFactor)?;
+
+    Ok(())
+}
+
+/// the relying party passkeys are registered for, derived from [`app_config.app_url`](`AppConfig`)
+fn relying_party(app_config: &AppConfig) -> Result<passkey::RelyingParty, AuthError> {
+    passkey::RelyingParty::from_app_url(&app_config.app_url).ok_or(AuthError::Internal)
+}
+
+/// stores a new challenge which has to be answered within [`passkey::TIMEOUT_MS`]
+fn create_passkey_challenge(db: &mut Connection, user_id: Option<ID>) -> Result<String, AuthError> {
+    // challenges which were never answered are forgotten once they expire
+    PasskeyChallenge::delete_expired(db, utc_now()).map_err(|_| AuthError::Internal)?;
+
+    let challenge = PasskeyChallenge::create(
+        db,
+        &PasskeyChallengeChangeset {
+            challenge: passkey::generate_challenge(),
+            user_id,
+            expires_at: utc_now() + chrono::Duration::milliseconds(i64::from(passkey::TIMEOUT_MS)),
+        },
+    )
+    .map_err(|_| AuthError::Internal)?;
+
+    Ok(challenge.challenge)
+}
+
+/// /passkeys/register/start
+///
+/// issues a challenge for registering a new passkey for the User associated with [`auth`](`Auth`),
+/// the browser's response has to be sent to [`finish_passkey_registration`]
+///
+/// # Errors
+/// - 500: [`AuthError::CouldNotFindUser`]
+/// - 500: [`AuthError::Internal`]
+pub fn start_passkey_registration(
+    db: &Database,
+    app_config: &AppConfig,
+    auth: &Auth,
+) -> Result<PasskeyOptions, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+    let rp = relying_party(app_config)?;
+
+    let Ok(user) = User::read(&mut db, auth.user_id) else {
+        return Err(AuthError::CouldNotFindUser);
+    };
+
+    // don't let the user register the same authenticator twice
+    let passkeys = UserPasskey::read_all(&mut db, auth.user_id).map_err(|_| AuthError::Internal)?;
+
+    let challenge = create_passkey_challenge(&mut db, Some(auth.user_id))?;
+
+    Ok(PasskeyOptions {
+        public_key: serde_json::json!({
+            "challenge": challenge,
+            "rp": { "id": rp.id, "name": rp.id },
+            "user": {
+                "id": data_encoding::BASE64URL_NOPAD.encode(&user.id.to_be_bytes()),
+                "name": user.email,
+                "displayName": user.email,
+            },
+            "pubKeyCredParams": [
+                { "type": "public-key", "alg": passkey::ES256 },
+                { "type": "public-key", "alg": passkey::EDDSA },
+            ],
+            "timeout": passkey::TIMEOUT_MS,
+            "attestation": "none",
+            // passkeys are the only factor of a passkey login, and they're found without an email
+            "authenticatorSelection": {
+                "residentKey": "required",
+                "requireResidentKey": true,
+                "userVerification": "required",
+            },
+            "excludeCredentials": passkeys
+                .iter()
+                .map(|passkey| serde_json::json!({ "type": "public-key", "id": passkey.credential_id }))
+                .collect::<Vec<_>>(),
+        }),
+        challenge,
+    })
+}
+
+/// /passkeys/register/finish
+///
+/// verifies the browser's response to a challenge from [`start_passkey_registration`]
+/// and stores the new passkey for the User associated with [`auth`](`Auth`)
+///
+/// # Errors
+/// - 400: [`AuthError::NameTooLong`]
+/// - 400: [`AuthError::InvalidChallenge`]
+/// - 400: [`AuthError::InvalidPasskeyRegistration`]
+/// - 400: [`AuthError::PasskeyAlreadyRegistered`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotRegisterPasskey`]
+pub fn finish_passkey_registration(
+    db: &Database,
+    app_config: &AppConfig,
+    auth: &Auth,
+    item: &PasskeyRegisterInput,
+) -> Result<UserPasskeyJson, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+    let rp = relying_party(app_config)?;
+
+    if item.name.as_ref().is_some_and(|name| name.len() > 256) {
+        return Err(AuthError::NameTooLong);
+    }
+
+    match PasskeyChallenge::consume(&mut db, &item.challenge) {
+        Ok(Some(challenge)) if challenge.user_id == Some(auth.user_id) => {}
+        Ok(_) => return Err(AuthError::InvalidChallenge),
+        Err(_) => return Err(AuthError::Internal),
+    }
+
+    let Ok(credential) = passkey::verify_registration(
+        &rp,
+        &item.challenge,
+        &item.client_data_json,
+        &item.attestation_object,
+    ) else {
+        return Err(AuthError::InvalidPasskeyRegistration);
+    };
+
+    match UserPasskey::find_by_credential_id(&mut db, &credential.credential_id).optional() {
+        Ok(None) => {}
+        Ok(Some(_)) => return Err(AuthError::PasskeyAlreadyRegistered),
+        Err(_) => return Err(AuthError::Internal),
+    }
+
+    let user_passkey = UserPasskey::create(
+        &mut db,
+        &UserPasskeyChangeset {
+            user_id: auth.user_id,
+            credential_id: credential.credential_id,
+            public_key: credential.public_key,
+            sign_count: i64::from(credential.sign_count),
+            name: item.name.clone(),
+            last_used_at: None,
+        },
+    )
+    .map_err(|_| AuthError::CouldNotRegisterPasskey)?;
+
+    Ok(UserPasskeyJson::from(&user_passkey))
+}
+
+/// /passkeys/login/start
+///
+/// issues a challenge for logging in with a passkey, the browser's response has to be sent
+/// to [`finish_passkey_login`]
+///
+/// the browser lets the user pick any passkey they have for this site (a discoverable credential),
+/// whatever [`item.email`](`PasskeyLoginStartInput`) is, so this can't be used to find out which
+/// emails have accounts or passkeys
+///
+/// # Errors
+/// - 500: [`AuthError::Internal`]
+pub fn start_passkey_login(
+    db: &Database,
+    app_config: &AppConfig,
+    _item: &PasskeyLoginStartInput,
+) -> Result<PasskeyOptions, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+    let rp = relying_party(app_config)?;
+
+    let challenge = create_passkey_challenge(&mut db, None)?;
+
+    Ok(PasskeyOptions {
+        public_key: serde_json::json!({
+            "challenge": challenge,
+            "rpId": rp.id,
+            "timeout": passkey::TIMEOUT_MS,
+            "userVerification": "required",
+            "allowCredentials": [],
+        }),
+        challenge,
+    })
+}
+
+/// /passkeys/login/finish
+///
+/// creates a user session for the owner of the passkey which answered a challenge from [`start_passkey_login`]
+///
+/// # Returns [`Result`]
+/// - Ok([`AccessToken`], [`RefreshToken`])
+///     - an access token that should be sent to the user in the response body,
+///     - a reset token that should be sent as a secure, http-only, and `same_site=strict` cookie.
+/// - Err([`AuthError`])
+///
+/// a passkey proves possession of the authenticator, and the authenticator has to have verified
+/// the user (with a PIN or biometrics, see [`passkey::verify_assertion`]), so no second factor is asked for;
+/// invalid assertions count as failed login attempts from the `client`, see [`login`]
+///
+/// # Errors
+/// - 400: [`AuthError::DeviceTooLong`]
+/// - 400: [`AuthError::AccountNotActivated`]
+/// - 401: [`AuthError::InvalidPasskey`]
+/// - 429: [`AuthError::TooManyFailedLogins`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotCreateSession`]
+pub fn finish_passkey_login(
+    db: &Database,
+    app_config: &AppConfig,
+    auth_config: &AuthConfig,
+    mailer: &Mailer,
+    item: &PasskeyLoginInput,
+    client: &ClientInfo,
+) -> Result<(AccessToken, RefreshToken), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+    let rp = relying_party(app_config)?;
+
+    check_ip_throttle(&mut db, auth_config, client.ip_address.as_deref())?;
+
+    if item
+        .device
+        .as_ref()
+        .is_some_and(|device| device.len() > 256)
+    {
+        return Err(AuthError::DeviceTooLong);
+    }
+
+    let challenge = match PasskeyChallenge::consume(&mut db, &item.challenge) {
+        Ok(Some(challenge)) => challenge,
+        Ok(None) => return Err(AuthError::InvalidPasskey),
+        Err(_) => return Err(AuthError::Internal),
+    };
+
+    let user_passkey = match UserPasskey::find_by_credential_id(&mut db, &item.credential_id)
+        .optional()
+    {
+        Ok(Some(user_passkey))
+            if challenge.user_id.is_none() || challenge.user_id == Some(user_passkey.user_id) =>
+        {
+            user_passkey
+        }
+        Ok(_) => {
+            register_failed_login(
+                &mut db,
+                auth_config,
+                mailer,
+                None,
+                client.ip_address.as_deref(),
+            )?;
+            return Err(AuthError::InvalidPasskey);
+        }
+        Err(_) => return Err(AuthError::Internal),
+    };
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let Ok(sign_count) = passkey::verify_assertion(
+        &rp,
+        &item.challenge,
+        &user_passkey.public_key,
+        user_passkey.sign_count as u32,
+        &item.client_data_json,
+        &item.authenticator_data,
+        &item.signature,
+    ) else {
+        register_failed_login(
+            &mut db,
+            auth_config,
+            mailer,
+            None,
+            client.ip_address.as_deref(),
+        )?;
+        return Err(AuthError::InvalidPasskey);
+    };
+
+    let user = match User::read(&mut db, user_passkey.user_id) {
+        Ok(user) if user.activated => user,
+        Ok(_) => return Err(AuthError::AccountNotActivated),
+        Err(_) => return Err(AuthError::Internal),
+    };
+
+    check_lockout(&mut db, user.id)?;
+
+    UserPasskey::mark_used(&mut db, user_passkey.id, i64::from(sign_count))
+        .map_err(|_| AuthError::Internal)?;
+
+    clear_failed_logins(&mut db, user.id)?;
+
+    create_user_session(
+        &mut db,
+        auth_config,
+        Some(mailer),
+        client,
+        item.device.clone(),
+        None,
+        user.id,
+    )
+}
+
+/// /passkeys
+///
+/// queries [`db`](`Database`) for all passkeys registered by the User
+/// associated with [`auth`](`Auth`)
+///
+/// # Errors
+/// - 500: [`AuthError::CouldNotFetchPasskeys`]
+pub fn get_passkeys(db: &Database, auth: &Auth) -> Result<UserPasskeyResponse, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let Ok(passkeys) = UserPasskey::read_all(&mut db, auth.user_id) else {
+        return Err(AuthError::CouldNotFetchPasskeys);
+    };
+
+    Ok(UserPasskeyResponse {
+        passkeys: passkeys.iter().map(UserPasskeyJson::from).collect(),
+    })
+}
+
+/// /passkeys/{id}
+///
+/// deletes the entry in the `user_passkeys` table with the specified [`item_id`](`ID`) from
+/// [`db`](`Database`) if it's owned by the User associated with [`auth`](`Auth`)
+///
+/// # Errors
+/// - 404: [`AuthError::PasskeyNotFound`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotDeletePasskey`]
+pub fn destroy_passkey(db: &Database, auth: &Auth, item_id: ID) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    match UserPasskey::read(&mut db, item_id).optional() {
+        Ok(Some(user_passkey)) if user_passkey.user_id == auth.user_id => {}
+        Ok(_) => return Err(AuthError::PasskeyNotFound),
+        Err(_) => return Err(AuthError::Internal),
+    }
 
-    UserSession::delete_all_for_user(&mut db, auth.user_id)
-        .map_err(|_| (500, "Could not delete sessions."))?;
+    UserPasskey::delete(&mut db, item_id).map_err(|_| AuthError::CouldNotDeletePasskey)?;
 
     Ok(())
 }
 
-type AccessToken = String;
-type RefreshToken = String;
-
-/// /login
+/// /api-keys
 ///
-/// creates a user session for the user associated with [`item`](`LoginInput`)
-/// in the request body (have the `content-type` header set to `application/json` and content that can be deserialized into [`LoginInput`])
+/// creates an API key for the User associated with [`auth`](`Auth`), which machine clients
+/// can send in the `Authorization: Bearer` header in place of an access token (see [`Auth::from_api_key`])
 ///
 /// # Returns [`Result`]
-/// - Ok([`AccessToken`], [`RefreshToken`])
-///     - an access token that should be sent to the user in the response body,
-///     - a reset token that should be sent as a secure, http-only, and `same_site=strict` cookie.
-/// - Err([`StatusCode`], [`Message`])
+/// - Ok([`ApiKeyCreated`])
+///     - the key, which can't be retrieved again, and its details
+/// - Err([`AuthError`])
 ///
 /// # Errors
-/// - 400: 'device' cannot be longer than 256 characters.
-/// - 400: Account has not been activated.
-/// - 401: Invalid credentials.
-///
-/// # Panics
-/// - could not connect to database
-/// - verifying the password hash fails
-///
-/// TODO: neither of these should panic, just return an error
-pub fn login(
+/// - 400: [`AuthError::InvalidName`]
+/// - 400: [`AuthError::InvalidPermission`]
+/// - 400: [`AuthError::ExpiryInPast`]
+/// - 403: [`AuthError::ApiKeyNotAllowed`]
+/// - 500: [`AuthError::CouldNotCreateApiKey`]
+pub fn create_api_key(
     db: &Database,
-    item: &LoginInput,
-) -> Result<(AccessToken, RefreshToken), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+    auth: &Auth,
+    item: &ApiKeyInput,
+) -> Result<ApiKeyCreated, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    // otherwise a limited key could create an unlimited one
+    if auth.api_key_id.is_some() {
+        return Err(AuthError::ApiKeyNotAllowed);
+    }
 
-    // verify device
-    let device = match item.device {
-        Some(ref device) if device.len() > 256 => {
-            return Err((400, "'device' cannot be longer than 256 characters."));
+    if item.name.is_empty() || item.name.len() > 256 {
+        return Err(AuthError::InvalidName);
+    }
+
+    if let Some(ref permissions) = item.permissions {
+        if permissions
+            .iter()
+            .any(|permission| permission.is_empty() || permission.contains('\n'))
+        {
+            return Err(AuthError::InvalidPermission);
         }
-        Some(ref device) => Some(device.clone()),
-        None => None,
-    };
+    }
 
-    let user = match User::find_by_email(&mut db, item.email.clone()) {
-        Ok(user) if user.activated => user,
-        Ok(_) => return Err((400, "Account has not been activated.")),
-        Err(_) => return Err((401, "Invalid credentials.")),
-    };
+    if item
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= utc_now())
+    {
+        return Err(AuthError::ExpiryInPast);
+    }
+
+    let key = UserApiKey::generate_key();
 
-    let is_valid = argon2::verify_encoded_ext(
-        &user.hash_password,
-        item.password.as_bytes(),
-        ARGON_CONFIG.secret,
-        ARGON_CONFIG.ad,
+    let api_key = UserApiKey::create(
+        &mut db,
+        &UserApiKeyChangeset {
+            user_id: auth.user_id,
+            name: item.name.clone(),
+            key_prefix: key.chars().take(API_KEY_PREFIX.len() + 8).collect(),
+            key_hash: UserApiKey::hash_key(&key),
+            permissions: item
+                .permissions
+                .as_ref()
+                .map(|permissions| permissions.join("\n")),
+            last_used_at: None,
+            expires_at: item.expires_at,
+        },
     )
-    .unwrap();
+    .map_err(|_| AuthError::CouldNotCreateApiKey)?;
 
-    if !is_valid {
-        return Err((401, "Invalid credentials."));
-    }
+    Ok(ApiKeyCreated {
+        key,
+        api_key: UserApiKeyJson::from(&api_key),
+    })
+}
+
+/// /api-keys
+///
+/// queries [`db`](`Database`) for all API keys owned by the User
+/// associated with [`auth`](`Auth`)
+///
+/// # Errors
+/// - 500: [`AuthError::CouldNotFetchApiKeys`]
+pub fn get_api_keys(db: &Database, auth: &Auth) -> Result<UserApiKeyResponse, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let Ok(api_keys) = UserApiKey::read_all(&mut db, auth.user_id) else {
+        return Err(AuthError::CouldNotFetchApiKeys);
+    };
 
-    create_user_session(&mut db, device, None, user.id)
+    Ok(UserApiKeyResponse {
+        api_keys: api_keys.iter().map(UserApiKeyJson::from).collect(),
+    })
 }
 
-// TODO: Wrap this in a database transaction
-/// create a user session for the user with [`user_id`](`i32`)
+/// /api-keys/{id}
+///
+/// revokes the API key with the specified [`item_id`](`ID`) if it's owned
+/// by the User associated with [`auth`](`Auth`)
 ///
 /// # Errors
-/// - 400: 'device' cannot be longer than 256 characters.
-/// - 500: An internal server error occurred.
-/// - 500: Could not create session.
+/// - 403: [`AuthError::ApiKeyNotAllowed`]
+/// - 404: [`AuthError::ApiKeyNotFound`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotDeleteApiKey`]
+pub fn destroy_api_key(db: &Database, auth: &Auth, item_id: ID) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    if auth.api_key_id.is_some() {
+        return Err(AuthError::ApiKeyNotAllowed);
+    }
+
+    match UserApiKey::read(&mut db, item_id).optional() {
+        Ok(Some(api_key)) if api_key.user_id == auth.user_id => {}
+        Ok(_) => return Err(AuthError::ApiKeyNotFound),
+        Err(_) => return Err(AuthError::Internal),
+    }
+
+    UserApiKey::delete(&mut db, item_id).map_err(|_| AuthError::CouldNotDeleteApiKey)?;
+
+    Ok(())
+}
+
+// TODO: Wrap this in a database transaction
+/// create a user session for the user with [`user_id`](`i32`), used from [`client`](`ClientInfo`)
 ///
-/// # Panics
-/// - could not connect to database
-/// - could not get `SECRET_KEY` from environment
+/// when [`auth_config.notify_new_device`](`AuthConfig::notify_new_device`) is set and a `mailer` is given,
+/// the user is emailed if none of their sessions has the same [device name](`crate::auth::user_agent::device_name`)
 ///
-/// TODO: don't panic if db connection fails, just return an error
+/// # Errors
+/// - 400: [`AuthError::DeviceTooLong`]
+/// - 500: [`AuthError::Internal`]
+/// - 500: [`AuthError::CouldNotCreateSession`]
 pub fn create_user_session(
     db: &mut Connection,
+    auth_config: &AuthConfig,
+    mailer: Option<&Mailer>,
+    client: &ClientInfo,
     device_type: Option<String>,
     ttl: Option<i64>,
     user_id: i32,
-) -> Result<(AccessToken, RefreshToken), (StatusCode, Message)> {
+) -> Result<(AccessToken, RefreshToken), AuthError> {
     // verify device
     let device = match device_type {
         Some(device) if device.len() > 256 => {
-            return Err((400, "'device' cannot be longer than 256 characters."));
+            return Err(AuthError::DeviceTooLong);
         }
         Some(device) => Some(device),
         None => None,
     };
 
     let Ok(permissions) = Permission::fetch_all(db, user_id) else {
-        return Err((500, "An internal server error occurred."));
+        return Err(AuthError::Internal);
     };
 
     let Ok(roles) = Role::fetch_all(db, user_id) else {
-        return Err((500, "An internal server error occurred."));
+        return Err(AuthError::Internal);
     };
 
-    let access_token_duration = chrono::Duration::seconds(
-        ttl.map_or_else(|| /* 15 minutes */ 15 * 60, |tt| std::cmp::max(tt, 1)),
-    );
+    let access_token_duration = ttl.map_or(auth_config.access_token_lifetime, |tt| {
+        chrono::Duration::seconds(std::cmp::max(tt, 1))
+    });
 
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     let access_token_claims = AccessTokenClaims {
@@ -327,24 +1611,37 @@ pub fn create_user_session(
 
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     let refresh_token_claims = RefreshTokenClaims {
-        exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
+        exp: (chrono::Utc::now() + auth_config.refresh_token_lifetime).timestamp() as usize,
         sub: user_id,
         token_type: "refresh_token".to_string(),
+        jti: generate_token_id(),
+        scope: None,
     };
 
-    let access_token = encode(
-        &Header::default(),
-        &access_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
+    let access_token = encode_token(auth_config.keys.as_ref(), &access_token_claims)
+        .map_err(|_| AuthError::Internal)?;
 
-    let refresh_token = encode(
-        &Header::default(),
-        &refresh_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
+    let refresh_token = encode_token(auth_config.keys.as_ref(), &refresh_token_claims)
+        .map_err(|_| AuthError::Internal)?;
+
+    let new_device_name = match (mailer, client.user_agent.as_deref()) {
+        (Some(_), Some(agent)) if auth_config.notify_new_device => {
+            let device_name = user_agent::device_name(agent);
+            let Ok(sessions) = UserSession::read_all_for_user(db, user_id) else {
+                return Err(AuthError::Internal);
+            };
+
+            let is_known = sessions.iter().any(|session| {
+                session
+                    .user_agent
+                    .as_deref()
+                    .is_some_and(|agent| user_agent::device_name(agent) == device_name)
+            });
+
+            (!is_known).then_some(device_name)
+        }
+        _ => None,
+    };
 
     UserSession::create(
         db,
@@ -352,9 +1649,28 @@ pub fn create_user_session(
             user_id,
             refresh_token: refresh_token.clone(),
             device,
+            family: generate_token_id(),
+            rotated: false,
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.as_deref().map(user_agent::truncate),
+            oauth_client_id: None,
+            logged_in_at: utc_now(),
         },
     )
-    .map_err(|_| (500, "Could not create session."))?;
+    .map_err(|_| AuthError::CouldNotCreateSession)?;
+
+    if let (Some(mailer), Some(device_name)) = (mailer, new_device_name) {
+        if let Ok(user) = User::read(db, user_id) {
+            // queued, so logging in doesn't wait for the mail server
+            let mailer = &localized(mailer, user.locale.as_deref()).with_fire_and_forget(true);
+            mailer.templates.send_new_device_login(
+                mailer,
+                &user.email,
+                &device_name,
+                client.ip_address.as_deref().unwrap_or("an unknown address"),
+            );
+        }
+    }
 
     Ok((access_token, refresh_token))
 }
@@ -363,26 +1679,24 @@ pub fn create_user_session(
 /// If this is successful, delete the cookie storing the refresh token
 ///
 /// # Errors
-/// - 401: Invalid session
-/// - 401: Invalid token
-/// - 500: Could not delete session
-///
-/// # Panics
-/// - could not connect to database
-///
-/// TODO: don't panic if db connection fails, just return an error
-pub fn logout(db: &Database, refresh_token: Option<&'_ str>) -> Result<(), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+/// - 401: [`AuthError::InvalidSession`]
+/// - 401: [`AuthError::InvalidToken`]
+/// - 500: [`AuthError::CouldNotDeleteSession`]
+pub fn logout(db: &Database, refresh_token: Option<&'_ str>) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
     let Some(refresh_token) = refresh_token else {
-        return Err((401, "Invalid session."));
+        return Err(AuthError::InvalidSession);
     };
 
     let Ok(session) = UserSession::find_by_refresh_token(&mut db, refresh_token) else {
-        return Err((401, "Invalid session."));
+        return Err(AuthError::InvalidSession);
     };
 
-    UserSession::delete(&mut db, session.id).map_err(|_| (500, "Could not delete session."))?;
+    UserSession::delete_family(&mut db, &session.family)
+        .map_err(|_| AuthError::CouldNotDeleteSession)?;
 
     Ok(())
 }
@@ -391,104 +1705,209 @@ pub fn logout(db: &Database, refresh_token: Option<&'_ str>) -> Result<(), (Stat
 ///
 /// refreshes the user session associated with the clients `refresh_token` cookie
 ///
+/// the refresh token is rotated: the old one can't be used again, and presenting it again
+/// revokes every session descended from the same login (see [`UserSession::family`]);
+/// the new entry records the [`client`](`ClientInfo`) the session was refreshed from
+///
 /// # Returns [`Result`]
 /// - Ok([`AccessToken`], [`RefreshToken`])
 ///     - an access token that should be sent to the user in the response body,
 ///     - a reset token that should be sent as a secure, http-only, and `same_site=strict` cookie.
-/// - Err([`StatusCode`], [`Message`])
+/// - Err([`AuthError`])
 ///
 /// # Errors
-/// - 401: Invalid session
-/// - 401: Invalid token
-/// - 500: Could not update session
-/// - 500: An internal server error occurred
-///
-/// # Panics
-/// - could not connect to database
-/// - could not get `SECRET_KEY` from environment
-///
-/// TODO: don't panic if db connection fails, just return an error
+/// - 401: [`AuthError::InvalidSession`]
+/// - 401: [`AuthError::InvalidToken`]
+/// - 500: [`AuthError::CouldNotUpdateSession`]
+/// - 500: [`AuthError::Internal`]
 pub fn refresh(
     db: &Database,
+    auth_config: &AuthConfig,
     refresh_token_str: Option<&'_ str>,
-) -> Result<(AccessToken, RefreshToken), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
-
-    let Some(refresh_token_str) = refresh_token_str else {
-        return Err((401, "Invalid session."));
-    };
-
-    let _refresh_token = match decode::<RefreshTokenClaims>(
-        refresh_token_str,
-        &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        &Validation::default(),
-    ) {
-        Ok(token)
-            if token
-                .claims
-                .token_type
-                .eq_ignore_ascii_case("refresh_token") =>
-        {
-            token
-        }
-        _ => return Err((401, "Invalid token.")),
-    };
+    client: &ClientInfo,
+) -> Result<(AccessToken, RefreshToken), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
-    let Ok(session) = UserSession::find_by_refresh_token(&mut db, refresh_token_str) else {
-        return Err((401, "Invalid session."));
-    };
+    let (user_id, _, refresh_token_str) =
+        rotate_session(&mut db, auth_config, refresh_token_str, None, client)?;
 
-    let Ok(permissions) = Permission::fetch_all(&mut db, session.user_id) else {
-        return Err((500, "An internal server error occurred."));
+    let Ok(permissions) = Permission::fetch_all(&mut db, user_id) else {
+        return Err(AuthError::Internal);
     };
 
-    let Ok(roles) = Role::fetch_all(&mut db, session.user_id) else {
-        return Err((500, "An internal server error occurred."));
+    let Ok(roles) = Role::fetch_all(&mut db, user_id) else {
+        return Err(AuthError::Internal);
     };
 
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     let access_token_claims = AccessTokenClaims {
-        exp: (chrono::Utc::now() + chrono::Duration::minutes(15)).timestamp() as usize,
-        sub: session.user_id,
+        exp: (chrono::Utc::now() + auth_config.access_token_lifetime).timestamp() as usize,
+        sub: user_id,
         token_type: "access_token".to_string(),
         roles,
         permissions,
     };
 
+    let access_token = encode_token(auth_config.keys.as_ref(), &access_token_claims)
+        .map_err(|_| AuthError::Internal)?;
+
+    Ok((access_token, refresh_token_str))
+}
+
+/// the scope granted to the [`OAuthClient`] a session belongs to
+type Scope = String;
+
+/// exchanges `refresh_token_str` for a new refresh token, see [`refresh`]
+///
+/// the session has to belong to the [`OAuthClient`] whose id is `oauth_client_id`,
+/// or to the app's own frontend if it's `None`, so clients can't refresh each other's sessions
+///
+/// returns the id of the session's User, the scope granted to its client, and the new refresh token
+///
+/// # Errors
+/// - 401: [`AuthError::InvalidSession`]
+/// - 401: [`AuthError::InvalidToken`]
+/// - 500: [`AuthError::CouldNotUpdateSession`]
+/// - 500: [`AuthError::Internal`]
+pub(crate) fn rotate_session(
+    db: &mut Connection,
+    auth_config: &AuthConfig,
+    refresh_token_str: Option<&'_ str>,
+    oauth_client_id: Option<ID>,
+    client: &ClientInfo,
+) -> Result<(ID, Option<Scope>, RefreshToken), AuthError> {
+    let Some(refresh_token_str) = refresh_token_str else {
+        return Err(AuthError::InvalidSession);
+    };
+
+    let refresh_token =
+        match decode_token::<RefreshTokenClaims>(auth_config.keys.as_ref(), refresh_token_str) {
+            Ok(token)
+                if token
+                    .claims
+                    .token_type
+                    .eq_ignore_ascii_case("refresh_token") =>
+            {
+                token
+            }
+            _ => return Err(AuthError::InvalidToken),
+        };
+
+    let Ok(session) = UserSession::find_by_refresh_token(db, refresh_token_str) else {
+        return Err(AuthError::InvalidSession);
+    };
+    if session.oauth_client_id != oauth_client_id {
+        return Err(AuthError::InvalidSession);
+    }
+
+    // a refresh token can only be exchanged once; if it's presented again, either the
+    // legitimate client or whoever stole it is using an old token, so we revoke the whole family
+    let is_first_use = UserSession::mark_rotated(db, session.id)
+        .map_err(|_| AuthError::CouldNotUpdateSession)?
+        == 1;
+
+    if !is_first_use {
+        UserSession::delete_family(db, &session.family)
+            .map_err(|_| AuthError::CouldNotUpdateSession)?;
+
+        return Err(AuthError::InvalidSession);
+    }
+
+    // rotated sessions are only kept for as long as their refresh token could be presented
+    UserSession::delete_rotated_before(db, utc_now() - auth_config.refresh_token_lifetime)
+        .map_err(|_| AuthError::CouldNotUpdateSession)?;
+
+    let scope = refresh_token.claims.scope;
+
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     let refresh_token_claims = RefreshTokenClaims {
-        exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
+        exp: (chrono::Utc::now() + auth_config.refresh_token_lifetime).timestamp() as usize,
         sub: session.user_id,
         token_type: "refresh_token".to_string(),
+        jti: generate_token_id(),
+        scope: scope.clone(),
     };
 
-    let access_token = encode(
-        &Header::default(),
-        &access_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
-
-    let refresh_token_str = encode(
-        &Header::default(),
-        &refresh_token_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
+    let refresh_token_str = encode_token(auth_config.keys.as_ref(), &refresh_token_claims)
+        .map_err(|_| AuthError::Internal)?;
 
-    // update session with the new refresh token
-    UserSession::update(
-        &mut db,
-        session.id,
+    // continue the session in a new entry of the same family
+    UserSession::create(
+        db,
         &UserSessionChangeset {
             user_id: session.user_id,
             refresh_token: refresh_token_str.clone(),
             device: session.device,
+            family: session.family,
+            rotated: false,
+            ip_address: client.ip_address.clone().or(session.ip_address),
+            user_agent: client
+                .user_agent
+                .as_deref()
+                .map(user_agent::truncate)
+                .or(session.user_agent),
+            oauth_client_id: session.oauth_client_id,
+            logged_in_at: session.logged_in_at,
         },
     )
-    .map_err(|_| (500, "Could not update session."))?;
+    .map_err(|_| AuthError::CouldNotUpdateSession)?;
 
-    Ok((access_token, refresh_token_str))
+    Ok((session.user_id, scope, refresh_token_str))
+}
+
+/// starts a session of the User whose id is `user_id` for the [`OAuthClient`]
+/// `oauth_client`, which was granted `scope`, see [`rotate_session`]
+///
+/// returns the session's refresh token, the client's access tokens are issued by the
+/// [`authorization_server`](`super::authorization_server`)
+///
+/// # Errors
+/// - 500: [`AuthError::CouldNotCreateSession`]
+/// - 500: [`AuthError::Internal`]
+pub(crate) fn create_client_session(
+    db: &mut Connection,
+    auth_config: &AuthConfig,
+    oauth_client: &OAuthClient,
+    scope: &str,
+    user_id: ID,
+) -> Result<RefreshToken, AuthError> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let refresh_token_claims = RefreshTokenClaims {
+        exp: (chrono::Utc::now() + auth_config.refresh_token_lifetime).timestamp() as usize,
+        sub: user_id,
+        token_type: "refresh_token".to_string(),
+        jti: generate_token_id(),
+        scope: Some(scope.to_string()),
+    };
+
+    let refresh_token = encode_token(auth_config.keys.as_ref(), &refresh_token_claims)
+        .map_err(|_| AuthError::Internal)?;
+
+    // the client's backend is the one asking for the tokens, not the user's browser
+    UserSession::create(
+        db,
+        &UserSessionChangeset {
+            user_id,
+            refresh_token: refresh_token.clone(),
+            device: Some(
+                format!("OAuth2 client - {}", oauth_client.name)
+                    .chars()
+                    .take(256)
+                    .collect(),
+            ),
+            family: generate_token_id(),
+            rotated: false,
+            ip_address: None,
+            user_agent: None,
+            oauth_client_id: Some(oauth_client.id),
+            logged_in_at: utc_now(),
+        },
+    )
+    .map_err(|_| AuthError::CouldNotCreateSession)?;
+
+    Ok(refresh_token)
 }
 
 /// /register
@@ -500,31 +1919,41 @@ pub fn refresh(
 /// that email address
 ///
 /// # Errors
-/// - 400: Already registered
-///
-/// # Panics
-/// - could not connect to database
-/// - could not get `SECRET_KEY` from environment
-/// - any of the database operations fail
-///
-/// TODO: don't panic if db connection fails, just return an error
+/// - 400: [`AuthError::AlreadyRegistered`]
+/// - 400: [`AuthError::InvalidLocale`]
+/// - 400: [`AuthError::PasswordPolicy`]
+/// - 500: [`AuthError::CouldNotCheckPassword`]
+/// - 500: [`AuthError::Internal`]
 pub fn register(
     db: &Database,
+    auth_config: &AuthConfig,
     item: &RegisterInput,
     mailer: &Mailer,
-) -> Result<(), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let existing_user = User::find_by_email(&mut db, item.email.to_string()).ok();
+    if existing_user.as_ref().is_some_and(|user| user.activated) {
+        return Err(AuthError::AlreadyRegistered);
+    }
 
-    match User::find_by_email(&mut db, item.email.to_string()) {
-        Ok(user) if user.activated => return Err((400, "Already registered.")),
-        Ok(user) => {
-            User::delete(&mut db, user.id).unwrap();
-        }
-        Err(_) => (),
+    if item
+        .locale
+        .as_deref()
+        .is_some_and(|locale| !crate::is_valid_locale(locale))
+    {
+        return Err(AuthError::InvalidLocale);
+    }
+
+    check_password_policy(auth_config, &item.password, &item.email)?;
+
+    if let Some(user) = existing_user {
+        User::delete(&mut db, user.id).map_err(|_| AuthError::Internal)?;
     }
 
-    let salt = generate_salt();
-    let hash = argon2::hash_encoded(item.password.as_bytes(), &salt, &ARGON_CONFIG).unwrap();
+    let hash = hash_password(&item.password)?;
 
     let user = User::create(
         &mut db,
@@ -534,7 +1963,11 @@ pub fn register(
             hash_password: hash,
         },
     )
-    .unwrap();
+    .and_then(|user| match &item.locale {
+        Some(locale) => User::set_locale(&mut db, user.id, Some(locale)),
+        None => Ok(user),
+    })
+    .map_err(|_| AuthError::Internal)?;
 
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     let registration_claims = RegistrationClaims {
@@ -543,13 +1976,10 @@ pub fn register(
         token_type: "activation_token".to_string(),
     };
 
-    let token = encode(
-        &Header::default(),
-        &registration_claims,
-        &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-    )
-    .unwrap();
+    let token = encode_token(auth_config.keys.as_ref(), &registration_claims)
+        .map_err(|_| AuthError::Internal)?;
 
+    let mailer = &localized(mailer, user.locale.as_deref());
     mailer
         .templates
         .send_register(mailer, &user.email, &format!("activate?token={token}"));
@@ -562,44 +1992,38 @@ pub fn register(
 /// activates the account associated with the token in [`item`](`ActivationInput`)
 ///
 /// # Errors
-/// - 401: Invalid token
-/// - 401: Invalid token
-/// - 400: Invalid token
-/// - 200: Already activated!
-/// - 500: Could not activate user
-///
-/// # Panics
-/// - could not connect to database
-/// - could not get `SECRET_KEY` from environment
-///
-/// TODO: don't panic if db connection fails, just return an error
+/// - 401: [`AuthError::InvalidToken`]
+/// - 401: [`AuthError::InvalidToken`]
+/// - 400: [`AuthError::AlreadyActivated`]
+/// - 500: [`AuthError::CouldNotActivateUser`]
 pub fn activate(
     db: &Database,
+    auth_config: &AuthConfig,
     item: &ActivationInput,
     mailer: &Mailer,
-) -> Result<(), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
-    let token = match decode::<RegistrationClaims>(
-        &item.activation_token,
-        &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        &Validation::default(),
-    ) {
-        Ok(token)
-            if token
-                .claims
-                .token_type
-                .eq_ignore_ascii_case("activation_token") =>
+    let token =
+        match decode_token::<RegistrationClaims>(auth_config.keys.as_ref(), &item.activation_token)
         {
-            token
-        }
-        _ => return Err((401, "Invalid token.")),
-    };
+            Ok(token)
+                if token
+                    .claims
+                    .token_type
+                    .eq_ignore_ascii_case("activation_token") =>
+            {
+                token
+            }
+            _ => return Err(AuthError::InvalidToken),
+        };
 
     let user = match User::read(&mut db, token.claims.sub) {
         Ok(user) if !user.activated => user,
-        Ok(_) => return Err((200, "Already activated!")),
-        Err(_) => return Err((400, "Invalid token.")),
+        Ok(_) => return Err(AuthError::AlreadyActivated),
+        Err(_) => return Err(AuthError::InvalidToken),
     };
 
     User::update(
@@ -611,8 +2035,9 @@ pub fn activate(
             hash_password: user.hash_password,
         },
     )
-    .map_err(|_| (500, "Could not activate user."))?;
+    .map_err(|_| AuthError::CouldNotActivateUser)?;
 
+    let mailer = &localized(mailer, user.locale.as_deref());
     mailer.templates.send_activated(mailer, &user.email);
 
     Ok(())
@@ -628,20 +2053,19 @@ pub fn activate(
 /// no accound accosiated with the email address)
 ///
 /// # Errors
-/// - None
+/// - 500: [`AuthError::Internal`]
 ///
 /// # Panics
-/// - could not connect to database
 /// - current timestamp could not be converted from `i64` to `usize`
-/// - could not get `SECRET_KEY` from environment
-///
-/// TODO: don't panic if db connection fails, just return an error
 pub fn forgot_password(
     db: &Database,
+    auth_config: &AuthConfig,
     item: &ForgotInput,
     mailer: &Mailer,
-) -> Result<(), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
     let user_result = User::find_by_email(&mut db, item.email.clone());
 
@@ -657,14 +2081,11 @@ pub fn forgot_password(
             token_type: "reset_token".to_string(),
         };
 
-        let reset_token = encode(
-            &Header::default(),
-            &reset_token_claims,
-            &EncodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        )
-        .unwrap();
+        let reset_token = encode_token(auth_config.keys.as_ref(), &reset_token_claims)
+            .map_err(|_| AuthError::Internal)?;
 
         let link = &format!("reset?token={reset_token}");
+        let mailer = &localized(mailer, user.locale.as_deref());
         mailer
             .templates
             .send_recover_existent_account(mailer, &user.email, link);
@@ -684,55 +2105,48 @@ pub fn forgot_password(
 /// from [`item.old_password`](`ChangeInput`) to [`item.new_password`](`ChangeInput`)
 ///
 /// # Errors
-/// - 400: Missing password
-/// - 400: The new password must be different
-/// - 400: Account has not been activated
-/// - 401: Invalid credentials
-/// - 500: Could not update password
-/// - 500: Could not find user
-///
-/// # Panics
-/// - could not connect to database
-/// - could not get `SECRET_KEY` from environment
-///
-/// TODO: don't panic if db connection fails, just return an error
+/// - 400: [`AuthError::MissingPassword`]
+/// - 400: [`AuthError::PasswordUnchanged`]
+/// - 400: [`AuthError::AccountNotActivated`]
+/// - 400: [`AuthError::PasswordPolicy`]
+/// - 401: [`AuthError::InvalidCredentials`]
+/// - 500: [`AuthError::CouldNotCheckPassword`]
+/// - 500: [`AuthError::CouldNotUpdatePassword`]
+/// - 500: [`AuthError::CouldNotFindUser`]
 pub fn change_password(
     db: &Database,
+    auth_config: &AuthConfig,
     item: &ChangeInput,
     auth: &Auth,
     mailer: &Mailer,
-) -> Result<(), (StatusCode, Message)> {
+) -> Result<(), AuthError> {
     if item.old_password.is_empty() || item.new_password.is_empty() {
-        return Err((400, "Missing password"));
+        return Err(AuthError::MissingPassword);
     }
 
     if item.old_password.eq(&item.new_password) {
-        return Err((400, "The new password must be different"));
+        return Err(AuthError::PasswordUnchanged);
     }
 
-    let mut db = db.get_connection().unwrap();
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
     let user = match User::read(&mut db, auth.user_id) {
         Ok(user) if user.activated => user,
-        Ok(_) => return Err((400, "Account has not been activated")),
-        Err(_) => return Err((500, "Could not find user")),
+        Ok(_) => return Err(AuthError::AccountNotActivated),
+        Err(_) => return Err(AuthError::CouldNotFindUser),
     };
 
-    let is_old_password_valid = argon2::verify_encoded_ext(
-        &user.hash_password,
-        item.old_password.as_bytes(),
-        ARGON_CONFIG.secret,
-        ARGON_CONFIG.ad,
-    )
-    .unwrap();
+    let is_old_password_valid = verify_password(&user.hash_password, &item.old_password)?;
 
     if !is_old_password_valid {
-        return Err((401, "Invalid credentials"));
+        return Err(AuthError::InvalidCredentials);
     }
 
-    let salt = generate_salt();
-    let new_hash =
-        argon2::hash_encoded(item.new_password.as_bytes(), &salt, &ARGON_CONFIG).unwrap();
+    check_password_policy(auth_config, &item.new_password, &user.email)?;
+
+    let new_hash = hash_password(&item.new_password)?;
 
     User::update(
         &mut db,
@@ -743,65 +2157,216 @@ pub fn change_password(
             activated: user.activated,
         },
     )
-    .map_err(|_| (500, "Could not update password"))?;
+    .map_err(|_| AuthError::CouldNotUpdatePassword)?;
 
+    let mailer = &localized(mailer, user.locale.as_deref());
     mailer.templates.send_password_changed(mailer, &user.email);
 
     Ok(())
 }
 
+/// /email/change
+///
+/// emails a confirmation link to [`item.new_email`](`ChangeEmailInput`) and a notice to the
+/// current email of the User associated with [`auth`](`Auth`); the email only changes once the link
+/// is confirmed (see [`confirm_email_change`])
+///
+/// # Errors
+/// - 400: [`AuthError::MissingEmail`]
+/// - 400: [`AuthError::EmailUnchanged`]
+/// - 400: [`AuthError::EmailInUse`]
+/// - 400: [`AuthError::AccountNotActivated`]
+/// - 401: [`AuthError::InvalidCredentials`]
+/// - 500: [`AuthError::CouldNotFindUser`]
+/// - 500: [`AuthError::Internal`]
+pub fn request_email_change(
+    db: &Database,
+    auth_config: &AuthConfig,
+    item: &ChangeEmailInput,
+    auth: &Auth,
+    mailer: &Mailer,
+) -> Result<(), AuthError> {
+    let new_email = item.new_email.trim();
+    if new_email.is_empty() {
+        return Err(AuthError::MissingEmail);
+    }
+
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let user = match User::read(&mut db, auth.user_id) {
+        Ok(user) if user.activated => user,
+        Ok(_) => return Err(AuthError::AccountNotActivated),
+        Err(_) => return Err(AuthError::CouldNotFindUser),
+    };
+
+    let is_password_valid = verify_password(&user.hash_password, &item.password)?;
+
+    if !is_password_valid {
+        return Err(AuthError::InvalidCredentials);
+    }
+
+    if user.email.eq_ignore_ascii_case(new_email) {
+        return Err(AuthError::EmailUnchanged);
+    }
+
+    if User::find_by_email(&mut db, new_email.to_string()).is_ok() {
+        return Err(AuthError::EmailInUse);
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let email_change_claims = EmailChangeClaims {
+        exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
+        sub: user.id,
+        token_type: "email_change_token".to_string(),
+        old_email: user.email.clone(),
+        new_email: new_email.to_string(),
+    };
+
+    let token = encode_token(auth_config.keys.as_ref(), &email_change_claims)
+        .map_err(|_| AuthError::Internal)?;
+
+    let mailer = &localized(mailer, user.locale.as_deref());
+    mailer
+        .templates
+        .send_email_change(mailer, new_email, &format!("email/confirm?token={token}"));
+    mailer
+        .templates
+        .send_email_change_notice(mailer, &user.email, new_email);
+
+    Ok(())
+}
+
+/// /email/change/confirm
+///
+/// changes the email of the User associated with [`item.email_change_token`](`ConfirmEmailChangeInput`)
+/// and logs them out everywhere except in the session the `refresh_token` cookie belongs to, if any
+///
+/// # Errors
+/// - 401: [`AuthError::InvalidToken`]
+/// - 400: [`AuthError::InvalidToken`]
+/// - 400: [`AuthError::EmailInUse`]
+/// - 500: [`AuthError::CouldNotUpdateEmail`]
+/// - 500: [`AuthError::CouldNotDeleteSessions`]
+pub fn confirm_email_change(
+    db: &Database,
+    auth_config: &AuthConfig,
+    item: &ConfirmEmailChangeInput,
+    refresh_token: Option<&'_ str>,
+) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let token = match decode_token::<EmailChangeClaims>(
+        auth_config.keys.as_ref(),
+        &item.email_change_token,
+    ) {
+        Ok(token)
+            if token
+                .claims
+                .token_type
+                .eq_ignore_ascii_case("email_change_token") =>
+        {
+            token
+        }
+        _ => return Err(AuthError::InvalidToken),
+    };
+
+    let user = match User::read(&mut db, token.claims.sub) {
+        Ok(user) if user.email == token.claims.old_email => user,
+        _ => return Err(AuthError::InvalidToken),
+    };
+
+    if User::find_by_email(&mut db, token.claims.new_email.clone()).is_ok() {
+        return Err(AuthError::EmailInUse);
+    }
+
+    let user_id = user.id;
+    User::update(
+        &mut db,
+        user_id,
+        &UserChangeset {
+            email: token.claims.new_email,
+            hash_password: user.hash_password,
+            activated: user.activated,
+        },
+    )
+    .map_err(|_| AuthError::CouldNotUpdateEmail)?;
+
+    let current_session = refresh_token
+        .and_then(|refresh_token| UserSession::find_by_refresh_token(&mut db, refresh_token).ok())
+        .filter(|session| session.user_id == user_id);
+
+    match current_session {
+        Some(session) => {
+            UserSession::delete_all_for_user_except_family(&mut db, user_id, &session.family)
+        }
+        None => UserSession::delete_all_for_user(&mut db, user_id),
+    }
+    .map_err(|_| AuthError::CouldNotDeleteSessions)?;
+
+    Ok(())
+}
+
 /// /check
 ///
 /// just a lifeline function, clients can post to this endpoint to check
 /// if the auth service is running
 pub const fn check(_: &Auth) {}
 
+/// /.well-known/jwks.json
+///
+/// the public keys other services can verify access tokens with,
+/// see [`KeyProvider::jwks`](`crate::auth::keys::KeyProvider::jwks`)
+#[must_use]
+pub fn jwks(auth_config: &AuthConfig) -> jsonwebtoken::jwk::JwkSet {
+    auth_config.keys.jwks()
+}
+
 /// reset
 ///
 /// changes the password of the user associated with [`item.reset_token`](`ResetInput`)
 /// to [`item.new_password`](`ResetInput`)
 ///
 /// # Errors
-/// - 400: Missing password
-/// - 401: Invalid token
-/// - 400: Invalid token
-/// - 400: Account has not been activated
-/// - 500: Could not update password
-///
-/// # Panics
-/// - could not connect to database
-/// - could not get `SECRET_KEY` from environment
-///
-/// TODO: don't panic if db connection fails, just return an error
+/// - 400: [`AuthError::MissingPassword`]
+/// - 401: [`AuthError::InvalidToken`]
+/// - 400: [`AuthError::InvalidToken`]
+/// - 400: [`AuthError::AccountNotActivated`]
+/// - 400: [`AuthError::PasswordPolicy`]
+/// - 500: [`AuthError::CouldNotCheckPassword`]
+/// - 500: [`AuthError::CouldNotUpdatePassword`]
 pub fn reset_password(
     db: &Database,
+    auth_config: &AuthConfig,
     item: &ResetInput,
     mailer: &Mailer,
-) -> Result<(), (StatusCode, Message)> {
-    let mut db = db.get_connection().unwrap();
+) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
     if item.new_password.is_empty() {
-        return Err((400, "Missing password"));
+        return Err(AuthError::MissingPassword);
     }
 
-    let token = match decode::<ResetTokenClaims>(
-        &item.reset_token,
-        &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-        &Validation::default(),
-    ) {
+    let token = match decode_token::<ResetTokenClaims>(auth_config.keys.as_ref(), &item.reset_token)
+    {
         Ok(token) if token.claims.token_type.eq_ignore_ascii_case("reset_token") => token,
-        _ => return Err((401, "Invalid token.")),
+        _ => return Err(AuthError::InvalidToken),
     };
 
     let user = match User::read(&mut db, token.claims.sub) {
         Ok(user) if user.activated => user,
-        Ok(_) => return Err((400, "Account has not been activated")),
-        Err(_) => return Err((400, "Invalid token.")),
+        Ok(_) => return Err(AuthError::AccountNotActivated),
+        Err(_) => return Err(AuthError::InvalidToken),
     };
 
-    let salt = generate_salt();
-    let new_hash =
-        argon2::hash_encoded(item.new_password.as_bytes(), &salt, &ARGON_CONFIG).unwrap();
+    check_password_policy(auth_config, &item.new_password, &user.email)?;
+
+    let new_hash = hash_password(&item.new_password)?;
 
     User::update(
         &mut db,
@@ -812,13 +2377,44 @@ pub fn reset_password(
             activated: user.activated,
         },
     )
-    .map_err(|_| (500, "Could not update password"))?;
+    .map_err(|_| AuthError::CouldNotUpdatePassword)?;
 
+    let mailer = &localized(mailer, user.locale.as_deref());
     mailer.templates.send_password_reset(mailer, &user.email);
 
     Ok(())
 }
 
+/// rejects `password`, chosen by the user with the given `email`, if it doesn't follow
+/// [`auth_config.password_policy`](`AuthConfig::password_policy`)
+fn check_password_policy(
+    auth_config: &AuthConfig,
+    password: &str,
+    email: &str,
+) -> Result<(), AuthError> {
+    let violations = auth_config
+        .password_policy
+        .check(password, email)
+        .map_err(|_| AuthError::CouldNotCheckPassword)?;
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(AuthError::PasswordPolicy { violations })
+    }
+}
+
+/// a random identifier for refresh tokens and session families
+fn generate_token_id() -> String {
+    use rand::{distributions::Alphanumeric, Rng};
+
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
 #[must_use]
 #[allow(clippy::missing_panics_doc)]
 pub fn generate_salt() -> [u8; 16] {
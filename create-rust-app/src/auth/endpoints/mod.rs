@@ -9,3 +9,8 @@ pub use service_actixweb::ApiDoc;
 mod service_poem;
 #[cfg(feature = "backend_poem")]
 pub use service_poem::api;
+
+#[cfg(feature = "backend_axum")]
+mod service_axum;
+#[cfg(feature = "backend_axum")]
+pub use service_axum::router;
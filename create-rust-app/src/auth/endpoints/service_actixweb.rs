@@ -1,28 +1,111 @@
 #[cfg(feature = "plugin_utoipa")]
+use crate::auth::admin::{AdminAuditLogResponse, AdminUserJson, AdminUserResponse};
+#[cfg(feature = "plugin_utoipa")]
+use crate::auth::authorization_server::TokenResponse;
+#[cfg(feature = "plugin_utoipa")]
+use crate::auth::controller::{
+    ApiKeyCreated, PasskeyOptions, TwoFactorEnrollment, TwoFactorRecoveryCodes,
+};
+#[cfg(feature = "plugin_utoipa")]
 use crate::auth::{
-    AuthMessageResponse, AuthTokenResponse, JwtSecurityAddon, UserSessionJson, UserSessionResponse,
+    AdminAuditLog, AuthMessageResponse, AuthTokenResponse, AuthTwoFactorRequiredResponse,
+    JwtSecurityAddon, PasswordPolicyResponse, PasswordViolation, UserApiKeyJson,
+    UserApiKeyResponse, UserPasskeyJson, UserPasskeyResponse, UserSessionJson, UserSessionResponse,
 };
 use actix_http::StatusCode;
 use actix_web::cookie::{Cookie, SameSite};
-use actix_web::{delete, get, post, web, Error as AWError, Result};
+use actix_web::dev::Payload;
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header::USER_AGENT;
+use actix_web::{delete, get, post, web, Error as AWError, FromRequest, Result};
 use actix_web::{
     web::{Data, Json, Path, Query},
     HttpRequest, HttpResponse,
 };
+use once_cell::sync::OnceCell;
 use serde_json::json;
+use std::future::{ready, Ready};
 #[cfg(feature = "plugin_utoipa")]
 use utoipa::OpenApi;
 
+#[cfg(feature = "plugin_utoipa")]
+use crate::auth::account::AccountDeletionResponse;
+use crate::auth::account::{self, DeleteAccountInput, SetLocaleInput};
+use crate::auth::authorization_server::{self, AuthorizeInput, AuthorizeOutcome, TokenInput};
 use crate::auth::{
+    admin,
+    admin::{
+        ActivationInput as AdminActivationInput, PermissionInput, RoleInput, UserSearchParams,
+    },
     controller,
     controller::{
-        ActivationInput, ChangeInput, ForgotInput, LoginInput, RegisterInput, ResetInput,
-        COOKIE_NAME,
+        ActivationInput, ApiKeyInput, ChangeEmailInput, ChangeInput, ClientInfo,
+        ConfirmEmailChangeInput, ForgotInput, LoginInput, LoginLinkInput, LoginLinkVerifyInput,
+        LoginOutcome, PasskeyLoginInput, PasskeyLoginStartInput, PasskeyRegisterInput,
+        RegisterInput, ResetInput, TwoFactorCodeInput, TwoFactorLoginInput, COOKIE_NAME,
     },
+    error::AuthError,
     Auth, PaginationParams, ID,
 };
 use crate::{auth::AuthConfig, AppConfig, Database, Mailer};
 
+/// the response to an [`AuthError`], a Json body with its `code` and `message`
+fn auth_error_response(error: AuthError) -> HttpResponse {
+    HttpResponse::build(
+        StatusCode::from_u16(error.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+    )
+    .body(error.to_json().to_string())
+}
+
+/// the [`AuthConfig`] registered as app data, or the [`fallback`](`AuthConfig::fallback`) one
+struct Config(Data<AuthConfig>);
+
+impl FromRequest for Config {
+    type Error = AWError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        static FALLBACK: OnceCell<Option<Data<AuthConfig>>> = OnceCell::new();
+
+        let auth_config = req.app_data::<Data<AuthConfig>>().cloned().or_else(|| {
+            FALLBACK
+                .get_or_init(|| AuthConfig::fallback().cloned().map(Data::new))
+                .clone()
+        });
+
+        ready(auth_config.map(Self).ok_or_else(|| {
+            ErrorInternalServerError("AuthConfig is not registered and SECRET_KEY isn't set")
+        }))
+    }
+}
+
+/// the address of the client making the request, see [`request_client_ip`](`crate::net::request_client_ip`)
+fn client_ip(req: &HttpRequest) -> Option<String> {
+    let header = |name| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+
+    crate::net::request_client_ip(
+        req.peer_addr().map(|addr| addr.ip()),
+        header("X-Forwarded-For"),
+        header("X-Real-IP"),
+    )
+}
+
+/// where the request came from, see [`ClientInfo`]
+fn client_info(req: &HttpRequest) -> ClientInfo {
+    ClientInfo {
+        ip_address: client_ip(req),
+        user_agent: req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string),
+    }
+}
+
 /// handler for GET requests at the .../sessions endpoint,
 ///
 /// requires auth
@@ -57,10 +140,7 @@ async fn sessions(
 
     match result {
         Ok(sessions) => Ok(HttpResponse::Ok().json(sessions)),
-        Err((status_code, error_message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": error_message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -95,10 +175,7 @@ async fn destroy_session(
         Ok(()) => Ok(
             HttpResponse::build(StatusCode::OK).body(json!({"message": "Deleted."}).to_string())
         ),
-        Err((status_code, error_message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": error_message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -126,10 +203,7 @@ async fn destroy_sessions(db: Data<Database>, auth: Auth) -> Result<HttpResponse
         Ok(()) => Ok(
             HttpResponse::build(StatusCode::OK).body(json!({"message": "Deleted."}).to_string())
         ),
-        Err((status_code, error_message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": error_message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -137,22 +211,186 @@ async fn destroy_sessions(db: Data<Database>, auth: Auth) -> Result<HttpResponse
 ///
 /// creates a user session for the user associated with [`item`](`LoginInput`)
 /// in the request body (have the `content-type` header set to `application/json` and content that can be deserialized into [`LoginInput`])
+///
+/// if the user has two-factor authentication enabled, no session is created; instead, the response contains
+/// a `two_factor_token` which should be sent along with a code to the .../login/2fa endpoint
 #[cfg_attr(feature = "plugin_utoipa", utoipa::path(
     context_path = "/api/auth",
     request_body(content = LoginInput, content_type = "application/json"),
     responses(
         (status = 200, description = "session created", body = AuthTokenResponse),
+        (status = 200, description = "password accepted, a second factor is required", body = AuthTwoFactorRequiredResponse),
         (status = 400, description = "'device' cannot be longer than 256 characters.", body = AuthMessageResponse),
         (status = 400, description = "Account has not been activated.", body = AuthMessageResponse),
         (status = 401, description = "Invalid credentials.", body = AuthMessageResponse),
+        (status = 429, description = "Too many failed login attempts, try again later.", body = AuthMessageResponse),
         (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
         (status = 500, description = "Could not create a session.", body = AuthMessageResponse),
     ),
     tag = "Sessions",
 ))]
 #[post("/login")]
-async fn login(db: Data<Database>, Json(item): Json<LoginInput>) -> Result<HttpResponse, AWError> {
-    let result = web::block(move || controller::login(&db, &item)).await?;
+async fn login(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    mailer: Data<Mailer>,
+    req: HttpRequest,
+    Json(item): Json<LoginInput>,
+) -> Result<HttpResponse, AWError> {
+    let client = client_info(&req);
+
+    let result =
+        web::block(move || controller::login(&db, &auth_config, &mailer, &item, &client)).await?;
+
+    match result {
+        Ok(LoginOutcome::Session(access_token, refresh_token)) => {
+            Ok(HttpResponse::build(StatusCode::OK)
+                .cookie(
+                    Cookie::build(COOKIE_NAME, refresh_token)
+                        .secure(true)
+                        .http_only(true)
+                        .same_site(SameSite::Strict)
+                        .path("/")
+                        .finish(),
+                )
+                .body(json!({ "access_token": access_token }).to_string()))
+        }
+        Ok(LoginOutcome::TwoFactorRequired(two_factor_token)) => {
+            Ok(HttpResponse::build(StatusCode::OK).body(
+                json!({ "two_factor_required": true, "two_factor_token": two_factor_token })
+                    .to_string(),
+            ))
+        }
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../login/link endpoint
+///
+/// emails a one-time login link to the user associated with the email in [`item`](`LoginLinkInput`),
+/// the token in the link should be sent to the .../login/link/verify endpoint
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = LoginLinkInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Sent login link if the account exists.", body = AuthMessageResponse),
+        (status = 429, description = "Too many failed login attempts, try again later.", body = AuthMessageResponse),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
+    ),
+    tag = "Sessions",
+))]
+#[post("/login/link")]
+async fn request_login_link(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    mailer: Data<Mailer>,
+    req: HttpRequest,
+    Json(item): Json<LoginLinkInput>,
+) -> Result<HttpResponse, AWError> {
+    let ip_address = client_ip(&req);
+
+    let result = web::block(move || {
+        controller::request_login_link(&db, &auth_config, &mailer, &item, ip_address.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
+            .body(json!({"message": "Sent login link if the account exists."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../login/link/verify endpoint
+///
+/// creates a user session for the user an emailed login link was sent to (see [`LoginLinkVerifyInput`]),
+/// each link can only be used once
+///
+/// like .../login, if the user has two-factor authentication enabled the response contains
+/// a `two_factor_token` which should be sent along with a code to the .../login/2fa endpoint
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = LoginLinkVerifyInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "session created", body = AuthTokenResponse),
+        (status = 200, description = "link accepted, a second factor is required", body = AuthTwoFactorRequiredResponse),
+        (status = 400, description = "'device' cannot be longer than 256 characters.", body = AuthMessageResponse),
+        (status = 400, description = "Account has not been activated.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid token.", body = AuthMessageResponse),
+        (status = 429, description = "Too many failed login attempts, try again later.", body = AuthMessageResponse),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
+        (status = 500, description = "Could not create a session.", body = AuthMessageResponse),
+    ),
+    tag = "Sessions",
+))]
+#[post("/login/link/verify")]
+async fn login_with_link(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    mailer: Data<Mailer>,
+    req: HttpRequest,
+    Json(item): Json<LoginLinkVerifyInput>,
+) -> Result<HttpResponse, AWError> {
+    let client = client_info(&req);
+
+    let result =
+        web::block(move || controller::login_with_link(&db, &auth_config, &mailer, &item, &client))
+            .await?;
+
+    match result {
+        Ok(LoginOutcome::Session(access_token, refresh_token)) => {
+            Ok(HttpResponse::build(StatusCode::OK)
+                .cookie(
+                    Cookie::build(COOKIE_NAME, refresh_token)
+                        .secure(true)
+                        .http_only(true)
+                        .same_site(SameSite::Strict)
+                        .path("/")
+                        .finish(),
+                )
+                .body(json!({ "access_token": access_token }).to_string()))
+        }
+        Ok(LoginOutcome::TwoFactorRequired(two_factor_token)) => {
+            Ok(HttpResponse::build(StatusCode::OK).body(
+                json!({ "two_factor_required": true, "two_factor_token": two_factor_token })
+                    .to_string(),
+            ))
+        }
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../login/2fa endpoint
+///
+/// completes a login for a user with two-factor authentication enabled by exchanging the
+/// `two_factor_token` returned by .../login and a code (see [`TwoFactorLoginInput`]) for a session
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = TwoFactorLoginInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "session created", body = AuthTokenResponse),
+        (status = 401, description = "Invalid token.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid code.", body = AuthMessageResponse),
+        (status = 429, description = "Too many failed login attempts, try again later.", body = AuthMessageResponse),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
+        (status = 500, description = "Could not create a session.", body = AuthMessageResponse),
+    ),
+    tag = "Sessions",
+))]
+#[post("/login/2fa")]
+async fn login_two_factor(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    mailer: Data<Mailer>,
+    req: HttpRequest,
+    Json(item): Json<TwoFactorLoginInput>,
+) -> Result<HttpResponse, AWError> {
+    let client = client_info(&req);
+
+    let result = web::block(move || {
+        controller::login_two_factor(&db, &auth_config, &mailer, &item, &client)
+    })
+    .await?;
 
     match result {
         Ok((access_token, refresh_token)) => Ok(HttpResponse::build(StatusCode::OK)
@@ -165,10 +403,7 @@ async fn login(db: Data<Database>, Json(item): Json<LoginInput>) -> Result<HttpR
                     .finish(),
             )
             .body(json!({ "access_token": access_token }).to_string())),
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -177,10 +412,10 @@ async fn login(db: Data<Database>, Json(item): Json<LoginInput>) -> Result<HttpR
 async fn oidc_login_redirect(
     db: Data<Database>,
     app_config: Data<AppConfig>,
-    auth_config: Data<AuthConfig>,
+    Config(auth_config): Config,
     provider: Path<String>,
 ) -> Result<HttpResponse, AWError> {
-    use actix_web::http::header::{HeaderValue, LOCATION};
+    use actix_web::http::header::LOCATION;
 
     let result = crate::auth::oidc::controller::oidc_login_url(
         &db,
@@ -191,14 +426,9 @@ async fn oidc_login_redirect(
     .await;
 
     match result {
-        Ok(Some(url)) => {
-            let mut response = HttpResponse::SeeOther().body(());
-            response
-                .headers_mut()
-                .append(LOCATION, HeaderValue::from_str(url.as_str()).unwrap());
-
-            Ok(response)
-        }
+        Ok(Some(url)) => Ok(HttpResponse::SeeOther()
+            .insert_header((LOCATION, url.as_str()))
+            .finish()),
         Ok(None) => Ok(HttpResponse::NotImplemented().finish()),
         Err(_) => Ok(HttpResponse::InternalServerError().finish()),
     }
@@ -214,16 +444,62 @@ pub struct OIDCLoginQueryParams {
 
 #[cfg(feature = "plugin_auth-oidc")]
 #[get("/oidc/{provider}/login")]
-/// TODO: return result (or specific http response) instead of panicking
 async fn oidc_login(
     db: Data<Database>,
     app_config: Data<AppConfig>,
-    auth_config: Data<AuthConfig>,
+    Config(auth_config): Config,
+    req: HttpRequest,
     path_params: Path<String>,
     query_params: Query<OIDCLoginQueryParams>,
 ) -> HttpResponse {
-    use actix_web::http::header::{HeaderValue, LOCATION};
-    let provider_name = path_params.to_string();
+    oidc_callback(
+        &db,
+        &app_config,
+        &auth_config,
+        path_params.to_string(),
+        query_params.into_inner(),
+        &req,
+    )
+    .await
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+#[post("/oidc/{provider}/login")]
+/// providers using `response_mode=form_post` (like Apple) post their response here
+async fn oidc_login_form_post(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    Config(auth_config): Config,
+    req: HttpRequest,
+    path_params: Path<String>,
+    form: web::Form<OIDCLoginQueryParams>,
+) -> HttpResponse {
+    oidc_callback(
+        &db,
+        &app_config,
+        &auth_config,
+        path_params.to_string(),
+        form.into_inner(),
+        &req,
+    )
+    .await
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// redirects to the provider's `success_uri` (with an access token, or the name of the provider if
+/// a logged in user linked it) or to its `error_uri`
+///
+/// TODO: return result (or specific http response) instead of panicking
+async fn oidc_callback(
+    db: &Database,
+    app_config: &AppConfig,
+    auth_config: &AuthConfig,
+    provider_name: String,
+    params: OIDCLoginQueryParams,
+    req: &HttpRequest,
+) -> HttpResponse {
+    use crate::auth::oidc::controller::LINK_STATE_COOKIE_NAME;
+    use actix_web::http::header::LOCATION;
 
     let provider = if let Some(provider) = auth_config
         .oidc_providers
@@ -242,57 +518,119 @@ async fn oidc_login(
         );
     };
 
-    let query_params = query_params.into_inner();
-    let query_param_code = query_params.code;
-    let query_param_state = query_params.state;
-    let query_param_error = query_params.error;
-
     let resp = crate::auth::oidc::controller::oauth_login(
-        &db,
-        &app_config,
-        &auth_config,
+        db,
+        app_config,
+        auth_config,
         provider_name,
-        query_param_code,
-        query_param_error,
-        query_param_state,
+        params.code,
+        params.error,
+        params.state,
+        req.cookie(LINK_STATE_COOKIE_NAME)
+            .map(|cookie| cookie.value().to_string()),
+        &client_info(req),
     )
     .await;
 
-    let mut response = HttpResponse::SeeOther().body(());
+    let location = match &resp {
+        Ok(Some((access_token, _))) => {
+            format!("{}?access_token={}", provider.success_uri, access_token)
+        }
+        Ok(None) => format!("{}?linked={}", provider.success_uri, provider.name),
+        Err(error) => crate::auth::oidc::controller::error_redirect_url(provider, error),
+    };
+
+    // an invalid header or cookie makes the builder respond with a 500 instead of panicking
+    let mut response = HttpResponse::SeeOther();
+    response.insert_header((LOCATION, location));
 
     match resp {
-        Ok((access_token, refresh_token)) => {
-            response.headers_mut().append(
-                LOCATION,
-                HeaderValue::from_str(&format!(
-                    "{}?access_token={}",
-                    provider.success_uri, access_token
-                ))
-                .expect("Invalid URL"),
+        Ok(Some((_, refresh_token))) => {
+            response.cookie(
+                Cookie::build(COOKIE_NAME, refresh_token)
+                    .secure(true)
+                    .http_only(true)
+                    .same_site(SameSite::Strict)
+                    .path("/")
+                    .finish(),
             );
-
-            response
-                .add_cookie(
-                    &Cookie::build(COOKIE_NAME, refresh_token)
-                        .secure(true)
-                        .http_only(true)
-                        .same_site(SameSite::Strict)
-                        .path("/")
-                        .finish(),
-                )
-                .expect("Could not add refresh_token cookie");
         }
-        Err((status_code, message)) => response.headers_mut().append(
-            LOCATION,
-            HeaderValue::from_str(&format!(
-                "{}?status_code={}&message={}",
-                provider.error_uri, status_code, message
-            ))
-            .expect("Invalid URL"),
-        ),
+        // the link flow is over, whether it succeeded or not
+        Ok(None) | Err(_) => {
+            let mut cookie = Cookie::named(LINK_STATE_COOKIE_NAME);
+            cookie.set_path("/");
+            cookie.make_removal();
+            response.cookie(cookie);
+        }
+    }
+
+    response.finish()
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+#[get("/oidc/{provider}/link")]
+/// returns the url a logged in user should be sent to, to link the provider to their account
+async fn oidc_link(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    Config(auth_config): Config,
+    provider: Path<String>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let result = crate::auth::oidc::controller::oidc_link_url(
+        &db,
+        app_config.as_ref(),
+        auth_config.as_ref(),
+        &auth,
+        provider.to_string(),
+    )
+    .await;
+
+    match result {
+        Ok(Some((url, state))) => Ok(HttpResponse::Ok()
+            .cookie(
+                // sent along with the provider's (cross-site) redirect or form post to the callback
+                Cookie::build(crate::auth::oidc::controller::LINK_STATE_COOKIE_NAME, state)
+                    .secure(true)
+                    .http_only(true)
+                    .same_site(SameSite::None)
+                    .path("/")
+                    .finish(),
+            )
+            .json(json!({ "url": url }))),
+        Ok(None) => Ok(HttpResponse::NotImplemented().finish()),
+        Err(_) => Ok(HttpResponse::InternalServerError().finish()),
+    }
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+#[get("/oidc/links")]
+/// the providers linked to the authenticated user
+async fn oidc_links(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result =
+        web::block(move || crate::auth::oidc::controller::get_oidc_links(&db, &auth)).await?;
+
+    match result {
+        Ok(links) => Ok(HttpResponse::Ok().json(links)),
+        Err(error) => Ok(auth_error_response(error)),
     }
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+#[delete("/oidc/links/{id}")]
+/// unlinks a provider from the authenticated user, unless it's the only way they can log in
+async fn oidc_unlink(db: Data<Database>, item_id: Path<ID>, auth: Auth) -> Result<HttpResponse> {
+    let result = web::block(move || {
+        crate::auth::oidc::controller::unlink_oidc_provider(&db, &auth, item_id.into_inner())
+    })
+    .await?;
 
-    response
+    match result {
+        Ok(()) => Ok(
+            HttpResponse::build(StatusCode::OK).body(json!({"message": "Deleted."}).to_string())
+        ),
+        Err(error) => Ok(auth_error_response(error)),
+    }
 }
 
 /// handler for POST requests to the .../logout endpount
@@ -328,10 +666,7 @@ async fn logout(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, AW
 
             Ok(HttpResponse::Ok().cookie(cookie).finish())
         }
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -351,13 +686,23 @@ async fn logout(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, AW
 ))]
 #[post("/refresh")]
 #[allow(clippy::future_not_send)] // safe because we're running blocking actions in a web::block
-async fn refresh(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, AWError> {
+async fn refresh(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
     let refresh_token = req
         .cookie(COOKIE_NAME)
         .map(|cookie| String::from(cookie.value()));
+    let client = client_info(&req);
 
     let result = web::block(move || {
-        controller::refresh(&db, refresh_token.as_ref().map(std::convert::AsRef::as_ref))
+        controller::refresh(
+            &db,
+            &auth_config,
+            refresh_token.as_ref().map(std::convert::AsRef::as_ref),
+            &client,
+        )
     })
     .await?;
 
@@ -372,10 +717,7 @@ async fn refresh(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, A
                     .finish(),
             )
             .body(json!({ "access_token": access_token }).to_string())),
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -392,24 +734,26 @@ async fn refresh(db: Data<Database>, req: HttpRequest) -> Result<HttpResponse, A
     responses(
         (status = 200, description = "Success, sends an email to the user with a link that will let them activate their account", body=AuthMessageResponse),
         (status = 400, description = "Already registered.", body = AuthMessageResponse),
+        (status = 400, description = "'locale' must be a language tag, like 'en' or 'fr-CA'.", body = AuthMessageResponse),
+        (status = 400, description = "The password doesn't follow the password policy.", body = PasswordPolicyResponse),
+        (status = 500, description = "Could not check password.", body = AuthMessageResponse),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
     ),
     tag = "Users",
 ))]
 #[post("/register")]
 async fn register(
     db: Data<Database>,
+    Config(auth_config): Config,
     Json(item): Json<RegisterInput>,
     mailer: Data<Mailer>,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::register(&db, &item, &mailer);
+    let result = controller::register(&db, &auth_config, &item, &mailer);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
             .body("{ \"message\": \"Registered! Check your email to activate your account.\" }")),
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -421,7 +765,7 @@ async fn register(
     params(ActivationInput),
     responses(
         (status = 200, description = "Success, account associated with activation_token is activated", body=AuthMessageResponse),
-        (status = 200, description = "Already activated.", body = AuthMessageResponse),
+        (status = 400, description = "Already activated!", body = AuthMessageResponse),
         (status = 400, description =  "Invalid token.", body = AuthMessageResponse),
         (status = 401, description =  "Invalid token", body = AuthMessageResponse),
         (status = 500, description =  "Could not activate user. ", body = AuthMessageResponse),
@@ -431,17 +775,15 @@ async fn register(
 #[get("/activate")]
 async fn activate(
     db: Data<Database>,
+    Config(auth_config): Config,
     Query(item): Query<ActivationInput>,
     mailer: Data<Mailer>,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::activate(&db, &item, &mailer);
+    let result = controller::activate(&db, &auth_config, &item, &mailer);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK).body("{ \"message\": \"Activated!\" }")),
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -459,24 +801,23 @@ async fn activate(
     request_body(content = ForgotInput, content_type = "application/json"),
     responses(
         (status = 200, description = "Success, password reset email is sent to users email", body=AuthMessageResponse),
+        (status = 500, description = "An internal server error occurred.", body=AuthMessageResponse),
     ),
     tag = "Users",
 ))]
 #[post("/forgot")]
 async fn forgot_password(
     db: Data<Database>,
+    Config(auth_config): Config,
     Json(item): Json<ForgotInput>,
     mailer: Data<Mailer>,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::forgot_password(&db, &item, &mailer);
+    let result = controller::forgot_password(&db, &auth_config, &item, &mailer);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
             .body("{ \"message\": \"Please check your email.\" }")),
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -495,6 +836,8 @@ async fn forgot_password(
         (status = 400, description = "The new password must be different.", body=AuthMessageResponse),
         (status = 400, description = "Account has not been activated.", body=AuthMessageResponse),
         (status = 400, description = "Invalid credentials.", body=AuthMessageResponse),
+        (status = 400, description = "The password doesn't follow the password policy.", body = PasswordPolicyResponse),
+        (status = 500, description = "Could not check password.", body = AuthMessageResponse),
         (status = 500, description = "Could not find user.", body=AuthMessageResponse),
         (status = 500, description = "Could not update password.", body=AuthMessageResponse),
     ),
@@ -504,71 +847,1162 @@ async fn forgot_password(
 #[post("/change")]
 async fn change_password(
     db: Data<Database>,
+    Config(auth_config): Config,
     Json(item): Json<ChangeInput>,
     auth: Auth,
     mailer: Data<Mailer>,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::change_password(&db, &item, &auth, &mailer);
+    let result = controller::change_password(&db, &auth_config, &item, &auth, &mailer);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
             .body(json!({"message": "Password changed."}).to_string())),
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
-/// handler for POST requests to the .../check endpoint
+/// handler for POST requests to the .../email/change endpoint
 ///
-/// requires auth, but doesn't match it to a user
+/// requires auth
+///
+/// emails a link to [`item.new_email`](`ChangeEmailInput`) which confirms the change,
+/// see [`controller::request_email_change`]
 #[cfg_attr(feature = "plugin_utoipa", utoipa::path(
     context_path = "/api/auth",
+    request_body(content = ChangeEmailInput, content_type = "application/json"),
     responses(
-        (status = 200, description = "Success, API is running"),
+        (status = 200, description = "Success, confirmation link sent", body=AuthMessageResponse),
+        (status = 400, description = "Missing email.", body=AuthMessageResponse),
+        (status = 400, description = "The new email must be different.", body=AuthMessageResponse),
+        (status = 400, description = "Email already in use.", body=AuthMessageResponse),
+        (status = 400, description = "Account has not been activated.", body=AuthMessageResponse),
+        (status = 401, description = "Invalid credentials.", body=AuthMessageResponse),
+        (status = 500, description = "Could not find user.", body=AuthMessageResponse),
     ),
     tag = "Users",
     security ( ("JWT" = []))
 ))]
-#[post("/check")]
-async fn check(auth: Auth) -> HttpResponse {
-    controller::check(&auth);
-    HttpResponse::Ok().finish()
+#[post("/email/change")]
+async fn request_email_change(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    Json(item): Json<ChangeEmailInput>,
+    auth: Auth,
+    mailer: Data<Mailer>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || {
+        controller::request_email_change(&db, &auth_config, &item, &auth, &mailer)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
+            .body(json!({"message": "Please check your new email."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
 }
 
-/// handler for POST requests to the .../reset endpoint
+/// handler for POST requests to the .../email/change/confirm endpoint
 ///
-/// changes the password of the user associated with [`item.reset_token`](`ResetInput`)
-/// to [`item.new_password`](`ResetInput`)
+/// changes the email and logs the user out of their other sessions,
+/// see [`controller::confirm_email_change`]
 #[cfg_attr(feature = "plugin_utoipa", utoipa::path(
     context_path = "/api/auth",
-    request_body(content = ResetInput, content_type = "application/json"),
+    request_body(content = ConfirmEmailChangeInput, content_type = "application/json"),
     responses(
-        (status = 200, description = "Password changed.", body=AuthMessageResponse),
+        (status = 200, description = "Success, email changed", body=AuthMessageResponse),
         (status = 400, description = "Invalid token.", body=AuthMessageResponse),
-        (status = 400, description = "Account has not been activated.", body=AuthMessageResponse),
-        (status = 400, description = "The new password must be different.", body=AuthMessageResponse),
+        (status = 400, description = "Email already in use.", body=AuthMessageResponse),
         (status = 401, description = "Invalid token.", body=AuthMessageResponse),
-        (status = 500, description = "Could not update password.", body=AuthMessageResponse),
+        (status = 500, description = "Could not update email.", body=AuthMessageResponse),
     ),
     tag = "Users",
 ))]
-#[post("/reset")]
-async fn reset_password(
+#[post("/email/change/confirm")]
+#[allow(clippy::future_not_send)] // safe because we're running blocking actions in a web::block
+async fn confirm_email_change(
     db: Data<Database>,
+    Config(auth_config): Config,
+    Json(item): Json<ConfirmEmailChangeInput>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let refresh_token = req
+        .cookie(COOKIE_NAME)
+        .map(|cookie| String::from(cookie.value()));
+
+    let result = web::block(move || {
+        controller::confirm_email_change(&db, &auth_config, &item, refresh_token.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
+            .body(json!({"message": "Email changed."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../check endpoint
+///
+/// requires auth, but doesn't match it to a user
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Success, API is running"),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/check")]
+async fn check(auth: Auth) -> HttpResponse {
+    controller::check(&auth);
+    HttpResponse::Ok().finish()
+}
+
+/// handler for GET requests to the .../.well-known/jwks.json endpoint
+///
+/// publishes the public keys access tokens are signed with as a JWK set,
+/// so other services can verify them without knowing any secrets
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Success, returns the JWK set"),
+    ),
+    tag = "Auth",
+))]
+#[get("/.well-known/jwks.json")]
+async fn jwks(Config(auth_config): Config) -> HttpResponse {
+    HttpResponse::Ok().json(controller::jwks(&auth_config))
+}
+
+/// handler for GET requests to the .../.well-known/openid-configuration endpoint
+///
+/// the discovery document of the authorization server, see [`authorization_server::discovery`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Success, returns the OpenID Connect discovery document"),
+        (status = 404, description = "The authorization server is disabled"),
+    ),
+    tag = "Authorization server",
+))]
+#[get("/.well-known/openid-configuration")]
+async fn openid_configuration(
+    app_config: Data<AppConfig>,
+    Config(auth_config): Config,
+) -> HttpResponse {
+    match authorization_server::discovery(&app_config, &auth_config) {
+        Some(document) => HttpResponse::Ok().json(document),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+/// handler for POST requests to the .../oauth/authorize endpoint
+///
+/// requires auth
+///
+/// issues an authorization code to an OAuth client on behalf of the authenticated user,
+/// see [`authorization_server::authorize`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = AuthorizeInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, returns {\"redirect_uri\"} to send the user to, or {\"consent_required\", \"client_name\", \"scopes\"} if the user has to consent first"),
+        (status = 400, description = "Unknown client.", body = AuthMessageResponse),
+        (status = 400, description = "Invalid redirect_uri.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "API keys cannot authorize clients.", body = AuthMessageResponse),
+        (status = 404, description = "The authorization server is disabled", body = AuthMessageResponse),
+        (status = 500, description = "Could not create authorization code.", body = AuthMessageResponse),
+    ),
+    tag = "Authorization server",
+    security ( ("JWT" = []))
+))]
+#[post("/oauth/authorize")]
+async fn oauth_authorize(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Json(item): Json<AuthorizeInput>,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || authorization_server::authorize(&db, &auth_config, &auth, &item))
+            .await?;
+
+    match result {
+        Ok(AuthorizeOutcome::Redirect(redirect_uri)) => {
+            Ok(HttpResponse::Ok().json(json!({ "redirect_uri": redirect_uri })))
+        }
+        Ok(AuthorizeOutcome::ConsentRequired {
+            client_name,
+            scopes,
+        }) => Ok(HttpResponse::Ok().json(
+            json!({ "consent_required": true, "client_name": client_name, "scopes": scopes }),
+        )),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../oauth/token endpoint
+///
+/// exchanges an authorization code or a refresh token for tokens,
+/// see [`authorization_server::token`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = TokenInput, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Success, returns the tokens", body = TokenResponse),
+        (status = 400, description = "{\"error\": \"invalid_request\" | \"invalid_grant\" | \"unsupported_grant_type\"}"),
+        (status = 401, description = "{\"error\": \"invalid_client\"}"),
+        (status = 404, description = "The authorization server is disabled"),
+    ),
+    tag = "Authorization server",
+))]
+#[post("/oauth/token")]
+async fn oauth_token(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    Config(auth_config): Config,
+    req: HttpRequest,
+    web::Form(item): web::Form<TokenInput>,
+) -> Result<HttpResponse, AWError> {
+    let credentials = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(authorization_server::basic_credentials);
+
+    let result = web::block(move || {
+        authorization_server::token(&db, &app_config, &auth_config, &item, credentials)
+    })
+    .await?;
+
+    match result {
+        Ok(tokens) => Ok(HttpResponse::Ok()
+            .insert_header((actix_web::http::header::CACHE_CONTROL, "no-store"))
+            .json(tokens)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests to the .../oauth/userinfo endpoint
+///
+/// requires an access token issued to a client by the .../oauth/token endpoint
+///
+/// the claims about the token's user, see [`authorization_server::userinfo`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Success, returns {\"sub\"}, and {\"email\", \"email_verified\"} if the token was granted the email scope"),
+        (status = 401, description = "Invalid token.", body = AuthMessageResponse),
+        (status = 403, description = "The token wasn't granted the openid scope", body = AuthMessageResponse),
+        (status = 404, description = "The authorization server is disabled", body = AuthMessageResponse),
+        (status = 500, description = "Could not find user.", body = AuthMessageResponse),
+    ),
+    tag = "Authorization server",
+    security ( ("JWT" = []))
+))]
+#[get("/oauth/userinfo")]
+async fn oauth_userinfo(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    req: HttpRequest,
+) -> Result<HttpResponse, AWError> {
+    let access_token = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(authorization_server::bearer_token)
+        .map(ToString::to_string);
+
+    let result = web::block(move || {
+        authorization_server::userinfo(&db, &auth_config, access_token.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(claims) => Ok(HttpResponse::Ok().json(claims)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../reset endpoint
+///
+/// changes the password of the user associated with [`item.reset_token`](`ResetInput`)
+/// to [`item.new_password`](`ResetInput`)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = ResetInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Password changed.", body=AuthMessageResponse),
+        (status = 400, description = "Invalid token.", body=AuthMessageResponse),
+        (status = 400, description = "Account has not been activated.", body=AuthMessageResponse),
+        (status = 400, description = "The new password must be different.", body=AuthMessageResponse),
+        (status = 401, description = "Invalid token.", body=AuthMessageResponse),
+        (status = 400, description = "The password doesn't follow the password policy.", body = PasswordPolicyResponse),
+        (status = 500, description = "Could not check password.", body = AuthMessageResponse),
+        (status = 500, description = "Could not update password.", body=AuthMessageResponse),
+    ),
+    tag = "Users",
+))]
+#[post("/reset")]
+async fn reset_password(
+    db: Data<Database>,
+    Config(auth_config): Config,
     Json(item): Json<ResetInput>,
     mailer: Data<Mailer>,
 ) -> Result<HttpResponse, AWError> {
-    let result = controller::reset_password(&db, &item, &mailer);
+    let result = controller::reset_password(&db, &auth_config, &item, &mailer);
 
     match result {
         Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
             .body(json!({"message": "Password reset"}).to_string())),
-        Err((status_code, message)) => Ok(HttpResponse::build(
-            StatusCode::from_u16(status_code).unwrap(),
-        )
-        .body(json!({ "message": message }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../2fa/enroll endpoint
+///
+/// requires auth
+///
+/// generates a new TOTP secret for the User associated with [`auth`](`Auth`);
+/// two-factor authentication is enabled once a code is sent to .../2fa/confirm
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Success, returns the secret and an otpauth:// url for authenticator apps", body = TwoFactorEnrollment),
+        (status = 400, description = "Two-factor authentication is already enabled.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 500, description = "Could not enroll.", body = AuthMessageResponse),
+    ),
+    tag = "Two-factor authentication",
+    security ( ("JWT" = []))
+))]
+#[post("/2fa/enroll")]
+async fn enroll_two_factor(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::enroll_two_factor(&db, &app_config, &auth)).await?;
+
+    match result {
+        Ok(enrollment) => Ok(HttpResponse::Ok().json(enrollment)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../2fa/confirm endpoint
+///
+/// requires auth
+///
+/// enables two-factor authentication for the User associated with [`auth`](`Auth`)
+/// if the code in [`item`](`TwoFactorCodeInput`) is valid, and returns their recovery codes
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = TwoFactorCodeInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, two-factor authentication is enabled", body = TwoFactorRecoveryCodes),
+        (status = 400, description = "Two-factor authentication has not been enrolled.", body = AuthMessageResponse),
+        (status = 400, description = "Two-factor authentication is already enabled.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid code.", body = AuthMessageResponse),
+        (status = 500, description = "Could not enable two-factor authentication.", body = AuthMessageResponse),
+    ),
+    tag = "Two-factor authentication",
+    security ( ("JWT" = []))
+))]
+#[post("/2fa/confirm")]
+async fn confirm_two_factor(
+    db: Data<Database>,
+    Json(item): Json<TwoFactorCodeInput>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::confirm_two_factor(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(recovery_codes) => Ok(HttpResponse::Ok().json(recovery_codes)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../2fa/disable endpoint
+///
+/// requires auth
+///
+/// disables two-factor authentication for the User associated with [`auth`](`Auth`)
+/// if the code in [`item`](`TwoFactorCodeInput`) is valid (recovery codes are accepted)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = TwoFactorCodeInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, two-factor authentication is disabled", body = AuthMessageResponse),
+        (status = 400, description = "Two-factor authentication is not enabled.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid code.", body = AuthMessageResponse),
+        (status = 500, description = "Could not disable two-factor authentication.", body = AuthMessageResponse),
+    ),
+    tag = "Two-factor authentication",
+    security ( ("JWT" = []))
+))]
+#[post("/2fa/disable")]
+async fn disable_two_factor(
+    db: Data<Database>,
+    Json(item): Json<TwoFactorCodeInput>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::disable_two_factor(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::build(StatusCode::OK)
+            .body(json!({"message": "Two-factor authentication disabled."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../passkeys/register/start endpoint
+///
+/// requires auth
+///
+/// issues a challenge for registering a passkey; pass the returned `public_key` options
+/// to `navigator.credentials.create()` and send the result to .../passkeys/register/finish
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Success, returns the challenge and the credential creation options", body = PasskeyOptions),
+        (status = 401, description = "User not authenticated"),
+        (status = 500, description = "Could not find user.", body = AuthMessageResponse),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
+    ),
+    tag = "Passkeys",
+    security ( ("JWT" = []))
+))]
+#[post("/passkeys/register/start")]
+async fn start_passkey_registration(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    auth: Auth,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || controller::start_passkey_registration(&db, &app_config, &auth)).await?;
+
+    match result {
+        Ok(options) => Ok(HttpResponse::Ok().json(options)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../passkeys/register/finish endpoint
+///
+/// requires auth
+///
+/// verifies the browser's response to a challenge from .../passkeys/register/start
+/// and stores the new passkey
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = PasskeyRegisterInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, returns the registered passkey", body = UserPasskeyJson),
+        (status = 400, description = "'name' cannot be longer than 256 characters.", body = AuthMessageResponse),
+        (status = 400, description = "Invalid challenge.", body = AuthMessageResponse),
+        (status = 400, description = "Invalid passkey.", body = AuthMessageResponse),
+        (status = 400, description = "Passkey is already registered.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
+        (status = 500, description = "Could not register passkey.", body = AuthMessageResponse),
+    ),
+    tag = "Passkeys",
+    security ( ("JWT" = []))
+))]
+#[post("/passkeys/register/finish")]
+async fn finish_passkey_registration(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    auth: Auth,
+    Json(item): Json<PasskeyRegisterInput>,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || controller::finish_passkey_registration(&db, &app_config, &auth, &item))
+            .await?;
+
+    match result {
+        Ok(passkey) => Ok(HttpResponse::Ok().json(passkey)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../passkeys/login/start endpoint
+///
+/// issues a challenge for logging in with a passkey; pass the returned `public_key` options
+/// to `navigator.credentials.get()` and send the result to .../passkeys/login/finish
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = PasskeyLoginStartInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, returns the challenge and the credential request options", body = PasskeyOptions),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
+    ),
+    tag = "Passkeys",
+))]
+#[post("/passkeys/login/start")]
+async fn start_passkey_login(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    Json(item): Json<PasskeyLoginStartInput>,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || controller::start_passkey_login(&db, &app_config, &item)).await?;
+
+    match result {
+        Ok(options) => Ok(HttpResponse::Ok().json(options)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../passkeys/login/finish endpoint
+///
+/// creates a session for the owner of the passkey which answered a challenge
+/// from .../passkeys/login/start
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = PasskeyLoginInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "session created", body = AuthTokenResponse),
+        (status = 400, description = "'device' cannot be longer than 256 characters.", body = AuthMessageResponse),
+        (status = 400, description = "Account has not been activated.", body = AuthMessageResponse),
+        (status = 401, description = "Invalid passkey.", body = AuthMessageResponse),
+        (status = 429, description = "Too many failed login attempts, try again later.", body = AuthMessageResponse),
+        (status = 500, description = "An internal server error occurred.", body = AuthMessageResponse),
+        (status = 500, description = "Could not create a session.", body = AuthMessageResponse),
+    ),
+    tag = "Passkeys",
+))]
+#[post("/passkeys/login/finish")]
+async fn finish_passkey_login(
+    db: Data<Database>,
+    app_config: Data<AppConfig>,
+    Config(auth_config): Config,
+    mailer: Data<Mailer>,
+    req: HttpRequest,
+    Json(item): Json<PasskeyLoginInput>,
+) -> Result<HttpResponse, AWError> {
+    let client = client_info(&req);
+
+    let result = web::block(move || {
+        controller::finish_passkey_login(&db, &app_config, &auth_config, &mailer, &item, &client)
+    })
+    .await?;
+
+    match result {
+        Ok((access_token, refresh_token)) => Ok(HttpResponse::build(StatusCode::OK)
+            .cookie(
+                Cookie::build(COOKIE_NAME, refresh_token)
+                    .secure(true)
+                    .http_only(true)
+                    .same_site(SameSite::Strict)
+                    .path("/")
+                    .finish(),
+            )
+            .body(json!({ "access_token": access_token }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../passkeys endpoint
+///
+/// requires auth
+///
+/// queries [`db`](`Database`) for all passkeys registered by the User
+/// associated with [`auth`](`Auth`)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "success, returns a json payload with all the passkeys of the authenticated user", body = UserPasskeyResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 500, description = "Could not fetch passkeys."),
+    ),
+    tag = "Passkeys",
+    security ( ("JWT" = []))
+))]
+#[get("/passkeys")]
+async fn passkeys(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result = web::block(move || controller::get_passkeys(&db, &auth)).await?;
+
+    match result {
+        Ok(passkeys) => Ok(HttpResponse::Ok().json(passkeys)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../passkeys/{id} endpoint.
+///
+/// requires auth
+///
+/// deletes the entry in the `user_passkeys` table with the specified [`item_id`](`ID`) from
+/// [`db`](`Database`) if it's owned by the User associated with [`auth`](`Auth`)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Deleted", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 404, description = "Passkey could not be found, or does not belong to authenticated user.", body = AuthMessageResponse),
+        (status = 500, description = "Internal Error.", body = AuthMessageResponse),
+        (status = 500, description = "Could not delete passkey.", body = AuthMessageResponse),
+    ),
+    tag = "Passkeys",
+    security ( ("JWT" = []))
+))]
+#[delete("/passkeys/{id}")]
+async fn destroy_passkey(
+    db: Data<Database>,
+    item_id: Path<ID>,
+    auth: Auth,
+) -> Result<HttpResponse> {
+    let result =
+        web::block(move || controller::destroy_passkey(&db, &auth, item_id.into_inner())).await?;
+
+    match result {
+        Ok(()) => Ok(
+            HttpResponse::build(StatusCode::OK).body(json!({"message": "Deleted."}).to_string())
+        ),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../api-keys endpoint
+///
+/// requires auth
+///
+/// creates an API key machine clients can use in place of an access token;
+/// the key is only returned once
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = ApiKeyInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success, returns the key and its details", body = ApiKeyCreated),
+        (status = 400, description = "'name' must be between 1 and 256 characters.", body = AuthMessageResponse),
+        (status = 400, description = "Invalid permission.", body = AuthMessageResponse),
+        (status = 400, description = "'expires_at' must be in the future.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "API keys cannot be managed with an API key.", body = AuthMessageResponse),
+        (status = 500, description = "Could not create API key.", body = AuthMessageResponse),
+    ),
+    tag = "API keys",
+    security ( ("JWT" = []))
+))]
+#[post("/api-keys")]
+async fn create_api_key(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<ApiKeyInput>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || controller::create_api_key(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(api_key) => Ok(HttpResponse::Ok().json(api_key)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../api-keys endpoint
+///
+/// requires auth
+///
+/// queries [`db`](`Database`) for all API keys owned by the User
+/// associated with [`auth`](`Auth`)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "success, returns a json payload with all the API keys of the authenticated user", body = UserApiKeyResponse),
+        (status = 401, description = "Error: Unauthorized"),
+        (status = 500, description = "Could not fetch API keys."),
+    ),
+    tag = "API keys",
+    security ( ("JWT" = []))
+))]
+#[get("/api-keys")]
+async fn api_keys(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result = web::block(move || controller::get_api_keys(&db, &auth)).await?;
+
+    match result {
+        Ok(api_keys) => Ok(HttpResponse::Ok().json(api_keys)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../api-keys/{id} endpoint.
+///
+/// requires auth
+///
+/// revokes the API key with the specified [`item_id`](`ID`) if it's owned
+/// by the User associated with [`auth`](`Auth`)
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Deleted", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "API keys cannot be managed with an API key.", body = AuthMessageResponse),
+        (status = 404, description = "API key could not be found, or does not belong to authenticated user.", body = AuthMessageResponse),
+        (status = 500, description = "Internal Error.", body = AuthMessageResponse),
+        (status = 500, description = "Could not delete API key.", body = AuthMessageResponse),
+    ),
+    tag = "API keys",
+    security ( ("JWT" = []))
+))]
+#[delete("/api-keys/{id}")]
+async fn destroy_api_key(
+    db: Data<Database>,
+    item_id: Path<ID>,
+    auth: Auth,
+) -> Result<HttpResponse> {
+    let result =
+        web::block(move || controller::destroy_api_key(&db, &auth, item_id.into_inner())).await?;
+
+    match result {
+        Ok(()) => Ok(
+            HttpResponse::build(StatusCode::OK).body(json!({"message": "Deleted."}).to_string())
+        ),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../account/deletion endpoint
+///
+/// requires auth
+///
+/// schedules the deletion of the account, see [`account::request_deletion`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = DeleteAccountInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "success, returns when the account will be deleted", body = AccountDeletionResponse),
+        (status = 401, description = "Invalid credentials.", body = AuthMessageResponse),
+        (status = 500, description = "Could not schedule deletion.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/account/deletion")]
+async fn request_account_deletion(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Json(item): Json<DeleteAccountInput>,
+) -> Result<HttpResponse> {
+    let result =
+        web::block(move || account::request_deletion(&db, &auth_config, &auth, &item)).await?;
+
+    match result {
+        Ok(deletion) => Ok(HttpResponse::Ok().json(deletion)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../account/deletion endpoint
+///
+/// requires auth
+///
+/// cancels the scheduled deletion of the account, see [`account::cancel_deletion`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Account deletion cancelled.", body = AuthMessageResponse),
+        (status = 400, description = "Account deletion is not scheduled.", body = AuthMessageResponse),
+        (status = 500, description = "Could not cancel deletion.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[delete("/account/deletion")]
+async fn cancel_account_deletion(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result = web::block(move || account::cancel_deletion(&db, &auth)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok()
+            .body(json!({ "message": "Account deletion cancelled." }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../account/locale endpoint
+///
+/// requires auth
+///
+/// sets the locale the user's emails are written in, see [`account::set_locale`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = SetLocaleInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Locale updated.", body = AuthMessageResponse),
+        (status = 400, description = "'locale' must be a language tag, like 'en' or 'fr-CA'.", body = AuthMessageResponse),
+        (status = 500, description = "Could not find user.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[post("/account/locale")]
+async fn set_account_locale(
+    db: Data<Database>,
+    auth: Auth,
+    Json(item): Json<SetLocaleInput>,
+) -> Result<HttpResponse> {
+    let result = web::block(move || account::set_locale(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({ "message": "Locale updated." }).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../account/export endpoint
+///
+/// requires auth
+///
+/// everything the library stores about the user, see [`account::export`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "success, returns a json payload with the user's data"),
+        (status = 500, description = "Could not export account.", body = AuthMessageResponse),
+    ),
+    tag = "Users",
+    security ( ("JWT" = []))
+))]
+#[get("/account/export")]
+async fn export_account(db: Data<Database>, auth: Auth) -> Result<HttpResponse> {
+    let result = web::block(move || account::export(&db, &auth)).await?;
+
+    match result {
+        Ok(export) => Ok(HttpResponse::Ok().json(export)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../admin/users endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// searches users by email, see [`admin::search_users`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    params(UserSearchParams),
+    responses(
+        (status = 200, description = "Success, returns a page of users", body = AdminUserResponse),
+        (status = 400, description = "'page' cannot be negative and 'page_size' must be at least 1.", body = AuthMessageResponse),
+        (status = 500, description = "Could not fetch users.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[get("/admin/users")]
+async fn admin_users(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Query(params): Query<UserSearchParams>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || admin::search_users(&db, &auth_config, &auth, &params)).await?;
+
+    match result {
+        Ok(users) => Ok(HttpResponse::Ok().json(users)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../admin/users/{id} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::get_user`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Success, returns the user", body = AdminUserJson),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not fetch user.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[get("/admin/users/{id}")]
+async fn admin_user(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    user_id: Path<ID>,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || admin::get_user(&db, &auth_config, &auth, user_id.into_inner())).await?;
+
+    match result {
+        Ok(user) => Ok(HttpResponse::Ok().json(user)),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../admin/users/{id}/activation endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// activates or deactivates a user, see [`admin::set_activation`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = AdminActivationInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Updated.", body = AuthMessageResponse),
+        (status = 400, description = "You cannot deactivate your own account.", body = AuthMessageResponse),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not update user.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[post("/admin/users/{id}/activation")]
+async fn admin_set_activation(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    user_id: Path<ID>,
+    Json(item): Json<AdminActivationInput>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || {
+        admin::set_activation(&db, &auth_config, &auth, user_id.into_inner(), &item)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({"message": "Updated."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../admin/users/{id}/roles endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::assign_role`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = RoleInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Role assigned.", body = AuthMessageResponse),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not assign role.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[post("/admin/users/{id}/roles")]
+async fn admin_assign_role(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    user_id: Path<ID>,
+    Json(item): Json<RoleInput>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || {
+        admin::assign_role(&db, &auth_config, &auth, user_id.into_inner(), &item)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({"message": "Role assigned."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../admin/users/{id}/roles/{role} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::unassign_role`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Role unassigned.", body = AuthMessageResponse),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not unassign role.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[delete("/admin/users/{id}/roles/{role}")]
+async fn admin_unassign_role(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    path: Path<(ID, String)>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || {
+        let (user_id, role) = path.into_inner();
+        admin::unassign_role(&db, &auth_config, &auth, user_id, &role)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({"message": "Role unassigned."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../admin/users/{id}/permissions endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::grant_permission`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    request_body(content = PermissionInput, content_type = "application/json"),
+    responses(
+        (status = 200, description = "Permission granted.", body = AuthMessageResponse),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not grant permission.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[post("/admin/users/{id}/permissions")]
+async fn admin_grant_permission(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    user_id: Path<ID>,
+    Json(item): Json<PermissionInput>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || {
+        admin::grant_permission(&db, &auth_config, &auth, user_id.into_inner(), &item)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => {
+            Ok(HttpResponse::Ok().body(json!({"message": "Permission granted."}).to_string()))
+        }
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../admin/users/{id}/permissions/{permission} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::revoke_permission`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Permission revoked.", body = AuthMessageResponse),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not revoke permission.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[delete("/admin/users/{id}/permissions/{permission}")]
+async fn admin_revoke_permission(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    path: Path<(ID, String)>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || {
+        let (user_id, permission) = path.into_inner();
+        admin::revoke_permission(&db, &auth_config, &auth, user_id, &permission)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => {
+            Ok(HttpResponse::Ok().body(json!({"message": "Permission revoked."}).to_string()))
+        }
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../admin/users/{id}/sessions endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// signs a user out of every session, see [`admin::force_logout`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "Deleted.", body = AuthMessageResponse),
+        (status = 404, description = "User not found.", body = AuthMessageResponse),
+        (status = 500, description = "Could not delete sessions.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[delete("/admin/users/{id}/sessions")]
+async fn admin_force_logout(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    user_id: Path<ID>,
+) -> Result<HttpResponse, AWError> {
+    let result =
+        web::block(move || admin::force_logout(&db, &auth_config, &auth, user_id.into_inner()))
+            .await?;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body(json!({"message": "Deleted."}).to_string())),
+        Err(error) => Ok(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../admin/audit endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// the audit trail of admin actions, see [`admin::get_audit_log`]
+#[cfg_attr(feature = "plugin_utoipa", utoipa::path(
+    context_path = "/api/auth",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Success, returns a page of the audit trail", body = AdminAuditLogResponse),
+        (status = 400, description = "'page' cannot be negative and 'page_size' must be at least 1.", body = AuthMessageResponse),
+        (status = 500, description = "Could not fetch audit log.", body = AuthMessageResponse),
+        (status = 401, description = "User not authenticated"),
+        (status = 403, description = "Forbidden.", body = AuthMessageResponse),
+        (status = 404, description = "Not found. (the admin endpoints are disabled)", body = AuthMessageResponse),
+    ),
+    tag = "Admin",
+    security ( ("JWT" = []))
+))]
+#[get("/admin/audit")]
+async fn admin_audit_log(
+    db: Data<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Query(info): Query<PaginationParams>,
+) -> Result<HttpResponse, AWError> {
+    let result = web::block(move || admin::get_audit_log(&db, &auth_config, &auth, &info)).await?;
+
+    match result {
+        Ok(entries) => Ok(HttpResponse::Ok().json(entries)),
+        Err(error) => Ok(auth_error_response(error)),
     }
 }
 
@@ -580,19 +2014,59 @@ pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
         .service(destroy_session)
         .service(destroy_sessions)
         .service(login)
+        .service(login_two_factor)
+        .service(request_login_link)
+        .service(login_with_link)
         .service(logout)
         .service(check)
+        .service(jwks)
+        .service(openid_configuration)
+        .service(oauth_authorize)
+        .service(oauth_token)
+        .service(oauth_userinfo)
         .service(refresh)
         .service(register)
         .service(activate)
         .service(forgot_password)
         .service(change_password)
-        .service(reset_password);
+        .service(request_email_change)
+        .service(confirm_email_change)
+        .service(reset_password)
+        .service(enroll_two_factor)
+        .service(confirm_two_factor)
+        .service(disable_two_factor)
+        .service(start_passkey_registration)
+        .service(finish_passkey_registration)
+        .service(start_passkey_login)
+        .service(finish_passkey_login)
+        .service(passkeys)
+        .service(destroy_passkey)
+        .service(create_api_key)
+        .service(api_keys)
+        .service(destroy_api_key)
+        .service(request_account_deletion)
+        .service(cancel_account_deletion)
+        .service(set_account_locale)
+        .service(export_account)
+        .service(admin_users)
+        .service(admin_user)
+        .service(admin_set_activation)
+        .service(admin_assign_role)
+        .service(admin_unassign_role)
+        .service(admin_grant_permission)
+        .service(admin_revoke_permission)
+        .service(admin_force_logout)
+        .service(admin_audit_log);
 
     #[cfg(feature = "plugin_auth-oidc")]
     {
+        // registered before `/oidc/{provider}` so that "links" isn't mistaken for a provider
+        scope = scope.service(oidc_links);
+        scope = scope.service(oidc_unlink);
         scope = scope.service(oidc_login_redirect);
+        scope = scope.service(oidc_link);
         scope = scope.service(oidc_login);
+        scope = scope.service(oidc_login_form_post);
     }
 
     scope
@@ -602,14 +2076,19 @@ pub fn endpoints(scope: actix_web::Scope) -> actix_web::Scope {
 #[cfg(feature = "plugin_utoipa")]
 #[derive(OpenApi)]
 #[openapi(
-    paths(sessions, destroy_session, destroy_sessions, login, logout, refresh, register, activate, forgot_password, change_password, check, reset_password),
+    paths(sessions, destroy_session, destroy_sessions, login, login_two_factor, request_login_link, login_with_link, logout, refresh, register, activate, forgot_password, change_password, request_email_change, confirm_email_change, check, jwks, openid_configuration, oauth_authorize, oauth_token, oauth_userinfo, reset_password, enroll_two_factor, confirm_two_factor, disable_two_factor, start_passkey_registration, finish_passkey_registration, start_passkey_login, finish_passkey_login, passkeys, destroy_passkey, create_api_key, api_keys, destroy_api_key, request_account_deletion, cancel_account_deletion, set_account_locale, export_account, admin_users, admin_user, admin_set_activation, admin_assign_role, admin_unassign_role, admin_grant_permission, admin_revoke_permission, admin_force_logout, admin_audit_log),
     components(
-        schemas(UserSessionResponse, UserSessionJson, AuthMessageResponse, AuthTokenResponse, LoginInput, RegisterInput, ForgotInput, ChangeInput, ResetInput)
+        schemas(UserSessionResponse, UserSessionJson, AuthMessageResponse, AuthError, PasswordPolicyResponse, PasswordViolation, AuthTokenResponse, AuthTwoFactorRequiredResponse, LoginInput, LoginLinkInput, LoginLinkVerifyInput, RegisterInput, ForgotInput, ChangeInput, ChangeEmailInput, ConfirmEmailChangeInput, ResetInput, TwoFactorLoginInput, TwoFactorCodeInput, TwoFactorEnrollment, TwoFactorRecoveryCodes, PasskeyOptions, PasskeyRegisterInput, PasskeyLoginStartInput, PasskeyLoginInput, UserPasskeyResponse, UserPasskeyJson, ApiKeyInput, ApiKeyCreated, UserApiKeyResponse, UserApiKeyJson, DeleteAccountInput, SetLocaleInput, AccountDeletionResponse, AdminUserResponse, AdminUserJson, AdminAuditLogResponse, AdminAuditLog, AdminActivationInput, RoleInput, PermissionInput, AuthorizeInput, TokenInput, TokenResponse)
     ),
     tags(
         (name = "Auth", description = "users and user_sessions management endpoints"),
         (name = "Sessions", description = "Endpoints for user_sessions management"),
         (name = "Users", description = "Endpoints for useres management"),
+        (name = "Two-factor authentication", description = "Endpoints for managing TOTP two-factor authentication"),
+        (name = "Passkeys", description = "Endpoints for registering passkeys and logging in with them"),
+        (name = "API keys", description = "Endpoints for managing API keys, which machine clients use in place of access tokens"),
+        (name = "Admin", description = "Endpoints for admins to manage users, requires the admin role"),
+        (name = "Authorization server", description = "OAuth 2.0 / OpenID Connect endpoints for the apps signing their users in through this backend"),
     ),
     modifiers(&JwtSecurityAddon)
 )]
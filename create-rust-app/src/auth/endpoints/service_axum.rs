@@ -0,0 +1,1928 @@
+use std::net::SocketAddr;
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Path, Query},
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Extension, Json, Router,
+};
+use cookie::{Cookie, SameSite};
+use serde_json::json;
+
+use crate::auth::account::{self, DeleteAccountInput, SetLocaleInput};
+use crate::auth::admin::{
+    self as admin, ActivationInput as AdminActivationInput, PermissionInput, RoleInput,
+    UserSearchParams,
+};
+use crate::auth::authorization_server::{self, AuthorizeInput, AuthorizeOutcome, TokenInput};
+use crate::auth::controller::{
+    ActivationInput, ApiKeyInput, ChangeEmailInput, ChangeInput, ClientInfo,
+    ConfirmEmailChangeInput, ForgotInput, LoginInput, LoginLinkInput, LoginLinkVerifyInput,
+    LoginOutcome, PasskeyLoginInput, PasskeyLoginStartInput, PasskeyRegisterInput, RegisterInput,
+    ResetInput, TwoFactorCodeInput, TwoFactorLoginInput, COOKIE_NAME,
+};
+use crate::auth::{controller, error::AuthError, Auth, AuthConfig, PaginationParams, ID};
+use crate::util::net::request_client_ip;
+use crate::{AppConfig, Database, Mailer};
+
+fn error_response(status_code: u16, message: &'static str) -> Response {
+    (
+        StatusCode::from_u16(status_code).unwrap(),
+        Json(json!({ "message": message })),
+    )
+        .into_response()
+}
+
+/// the response to an [`AuthError`], a Json body with its `code` and `message`
+fn auth_error_response(error: AuthError) -> Response {
+    (
+        StatusCode::from_u16(error.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(error.to_json()),
+    )
+        .into_response()
+}
+
+/// runs `f` on tokio's blocking thread pool, like the actix-web endpoints do with `web::block`:
+/// the controllers use diesel, argon2 and the [`Mailer`] synchronously, which would stall the runtime
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, Response> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|_| auth_error_response(AuthError::Internal))
+}
+
+/// the [`AuthConfig`] registered with an [`Extension`] layer, or the [`fallback`](`AuthConfig::fallback`) one
+struct Config(AuthConfig);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Config {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthConfig>()
+            .or_else(|| AuthConfig::fallback())
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| {
+                error_response(500, "AuthConfig is not registered and SECRET_KEY isn't set")
+            })
+    }
+}
+
+/// the address of the client making the request, see [`request_client_ip`]
+///
+/// it's the [`ConnectInfo`] of the connection, if the app was served with
+/// `into_make_service_with_connect_info::<SocketAddr>()`, or the address a trusted proxy reported
+struct ClientIp(Option<String>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+
+        Ok(Self(request_client_ip(
+            peer,
+            header("X-Forwarded-For"),
+            header("X-Real-IP"),
+        )))
+    }
+}
+
+/// where the request came from: its [`ClientIp`] and `User-Agent` header, see [`ClientInfo`]
+struct Client(ClientInfo);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Client {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ClientIp(ip_address) = ClientIp::from_request_parts(parts, state).await?;
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+
+        Ok(Self(ClientInfo {
+            ip_address,
+            user_agent,
+        }))
+    }
+}
+
+/// the value of the cookie named `name` sent with the request, if any
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(Cookie::split_parse)
+        .filter_map(Result::ok)
+        .find(|cookie| cookie.name() == name)
+        .map(|cookie| cookie.value().to_string())
+}
+
+/// the value of the refresh token cookie sent with the request, if any
+fn refresh_token_cookie(headers: &HeaderMap) -> Option<String> {
+    cookie_value(headers, COOKIE_NAME)
+}
+
+/// a `Set-Cookie` header for `cookie`, or the response to [`AuthError::Internal`] if it isn't a valid header value
+fn set_cookie_header(cookie: &Cookie) -> Result<[(header::HeaderName, HeaderValue); 1], Response> {
+    HeaderValue::from_str(&cookie.to_string())
+        .map(|value| [(header::SET_COOKIE, value)])
+        .map_err(|_| auth_error_response(AuthError::Internal))
+}
+
+/// a `Set-Cookie` header storing the refresh token in a secure, http-only, `same_site=strict` cookie
+fn set_refresh_token_cookie(
+    refresh_token: String,
+) -> Result<[(header::HeaderName, HeaderValue); 1], Response> {
+    let mut cookie = Cookie::new(COOKIE_NAME, refresh_token);
+    cookie.set_secure(true);
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Strict);
+    cookie.set_path("/");
+
+    set_cookie_header(&cookie)
+}
+
+/// a `Set-Cookie` header deleting the refresh token cookie
+fn remove_refresh_token_cookie() -> Result<[(header::HeaderName, HeaderValue); 1], Response> {
+    let mut cookie = Cookie::named(COOKIE_NAME);
+    cookie.set_path("/");
+    cookie.make_removal();
+
+    set_cookie_header(&cookie)
+}
+
+/// handler for GET requests at the .../sessions endpoint,
+///
+/// requires auth
+///
+/// request should be a query that contains [`PaginationParams`]
+///
+/// see [`controller::get_sessions`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserSessionResponse`](`crate::auth::UserSessionResponse`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_fetch_sessions", "message": "Could not fetch sessions."}
+/// TODO: document the rest of the possible StatusCodes
+async fn sessions(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+    Query(info): Query<PaginationParams>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::get_sessions(&db, &auth, &info)).await?;
+
+    match result {
+        Ok(sessions) => Ok(Json(sessions)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../sessions enpoint
+///
+/// requires auth
+///
+/// deletes all current sessions belonging to the user
+///
+/// see [`controller::destroy_sessions`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 500 | Json payload : {"code": "could_not_delete_sessions", "message": "Could not delete sessions."}
+/// TODO: document the rest of the possible StatusCodes
+async fn destroy_sessions(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::destroy_sessions(&db, &auth)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Deleted."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../sessions/{id} endpoint.
+///
+/// requires auth
+///
+/// see [`controller::destroy_session`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 404 | Json payload : {"code": "session_not_found", "message": "Session not found."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_delete_session", "message": "Could not delete session."}
+/// TODO: document the rest of the possible StatusCodes
+async fn destroy_session(
+    Extension(db): Extension<Database>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::destroy_session(&db, &auth, item_id)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Deleted."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../login endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`LoginInput`]
+///
+/// see [`controller::login`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
+/// | 200 | Json payload : {"two_factor_required": true, "two_factor_token": "..."}, send the token and a code to .../login/2fa
+/// | 400 | Json payload : {"code": "device_too_long", "message": "'device' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
+/// TODO: document the rest of the possible StatusCodes
+async fn login(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Client(client): Client,
+    Json(item): Json<LoginInput>,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || controller::login(&db, &auth_config, &mailer, &item, &client)).await?;
+
+    match result {
+        Ok(LoginOutcome::Session(access_token, refresh_token)) => Ok((
+            set_refresh_token_cookie(refresh_token)?,
+            Json(json!({ "access_token": access_token })),
+        )
+            .into_response()),
+        Ok(LoginOutcome::TwoFactorRequired(two_factor_token)) => Ok(Json(
+            json!({ "two_factor_required": true, "two_factor_token": two_factor_token }),
+        )
+        .into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../login/link endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`LoginLinkInput`]
+///
+/// see [`controller::request_login_link`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Sent login link if the account exists."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+async fn request_login_link(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    ClientIp(ip_address): ClientIp,
+    Json(item): Json<LoginLinkInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || {
+        controller::request_login_link(&db, &auth_config, &mailer, &item, ip_address.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(Json(
+            json!({"message": "Sent login link if the account exists."}),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../login/link/verify endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`LoginLinkVerifyInput`]
+///
+/// see [`controller::login_with_link`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
+/// | 200 | Json payload : {"two_factor_required": true, "two_factor_token": "..."}, send the token and a code to .../login/2fa
+/// | 400 | Json payload : {"code": "device_too_long", "message": "'device' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
+async fn login_with_link(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Client(client): Client,
+    Json(item): Json<LoginLinkVerifyInput>,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || controller::login_with_link(&db, &auth_config, &mailer, &item, &client))
+            .await?;
+
+    match result {
+        Ok(LoginOutcome::Session(access_token, refresh_token)) => Ok((
+            set_refresh_token_cookie(refresh_token)?,
+            Json(json!({ "access_token": access_token })),
+        )
+            .into_response()),
+        Ok(LoginOutcome::TwoFactorRequired(two_factor_token)) => Ok(Json(
+            json!({ "two_factor_required": true, "two_factor_token": two_factor_token }),
+        )
+        .into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../login/2fa endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`TwoFactorLoginInput`]
+///
+/// see [`controller::login_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 401 | Json payload : {"code": "invalid_code", "message": "Invalid code."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
+async fn login_two_factor(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Client(client): Client,
+    Json(item): Json<TwoFactorLoginInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || controller::login_two_factor(&db, &auth_config, &mailer, &item, &client))
+            .await?;
+
+    match result {
+        Ok((access_token, refresh_token)) => Ok((
+            set_refresh_token_cookie(refresh_token)?,
+            Json(json!({ "access_token": access_token })),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../logout endpount
+///
+/// see [`controller::logout`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | command to delete the "refresh_token" cookie
+/// | 401 | Json payload : {"code": "invalid_session", "message": "Invalid session."}
+/// | 500 | Json payload : {"code": "could_not_delete_session", "message": "Could not delete session."}
+/// TODO: document the rest of the possible StatusCodes
+async fn logout(
+    Extension(db): Extension<Database>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, Response> {
+    let refresh_token = refresh_token_cookie(&headers);
+
+    let result = blocking(move || controller::logout(&db, refresh_token.as_deref())).await?;
+
+    match result {
+        Ok(()) => Ok((remove_refresh_token_cookie()?, StatusCode::OK)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../refresh endpoint
+///
+/// see [`controller::refresh`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
+/// | 401 | Json payload : {"code": "invalid_session", "message": "Invalid session."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// TODO: document the rest of the possible StatusCodes
+async fn refresh(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Client(client): Client,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, Response> {
+    let refresh_token = refresh_token_cookie(&headers);
+
+    let result =
+        blocking(move || controller::refresh(&db, &auth_config, refresh_token.as_deref(), &client))
+            .await?;
+
+    match result {
+        Ok((access_token, refresh_token)) => Ok((
+            set_refresh_token_cookie(refresh_token)?,
+            Json(json!({ "access_token": access_token })),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../register endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`RegisterInput`]
+///
+/// see [`controller::register`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Registered! Check your email to activate your account."}
+/// | 400 | Json payload : {"code": "already_registered", "message": "Already registered."}
+/// | 400 | Json payload : {"code": "invalid_locale", "message": "'locale' must be a language tag, like 'en' or 'fr-CA'."}
+/// | 400 | Json payload : {"message": "The password doesn't follow the password policy.", "violations": [{"code": "too_short", "min_length": 8}, ...]}
+/// TODO: document the rest of the possible StatusCodes
+async fn register(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Json(item): Json<RegisterInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::register(&db, &auth_config, &item, &mailer)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(
+            json!({"message": "Registered! Check your email to activate your account."}),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests to the .../activate endpoint
+///
+/// see [`controller::activate`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Activated."}
+/// | 400 | Json payload : {"code": "already_activated", "message": "Already activated!"}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 500 | Json payload : {"code": "could_not_activate_user", "message": "Could not activate user."}
+/// TODO: document the rest of the possible StatusCodes
+async fn activate(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Query(item): Query<ActivationInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::activate(&db, &auth_config, &item, &mailer)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Activated!"}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../forgot endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ForgotInput`]
+///
+/// see [`controller::forgot_password`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Please check your email."}
+/// TODO: document the rest of the possible StatusCodes
+async fn forgot_password(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Json(item): Json<ForgotInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || controller::forgot_password(&db, &auth_config, &item, &mailer)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Please check your email."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../change endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ChangeInput`]
+///
+/// see [`controller::change_password`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Password changed."}
+/// | 400 | Json payload : {"code": "missing_password", "message": "Missing password."}
+/// | 400 | Json payload : {"code": "password_unchanged", "message": "The new password must be different."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 400 | Json payload : {"message": "The password doesn't follow the password policy.", "violations": [{"code": "too_short", "min_length": 8}, ...]}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+/// | 500 | Json payload : {"code": "could_not_update_password", "message": "Could not update password."}
+/// TODO: document the rest of the possible StatusCodes
+async fn change_password(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    auth: Auth,
+    Json(item): Json<ChangeInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || controller::change_password(&db, &auth_config, &item, &auth, &mailer))
+            .await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Password changed"}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../email/change endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ChangeEmailInput`]
+///
+/// see [`controller::request_email_change`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Please check your new email."}
+/// | 400 | Json payload : {"code": "missing_email", "message": "Missing email."}
+/// | 400 | Json payload : {"code": "email_unchanged", "message": "The new email must be different."}
+/// | 400 | Json payload : {"code": "email_in_use", "message": "Email already in use."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+async fn request_email_change(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    auth: Auth,
+    Json(item): Json<ChangeEmailInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || {
+        controller::request_email_change(&db, &auth_config, &item, &auth, &mailer)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Please check your new email."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../email/change/confirm endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ConfirmEmailChangeInput`]
+///
+/// logs the user out of every session but the one the "refresh_token" cookie belongs to,
+/// see [`controller::confirm_email_change`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Email changed."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 400 | Json payload : {"code": "email_in_use", "message": "Email already in use."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 500 | Json payload : {"code": "could_not_update_email", "message": "Could not update email."}
+async fn confirm_email_change(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    headers: HeaderMap,
+    Json(item): Json<ConfirmEmailChangeInput>,
+) -> Result<impl IntoResponse, Response> {
+    let refresh_token = refresh_token_cookie(&headers);
+
+    let result = blocking(move || {
+        controller::confirm_email_change(&db, &auth_config, &item, refresh_token.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Email changed."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../reset endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ResetInput`]
+///
+/// see [`controller::reset_password`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Password changed."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 400 | Json payload : {"code": "password_unchanged", "message": "The new password must be different."}
+/// | 400 | Json payload : {"message": "The password doesn't follow the password policy.", "violations": [{"code": "too_short", "min_length": 8}, ...]}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 500 | Json payload : {"code": "could_not_update_password", "message": "Could not update password."}
+async fn reset_password(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Json(item): Json<ResetInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || controller::reset_password(&db, &auth_config, &item, &mailer)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Password reset"}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../check endpoint
+///
+/// requires auth, but doesn't match it to a user
+///
+/// see [`controller::check`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | ()
+async fn check(auth: Auth) -> StatusCode {
+    controller::check(&auth);
+    StatusCode::OK
+}
+
+/// handler for GET requests to the .../.well-known/jwks.json endpoint
+///
+/// see [`controller::jwks`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the JWK set containing the public keys access tokens are signed with
+async fn jwks(Config(auth_config): Config) -> Json<jsonwebtoken::jwk::JwkSet> {
+    Json(controller::jwks(&auth_config))
+}
+
+/// handler for GET requests to the .../.well-known/openid-configuration endpoint
+///
+/// see [`authorization_server::discovery`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the OpenID Connect discovery document
+/// | 404 | () the authorization server is disabled
+async fn openid_configuration(
+    Extension(app_config): Extension<AppConfig>,
+    Config(auth_config): Config,
+) -> Response {
+    match authorization_server::discovery(&app_config, &auth_config) {
+        Some(document) => Json(document).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// handler for POST requests to the .../oauth/authorize endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`AuthorizeInput`]
+///
+/// see [`authorization_server::authorize`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"redirect_uri": "..."}, the user should be sent there
+/// | 200 | Json payload : {"consent_required": true, "client_name": "...", "scopes": [...]}
+/// | 400 | Json payload : {"code": "unknown_client", "message": "Unknown client."}
+/// | 400 | Json payload : {"code": "invalid_redirect_uri", "message": "Invalid redirect_uri."}
+/// | 403 | Json payload : {"code": "api_key_cannot_authorize", "message": "API keys cannot authorize clients."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."}
+/// | 500 | Json payload : {"code": "could_not_create_authorization_code", "message": "Could not create authorization code."}
+async fn oauth_authorize(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Json(item): Json<AuthorizeInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || authorization_server::authorize(&db, &auth_config, &auth, &item)).await?;
+
+    match result {
+        Ok(AuthorizeOutcome::Redirect(redirect_uri)) => {
+            Ok(Json(json!({ "redirect_uri": redirect_uri })))
+        }
+        Ok(AuthorizeOutcome::ConsentRequired {
+            client_name,
+            scopes,
+        }) => Ok(Json(
+            json!({ "consent_required": true, "client_name": client_name, "scopes": scopes }),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../oauth/token endpoint
+///
+/// request must have the `Content-Type: application/x-www-form-urlencoded` header, and a form that can be deserialized into [`TokenInput`]
+///
+/// see [`authorization_server::token`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`TokenResponse`](`authorization_server::TokenResponse`) deserialized into a Json payload
+/// | 400 | Json payload : {"error": "invalid_request" \| "invalid_grant" \| "unsupported_grant_type"}
+/// | 401 | Json payload : {"error": "invalid_client"}
+/// | 404 | Json payload : {"error": "Not found."}
+async fn oauth_token(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    Config(auth_config): Config,
+    headers: HeaderMap,
+    axum::Form(item): axum::Form<TokenInput>,
+) -> Response {
+    let credentials = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(authorization_server::basic_credentials);
+
+    let result = match blocking(move || {
+        authorization_server::token(&db, &app_config, &auth_config, &item, credentials)
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(response) => return response,
+    };
+
+    match result {
+        Ok(tokens) => ([(header::CACHE_CONTROL, "no-store")], Json(tokens)).into_response(),
+        Err(error) => auth_error_response(error),
+    }
+}
+
+/// handler for GET requests to the .../oauth/userinfo endpoint
+///
+/// requires an access token issued to a client by the .../oauth/token endpoint
+///
+/// see [`authorization_server::userinfo`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"sub": "..."}, with "email" and "email_verified" if the token was granted the email scope
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}, the token wasn't granted the openid scope
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+async fn oauth_userinfo(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, Response> {
+    let access_token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(authorization_server::bearer_token)
+        .map(ToString::to_string);
+
+    let result = blocking(move || {
+        authorization_server::userinfo(&db, &auth_config, access_token.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(claims) => Ok(Json(claims)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../2fa/enroll endpoint
+///
+/// requires auth
+///
+/// see [`controller::enroll_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`TwoFactorEnrollment`](`controller::TwoFactorEnrollment`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "two_factor_already_enabled", "message": "Two-factor authentication is already enabled."}
+/// | 500 | Json payload : {"code": "could_not_enroll_two_factor", "message": "Could not enroll."}
+async fn enroll_two_factor(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::enroll_two_factor(&db, &app_config, &auth)).await?;
+
+    match result {
+        Ok(enrollment) => Ok(Json(enrollment)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../2fa/confirm endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`TwoFactorCodeInput`]
+///
+/// see [`controller::confirm_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`TwoFactorRecoveryCodes`](`controller::TwoFactorRecoveryCodes`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "two_factor_not_enrolled", "message": "Two-factor authentication has not been enrolled."}
+/// | 400 | Json payload : {"code": "two_factor_already_enabled", "message": "Two-factor authentication is already enabled."}
+/// | 401 | Json payload : {"code": "invalid_code", "message": "Invalid code."}
+/// | 500 | Json payload : {"code": "could_not_enable_two_factor", "message": "Could not enable two-factor authentication."}
+async fn confirm_two_factor(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+    Json(item): Json<TwoFactorCodeInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::confirm_two_factor(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(recovery_codes) => Ok(Json(recovery_codes)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../2fa/disable endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`TwoFactorCodeInput`]
+///
+/// see [`controller::disable_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Two-factor authentication disabled."}
+/// | 400 | Json payload : {"code": "two_factor_not_enabled", "message": "Two-factor authentication is not enabled."}
+/// | 401 | Json payload : {"code": "invalid_code", "message": "Invalid code."}
+/// | 500 | Json payload : {"code": "could_not_disable_two_factor", "message": "Could not disable two-factor authentication."}
+async fn disable_two_factor(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+    Json(item): Json<TwoFactorCodeInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::disable_two_factor(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(
+            json!({"message": "Two-factor authentication disabled."}),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// handler for GET requests to the .../oidc/{provider} endpoint
+///
+/// redirects to the login page of the given OIDC provider
+///
+/// see [`crate::auth::oidc::controller::oidc_login_url`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 303 | redirect to the provider
+/// | 501 | the provider is not configured
+/// | 500 | ()
+async fn oidc_login_redirect(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    Config(auth_config): Config,
+    Path(provider): Path<String>,
+) -> Response {
+    let result =
+        crate::auth::oidc::controller::oidc_login_url(&db, &app_config, &auth_config, provider)
+            .await;
+
+    match result {
+        Ok(Some(url)) => axum::response::Redirect::to(&url).into_response(),
+        Ok(None) => StatusCode::NOT_IMPLEMENTED.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+#[derive(serde::Deserialize)]
+struct OIDCLoginQueryParams {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// handler for GET requests to the .../oidc/{provider}/login endpoint, the provider redirects here
+///
+/// see [`oidc_callback`]
+async fn oidc_login(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    Config(auth_config): Config,
+    Client(client): Client,
+    Path(provider_name): Path<String>,
+    headers: HeaderMap,
+    Query(query_params): Query<OIDCLoginQueryParams>,
+) -> Response {
+    oidc_callback(
+        &db,
+        &app_config,
+        &auth_config,
+        provider_name,
+        query_params,
+        &headers,
+        &client,
+    )
+    .await
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// handler for POST requests to the .../oidc/{provider}/login endpoint, providers using
+/// `response_mode=form_post` (like Apple) post their response here
+///
+/// see [`oidc_callback`]
+async fn oidc_login_form_post(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    Config(auth_config): Config,
+    Client(client): Client,
+    Path(provider_name): Path<String>,
+    headers: HeaderMap,
+    axum::Form(form): axum::Form<OIDCLoginQueryParams>,
+) -> Response {
+    oidc_callback(
+        &db,
+        &app_config,
+        &auth_config,
+        provider_name,
+        form,
+        &headers,
+        &client,
+    )
+    .await
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// completes the OIDC flow the provider redirected the user back from
+///
+/// see [`crate::auth::oidc::controller::oauth_login`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 303 | redirect to the provider's `success_uri` with an "access_token" query parameter, and a "refresh_token" cookie
+/// | 303 | redirect to the provider's `success_uri` with a "linked" query parameter, when a logged in user linked the provider
+/// | 303 | redirect to the provider's `error_uri` with "status_code" and "message" query parameters
+/// | 500 | Json payload : {"success": false, "message": "Provider not configured", "provider": "..."}
+async fn oidc_callback(
+    db: &Database,
+    app_config: &AppConfig,
+    auth_config: &AuthConfig,
+    provider_name: String,
+    params: OIDCLoginQueryParams,
+    headers: &HeaderMap,
+    client: &ClientInfo,
+) -> Response {
+    use crate::auth::oidc::controller::LINK_STATE_COOKIE_NAME;
+    use axum::response::Redirect;
+
+    let Some(provider) = auth_config
+        .oidc_providers
+        .iter()
+        .find(|p| p.name.eq(&provider_name))
+        .cloned()
+    else {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Provider not configured",
+                "provider": &provider_name
+            })),
+        )
+            .into_response();
+    };
+
+    let result = crate::auth::oidc::controller::oauth_login(
+        db,
+        app_config,
+        auth_config,
+        provider_name,
+        params.code,
+        params.error,
+        params.state,
+        cookie_value(headers, LINK_STATE_COOKIE_NAME),
+        client,
+    )
+    .await;
+
+    // the link flow is over, whether it succeeded or not
+    let mut link_state_cookie = Cookie::named(LINK_STATE_COOKIE_NAME);
+    link_state_cookie.set_path("/");
+    link_state_cookie.make_removal();
+    let remove_link_state_cookie = match set_cookie_header(&link_state_cookie) {
+        Ok(header) => header,
+        Err(response) => return response,
+    };
+
+    match result {
+        Ok(Some((access_token, refresh_token))) => match set_refresh_token_cookie(refresh_token) {
+            Ok(header) => (
+                header,
+                Redirect::to(&format!(
+                    "{}?access_token={}",
+                    provider.success_uri, access_token
+                )),
+            )
+                .into_response(),
+            Err(response) => response,
+        },
+        Ok(None) => (
+            remove_link_state_cookie,
+            Redirect::to(&format!(
+                "{}?linked={}",
+                provider.success_uri, provider.name
+            )),
+        )
+            .into_response(),
+        Err(error) => (
+            remove_link_state_cookie,
+            Redirect::to(&crate::auth::oidc::controller::error_redirect_url(
+                &provider, &error,
+            )),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// handler for GET requests to the .../oidc/{provider}/link endpoint
+///
+/// requires auth
+///
+/// see [`crate::auth::oidc::controller::oidc_link_url`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"url": "..."}, the user should be sent there to link the provider, and an "oidc_link_state" cookie
+/// | 501 | the provider is not configured
+/// | 500 | ()
+async fn oidc_link(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    Config(auth_config): Config,
+    Path(provider): Path<String>,
+    auth: Auth,
+) -> Response {
+    let result = crate::auth::oidc::controller::oidc_link_url(
+        &db,
+        &app_config,
+        &auth_config,
+        &auth,
+        provider,
+    )
+    .await;
+
+    match result {
+        Ok(Some((url, state))) => {
+            // sent along with the provider's (cross-site) redirect or form post to the callback
+            let mut cookie =
+                Cookie::new(crate::auth::oidc::controller::LINK_STATE_COOKIE_NAME, state);
+            cookie.set_secure(true);
+            cookie.set_http_only(true);
+            cookie.set_same_site(SameSite::None);
+            cookie.set_path("/");
+
+            match set_cookie_header(&cookie) {
+                Ok(header) => (header, Json(json!({ "url": url }))).into_response(),
+                Err(response) => response,
+            }
+        }
+        Ok(None) => StatusCode::NOT_IMPLEMENTED.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// handler for GET requests at the .../oidc/links endpoint
+///
+/// requires auth
+///
+/// see [`crate::auth::oidc::controller::get_oidc_links`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserOauth2LinkResponse`](`crate::auth::oidc::UserOauth2LinkResponse`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_fetch_links", "message": "Could not fetch linked accounts."}
+async fn oidc_links(Extension(db): Extension<Database>, auth: Auth) -> Result<Response, Response> {
+    let result =
+        blocking(move || crate::auth::oidc::controller::get_oidc_links(&db, &auth)).await?;
+
+    Ok(match result {
+        Ok(links) => Json(links).into_response(),
+        Err(error) => auth_error_response(error),
+    })
+}
+
+#[cfg(feature = "plugin_auth-oidc")]
+/// handler for DELETE requests at the .../oidc/links/{id} endpoint
+///
+/// requires auth
+///
+/// see [`crate::auth::oidc::controller::unlink_oidc_provider`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 400 | Json payload : {"code": "cannot_unlink_only_login", "message": "Cannot unlink the only way to log in."}
+/// | 404 | Json payload : {"code": "link_not_found", "message": "Linked account not found."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+async fn oidc_unlink(
+    Extension(db): Extension<Database>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || crate::auth::oidc::controller::unlink_oidc_provider(&db, &auth, item_id))
+            .await?;
+
+    Ok(match result {
+        Ok(()) => Json(json!({"message": "Deleted."})).into_response(),
+        Err(error) => auth_error_response(error),
+    })
+}
+
+/// handler for POST requests to the .../passkeys/register/start endpoint
+///
+/// requires auth
+///
+/// see [`controller::start_passkey_registration`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`PasskeyOptions`](`controller::PasskeyOptions`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+async fn start_passkey_registration(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || controller::start_passkey_registration(&db, &app_config, &auth)).await?;
+
+    match result {
+        Ok(options) => Ok(Json(options)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../passkeys/register/finish endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PasskeyRegisterInput`]
+///
+/// see [`controller::finish_passkey_registration`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserPasskeyJson`](`crate::auth::UserPasskeyJson`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "name_too_long", "message": "'name' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "invalid_challenge", "message": "Invalid challenge."}
+/// | 400 | Json payload : {"code": "invalid_passkey_registration", "message": "Invalid passkey."}
+/// | 400 | Json payload : {"code": "passkey_already_registered", "message": "Passkey is already registered."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_register_passkey", "message": "Could not register passkey."}
+async fn finish_passkey_registration(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    auth: Auth,
+    Json(item): Json<PasskeyRegisterInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || controller::finish_passkey_registration(&db, &app_config, &auth, &item))
+            .await?;
+
+    match result {
+        Ok(passkey) => Ok(Json(passkey)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../passkeys/login/start endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PasskeyLoginStartInput`]
+///
+/// see [`controller::start_passkey_login`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`PasskeyOptions`](`controller::PasskeyOptions`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+async fn start_passkey_login(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    Json(item): Json<PasskeyLoginStartInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::start_passkey_login(&db, &app_config, &item)).await?;
+
+    match result {
+        Ok(options) => Ok(Json(options)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests to the .../passkeys/login/finish endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PasskeyLoginInput`]
+///
+/// see [`controller::finish_passkey_login`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT associated with the user
+/// | 400 | Json payload : {"code": "device_too_long", "message": "'device' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_passkey", "message": "Invalid passkey."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
+async fn finish_passkey_login(
+    Extension(db): Extension<Database>,
+    Extension(app_config): Extension<AppConfig>,
+    Config(auth_config): Config,
+    Extension(mailer): Extension<Mailer>,
+    Client(client): Client,
+    Json(item): Json<PasskeyLoginInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || {
+        controller::finish_passkey_login(&db, &app_config, &auth_config, &mailer, &item, &client)
+    })
+    .await?;
+
+    match result {
+        Ok((access_token, refresh_token)) => Ok((
+            set_refresh_token_cookie(refresh_token)?,
+            Json(json!({ "access_token": access_token })),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../passkeys endpoint
+///
+/// requires auth
+///
+/// see [`controller::get_passkeys`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserPasskeyResponse`](`crate::auth::UserPasskeyResponse`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_fetch_passkeys", "message": "Could not fetch passkeys."}
+async fn passkeys(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::get_passkeys(&db, &auth)).await?;
+
+    match result {
+        Ok(passkeys) => Ok(Json(passkeys)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../passkeys/{id} endpoint.
+///
+/// requires auth
+///
+/// see [`controller::destroy_passkey`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 404 | Json payload : {"code": "passkey_not_found", "message": "Passkey not found."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_delete_passkey", "message": "Could not delete passkey."}
+async fn destroy_passkey(
+    Extension(db): Extension<Database>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::destroy_passkey(&db, &auth, item_id)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Deleted."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../api-keys endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ApiKeyInput`]
+///
+/// see [`controller::create_api_key`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`ApiKeyCreated`](`controller::ApiKeyCreated`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "invalid_name", "message": "'name' must be between 1 and 256 characters."}
+/// | 400 | Json payload : {"code": "invalid_permission", "message": "Invalid permission."}
+/// | 400 | Json payload : {"code": "expiry_in_past", "message": "'expires_at' must be in the future."}
+/// | 403 | Json payload : {"code": "api_key_not_allowed", "message": "API keys cannot be managed with an API key."}
+/// | 500 | Json payload : {"code": "could_not_create_api_key", "message": "Could not create API key."}
+async fn create_api_key(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+    Json(item): Json<ApiKeyInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::create_api_key(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(api_key) => Ok(Json(api_key)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../api-keys endpoint
+///
+/// requires auth
+///
+/// see [`controller::get_api_keys`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserApiKeyResponse`](`crate::auth::UserApiKeyResponse`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_fetch_api_keys", "message": "Could not fetch API keys."}
+async fn api_keys(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::get_api_keys(&db, &auth)).await?;
+
+    match result {
+        Ok(api_keys) => Ok(Json(api_keys)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../api-keys/{id} endpoint.
+///
+/// requires auth
+///
+/// see [`controller::destroy_api_key`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 403 | Json payload : {"code": "api_key_not_allowed", "message": "API keys cannot be managed with an API key."}
+/// | 404 | Json payload : {"code": "api_key_not_found", "message": "API key not found."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_delete_api_key", "message": "Could not delete API key."}
+async fn destroy_api_key(
+    Extension(db): Extension<Database>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || controller::destroy_api_key(&db, &auth, item_id)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Deleted."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../account/deletion endpoint
+///
+/// requires auth
+///
+/// see [`account::request_deletion`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AccountDeletionResponse`](`crate::auth::account::AccountDeletionResponse`) deserialized into a Json payload
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 500 | Json payload : {"code": "could_not_schedule_deletion", "message": "Could not schedule deletion."}
+async fn request_account_deletion(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Json(item): Json<DeleteAccountInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result =
+        blocking(move || account::request_deletion(&db, &auth_config, &auth, &item)).await?;
+
+    match result {
+        Ok(deletion) => Ok(Json(deletion)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../account/deletion endpoint
+///
+/// requires auth
+///
+/// see [`account::cancel_deletion`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Account deletion cancelled."}
+/// | 400 | Json payload : {"code": "deletion_not_scheduled", "message": "Account deletion is not scheduled."}
+/// | 500 | Json payload : {"code": "could_not_cancel_deletion", "message": "Could not cancel deletion."}
+async fn cancel_account_deletion(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || account::cancel_deletion(&db, &auth)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Account deletion cancelled."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../account/locale endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`SetLocaleInput`]
+///
+/// see [`account::set_locale`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Locale updated."}
+/// | 400 | Json payload : {"code": "invalid_locale", "message": "'locale' must be a language tag, like 'en' or 'fr-CA'."}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+async fn set_account_locale(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+    Json(item): Json<SetLocaleInput>,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || account::set_locale(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Locale updated."}))),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../account/export endpoint
+///
+/// requires auth
+///
+/// see [`account::export`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AccountExport`](`crate::auth::account::AccountExport`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_export_account", "message": "Could not export account."}
+async fn export_account(
+    Extension(db): Extension<Database>,
+    auth: Auth,
+) -> Result<impl IntoResponse, Response> {
+    let result = blocking(move || account::export(&db, &auth)).await?;
+
+    match result {
+        Ok(export) => Ok(Json(export)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../admin/users endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// searches users by email
+///
+/// request should be a query that contains [`UserSearchParams`]
+///
+/// see [`admin::search_users`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AdminUserResponse`](`admin::AdminUserResponse`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "invalid_pagination", "message": "'page' cannot be negative and 'page_size' must be at least 1."}
+/// | 500 | Json payload : {"code": "could_not_fetch_users", "message": "Could not fetch users."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_users(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Query(params): Query<UserSearchParams>,
+) -> Result<Response, Response> {
+    let result = blocking(move || admin::search_users(&db, &auth_config, &auth, &params)).await?;
+
+    match result {
+        Ok(body) => Ok(Json(body).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../admin/users/{id} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::get_user`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AdminUserJson`](`admin::AdminUserJson`) deserialized into a Json payload
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_fetch_user", "message": "Could not fetch user."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_user(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+) -> Result<Response, Response> {
+    let result = blocking(move || admin::get_user(&db, &auth_config, &auth, user_id)).await?;
+
+    match result {
+        Ok(body) => Ok(Json(body).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../admin/users/{id}/activation endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// activates or deactivates a user
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`AdminActivationInput`]
+///
+/// see [`admin::set_activation`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Updated."}
+/// | 400 | Json payload : {"code": "cannot_deactivate_self", "message": "You cannot deactivate your own account."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_update_user", "message": "Could not update user."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_set_activation(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+    Json(item): Json<AdminActivationInput>,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || admin::set_activation(&db, &auth_config, &auth, user_id, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Updated."})).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../admin/users/{id}/roles endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`RoleInput`]
+///
+/// see [`admin::assign_role`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Role assigned."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_assign_role", "message": "Could not assign role."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_assign_role(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+    Json(item): Json<RoleInput>,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || admin::assign_role(&db, &auth_config, &auth, user_id, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Role assigned."})).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../admin/users/{id}/roles/{role} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::unassign_role`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Role unassigned."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_unassign_role", "message": "Could not unassign role."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_unassign_role(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Path((user_id, role)): Path<(ID, String)>,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || admin::unassign_role(&db, &auth_config, &auth, user_id, &role)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Role unassigned."})).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for POST requests at the .../admin/users/{id}/permissions endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PermissionInput`]
+///
+/// see [`admin::grant_permission`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Permission granted."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_grant_permission", "message": "Could not grant permission."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_grant_permission(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+    Json(item): Json<PermissionInput>,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || admin::grant_permission(&db, &auth_config, &auth, user_id, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Permission granted."})).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../admin/users/{id}/permissions/{permission} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::revoke_permission`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Permission revoked."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_revoke_permission", "message": "Could not revoke permission."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_revoke_permission(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Path((user_id, permission)): Path<(ID, String)>,
+) -> Result<Response, Response> {
+    let result =
+        blocking(move || admin::revoke_permission(&db, &auth_config, &auth, user_id, &permission))
+            .await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Permission revoked."})).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for DELETE requests at the .../admin/users/{id}/sessions endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// signs a user out of every session
+///
+/// see [`admin::force_logout`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_delete_sessions", "message": "Could not delete sessions."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_force_logout(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+) -> Result<Response, Response> {
+    let result = blocking(move || admin::force_logout(&db, &auth_config, &auth, user_id)).await?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"message": "Deleted."})).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// handler for GET requests at the .../admin/audit endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// the audit trail of admin actions, most recent first
+///
+/// request should be a query that contains [`PaginationParams`]
+///
+/// see [`admin::get_audit_log`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AdminAuditLogResponse`](`admin::AdminAuditLogResponse`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "invalid_pagination", "message": "'page' cannot be negative and 'page_size' must be at least 1."}
+/// | 500 | Json payload : {"code": "could_not_fetch_audit_log", "message": "Could not fetch audit log."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_audit_log(
+    Extension(db): Extension<Database>,
+    Config(auth_config): Config,
+    auth: Auth,
+    Query(info): Query<PaginationParams>,
+) -> Result<Response, Response> {
+    let result = blocking(move || admin::get_audit_log(&db, &auth_config, &auth, &info)).await?;
+
+    match result {
+        Ok(body) => Ok(Json(body).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+/// returns the endpoints for the Auth service
+///
+/// the [`Database`], [`Mailer`], [`AuthConfig`] and [`AppConfig`] must be registered with
+/// [`Extension`] layers, for example:
+/// ```ignore
+/// let app = Router::new()
+///     .nest("/api/auth", create_rust_app::auth::router())
+///     .layer(Extension(app_data.database))
+///     .layer(Extension(app_data.mailer))
+///     .layer(Extension(AuthConfig::from_env()?))
+///     .layer(Extension(AppConfig { app_url }));
+/// ```
+pub fn router() -> Router {
+    let router = Router::new()
+        .route("/sessions", get(sessions).delete(destroy_sessions))
+        .route("/sessions/:id", delete(destroy_session))
+        .route("/login", post(login))
+        .route("/login/2fa", post(login_two_factor))
+        .route("/login/link", post(request_login_link))
+        .route("/login/link/verify", post(login_with_link))
+        .route("/logout", post(logout))
+        .route("/check", post(check))
+        .route("/.well-known/jwks.json", get(jwks))
+        .route(
+            "/.well-known/openid-configuration",
+            get(openid_configuration),
+        )
+        .route("/oauth/authorize", post(oauth_authorize))
+        .route("/oauth/token", post(oauth_token))
+        .route("/oauth/userinfo", get(oauth_userinfo))
+        .route("/refresh", post(refresh))
+        .route("/register", post(register))
+        .route("/activate", get(activate))
+        .route("/forgot", post(forgot_password))
+        .route("/change", post(change_password))
+        .route("/email/change", post(request_email_change))
+        .route("/email/change/confirm", post(confirm_email_change))
+        .route("/reset", post(reset_password))
+        .route("/2fa/enroll", post(enroll_two_factor))
+        .route("/2fa/confirm", post(confirm_two_factor))
+        .route("/2fa/disable", post(disable_two_factor))
+        .route("/passkeys", get(passkeys))
+        .route("/passkeys/:id", delete(destroy_passkey))
+        .route("/passkeys/register/start", post(start_passkey_registration))
+        .route(
+            "/passkeys/register/finish",
+            post(finish_passkey_registration),
+        )
+        .route("/passkeys/login/start", post(start_passkey_login))
+        .route("/passkeys/login/finish", post(finish_passkey_login))
+        .route("/api-keys", get(api_keys).post(create_api_key))
+        .route("/api-keys/:id", delete(destroy_api_key))
+        .route(
+            "/account/deletion",
+            post(request_account_deletion).delete(cancel_account_deletion),
+        )
+        .route("/account/locale", post(set_account_locale))
+        .route("/account/export", get(export_account))
+        .route("/admin/users", get(admin_users))
+        .route("/admin/users/:id", get(admin_user))
+        .route("/admin/users/:id/activation", post(admin_set_activation))
+        .route("/admin/users/:id/roles", post(admin_assign_role))
+        .route("/admin/users/:id/roles/:role", delete(admin_unassign_role))
+        .route("/admin/users/:id/permissions", post(admin_grant_permission))
+        .route(
+            "/admin/users/:id/permissions/:permission",
+            delete(admin_revoke_permission),
+        )
+        .route("/admin/users/:id/sessions", delete(admin_force_logout))
+        .route("/admin/audit", get(admin_audit_log));
+
+    #[cfg(feature = "plugin_auth-oidc")]
+    let router = router
+        .route("/oidc/links", get(oidc_links))
+        .route("/oidc/links/:id", delete(oidc_unlink))
+        .route("/oidc/:provider", get(oidc_login_redirect))
+        .route("/oidc/:provider/link", get(oidc_link))
+        .route(
+            "/oidc/:provider/login",
+            get(oidc_login).post(oidc_login_form_post),
+        );
+
+    router
+}
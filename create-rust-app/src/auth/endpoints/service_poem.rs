@@ -1,20 +1,29 @@
 use poem::{
-    delete, get, handler,
-    http::StatusCode,
+    async_trait, delete, get, handler,
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
     post,
     web::{
         cookie::{Cookie, CookieJar, SameSite},
-        Data, Json, Path, Query,
+        Data, Form, Json, Path, Query, RemoteAddr,
     },
-    Error, IntoResponse, Response, Result, Route,
+    Error, FromRequest, IntoResponse, Request, RequestBody, Response, Result, Route,
 };
 use serde_json::json;
 
+use crate::auth::account::{self, DeleteAccountInput, SetLocaleInput};
+use crate::auth::admin::{
+    self as admin, ActivationInput as AdminActivationInput, PermissionInput, RoleInput,
+    UserSearchParams,
+};
+use crate::auth::authorization_server::{self, AuthorizeInput, AuthorizeOutcome, TokenInput};
 use crate::auth::controller::{
-    ActivationInput, ChangeInput, ForgotInput, LoginInput, RegisterInput, ResetInput, COOKIE_NAME,
+    ActivationInput, ApiKeyInput, ChangeEmailInput, ChangeInput, ClientInfo,
+    ConfirmEmailChangeInput, ForgotInput, LoginInput, LoginLinkInput, LoginLinkVerifyInput,
+    LoginOutcome, PasskeyLoginInput, PasskeyLoginStartInput, PasskeyRegisterInput, RegisterInput,
+    ResetInput, TwoFactorCodeInput, TwoFactorLoginInput, COOKIE_NAME,
 };
-use crate::auth::{controller, Auth, PaginationParams, ID};
-use crate::{Database, Mailer};
+use crate::auth::{controller, error::AuthError, Auth, AuthConfig, PaginationParams, ID};
+use crate::{AppConfig, Database, Mailer};
 
 fn error_response(status_code: u16, message: &'static str) -> Error {
     Error::from_string(
@@ -23,6 +32,59 @@ fn error_response(status_code: u16, message: &'static str) -> Error {
     )
 }
 
+/// the error response to an [`AuthError`], a Json body with its `code` and `message`
+fn auth_error_response(error: AuthError) -> Error {
+    Error::from_string(
+        error.to_json().to_string(),
+        StatusCode::from_u16(error.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+    )
+}
+
+/// runs `f` on tokio's blocking thread pool, like the actix-web endpoints do with `web::block`:
+/// the controllers use diesel, argon2 and the [`Mailer`] synchronously, which would stall the runtime
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|_| auth_error_response(AuthError::Internal))
+}
+
+/// the [`AuthConfig`] registered as data, or the [`fallback`](`AuthConfig::fallback`) one
+struct Config<'a>(&'a AuthConfig);
+
+#[async_trait]
+impl<'a> FromRequest<'a> for Config<'a> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        req.data::<AuthConfig>()
+            .or_else(|| AuthConfig::fallback())
+            .map(Self)
+            .ok_or_else(|| {
+                error_response(500, "AuthConfig is not registered and SECRET_KEY isn't set")
+            })
+    }
+}
+
+/// the address of the client making the request, see [`request_client_ip`](`crate::net::request_client_ip`)
+fn client_ip(remote_addr: &RemoteAddr, headers: &HeaderMap) -> Option<String> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+    crate::net::request_client_ip(
+        remote_addr.as_socket_addr().map(std::net::SocketAddr::ip),
+        header("X-Forwarded-For"),
+        header("X-Real-IP"),
+    )
+}
+
+/// where the request came from, see [`ClientInfo`]
+fn client_info(remote_addr: &RemoteAddr, headers: &HeaderMap) -> ClientInfo {
+    ClientInfo {
+        ip_address: client_ip(remote_addr, headers),
+        user_agent: headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string),
+    }
+}
+
 #[handler]
 /// handler for GET requests at the .../sessions endpoint,
 ///
@@ -36,18 +98,19 @@ fn error_response(status_code: u16, message: &'static str) -> Error {
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | [`UserSessionResponse`](`crate::auth::UserSessionResponse`) deserialized into a Json payload
-/// | 500 | Json payload : {"message": "Could not fetch sessions."}
+/// | 500 | Json payload : {"code": "could_not_fetch_sessions", "message": "Could not fetch sessions."}
 /// TODO: document the rest of the possible StatusCodes
 async fn sessions(
     db: Data<&Database>,
     auth: Auth,
     Query(info): Query<PaginationParams>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::get_sessions(db.0, &auth, &info);
+    let db = db.0.clone();
+    let result = blocking(move || controller::get_sessions(&db, &auth, &info)).await?;
 
     match result {
         Ok(sessions) => Ok(Json(sessions)),
-        Err((status_code, message)) => Err(error_response(status_code, message)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -64,14 +127,15 @@ async fn sessions(
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Deleted."}
-/// | 500 | Json payload : {"message": "Could not delete sessions."}
+/// | 500 | Json payload : {"code": "could_not_delete_sessions", "message": "Could not delete sessions."}
 /// TODO: document the rest of the possible StatusCodes
 async fn destroy_sessions(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
-    let result = controller::destroy_sessions(db.0, &auth);
+    let db = db.0.clone();
+    let result = blocking(move || controller::destroy_sessions(&db, &auth)).await?;
 
     match result {
         Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -87,20 +151,21 @@ async fn destroy_sessions(db: Data<&Database>, auth: Auth) -> Result<impl IntoRe
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Deleted."}
-/// | 404 | Json payload : {"message": "Session not found."}
-/// | 500 | Json payload : {"message": "Internal error."}
-/// | 500 | Json payload : {"message": "Could not delete session.`}
+/// | 404 | Json payload : {"code": "session_not_found", "message": "Session not found."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_delete_session", "message": "Could not delete session."}
 /// TODO: document the rest of the possible StatusCodes
 async fn destroy_session(
     db: Data<&Database>,
     Path(item_id): Path<ID>,
     auth: Auth,
 ) -> Result<impl IntoResponse> {
-    let result = controller::destroy_session(db.0, &auth, item_id);
+    let db = db.0.clone();
+    let result = blocking(move || controller::destroy_session(&db, &auth, item_id)).await?;
 
     match result {
         Ok(_) => Ok(Response::builder().status(StatusCode::OK).finish()),
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -115,18 +180,185 @@ async fn destroy_session(
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
-/// | 400 | Json payload : {"message": "'device' cannot be longer than 256 characters."}
-/// | 400 | Json payload : {"message": "Account has not been activated."}
-/// | 401 | Json payload : {"message": "Invalid credentials."}
-/// | 500 | Json payload : {"message": "An internal server error occurred."}
-/// | 500 | Json payload : {"message": "Could not create a session."}
+/// | 200 | Json payload : {"two_factor_required": true, "two_factor_token": "..."}, send the token and a code to .../login/2fa
+/// | 400 | Json payload : {"code": "device_too_long", "message": "'device' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
 /// TODO: document the rest of the possible StatusCodes
 async fn login(
     db: Data<&Database>,
+    auth_config: Config<'_>,
+    mailer: Data<&Mailer>,
+    remote_addr: &RemoteAddr,
+    headers: &HeaderMap,
     Json(item): Json<LoginInput>,
     cookie_jar: &CookieJar,
 ) -> Result<impl IntoResponse> {
-    let result = controller::login(db.0, &item);
+    let client = client_info(remote_addr, headers);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result =
+        blocking(move || controller::login(&db, &auth_config, &mailer, &item, &client)).await?;
+
+    match result {
+        Ok(LoginOutcome::Session(access_token, refresh_token)) => {
+            let mut cookie = Cookie::new(COOKIE_NAME, refresh_token);
+            cookie.set_secure(true);
+            cookie.set_http_only(true);
+            cookie.set_same_site(SameSite::Strict);
+            cookie.set_path("/");
+            cookie_jar.add(cookie);
+
+            let json = json!({ "access_token": access_token }).to_string();
+            let response = Response::builder().status(StatusCode::OK).body(json);
+
+            Ok(response)
+        }
+        Ok(LoginOutcome::TwoFactorRequired(two_factor_token)) => {
+            let json = json!({ "two_factor_required": true, "two_factor_token": two_factor_token })
+                .to_string();
+            let response = Response::builder().status(StatusCode::OK).body(json);
+
+            Ok(response)
+        }
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../login/link endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`LoginLinkInput`]
+///
+/// see [`controller::request_login_link`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Sent login link if the account exists."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+async fn request_login_link(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    mailer: Data<&Mailer>,
+    remote_addr: &RemoteAddr,
+    headers: &HeaderMap,
+    Json(item): Json<LoginLinkInput>,
+) -> Result<impl IntoResponse> {
+    let ip_address = client_ip(remote_addr, headers);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result = blocking(move || {
+        controller::request_login_link(&db, &auth_config, &mailer, &item, ip_address.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Sent login link if the account exists."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../login/link/verify endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`LoginLinkVerifyInput`]
+///
+/// see [`controller::login_with_link`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
+/// | 200 | Json payload : {"two_factor_required": true, "two_factor_token": "..."}, send the token and a code to .../login/2fa
+/// | 400 | Json payload : {"code": "device_too_long", "message": "'device' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
+async fn login_with_link(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    mailer: Data<&Mailer>,
+    remote_addr: &RemoteAddr,
+    headers: &HeaderMap,
+    Json(item): Json<LoginLinkVerifyInput>,
+    cookie_jar: &CookieJar,
+) -> Result<impl IntoResponse> {
+    let client = client_info(remote_addr, headers);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result =
+        blocking(move || controller::login_with_link(&db, &auth_config, &mailer, &item, &client))
+            .await?;
+
+    match result {
+        Ok(LoginOutcome::Session(access_token, refresh_token)) => {
+            let mut cookie = Cookie::new(COOKIE_NAME, refresh_token);
+            cookie.set_secure(true);
+            cookie.set_http_only(true);
+            cookie.set_same_site(SameSite::Strict);
+            cookie.set_path("/");
+            cookie_jar.add(cookie);
+
+            let json = json!({ "access_token": access_token }).to_string();
+            let response = Response::builder().status(StatusCode::OK).body(json);
+
+            Ok(response)
+        }
+        Ok(LoginOutcome::TwoFactorRequired(two_factor_token)) => {
+            let json = json!({ "two_factor_required": true, "two_factor_token": two_factor_token })
+                .to_string();
+            let response = Response::builder().status(StatusCode::OK).body(json);
+
+            Ok(response)
+        }
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../login/2fa endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`TwoFactorLoginInput`]
+///
+/// see [`controller::login_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 401 | Json payload : {"code": "invalid_code", "message": "Invalid code."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
+async fn login_two_factor(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    mailer: Data<&Mailer>,
+    remote_addr: &RemoteAddr,
+    headers: &HeaderMap,
+    Json(item): Json<TwoFactorLoginInput>,
+    cookie_jar: &CookieJar,
+) -> Result<impl IntoResponse> {
+    let client = client_info(remote_addr, headers);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result =
+        blocking(move || controller::login_two_factor(&db, &auth_config, &mailer, &item, &client))
+            .await?;
 
     match result {
         Ok((access_token, refresh_token)) => {
@@ -142,7 +374,7 @@ async fn login(
 
             Ok(response)
         }
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -155,15 +387,16 @@ async fn login(
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | command to delete the "refresh_token" cookie
-/// | 401 | Json payload : {"message": "Invalid session."}
-/// | 401 | Json payload : {"message": "Could not delete session."}
+/// | 401 | Json payload : {"code": "invalid_session", "message": "Invalid session."}
+/// | 500 | Json payload : {"code": "could_not_delete_session", "message": "Could not delete session."}
 /// TODO: document the rest of the possible StatusCodes
 async fn logout(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl IntoResponse> {
     let refresh_token = cookie_jar
         .get(COOKIE_NAME)
         .map(|cookie| String::from(cookie.value_str()));
 
-    let result = controller::logout(db.0, refresh_token.as_deref());
+    let db = db.0.clone();
+    let result = blocking(move || controller::logout(&db, refresh_token.as_deref())).await?;
 
     match result {
         Ok(_) => {
@@ -174,7 +407,7 @@ async fn logout(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl Into
 
             Ok(Response::builder().status(StatusCode::OK).finish())
         }
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -187,15 +420,26 @@ async fn logout(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl Into
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload with an "assess_token" field containing a JWT associated with the user
-/// | 401 | Json payload : {"message": "Invalid session."}
-/// | 401 | Json payload : {"message": "Invalid token."}
+/// | 401 | Json payload : {"code": "invalid_session", "message": "Invalid session."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
 /// TODO: document the rest of the possible StatusCodes
-async fn refresh(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl IntoResponse> {
+async fn refresh(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    remote_addr: &RemoteAddr,
+    headers: &HeaderMap,
+    cookie_jar: &CookieJar,
+) -> Result<impl IntoResponse> {
     let refresh_token = cookie_jar
         .get(COOKIE_NAME)
         .map(|cookie| String::from(cookie.value_str()));
+    let client = client_info(remote_addr, headers);
 
-    let result = controller::refresh(db.0, refresh_token.as_deref());
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || controller::refresh(&db, &auth_config, refresh_token.as_deref(), &client))
+            .await?;
 
     match result {
         Ok((access_token, refresh_token)) => {
@@ -210,7 +454,7 @@ async fn refresh(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl Int
                 .status(StatusCode::OK)
                 .body(json!({ "access_token": access_token }).to_string()))
         }
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -225,20 +469,26 @@ async fn refresh(db: Data<&Database>, cookie_jar: &CookieJar) -> Result<impl Int
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Registered! Check your email to activate your account."}
-/// | 400 | Json payload : {"message": "Already registered."}
+/// | 400 | Json payload : {"code": "already_registered", "message": "Already registered."}
+/// | 400 | Json payload : {"code": "invalid_locale", "message": "'locale' must be a language tag, like 'en' or 'fr-CA'."}
+/// | 400 | Json payload : {"message": "The password doesn't follow the password policy.", "violations": [{"code": "too_short", "min_length": 8}, ...]}
 /// TODO: document the rest of the possible StatusCodes
 async fn register(
     db: Data<&Database>,
+    auth_config: Config<'_>,
     Json(item): Json<RegisterInput>,
     mailer: Data<&Mailer>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::register(db.0, &item, mailer.0);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result = blocking(move || controller::register(&db, &auth_config, &item, &mailer)).await?;
 
     match result {
         Ok(_) => Ok(Response::builder()
             .status(StatusCode::OK)
             .body("{ \"message\": \"Registered! Check your email to activate your account.\" }")),
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -251,24 +501,28 @@ async fn register(
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Activated."}
-/// | 200 | Json payload : {"message": "Already activated."}
-/// | 400 | Json payload : {"message": "Invalid token."}
-/// | 401 | Json payload : {"message": "Invalid token"}
-/// | 500 | Json payload : {"message": "Could not activate user. "}
+/// | 400 | Json payload : {"code": "already_activated", "message": "Already activated!"}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 500 | Json payload : {"code": "could_not_activate_user", "message": "Could not activate user."}
 /// TODO: document the rest of the possible StatusCodes
 async fn activate(
     db: Data<&Database>,
+    auth_config: Config<'_>,
     Query(item): Query<ActivationInput>,
     mailer: Data<&Mailer>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::activate(db.0, &item, mailer.0);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result = blocking(move || controller::activate(&db, &auth_config, &item, &mailer)).await?;
 
     match result {
         Ok(_) => Ok(Response::builder()
             .status(StatusCode::OK)
             .body("{ \"message\": \"Activated!\" }")),
 
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -286,16 +540,21 @@ async fn activate(
 /// TODO: document the rest of the possible StatusCodes
 async fn forgot_password(
     db: Data<&Database>,
+    auth_config: Config<'_>,
     Json(item): Json<ForgotInput>,
     mailer: Data<&Mailer>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::forgot_password(db.0, &item, mailer.0);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result =
+        blocking(move || controller::forgot_password(&db, &auth_config, &item, &mailer)).await?;
 
     match result {
         Ok(_) => Ok(Response::builder()
             .status(StatusCode::OK)
             .body("{ \"message\": \"Please check your email.\" }")),
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -312,26 +571,116 @@ async fn forgot_password(
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Password changed."}
-/// | 400 | Json payload : {"message": "Missing password."}
-/// | 400 | Json payload : {"message": "The new password must be different."}
-/// | 400 | Json payload : {"message": "Account has not been activated."}
-/// | 400 | Json payload : {"message": "Invalid credentials."}
-/// | 500 | Json payload : {"message": "Could not find user."}
-/// | 500 | Json payload : {"message": "Could not update password."}
+/// | 400 | Json payload : {"code": "missing_password", "message": "Missing password."}
+/// | 400 | Json payload : {"code": "password_unchanged", "message": "The new password must be different."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 400 | Json payload : {"message": "The password doesn't follow the password policy.", "violations": [{"code": "too_short", "min_length": 8}, ...]}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+/// | 500 | Json payload : {"code": "could_not_update_password", "message": "Could not update password."}
 /// TODO: document the rest of the possible StatusCodes
 async fn change_password(
     db: Data<&Database>,
+    auth_config: Config<'_>,
     Json(item): Json<ChangeInput>,
     auth: Auth,
     mailer: Data<&Mailer>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::change_password(db.0, &item, &auth, mailer.0);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result =
+        blocking(move || controller::change_password(&db, &auth_config, &item, &auth, &mailer))
+            .await?;
 
     match result {
         Ok(_) => Ok(Response::builder()
             .status(StatusCode::OK)
             .body(json!({"message": "Password changed"}).to_string())),
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../email/change endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ChangeEmailInput`]
+///
+/// see [`controller::request_email_change`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Please check your new email."}
+/// | 400 | Json payload : {"code": "missing_email", "message": "Missing email."}
+/// | 400 | Json payload : {"code": "email_unchanged", "message": "The new email must be different."}
+/// | 400 | Json payload : {"code": "email_in_use", "message": "Email already in use."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+async fn request_email_change(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    Json(item): Json<ChangeEmailInput>,
+    auth: Auth,
+    mailer: Data<&Mailer>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result = blocking(move || {
+        controller::request_email_change(&db, &auth_config, &item, &auth, &mailer)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Please check your new email."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../email/change/confirm endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ConfirmEmailChangeInput`]
+///
+/// logs the user out of every session but the one the "refresh_token" cookie belongs to,
+/// see [`controller::confirm_email_change`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Email changed."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 400 | Json payload : {"code": "email_in_use", "message": "Email already in use."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 500 | Json payload : {"code": "could_not_update_email", "message": "Could not update email."}
+async fn confirm_email_change(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    Json(item): Json<ConfirmEmailChangeInput>,
+    cookie_jar: &CookieJar,
+) -> Result<impl IntoResponse> {
+    let refresh_token = cookie_jar
+        .get(COOKIE_NAME)
+        .map(|cookie| String::from(cookie.value_str()));
+
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result = blocking(move || {
+        controller::confirm_email_change(&db, &auth_config, &item, refresh_token.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Email changed."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -346,23 +695,29 @@ async fn change_password(
 /// | StatusCode | content |
 /// |:------------|---------|
 /// | 200 | Json payload : {"message": "Password changed."}
-/// | 400 | Json payload : {"message": "Invalid token."}
-/// | 400 | Json payload : {"message": "Account has not been activated."}
-/// | 400 | Json payload : {"message": "The new password must be different."}
-/// | 401 | Json payload : {"message": "Invalid token."}
-/// | 500 | Json payload : {"message": "Could not update password."}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 400 | Json payload : {"code": "password_unchanged", "message": "The new password must be different."}
+/// | 400 | Json payload : {"message": "The password doesn't follow the password policy.", "violations": [{"code": "too_short", "min_length": 8}, ...]}
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 500 | Json payload : {"code": "could_not_update_password", "message": "Could not update password."}
 async fn reset_password(
     db: Data<&Database>,
+    auth_config: Config<'_>,
     Json(item): Json<ResetInput>,
     mailer: Data<&Mailer>,
 ) -> Result<impl IntoResponse> {
-    let result = controller::reset_password(db.0, &item, mailer.0);
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result =
+        blocking(move || controller::reset_password(&db, &auth_config, &item, &mailer)).await?;
 
     match result {
         Ok(_) => Ok(Response::builder()
             .status(StatusCode::OK)
             .body(json!({"message": "Password reset"}).to_string())),
-        Err((s, m)) => Err(error_response(s, m)),
+        Err(error) => Err(auth_error_response(error)),
     }
 }
 
@@ -382,19 +737,1027 @@ async fn check(auth: Auth) -> Result<impl IntoResponse> {
     Ok(Response::builder().status(StatusCode::OK).finish())
 }
 
+#[handler]
+/// handler for GET requests to the .../.well-known/jwks.json endpoint
+///
+/// see [`controller::jwks`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the JWK set containing the public keys access tokens are signed with
+async fn jwks(auth_config: Config<'_>) -> Json<jsonwebtoken::jwk::JwkSet> {
+    Json(controller::jwks(auth_config.0))
+}
+
+#[handler]
+/// handler for GET requests to the .../.well-known/openid-configuration endpoint
+///
+/// see [`authorization_server::discovery`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : the OpenID Connect discovery document
+/// | 404 | () the authorization server is disabled
+async fn openid_configuration(
+    app_config: Data<&AppConfig>,
+    auth_config: Config<'_>,
+) -> Result<impl IntoResponse> {
+    match authorization_server::discovery(app_config.0, auth_config.0) {
+        Some(document) => Ok(Json(document)),
+        None => Err(Error::from_status(StatusCode::NOT_FOUND)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../oauth/authorize endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`AuthorizeInput`]
+///
+/// see [`authorization_server::authorize`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"redirect_uri": "..."}, the user should be sent there
+/// | 200 | Json payload : {"consent_required": true, "client_name": "...", "scopes": [...]}
+/// | 400 | Json payload : {"code": "unknown_client", "message": "Unknown client."}
+/// | 400 | Json payload : {"code": "invalid_redirect_uri", "message": "Invalid redirect_uri."}
+/// | 403 | Json payload : {"code": "api_key_cannot_authorize", "message": "API keys cannot authorize clients."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."}
+/// | 500 | Json payload : {"code": "could_not_create_authorization_code", "message": "Could not create authorization code."}
+async fn oauth_authorize(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Json(item): Json<AuthorizeInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || authorization_server::authorize(&db, &auth_config, &auth, &item)).await?;
+
+    match result {
+        Ok(AuthorizeOutcome::Redirect(redirect_uri)) => {
+            Ok(Json(json!({ "redirect_uri": redirect_uri })))
+        }
+        Ok(AuthorizeOutcome::ConsentRequired {
+            client_name,
+            scopes,
+        }) => Ok(Json(
+            json!({ "consent_required": true, "client_name": client_name, "scopes": scopes }),
+        )),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../oauth/token endpoint
+///
+/// request must have the `Content-Type: application/x-www-form-urlencoded` header, and a form that can be deserialized into [`TokenInput`]
+///
+/// see [`authorization_server::token`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`TokenResponse`](`authorization_server::TokenResponse`) deserialized into a Json payload
+/// | 400 | Json payload : {"error": "invalid_request" \| "invalid_grant" \| "unsupported_grant_type"}
+/// | 401 | Json payload : {"error": "invalid_client"}
+/// | 404 | Json payload : {"error": "Not found."}
+async fn oauth_token(
+    db: Data<&Database>,
+    app_config: Data<&AppConfig>,
+    auth_config: Config<'_>,
+    req: &poem::Request,
+    Form(item): Form<TokenInput>,
+) -> Response {
+    let credentials = req
+        .header("Authorization")
+        .and_then(authorization_server::basic_credentials);
+
+    let db = db.0.clone();
+    let app_config = app_config.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result = match blocking(move || {
+        authorization_server::token(&db, &app_config, &auth_config, &item, credentials)
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(error) => return error.into_response(),
+    };
+
+    match result {
+        Ok(tokens) => Json(tokens)
+            .with_header("Cache-Control", "no-store")
+            .into_response(),
+        Err(error) => auth_error_response(error).into_response(),
+    }
+}
+
+#[handler]
+/// handler for GET requests to the .../oauth/userinfo endpoint
+///
+/// requires an access token issued to a client by the .../oauth/token endpoint
+///
+/// see [`authorization_server::userinfo`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"sub": "..."}, with "email" and "email_verified" if the token was granted the email scope
+/// | 401 | Json payload : {"code": "invalid_token", "message": "Invalid token."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}, the token wasn't granted the openid scope
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+async fn oauth_userinfo(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    req: &poem::Request,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let access_token = req
+        .header("Authorization")
+        .and_then(authorization_server::bearer_token)
+        .map(ToString::to_string);
+    let result = blocking(move || {
+        authorization_server::userinfo(&db, &auth_config, access_token.as_deref())
+    })
+    .await?;
+
+    match result {
+        Ok(claims) => Ok(Json(claims)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../2fa/enroll endpoint
+///
+/// requires auth
+///
+/// see [`controller::enroll_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`TwoFactorEnrollment`](`controller::TwoFactorEnrollment`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "two_factor_already_enabled", "message": "Two-factor authentication is already enabled."}
+/// | 500 | Json payload : {"code": "could_not_enroll_two_factor", "message": "Could not enroll."}
+async fn enroll_two_factor(
+    db: Data<&Database>,
+    app_config: Data<&AppConfig>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let app_config = app_config.0.clone();
+    let result = blocking(move || controller::enroll_two_factor(&db, &app_config, &auth)).await?;
+
+    match result {
+        Ok(enrollment) => Ok(Json(enrollment)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../2fa/confirm endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`TwoFactorCodeInput`]
+///
+/// see [`controller::confirm_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`TwoFactorRecoveryCodes`](`controller::TwoFactorRecoveryCodes`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "two_factor_not_enrolled", "message": "Two-factor authentication has not been enrolled."}
+/// | 400 | Json payload : {"code": "two_factor_already_enabled", "message": "Two-factor authentication is already enabled."}
+/// | 401 | Json payload : {"code": "invalid_code", "message": "Invalid code."}
+/// | 500 | Json payload : {"code": "could_not_enable_two_factor", "message": "Could not enable two-factor authentication."}
+async fn confirm_two_factor(
+    db: Data<&Database>,
+    Json(item): Json<TwoFactorCodeInput>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || controller::confirm_two_factor(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(recovery_codes) => Ok(Json(recovery_codes)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../2fa/disable endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`TwoFactorCodeInput`]
+///
+/// see [`controller::disable_two_factor`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Two-factor authentication disabled."}
+/// | 400 | Json payload : {"code": "two_factor_not_enabled", "message": "Two-factor authentication is not enabled."}
+/// | 401 | Json payload : {"code": "invalid_code", "message": "Invalid code."}
+/// | 500 | Json payload : {"code": "could_not_disable_two_factor", "message": "Could not disable two-factor authentication."}
+async fn disable_two_factor(
+    db: Data<&Database>,
+    Json(item): Json<TwoFactorCodeInput>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || controller::disable_two_factor(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(_) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Two-factor authentication disabled."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../passkeys/register/start endpoint
+///
+/// requires auth
+///
+/// see [`controller::start_passkey_registration`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`PasskeyOptions`](`controller::PasskeyOptions`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+async fn start_passkey_registration(
+    db: Data<&Database>,
+    app_config: Data<&AppConfig>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let app_config = app_config.0.clone();
+    let result =
+        blocking(move || controller::start_passkey_registration(&db, &app_config, &auth)).await?;
+
+    match result {
+        Ok(options) => Ok(Json(options)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../passkeys/register/finish endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PasskeyRegisterInput`]
+///
+/// see [`controller::finish_passkey_registration`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserPasskeyJson`](`crate::auth::UserPasskeyJson`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "name_too_long", "message": "'name' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "invalid_challenge", "message": "Invalid challenge."}
+/// | 400 | Json payload : {"code": "invalid_passkey_registration", "message": "Invalid passkey."}
+/// | 400 | Json payload : {"code": "passkey_already_registered", "message": "Passkey is already registered."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_register_passkey", "message": "Could not register passkey."}
+async fn finish_passkey_registration(
+    db: Data<&Database>,
+    app_config: Data<&AppConfig>,
+    auth: Auth,
+    Json(item): Json<PasskeyRegisterInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let app_config = app_config.0.clone();
+    let result =
+        blocking(move || controller::finish_passkey_registration(&db, &app_config, &auth, &item))
+            .await?;
+
+    match result {
+        Ok(passkey) => Ok(Json(passkey)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../passkeys/login/start endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PasskeyLoginStartInput`]
+///
+/// see [`controller::start_passkey_login`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`PasskeyOptions`](`controller::PasskeyOptions`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+async fn start_passkey_login(
+    db: Data<&Database>,
+    app_config: Data<&AppConfig>,
+    Json(item): Json<PasskeyLoginStartInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let app_config = app_config.0.clone();
+    let result = blocking(move || controller::start_passkey_login(&db, &app_config, &item)).await?;
+
+    match result {
+        Ok(options) => Ok(Json(options)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests to the .../passkeys/login/finish endpoint
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PasskeyLoginInput`]
+///
+/// see [`controller::finish_passkey_login`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload with an "access_token" field containing a JWT associated with the user
+/// | 400 | Json payload : {"code": "device_too_long", "message": "'device' cannot be longer than 256 characters."}
+/// | 400 | Json payload : {"code": "account_not_activated", "message": "Account has not been activated."}
+/// | 401 | Json payload : {"code": "invalid_passkey", "message": "Invalid passkey."}
+/// | 429 | Json payload : {"code": "too_many_failed_logins", "message": "Too many failed login attempts, try again later."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_create_session", "message": "Could not create session."}
+#[allow(clippy::too_many_arguments)]
+async fn finish_passkey_login(
+    db: Data<&Database>,
+    app_config: Data<&AppConfig>,
+    auth_config: Config<'_>,
+    mailer: Data<&Mailer>,
+    remote_addr: &RemoteAddr,
+    headers: &HeaderMap,
+    Json(item): Json<PasskeyLoginInput>,
+    cookie_jar: &CookieJar,
+) -> Result<impl IntoResponse> {
+    let client = client_info(remote_addr, headers);
+    let db = db.0.clone();
+    let app_config = app_config.0.clone();
+    let auth_config = auth_config.0.clone();
+    let mailer = mailer.0.clone();
+    let result = blocking(move || {
+        controller::finish_passkey_login(&db, &app_config, &auth_config, &mailer, &item, &client)
+    })
+    .await?;
+
+    match result {
+        Ok((access_token, refresh_token)) => {
+            let mut cookie = Cookie::new(COOKIE_NAME, refresh_token);
+            cookie.set_secure(true);
+            cookie.set_http_only(true);
+            cookie.set_same_site(SameSite::Strict);
+            cookie.set_path("/");
+            cookie_jar.add(cookie);
+
+            let json = json!({ "access_token": access_token }).to_string();
+            let response = Response::builder().status(StatusCode::OK).body(json);
+
+            Ok(response)
+        }
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../passkeys endpoint
+///
+/// requires auth
+///
+/// see [`controller::get_passkeys`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserPasskeyResponse`](`crate::auth::UserPasskeyResponse`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_fetch_passkeys", "message": "Could not fetch passkeys."}
+async fn passkeys(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || controller::get_passkeys(&db, &auth)).await?;
+
+    match result {
+        Ok(passkeys) => Ok(Json(passkeys)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../passkeys/{id} endpoint.
+///
+/// requires auth
+///
+/// see [`controller::destroy_passkey`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 404 | Json payload : {"code": "passkey_not_found", "message": "Passkey not found."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_delete_passkey", "message": "Could not delete passkey."}
+async fn destroy_passkey(
+    db: Data<&Database>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || controller::destroy_passkey(&db, &auth, item_id)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Deleted."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../api-keys endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`ApiKeyInput`]
+///
+/// see [`controller::create_api_key`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`ApiKeyCreated`](`controller::ApiKeyCreated`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "invalid_name", "message": "'name' must be between 1 and 256 characters."}
+/// | 400 | Json payload : {"code": "invalid_permission", "message": "Invalid permission."}
+/// | 400 | Json payload : {"code": "expiry_in_past", "message": "'expires_at' must be in the future."}
+/// | 403 | Json payload : {"code": "api_key_not_allowed", "message": "API keys cannot be managed with an API key."}
+/// | 500 | Json payload : {"code": "could_not_create_api_key", "message": "Could not create API key."}
+async fn create_api_key(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<ApiKeyInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || controller::create_api_key(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(api_key) => Ok(Json(api_key)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../api-keys endpoint
+///
+/// requires auth
+///
+/// see [`controller::get_api_keys`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`UserApiKeyResponse`](`crate::auth::UserApiKeyResponse`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_fetch_api_keys", "message": "Could not fetch API keys."}
+async fn api_keys(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || controller::get_api_keys(&db, &auth)).await?;
+
+    match result {
+        Ok(api_keys) => Ok(Json(api_keys)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../api-keys/{id} endpoint.
+///
+/// requires auth
+///
+/// see [`controller::destroy_api_key`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 403 | Json payload : {"code": "api_key_not_allowed", "message": "API keys cannot be managed with an API key."}
+/// | 404 | Json payload : {"code": "api_key_not_found", "message": "API key not found."}
+/// | 500 | Json payload : {"code": "internal", "message": "An internal server error occurred."}
+/// | 500 | Json payload : {"code": "could_not_delete_api_key", "message": "Could not delete API key."}
+async fn destroy_api_key(
+    db: Data<&Database>,
+    Path(item_id): Path<ID>,
+    auth: Auth,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || controller::destroy_api_key(&db, &auth, item_id)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Deleted."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../account/deletion endpoint
+///
+/// requires auth
+///
+/// see [`account::request_deletion`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AccountDeletionResponse`](`crate::auth::account::AccountDeletionResponse`) deserialized into a Json payload
+/// | 401 | Json payload : {"code": "invalid_credentials", "message": "Invalid credentials."}
+/// | 500 | Json payload : {"code": "could_not_schedule_deletion", "message": "Could not schedule deletion."}
+async fn request_account_deletion(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Json(item): Json<DeleteAccountInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || account::request_deletion(&db, &auth_config, &auth, &item)).await?;
+
+    match result {
+        Ok(deletion) => Ok(Json(deletion)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../account/deletion endpoint
+///
+/// requires auth
+///
+/// see [`account::cancel_deletion`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Account deletion cancelled."}
+/// | 400 | Json payload : {"code": "deletion_not_scheduled", "message": "Account deletion is not scheduled."}
+/// | 500 | Json payload : {"code": "could_not_cancel_deletion", "message": "Could not cancel deletion."}
+async fn cancel_account_deletion(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || account::cancel_deletion(&db, &auth)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Account deletion cancelled."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../account/locale endpoint
+///
+/// requires auth
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`SetLocaleInput`]
+///
+/// see [`account::set_locale`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Locale updated."}
+/// | 400 | Json payload : {"code": "invalid_locale", "message": "'locale' must be a language tag, like 'en' or 'fr-CA'."}
+/// | 500 | Json payload : {"code": "could_not_find_user", "message": "Could not find user."}
+async fn set_account_locale(
+    db: Data<&Database>,
+    auth: Auth,
+    Json(item): Json<SetLocaleInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || account::set_locale(&db, &auth, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Locale updated."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../account/export endpoint
+///
+/// requires auth
+///
+/// see [`account::export`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AccountExport`](`crate::auth::account::AccountExport`) deserialized into a Json payload
+/// | 500 | Json payload : {"code": "could_not_export_account", "message": "Could not export account."}
+async fn export_account(db: Data<&Database>, auth: Auth) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let result = blocking(move || account::export(&db, &auth)).await?;
+
+    match result {
+        Ok(export) => Ok(Json(export)),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../admin/users endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// searches users by email
+///
+/// request should be a query that contains [`UserSearchParams`]
+///
+/// see [`admin::search_users`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AdminUserResponse`](`admin::AdminUserResponse`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "invalid_pagination", "message": "'page' cannot be negative and 'page_size' must be at least 1."}
+/// | 500 | Json payload : {"code": "could_not_fetch_users", "message": "Could not fetch users."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_users(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Query(params): Query<UserSearchParams>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result = blocking(move || admin::search_users(&db, &auth_config, &auth, &params)).await?;
+
+    match result {
+        Ok(body) => Ok(Json(body).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../admin/users/{id} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::get_user`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AdminUserJson`](`admin::AdminUserJson`) deserialized into a Json payload
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_fetch_user", "message": "Could not fetch user."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_user(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result = blocking(move || admin::get_user(&db, &auth_config, &auth, user_id)).await?;
+
+    match result {
+        Ok(body) => Ok(Json(body).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../admin/users/{id}/activation endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// activates or deactivates a user
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`AdminActivationInput`]
+///
+/// see [`admin::set_activation`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Updated."}
+/// | 400 | Json payload : {"code": "cannot_deactivate_self", "message": "You cannot deactivate your own account."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_update_user", "message": "Could not update user."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_set_activation(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+    Json(item): Json<AdminActivationInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || admin::set_activation(&db, &auth_config, &auth, user_id, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Updated."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../admin/users/{id}/roles endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`RoleInput`]
+///
+/// see [`admin::assign_role`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Role assigned."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_assign_role", "message": "Could not assign role."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_assign_role(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+    Json(item): Json<RoleInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || admin::assign_role(&db, &auth_config, &auth, user_id, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Role assigned."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../admin/users/{id}/roles/{role} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::unassign_role`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Role unassigned."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_unassign_role", "message": "Could not unassign role."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_unassign_role(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Path((user_id, role)): Path<(ID, String)>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || admin::unassign_role(&db, &auth_config, &auth, user_id, &role)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Role unassigned."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for POST requests at the .../admin/users/{id}/permissions endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// request must have the `Content-Type: application/json` header, and a Json payload that can be deserialized into [`PermissionInput`]
+///
+/// see [`admin::grant_permission`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Permission granted."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_grant_permission", "message": "Could not grant permission."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_grant_permission(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+    Json(item): Json<PermissionInput>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || admin::grant_permission(&db, &auth_config, &auth, user_id, &item)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Permission granted."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../admin/users/{id}/permissions/{permission} endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// see [`admin::revoke_permission`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Permission revoked."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_revoke_permission", "message": "Could not revoke permission."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_revoke_permission(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Path((user_id, permission)): Path<(ID, String)>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result =
+        blocking(move || admin::revoke_permission(&db, &auth_config, &auth, user_id, &permission))
+            .await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Permission revoked."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for DELETE requests at the .../admin/users/{id}/sessions endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// signs a user out of every session
+///
+/// see [`admin::force_logout`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | Json payload : {"message": "Deleted."}
+/// | 404 | Json payload : {"code": "user_not_found", "message": "User not found."}
+/// | 500 | Json payload : {"code": "could_not_delete_sessions", "message": "Could not delete sessions."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_force_logout(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Path(user_id): Path<ID>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result = blocking(move || admin::force_logout(&db, &auth_config, &auth, user_id)).await?;
+
+    match result {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(json!({"message": "Deleted."}).to_string())),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
+#[handler]
+/// handler for GET requests at the .../admin/audit endpoint
+///
+/// requires auth, and the [`admin role`](`AuthConfig::admin_role`)
+///
+/// the audit trail of admin actions, most recent first
+///
+/// request should be a query that contains [`PaginationParams`]
+///
+/// see [`admin::get_audit_log`]
+///
+/// # Responses
+/// | StatusCode | content |
+/// |:------------|---------|
+/// | 200 | [`AdminAuditLogResponse`](`admin::AdminAuditLogResponse`) deserialized into a Json payload
+/// | 400 | Json payload : {"code": "invalid_pagination", "message": "'page' cannot be negative and 'page_size' must be at least 1."}
+/// | 500 | Json payload : {"code": "could_not_fetch_audit_log", "message": "Could not fetch audit log."}
+/// | 403 | Json payload : {"code": "forbidden", "message": "Forbidden."}
+/// | 404 | Json payload : {"code": "not_found", "message": "Not found."} (the admin endpoints are disabled)
+async fn admin_audit_log(
+    db: Data<&Database>,
+    auth_config: Config<'_>,
+    auth: Auth,
+    Query(info): Query<PaginationParams>,
+) -> Result<impl IntoResponse> {
+    let db = db.0.clone();
+    let auth_config = auth_config.0.clone();
+    let result = blocking(move || admin::get_audit_log(&db, &auth_config, &auth, &info)).await?;
+
+    match result {
+        Ok(body) => Ok(Json(body).into_response()),
+        Err(error) => Err(auth_error_response(error)),
+    }
+}
+
 /// returns endpoints for the Auth service
 pub fn api() -> Route {
-    /// TODO: OIDC endpoints!
+    // TODO: OIDC endpoints!
     Route::new()
         .at("/sessions", get(sessions).delete(destroy_sessions))
         .at("/sessions/:id", delete(destroy_session))
         .at("/login", post(login))
+        .at("/login/2fa", post(login_two_factor))
+        .at("/login/link", post(request_login_link))
+        .at("/login/link/verify", post(login_with_link))
         .at("/logout", post(logout))
         .at("/check", post(check))
+        .at("/.well-known/jwks.json", get(jwks))
+        .at(
+            "/.well-known/openid-configuration",
+            get(openid_configuration),
+        )
+        .at("/oauth/authorize", post(oauth_authorize))
+        .at("/oauth/token", post(oauth_token))
+        .at("/oauth/userinfo", get(oauth_userinfo))
         .at("/refresh", post(refresh))
         .at("/register", post(register))
         .at("/activate", get(activate))
         .at("/forgot", post(forgot_password))
         .at("/change", post(change_password))
+        .at("/email/change", post(request_email_change))
+        .at("/email/change/confirm", post(confirm_email_change))
         .at("/reset", post(reset_password))
+        .at("/2fa/enroll", post(enroll_two_factor))
+        .at("/2fa/confirm", post(confirm_two_factor))
+        .at("/2fa/disable", post(disable_two_factor))
+        .at("/passkeys", get(passkeys))
+        .at("/passkeys/:id", delete(destroy_passkey))
+        .at("/passkeys/register/start", post(start_passkey_registration))
+        .at(
+            "/passkeys/register/finish",
+            post(finish_passkey_registration),
+        )
+        .at("/passkeys/login/start", post(start_passkey_login))
+        .at("/passkeys/login/finish", post(finish_passkey_login))
+        .at("/api-keys", get(api_keys).post(create_api_key))
+        .at("/api-keys/:id", delete(destroy_api_key))
+        .at(
+            "/account/deletion",
+            post(request_account_deletion).delete(cancel_account_deletion),
+        )
+        .at("/account/locale", post(set_account_locale))
+        .at("/account/export", get(export_account))
+        .at("/admin/users", get(admin_users))
+        .at("/admin/users/:id", get(admin_user))
+        .at("/admin/users/:id/activation", post(admin_set_activation))
+        .at("/admin/users/:id/roles", post(admin_assign_role))
+        .at("/admin/users/:id/roles/:role", delete(admin_unassign_role))
+        .at("/admin/users/:id/permissions", post(admin_grant_permission))
+        .at(
+            "/admin/users/:id/permissions/:permission",
+            delete(admin_revoke_permission),
+        )
+        .at("/admin/users/:id/sessions", delete(admin_force_logout))
+        .at("/admin/audit", get(admin_audit_log))
 }
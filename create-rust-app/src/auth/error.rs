@@ -0,0 +1,372 @@
+use super::{authorization_server::TokenError, PasswordViolation};
+use serde::{Deserialize, Serialize};
+
+#[tsync::tsync]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// why a request to the auth endpoints (see [`controller`](`crate::auth::controller`)) failed
+///
+/// the variant names are stable and sent as the snake-cased `code` of error responses,
+/// alongside an english `message`, so frontends can show their own (localised) messages
+pub enum AuthError {
+    /* 400 */
+    AccountNotActivated,
+    /// the user already activated their account
+    AlreadyActivated,
+    AlreadyRegistered,
+    CannotDeactivateSelf,
+    /// unlinking the provider would leave the user without a way to log in
+    CannotUnlinkOnlyLogin,
+    DeletionNotScheduled,
+    DeviceTooLong,
+    EmailInUse,
+    EmailUnchanged,
+    ExpiryInPast,
+    InvalidChallenge,
+    InvalidCsrfToken,
+    InvalidLocale,
+    InvalidName,
+    /// a negative `page`, or a `page_size` below 1
+    InvalidPagination,
+    InvalidPasskeyRegistration,
+    InvalidPermission,
+    InvalidRedirectUri,
+    /// a request to the authorization server's token endpoint failed, `error` is its
+    /// [RFC 6749](https://datatracker.ietf.org/doc/html/rfc6749#section-5.2) error code (400, or 401 for `invalid_client`)
+    InvalidTokenRequest {
+        error: TokenError,
+    },
+    MissingEmail,
+    MissingPassword,
+    NameTooLong,
+    PasskeyAlreadyRegistered,
+    PasswordUnchanged,
+    /// the new password doesn't follow [`AuthConfig::password_policy`](`crate::auth::AuthConfig`)
+    PasswordPolicy {
+        violations: Vec<PasswordViolation>,
+    },
+    TwoFactorAlreadyEnabled,
+    TwoFactorNotEnabled,
+    TwoFactorNotEnrolled,
+    UnknownClient,
+
+    /* 401 */
+    InvalidCode,
+    InvalidCredentials,
+    InvalidPasskey,
+    InvalidSession,
+    InvalidToken,
+    /// the OIDC provider redirected back with an `error`
+    OidcProviderError {
+        error: String,
+    },
+
+    /* 403 */
+    ApiKeyCannotAuthorize,
+    ApiKeyNotAllowed,
+    Forbidden,
+
+    /* 404 */
+    ApiKeyNotFound,
+    LinkNotFound,
+    /// the endpoint is disabled, like the admin endpoints without [`AuthConfig::admin_role`](`crate::auth::AuthConfig`)
+    NotFound,
+    PasskeyNotFound,
+    SessionNotFound,
+    UserNotFound,
+
+    /* 409 */
+    /// the provider's account is already linked to another user
+    LinkedToOtherUser,
+
+    /* 429 */
+    TooManyFailedLogins,
+
+    /* 500 */
+    CouldNotActivateUser,
+    CouldNotAssignRole,
+    CouldNotCancelDeletion,
+    CouldNotCheckPassword,
+    CouldNotCreateApiKey,
+    CouldNotCreateAuthorizationCode,
+    CouldNotCreateSession,
+    CouldNotDeleteApiKey,
+    CouldNotDeletePasskey,
+    CouldNotDeleteSession,
+    CouldNotDeleteSessions,
+    CouldNotDisableTwoFactor,
+    CouldNotEnableTwoFactor,
+    CouldNotEnrollTwoFactor,
+    CouldNotExportAccount,
+    CouldNotFetchApiKeys,
+    CouldNotFetchAuditLog,
+    CouldNotFetchLinks,
+    CouldNotFetchPasskeys,
+    CouldNotFetchSessions,
+    CouldNotFetchUser,
+    CouldNotFetchUserInfo,
+    CouldNotFetchUsers,
+    CouldNotFindUser,
+    CouldNotGrantPermission,
+    CouldNotRecordAdminAction,
+    CouldNotRegisterPasskey,
+    CouldNotRevokePermission,
+    CouldNotScheduleDeletion,
+    CouldNotSyncRoles,
+    CouldNotUnassignRole,
+    CouldNotUpdateEmail,
+    CouldNotUpdatePassword,
+    CouldNotUpdateSession,
+    CouldNotUpdateUser,
+    /// the OIDC provider's tokens or claims couldn't be verified
+    InvalidProviderResponse,
+    /// the OIDC provider didn't tell us a verified email for a new user
+    NoVerifiedEmail,
+    /// the database connection pool couldn't hand out a connection, any controller function can fail with this
+    DatabaseUnavailable,
+    Internal,
+
+    /* 501 */
+    /// there's no OIDC provider with the requested name
+    ProviderNotSupported,
+}
+
+impl AuthError {
+    /// the HTTP status code the error should be answered with
+    #[must_use]
+    pub const fn status_code(&self) -> u16 {
+        match self {
+            Self::AccountNotActivated
+            | Self::AlreadyActivated
+            | Self::AlreadyRegistered
+            | Self::CannotDeactivateSelf
+            | Self::CannotUnlinkOnlyLogin
+            | Self::DeletionNotScheduled
+            | Self::DeviceTooLong
+            | Self::EmailInUse
+            | Self::EmailUnchanged
+            | Self::ExpiryInPast
+            | Self::InvalidChallenge
+            | Self::InvalidCsrfToken
+            | Self::InvalidLocale
+            | Self::InvalidName
+            | Self::InvalidPagination
+            | Self::InvalidPasskeyRegistration
+            | Self::InvalidPermission
+            | Self::InvalidRedirectUri
+            | Self::MissingEmail
+            | Self::MissingPassword
+            | Self::NameTooLong
+            | Self::PasskeyAlreadyRegistered
+            | Self::PasswordUnchanged
+            | Self::PasswordPolicy { .. }
+            | Self::TwoFactorAlreadyEnabled
+            | Self::TwoFactorNotEnabled
+            | Self::TwoFactorNotEnrolled
+            | Self::UnknownClient => 400,
+            Self::InvalidTokenRequest { error } => match error {
+                TokenError::InvalidClient => 401,
+                TokenError::ServerError => 500,
+                _ => 400,
+            },
+            Self::InvalidCode
+            | Self::InvalidCredentials
+            | Self::InvalidPasskey
+            | Self::InvalidSession
+            | Self::InvalidToken
+            | Self::OidcProviderError { .. } => 401,
+            Self::ApiKeyCannotAuthorize | Self::ApiKeyNotAllowed | Self::Forbidden => 403,
+            Self::ApiKeyNotFound
+            | Self::LinkNotFound
+            | Self::NotFound
+            | Self::PasskeyNotFound
+            | Self::SessionNotFound
+            | Self::UserNotFound => 404,
+            Self::LinkedToOtherUser => 409,
+            Self::TooManyFailedLogins => 429,
+            Self::CouldNotActivateUser
+            | Self::CouldNotAssignRole
+            | Self::CouldNotCancelDeletion
+            | Self::CouldNotCheckPassword
+            | Self::CouldNotCreateApiKey
+            | Self::CouldNotCreateAuthorizationCode
+            | Self::CouldNotCreateSession
+            | Self::CouldNotDeleteApiKey
+            | Self::CouldNotDeletePasskey
+            | Self::CouldNotDeleteSession
+            | Self::CouldNotDeleteSessions
+            | Self::CouldNotDisableTwoFactor
+            | Self::CouldNotEnableTwoFactor
+            | Self::CouldNotEnrollTwoFactor
+            | Self::CouldNotExportAccount
+            | Self::CouldNotFetchApiKeys
+            | Self::CouldNotFetchAuditLog
+            | Self::CouldNotFetchLinks
+            | Self::CouldNotFetchPasskeys
+            | Self::CouldNotFetchSessions
+            | Self::CouldNotFetchUser
+            | Self::CouldNotFetchUserInfo
+            | Self::CouldNotFetchUsers
+            | Self::CouldNotFindUser
+            | Self::CouldNotGrantPermission
+            | Self::CouldNotRecordAdminAction
+            | Self::CouldNotRegisterPasskey
+            | Self::CouldNotRevokePermission
+            | Self::CouldNotScheduleDeletion
+            | Self::CouldNotSyncRoles
+            | Self::CouldNotUnassignRole
+            | Self::CouldNotUpdateEmail
+            | Self::CouldNotUpdatePassword
+            | Self::CouldNotUpdateSession
+            | Self::CouldNotUpdateUser
+            | Self::InvalidProviderResponse
+            | Self::NoVerifiedEmail
+            | Self::DatabaseUnavailable
+            | Self::Internal => 500,
+            Self::ProviderNotSupported => 501,
+        }
+    }
+
+    /// an english description of the error
+    #[must_use]
+    pub const fn message(&self) -> &'static str {
+        match self {
+            Self::AlreadyActivated => "Already activated!",
+            Self::AccountNotActivated => "Account has not been activated.",
+            Self::AlreadyRegistered => "Already registered.",
+            Self::CannotDeactivateSelf => "You cannot deactivate your own account.",
+            Self::CannotUnlinkOnlyLogin => "Cannot unlink the only way to log in.",
+            Self::DeletionNotScheduled => "Account deletion is not scheduled.",
+            Self::DeviceTooLong => "'device' cannot be longer than 256 characters.",
+            Self::EmailInUse => "Email already in use.",
+            Self::EmailUnchanged => "The new email must be different.",
+            Self::ExpiryInPast => "'expires_at' must be in the future.",
+            Self::InvalidChallenge => "Invalid challenge.",
+            Self::InvalidCsrfToken => "Invalid CSRF token.",
+            Self::InvalidLocale => "'locale' must be a language tag, like 'en' or 'fr-CA'.",
+            Self::InvalidName => "'name' must be between 1 and 256 characters.",
+            Self::InvalidPagination => {
+                "'page' cannot be negative and 'page_size' must be at least 1."
+            }
+            Self::InvalidPasskeyRegistration | Self::InvalidPasskey => "Invalid passkey.",
+            Self::InvalidPermission => "Invalid permission.",
+            Self::InvalidRedirectUri => "Invalid redirect_uri.",
+            Self::InvalidTokenRequest { .. } => "Invalid token request.",
+            Self::MissingEmail => "Missing email.",
+            Self::MissingPassword => "Missing password.",
+            Self::NameTooLong => "'name' cannot be longer than 256 characters.",
+            Self::PasskeyAlreadyRegistered => "Passkey is already registered.",
+            Self::PasswordUnchanged => "The new password must be different.",
+            Self::PasswordPolicy { .. } => "The password doesn't follow the password policy.",
+            Self::TwoFactorAlreadyEnabled => "Two-factor authentication is already enabled.",
+            Self::TwoFactorNotEnabled => "Two-factor authentication is not enabled.",
+            Self::TwoFactorNotEnrolled => "Two-factor authentication has not been enrolled.",
+            Self::UnknownClient => "Unknown client.",
+            Self::InvalidCode => "Invalid code.",
+            Self::InvalidCredentials => "Invalid credentials.",
+            Self::InvalidSession => "Invalid session.",
+            Self::InvalidToken => "Invalid token.",
+            Self::OidcProviderError { .. } => "The provider could not log you in.",
+            Self::ApiKeyCannotAuthorize => "API keys cannot authorize clients.",
+            Self::ApiKeyNotAllowed => "API keys cannot be managed with an API key.",
+            Self::Forbidden => "Forbidden.",
+            Self::ApiKeyNotFound => "API key not found.",
+            Self::LinkNotFound => "Linked account not found.",
+            Self::NotFound => "Not found.",
+            Self::PasskeyNotFound => "Passkey not found.",
+            Self::SessionNotFound => "Session not found.",
+            Self::UserNotFound => "User not found.",
+            Self::LinkedToOtherUser => "This account is already linked to another user.",
+            Self::TooManyFailedLogins => "Too many failed login attempts, try again later.",
+            Self::CouldNotActivateUser => "Could not activate user.",
+            Self::CouldNotAssignRole => "Could not assign role.",
+            Self::CouldNotCancelDeletion => "Could not cancel deletion.",
+            Self::CouldNotCheckPassword => "Could not check password.",
+            Self::CouldNotCreateApiKey => "Could not create API key.",
+            Self::CouldNotCreateAuthorizationCode => "Could not create authorization code.",
+            Self::CouldNotCreateSession => "Could not create session.",
+            Self::CouldNotDeleteApiKey => "Could not delete API key.",
+            Self::CouldNotDeletePasskey => "Could not delete passkey.",
+            Self::CouldNotDeleteSession => "Could not delete session.",
+            Self::CouldNotDeleteSessions => "Could not delete sessions.",
+            Self::CouldNotDisableTwoFactor => "Could not disable two-factor authentication.",
+            Self::CouldNotEnableTwoFactor => "Could not enable two-factor authentication.",
+            Self::CouldNotEnrollTwoFactor => "Could not enroll.",
+            Self::CouldNotExportAccount => "Could not export account.",
+            Self::CouldNotFetchApiKeys => "Could not fetch API keys.",
+            Self::CouldNotFetchAuditLog => "Could not fetch audit log.",
+            Self::CouldNotFetchLinks => "Could not fetch linked accounts.",
+            Self::CouldNotFetchPasskeys => "Could not fetch passkeys.",
+            Self::CouldNotFetchSessions => "Could not fetch sessions.",
+            Self::CouldNotFetchUser => "Could not fetch user.",
+            Self::CouldNotFetchUserInfo => "Could not fetch user info.",
+            Self::CouldNotFetchUsers => "Could not fetch users.",
+            Self::CouldNotFindUser => "Could not find user.",
+            Self::CouldNotGrantPermission => "Could not grant permission.",
+            Self::CouldNotRecordAdminAction => "Could not record admin action.",
+            Self::CouldNotRegisterPasskey => "Could not register passkey.",
+            Self::CouldNotRevokePermission => "Could not revoke permission.",
+            Self::CouldNotScheduleDeletion => "Could not schedule deletion.",
+            Self::CouldNotSyncRoles => "Could not sync roles.",
+            Self::CouldNotUnassignRole => "Could not unassign role.",
+            Self::CouldNotUpdateEmail => "Could not update email.",
+            Self::CouldNotUpdatePassword => "Could not update password.",
+            Self::CouldNotUpdateSession => "Could not update session.",
+            Self::CouldNotUpdateUser => "Could not update user.",
+            Self::InvalidProviderResponse => "The provider's response could not be verified.",
+            Self::NoVerifiedEmail => "The provider did not return a verified email.",
+            Self::DatabaseUnavailable => "Could not connect to the database.",
+            Self::Internal => "An internal server error occurred.",
+            Self::ProviderNotSupported => "This oauth provider is not supported.",
+        }
+    }
+
+    /// the Json body of the error response: the error's `code` (and details), and its `message`
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_else(|_| serde_json::json!({}));
+        json["message"] = self.message().into();
+        json
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// lets controllers run their queries in a [`transaction`](`diesel::Connection::transaction`),
+/// which fails with a [`diesel::result::Error`] if it can't be started or committed
+impl From<diesel::result::Error> for AuthError {
+    fn from(_: diesel::result::Error) -> Self {
+        Self::Internal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json_contains_code_and_message() {
+        assert_eq!(
+            AuthError::InvalidCredentials.to_json(),
+            serde_json::json!({ "code": "invalid_credentials", "message": "Invalid credentials." })
+        );
+        assert_eq!(
+            AuthError::PasswordPolicy {
+                violations: vec![PasswordViolation::MissingDigit]
+            }
+            .to_json(),
+            serde_json::json!({
+                "code": "password_policy",
+                "violations": [{ "code": "missing_digit" }],
+                "message": "The password doesn't follow the password policy.",
+            })
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashSet;
 
-use crate::auth::{Permission, ID};
+use crate::auth::{utc_now, Permission, Role, User, UserApiKey, ID};
+use crate::database::Connection;
+use diesel::OptionalExtension;
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone)]
@@ -11,9 +13,68 @@ pub struct Auth {
     pub user_id: ID,
     pub roles: HashSet<String>,
     pub permissions: HashSet<Permission>,
+    /// set when the request was authenticated with an API key (see [`UserApiKey`]) instead of an access token
+    pub api_key_id: Option<ID>,
+    /// the permissions the API key is limited to, `None` if it isn't limited
+    pub api_key_permissions: Option<HashSet<String>>,
 }
 
 impl Auth {
+    /// authenticates a request carrying an API key (see [`UserApiKey`]) instead of an access token
+    ///
+    /// returns `None` if the key doesn't exist, has expired, or belongs to a deactivated user,
+    /// otherwise the key's use is recorded
+    ///
+    /// permissions and roles are read from the database, so changes apply immediately;
+    /// a key limited to some permissions only gets those (if the user still has them) and no roles
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if a query fails
+    pub fn from_api_key(db: &mut Connection, key: &str) -> anyhow::Result<Option<Self>> {
+        let Some(api_key) = UserApiKey::find_by_key(db, key).optional()? else {
+            return Ok(None);
+        };
+
+        if api_key
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= utc_now())
+        {
+            return Ok(None);
+        }
+
+        let user = User::read(db, api_key.user_id)?;
+        if !user.activated {
+            return Ok(None);
+        }
+
+        let api_key_permissions: Option<HashSet<String>> = api_key
+            .permission_list()
+            .map(|permissions| permissions.into_iter().collect());
+
+        let permissions = Permission::fetch_all(db, user.id)?
+            .into_iter()
+            .filter(|permission| match api_key_permissions {
+                Some(ref allowed) => allowed.contains(&permission.permission),
+                None => true,
+            })
+            .collect();
+
+        let roles = match api_key_permissions {
+            Some(_) => HashSet::new(),
+            None => Role::fetch_all(db, user.id)?.into_iter().collect(),
+        };
+
+        UserApiKey::mark_used(db, api_key.id)?;
+
+        Ok(Some(Self {
+            user_id: user.id,
+            roles,
+            permissions,
+            api_key_id: Some(api_key.id),
+            api_key_permissions,
+        }))
+    }
+
     /// does the user with the id [`self.user_id`](`ID`) have the given `permission`
     #[must_use]
     pub fn has_permission(&self, permission: String) -> bool {
@@ -57,4 +118,61 @@ impl Auth {
     pub fn has_any_roles(&self, roles: impl AsRef<[String]>) -> bool {
         roles.as_ref().iter().any(|r| self.has_role(r))
     }
+
+    /// can the user with the id [`self.user_id`](`ID`) use `permission` on the record identified by
+    /// (`resource_type`, [`resource_id`](`ID`))
+    ///
+    /// true if the user has `permission` globally, or if it was granted to them (or one of their roles)
+    /// on that specific record, see [`Permission::grant_to_user_on`] and [`Permission::grant_to_role_on`];
+    /// always false for permissions outside of [`self.api_key_permissions`](`Auth::api_key_permissions`)
+    ///
+    /// ```rust,ignore
+    /// if !auth.can(&mut db, "project:edit", "project", project_id)? {
+    ///     return Err(StatusCode::FORBIDDEN);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn can(
+        &self,
+        db: &mut Connection,
+        permission: &str,
+        resource_type: &str,
+        resource_id: ID,
+    ) -> anyhow::Result<bool> {
+        if self.has_permission(permission.to_string()) {
+            return Ok(true);
+        }
+
+        if let Some(ref allowed) = self.api_key_permissions {
+            if !allowed.contains(permission) {
+                return Ok(false);
+            }
+        }
+
+        Permission::is_granted_on(db, self.user_id, permission, resource_type, resource_id)
+    }
+
+    /// returns the id of every `resource_type` record on which the user with the id [`self.user_id`](`ID`)
+    /// was granted `permission`, see [`Permission::fetch_resource_ids`]
+    ///
+    /// note: this does not account for global permissions, check [`Auth::has_permission`] first
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn resource_ids(
+        &self,
+        db: &mut Connection,
+        permission: &str,
+        resource_type: &str,
+    ) -> anyhow::Result<Vec<ID>> {
+        if let Some(ref allowed) = self.api_key_permissions {
+            if !allowed.contains(permission) {
+                return Ok(vec![]);
+            }
+        }
+
+        Permission::fetch_resource_ids(db, self.user_id, permission, resource_type)
+    }
 }
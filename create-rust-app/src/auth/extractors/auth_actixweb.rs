@@ -1,95 +1,91 @@
 use super::auth::Auth;
-use crate::auth::{permissions::Permission, AccessTokenClaims};
+use crate::auth::{
+    error::AuthError, keys::decode_token, permissions::Permission, AccessTokenClaims, AuthConfig,
+    API_KEY_PREFIX,
+};
+use crate::Database;
 use actix_web::dev::Payload;
 use actix_web::error::ResponseError;
 use actix_web::http::StatusCode;
+use actix_web::web::{self, Data};
 use actix_web::{FromRequest, HttpRequest, HttpResponse};
-use derive_more::{Display, Error};
-use futures::future::{ready, Ready};
-use jsonwebtoken::decode;
-use jsonwebtoken::DecodingKey;
-use jsonwebtoken::Validation;
-use serde_json::json;
+use futures::future::{ready, LocalBoxFuture};
 use std::collections::HashSet;
 
-#[derive(Debug, Display, Error)]
-#[display(fmt = "Unauthorized ({status:?}), reason: {reason:?}")]
-/// custom error type for Authorization related errors
-pub struct AuthError {
-    reason: String,
-    status: StatusCode,
-}
-
-impl AuthError {
-    #[must_use]
-    pub const fn new(reason: String, status: StatusCode) -> Self {
-        Self { reason, status }
-    }
-
-    #[must_use]
-    pub const fn reason(reason: String) -> Self {
-        Self {
-            reason,
-            status: StatusCode::UNAUTHORIZED,
-        }
-    }
-}
-
 impl ResponseError for AuthError {
-    /// builds an [`HttpResponse`] for [`self`](`AuthError`)
+    /// builds an [`HttpResponse`] for [`self`](`AuthError`), a Json body with its `code` and `message`
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code()).body(
-            json!({
-              "message": self.reason.as_str()
-            })
-            .to_string(),
-        )
+        HttpResponse::build(ResponseError::status_code(self)).json(self.to_json())
     }
 
     /// return the [`StatusCode`] associated with an [`AuthError`]
     fn status_code(&self) -> StatusCode {
-        StatusCode::UNAUTHORIZED
+        StatusCode::from_u16(AuthError::status_code(self))
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
     }
 }
 
 impl FromRequest for Auth {
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
     type Error = AuthError;
 
     /// extracts [`Auth`] from the given [`req`](`HttpRequest`)
+    ///
+    /// the bearer token may be an access token or an API key (see [`Auth::from_api_key`]),
+    /// which is looked up in the [`Database`] registered as app data
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> <Self as FromRequest>::Future {
         let access_token_str = match req.headers().get("Authorization").map(|h| h.to_str()) {
-            Some(Ok(auth_header)) if auth_header.starts_with("Bearer ") => auth_header,
-            Some(_) => {
-                return ready(Err(AuthError::reason(
-                    "Invalid authorization header".to_string(),
-                )))
-            }
-            None => {
-                return ready(Err(AuthError::reason(
-                    "Authorization header required".to_string(),
-                )))
+            Some(Ok(auth_header)) if auth_header.starts_with("Bearer ") => {
+                auth_header.trim_start_matches("Bearer ")
             }
+            Some(_) => return Box::pin(ready(Err(AuthError::InvalidToken))),
+            None => return Box::pin(ready(Err(AuthError::InvalidToken))),
         };
 
-        let access_token = match decode::<AccessTokenClaims>(
-            access_token_str.trim_start_matches("Bearer "),
-            &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-            &Validation::default(),
-        ) {
-            Ok(token) if token.claims.token_type.eq_ignore_ascii_case("access_token") => token,
-            _ => return ready(Err(AuthError::reason("Invalid access token".to_string()))),
+        if access_token_str.starts_with(API_KEY_PREFIX) {
+            let Some(db) = req.app_data::<Data<Database>>().cloned() else {
+                return Box::pin(ready(Err(AuthError::Internal)));
+            };
+            let api_key = access_token_str.to_string();
+
+            return Box::pin(async move {
+                let result =
+                    web::block(move || Self::from_api_key(&mut db.get_connection()?, &api_key))
+                        .await;
+
+                match result {
+                    Ok(Ok(Some(auth))) => Ok(auth),
+                    Ok(Ok(None)) => Err(AuthError::InvalidToken),
+                    _ => Err(AuthError::Internal),
+                }
+            });
+        }
+
+        let Some(auth_config) = req
+            .app_data::<Data<AuthConfig>>()
+            .map(Data::get_ref)
+            .or_else(|| AuthConfig::fallback())
+        else {
+            return Box::pin(ready(Err(AuthError::Internal)));
         };
 
+        let access_token =
+            match decode_token::<AccessTokenClaims>(auth_config.keys.as_ref(), access_token_str) {
+                Ok(token) if token.claims.token_type.eq_ignore_ascii_case("access_token") => token,
+                _ => return Box::pin(ready(Err(AuthError::InvalidToken))),
+            };
+
         let user_id = access_token.claims.sub;
         let permissions: HashSet<Permission> =
             access_token.claims.permissions.iter().cloned().collect();
         let roles: HashSet<String> = access_token.claims.roles.iter().cloned().collect();
 
-        ready(Ok(Self {
+        Box::pin(ready(Ok(Self {
             user_id,
             roles,
             permissions,
-        }))
+            api_key_id: None,
+            api_key_permissions: None,
+        })))
     }
 }
@@ -0,0 +1,88 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    Json,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::auth::Auth;
+use crate::auth::{
+    error::AuthError, keys::decode_token, permissions::Permission, AccessTokenClaims, AuthConfig,
+    API_KEY_PREFIX,
+};
+use crate::Database;
+
+/// the rejection for an [`AuthError`], a Json body with its `code` and `message`
+fn rejection(error: &AuthError) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::from_u16(error.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(error.to_json()),
+    )
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Auth {
+    type Rejection = (StatusCode, Json<Value>);
+
+    /// extracts [`Auth`] from the given [`parts`](`Parts`) of a request
+    ///
+    /// the [`AuthConfig`] should be registered with an [`Extension`](`axum::Extension`) layer
+    /// (otherwise [`AuthConfig::from_env`] is used), and so must the [`Database`] if API keys
+    /// (see [`Auth::from_api_key`]) are used
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let access_token_str = match parts.headers.get("Authorization").map(|h| h.to_str()) {
+            Some(Ok(auth_header)) if auth_header.starts_with("Bearer ") => {
+                auth_header.trim_start_matches("Bearer ")
+            }
+            Some(_) => return Err(rejection(&AuthError::InvalidToken)),
+            None => return Err(rejection(&AuthError::InvalidToken)),
+        };
+
+        if access_token_str.starts_with(API_KEY_PREFIX) {
+            let Some(db) = parts.extensions.get::<Database>().cloned() else {
+                return Err(rejection(&AuthError::Internal));
+            };
+            let api_key = access_token_str.to_string();
+
+            let result = tokio::task::spawn_blocking(move || {
+                Self::from_api_key(&mut db.get_connection()?, &api_key)
+            })
+            .await;
+
+            return match result {
+                Ok(Ok(Some(auth))) => Ok(auth),
+                Ok(Ok(None)) => Err(rejection(&AuthError::InvalidToken)),
+                _ => Err(rejection(&AuthError::Internal)),
+            };
+        }
+
+        let Some(auth_config) = parts
+            .extensions
+            .get::<AuthConfig>()
+            .or_else(|| AuthConfig::fallback())
+        else {
+            return Err(rejection(&AuthError::Internal));
+        };
+
+        let access_token =
+            match decode_token::<AccessTokenClaims>(auth_config.keys.as_ref(), access_token_str) {
+                Ok(token) if token.claims.token_type.eq_ignore_ascii_case("access_token") => token,
+                _ => return Err(rejection(&AuthError::InvalidToken)),
+            };
+
+        let user_id = access_token.claims.sub;
+        let permissions: HashSet<Permission> =
+            access_token.claims.permissions.iter().cloned().collect();
+        let roles: HashSet<String> = access_token.claims.roles.iter().cloned().collect();
+
+        Ok(Self {
+            user_id,
+            roles,
+            permissions,
+            api_key_id: None,
+            api_key_permissions: None,
+        })
+    }
+}
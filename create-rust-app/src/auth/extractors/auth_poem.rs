@@ -1,49 +1,79 @@
 use poem::{
-    async_trait, http::HeaderValue, http::StatusCode, Error, FromRequest, Request, RequestBody,
-    Result,
+    async_trait, error::ResponseError, http::HeaderValue, http::StatusCode, FromRequest, Request,
+    RequestBody, Response, Result,
 };
 use std::collections::HashSet;
 
 use super::auth::Auth;
-use crate::auth::{permissions::Permission, AccessTokenClaims, ID};
-use jsonwebtoken::decode;
-use jsonwebtoken::DecodingKey;
-use jsonwebtoken::Validation;
+use crate::auth::{
+    error::AuthError, keys::decode_token, permissions::Permission, AccessTokenClaims, AuthConfig,
+    API_KEY_PREFIX,
+};
+use crate::Database;
 use std::iter::FromIterator;
 
+impl ResponseError for AuthError {
+    /// return the [`StatusCode`] associated with an [`AuthError`]
+    fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// builds a [`Response`] for [`self`](`AuthError`), a Json body with its `code` and `message`
+    fn as_response(&self) -> Response {
+        Response::builder()
+            .status(ResponseError::status(self))
+            .content_type("application/json")
+            .body(self.to_json().to_string())
+    }
+}
+
 #[async_trait]
 impl<'a> FromRequest<'a> for Auth {
     /// extracts [`Auth`] from the given [`req`](`Request`)
+    ///
+    /// the bearer token may be an access token or an API key (see [`Auth::from_api_key`]),
+    /// which is looked up in the [`Database`] registered as data
     async fn from_request(req: &'a Request, _: &mut RequestBody) -> Result<Self> {
         let auth_header_opt: Option<&HeaderValue> = req.headers().get("Authorization");
 
         if auth_header_opt.is_none() {
-            return Err(Error::from_string(
-                "Authorization header required",
-                StatusCode::UNAUTHORIZED,
-            ));
+            return Err(AuthError::InvalidToken.into());
         }
 
         let access_token_str = auth_header_opt.unwrap().to_str().unwrap_or("");
 
         if !access_token_str.starts_with("Bearer ") {
-            return Err(Error::from_string(
-                "Invalid authorization header",
-                StatusCode::UNAUTHORIZED,
-            ));
+            return Err(AuthError::InvalidToken.into());
         }
 
-        let access_token = decode::<AccessTokenClaims>(
-            access_token_str.trim_start_matches("Bearer "),
-            &DecodingKey::from_secret(std::env::var("SECRET_KEY").unwrap().as_ref()),
-            &Validation::default(),
-        );
+        let bearer = access_token_str.trim_start_matches("Bearer ");
+
+        if bearer.starts_with(API_KEY_PREFIX) {
+            let Some(db) = req.data::<Database>().cloned() else {
+                return Err(AuthError::Internal.into());
+            };
+            let api_key = bearer.to_string();
+
+            let result = tokio::task::spawn_blocking(move || {
+                Self::from_api_key(&mut db.get_connection()?, &api_key)
+            })
+            .await;
+
+            return match result {
+                Ok(Ok(Some(auth))) => Ok(auth),
+                Ok(Ok(None)) => Err(AuthError::InvalidToken.into()),
+                _ => Err(AuthError::Internal.into()),
+            };
+        }
+
+        let Some(auth_config) = req.data::<AuthConfig>().or_else(|| AuthConfig::fallback()) else {
+            return Err(AuthError::Internal.into());
+        };
+
+        let access_token = decode_token::<AccessTokenClaims>(auth_config.keys.as_ref(), bearer);
 
         if access_token.is_err() {
-            return Err(Error::from_string(
-                "Invalid access token",
-                StatusCode::UNAUTHORIZED,
-            ));
+            return Err(AuthError::InvalidToken.into());
         }
 
         let access_token = access_token.unwrap();
@@ -53,10 +83,7 @@ impl<'a> FromRequest<'a> for Auth {
             .token_type
             .eq_ignore_ascii_case("access_token")
         {
-            return Err(Error::from_string(
-                "Invalid access token",
-                StatusCode::UNAUTHORIZED,
-            ));
+            return Err(AuthError::InvalidToken.into());
         }
 
         let user_id = access_token.claims.sub;
@@ -68,6 +95,8 @@ impl<'a> FromRequest<'a> for Auth {
             user_id,
             roles,
             permissions,
+            api_key_id: None,
+            api_key_permissions: None,
         });
     }
 }
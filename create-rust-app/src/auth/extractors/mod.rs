@@ -3,8 +3,9 @@ pub use auth::Auth;
 
 #[cfg(feature = "backend_actix-web")]
 mod auth_actixweb;
-#[cfg(feature = "backend_actix-web")]
-pub use auth_actixweb::AuthError;
 
 #[cfg(feature = "backend_poem")]
 mod auth_poem;
+
+#[cfg(feature = "backend_axum")]
+mod auth_axum;
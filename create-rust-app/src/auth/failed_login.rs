@@ -0,0 +1,132 @@
+use super::schema::failed_logins;
+use crate::diesel::{
+    insert_into, AsChangeset, ExpressionMethods, Identifiable, Insertable, QueryDsl, Queryable,
+    RunQueryDsl,
+};
+
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, AsChangeset,
+)]
+#[diesel(table_name=failed_logins)]
+/// Rust struct modeling an entry in the `failed_logins` table
+pub struct FailedLogin {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+
+    /// the account the attempt was for, if the email belonged to one
+    pub user_id: Option<ID>,
+    pub ip_address: Option<String>,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=failed_logins)]
+/// Rust struct modeling mutable data in an entry in the `failed_logins` table
+pub struct FailedLoginChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: Option<ID>,
+    pub ip_address: Option<String>,
+}
+
+impl FailedLogin {
+    /// Create an entry in [`db`](`Connection`)'s `failed_logins` table using the data in [`item`](`FailedLoginChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &FailedLoginChangeset) -> QueryResult<Self> {
+        use super::schema::failed_logins::dsl::failed_logins;
+
+        insert_into(failed_logins)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Count the entries in [`db`](`Connection`)'s `failed_logins` table for the User whose id is
+    /// [`item_user_id`](`ID`) which were created after `since`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn count_for_user_since(
+        db: &mut Connection,
+        item_user_id: ID,
+        since: Utc,
+    ) -> QueryResult<i64> {
+        use super::schema::failed_logins::dsl::{created_at, failed_logins, user_id};
+
+        failed_logins
+            .filter(user_id.eq(item_user_id))
+            .filter(created_at.gt(since))
+            .count()
+            .get_result(db)
+    }
+
+    /// Count the entries in [`db`](`Connection`)'s `failed_logins` table from `item_ip_address`
+    /// which were created after `since`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn count_for_ip_since(
+        db: &mut Connection,
+        item_ip_address: &str,
+        since: Utc,
+    ) -> QueryResult<i64> {
+        use super::schema::failed_logins::dsl::{created_at, failed_logins, ip_address};
+
+        failed_logins
+            .filter(ip_address.eq(item_ip_address))
+            .filter(created_at.gt(since))
+            .count()
+            .get_result(db)
+    }
+
+    /// Query [`db`](`Connection`) for all entries in the `failed_logins` table
+    /// who's `user_id` matches [`item_user_id`](`ID`), oldest first
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::failed_logins::dsl::{created_at, failed_logins, user_id};
+
+        failed_logins
+            .filter(user_id.eq(item_user_id))
+            .order(created_at.asc())
+            .load::<Self>(db)
+    }
+
+    /// Delete all entries in [`db`](`Connection`)'s `failed_logins` table who's
+    /// '`user_id`' matches [`item_user_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::failed_logins::dsl::{failed_logins, user_id};
+
+        diesel::delete(failed_logins.filter(user_id.eq(item_user_id))).execute(db)
+    }
+
+    /// Delete all entries in [`db`](`Connection`)'s `failed_logins` table created before `before`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_older_than(db: &mut Connection, before: Utc) -> QueryResult<usize> {
+        use super::schema::failed_logins::dsl::{created_at, failed_logins};
+
+        diesel::delete(failed_logins.filter(created_at.lt(before))).execute(db)
+    }
+}
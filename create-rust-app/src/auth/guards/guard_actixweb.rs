@@ -0,0 +1,88 @@
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use futures::future::{ready, LocalBoxFuture, Ready};
+
+use super::{AuthGuard, RequirePermission, RequireRole};
+use crate::auth::{error::AuthError, Auth};
+
+/// the middleware [`RequirePermission`] and [`RequireRole`] wrap a service in
+pub struct GuardMiddleware<S, G> {
+    service: Rc<S>,
+    guard: G,
+}
+
+impl<S, B, G> Service<ServiceRequest> for GuardMiddleware<S, G>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+    G: AuthGuard,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let guard = self.guard;
+
+        Box::pin(async move {
+            let auth = match req.extract::<Auth>().await {
+                Ok(auth) => auth,
+                Err(error) => return Ok(req.error_response(error).map_into_right_body()),
+            };
+
+            if guard.check(&auth).is_err() {
+                return Ok(req
+                    .error_response(AuthError::Forbidden)
+                    .map_into_right_body());
+            }
+
+            service
+                .call(req)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequirePermission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = GuardMiddleware<S, Self>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(GuardMiddleware {
+            service: Rc::new(service),
+            guard: *self,
+        }))
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireRole
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = GuardMiddleware<S, Self>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(GuardMiddleware {
+            service: Rc::new(service),
+            guard: *self,
+        }))
+    }
+}
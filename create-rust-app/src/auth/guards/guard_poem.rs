@@ -0,0 +1,47 @@
+use poem::{async_trait, Endpoint, FromRequest, Middleware, Request, Result};
+
+use super::{AuthGuard, RequirePermission, RequireRole};
+use crate::auth::{error::AuthError, Auth};
+
+/// the endpoint [`RequirePermission`] and [`RequireRole`] wrap an endpoint in
+pub struct GuardEndpoint<E, G> {
+    inner: E,
+    guard: G,
+}
+
+#[async_trait]
+impl<E: Endpoint, G: AuthGuard> Endpoint for GuardEndpoint<E, G> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let auth = Auth::from_request_without_body(&req).await?;
+
+        if self.guard.check(&auth).is_err() {
+            return Err(AuthError::Forbidden.into());
+        }
+
+        self.inner.call(req).await
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RequirePermission {
+    type Output = GuardEndpoint<E, Self>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        GuardEndpoint {
+            inner: ep,
+            guard: *self,
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RequireRole {
+    type Output = GuardEndpoint<E, Self>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        GuardEndpoint {
+            inner: ep,
+            guard: *self,
+        }
+    }
+}
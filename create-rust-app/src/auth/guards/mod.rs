@@ -0,0 +1,250 @@
+//! guards which only let a request through when the user has a given permission or role
+//!
+//! requests without a valid access token are rejected with a 401,
+//! and requests from users who lack the permission or role with a 403,
+//! both with the same Json payload as the [`Auth`] extractor: `{"message": "..."}`
+//!
+//! ```ignore
+//! // actix-web
+//! web::scope("/todos").wrap(RequirePermission("todo:write"))
+//! // poem
+//! Route::new().at("/todos", post(create)).with(RequirePermission("todo:write"))
+//! ```
+use super::Auth;
+
+#[cfg(feature = "backend_actix-web")]
+mod guard_actixweb;
+#[cfg(feature = "backend_actix-web")]
+pub use guard_actixweb::GuardMiddleware;
+
+#[cfg(feature = "backend_poem")]
+mod guard_poem;
+#[cfg(feature = "backend_poem")]
+pub use guard_poem::GuardEndpoint;
+
+/// a check an authenticated user has to pass before a request is handled
+pub trait AuthGuard: Copy + Send + Sync + 'static {
+    /// `Err` holds the reason the user was rejected
+    ///
+    /// # Errors
+    /// * the user doesn't pass the check
+    fn check(&self, auth: &Auth) -> Result<(), String>;
+
+    /// the scope listed in the OpenAPI security requirement of guarded operations
+    fn scope(&self) -> String;
+}
+
+#[derive(Debug, Clone, Copy)]
+/// only lets users with the given permission through
+pub struct RequirePermission(pub &'static str);
+
+impl AuthGuard for RequirePermission {
+    fn check(&self, auth: &Auth) -> Result<(), String> {
+        if auth.has_permission(self.0.to_string()) {
+            Ok(())
+        } else {
+            Err(format!("Missing permission '{}'.", self.0))
+        }
+    }
+
+    fn scope(&self) -> String {
+        format!("permission:{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// only lets users with the given role through
+pub struct RequireRole(pub &'static str);
+
+impl AuthGuard for RequireRole {
+    fn check(&self, auth: &Auth) -> Result<(), String> {
+        if auth.has_role(self.0) {
+            Ok(())
+        } else {
+            Err(format!("Missing role '{}'.", self.0))
+        }
+    }
+
+    fn scope(&self) -> String {
+        format!("role:{}", self.0)
+    }
+}
+
+#[cfg(feature = "plugin_utoipa")]
+impl RequirePermission {
+    /// the guard, documented on the operations under `paths` only, see [`Guarded`]
+    #[must_use]
+    pub const fn on(self, paths: &'static [&'static str]) -> Guarded<Self> {
+        Guarded { guard: self, paths }
+    }
+}
+
+#[cfg(feature = "plugin_utoipa")]
+impl RequireRole {
+    /// the guard, documented on the operations under `paths` only, see [`Guarded`]
+    #[must_use]
+    pub const fn on(self, paths: &'static [&'static str]) -> Guarded<Self> {
+        Guarded { guard: self, paths }
+    }
+}
+
+/// a guard and the paths it protects, as an OpenAPI modifier
+///
+/// adds the guard to the operations of those paths (and the paths below them, like a guarded scope),
+/// as a scope of the "JWT" security requirement and in the operation's description;
+/// the document's other operations are left as they are
+///
+/// ```ignore
+/// const TODO_WRITE: Guarded<RequirePermission> = RequirePermission("todo:write").on(&["/api/todos"]);
+///
+/// #[derive(OpenApi)]
+/// #[openapi(paths(create, update), modifiers(&TODO_WRITE))]
+/// struct TodoApiDoc;
+/// ```
+#[cfg(feature = "plugin_utoipa")]
+#[derive(Debug, Clone, Copy)]
+pub struct Guarded<G: AuthGuard> {
+    guard: G,
+    paths: &'static [&'static str],
+}
+
+#[cfg(feature = "plugin_utoipa")]
+impl<G: AuthGuard> Guarded<G> {
+    fn guards(&self, path: &str) -> bool {
+        self.paths.iter().any(|guarded| {
+            let guarded = guarded.trim_end_matches('/');
+            path == guarded
+                || path
+                    .strip_prefix(guarded)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+#[cfg(feature = "plugin_utoipa")]
+impl<G: AuthGuard> utoipa::Modify for Guarded<G> {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        use utoipa::openapi::security::SecurityRequirement;
+
+        let scope = self.guard.scope();
+
+        for operation in openapi
+            .paths
+            .paths
+            .iter_mut()
+            .filter(|(path, _)| self.guards(path))
+            .flat_map(|(_, path_item)| path_item.operations.values_mut())
+        {
+            // several guards on the same operation all have to pass, so their scopes are merged
+            // into a single "JWT" requirement (separate requirements would mean any one of them is enough)
+            let security = operation.security.get_or_insert_with(Vec::new);
+            let mut scopes = vec![];
+            security.retain(|requirement| {
+                let jwt_scopes = serde_json::to_value(requirement)
+                    .ok()
+                    .and_then(|value| value.get("JWT").cloned())
+                    .and_then(|value| serde_json::from_value::<Vec<String>>(value).ok());
+
+                let Some(jwt_scopes) = jwt_scopes else {
+                    return true;
+                };
+                scopes.extend(jwt_scopes);
+                false
+            });
+            scopes.push(scope.clone());
+            security.push(SecurityRequirement::new("JWT", scopes));
+
+            let requires = format!("Requires `{scope}`.");
+            operation.description = Some(match operation.description.take() {
+                Some(description) if !description.is_empty() => {
+                    format!("{description}\n\n{requires}")
+                }
+                _ => requires,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Permission;
+    use std::collections::HashSet;
+
+    fn auth() -> Auth {
+        Auth {
+            user_id: 1,
+            roles: HashSet::from(["admin".to_string()]),
+            permissions: HashSet::from([Permission {
+                permission: "todo:write".to_string(),
+                from_role: String::new(),
+            }]),
+            api_key_id: None,
+            api_key_permissions: None,
+        }
+    }
+
+    #[test]
+    fn test_guards_check_permissions_and_roles() {
+        assert!(RequirePermission("todo:write").check(&auth()).is_ok());
+        assert!(RequirePermission("todo:delete").check(&auth()).is_err());
+        assert!(RequireRole("admin").check(&auth()).is_ok());
+        assert!(RequireRole("owner").check(&auth()).is_err());
+    }
+
+    #[cfg(feature = "plugin_utoipa")]
+    #[test]
+    fn test_guards_merge_into_one_security_requirement() {
+        use utoipa::openapi::{
+            path::{OperationBuilder, PathItemType, PathsBuilder},
+            security::SecurityRequirement,
+            OpenApiBuilder, PathItem,
+        };
+        use utoipa::Modify;
+
+        let mut openapi = OpenApiBuilder::new()
+            .paths(
+                PathsBuilder::new()
+                    .path(
+                        "/todos",
+                        PathItem::new(PathItemType::Post, OperationBuilder::new().build()),
+                    )
+                    .path(
+                        "/todos/{id}",
+                        PathItem::new(PathItemType::Put, OperationBuilder::new().build()),
+                    )
+                    .path(
+                        "/todos-public",
+                        PathItem::new(PathItemType::Get, OperationBuilder::new().build()),
+                    ),
+            )
+            .build();
+
+        RequirePermission("todo:write")
+            .on(&["/todos"])
+            .modify(&mut openapi);
+        RequireRole("admin").on(&["/todos/"]).modify(&mut openapi);
+
+        for (path, method) in [
+            ("/todos", PathItemType::Post),
+            ("/todos/{id}", PathItemType::Put),
+        ] {
+            let operation = &openapi.paths.paths[path].operations[&method];
+            assert!(
+                operation.security
+                    == Some(vec![SecurityRequirement::new(
+                        "JWT",
+                        ["permission:todo:write", "role:admin"]
+                    )])
+            );
+            assert_eq!(
+                operation.description.as_deref(),
+                Some("Requires `permission:todo:write`.\n\nRequires `role:admin`.")
+            );
+        }
+
+        let unguarded = &openapi.paths.paths["/todos-public"].operations[&PathItemType::Get];
+        assert!(unguarded.security.is_none());
+        assert!(unguarded.description.is_none());
+    }
+}
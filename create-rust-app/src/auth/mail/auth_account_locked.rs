@@ -0,0 +1,49 @@
+use crate::mailer::RenderedEmail;
+use crate::Mailer;
+use tera::Context;
+
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, locked_until: &str) {
+    let mut context = Context::new();
+    context.insert("locked_until", locked_until);
+    let email = mailer
+        .render("auth_account_locked", &context)
+        .unwrap_or_else(|| built_in(locked_until));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_account_locked` mail template
+fn built_in(locked_until: &str) -> RenderedEmail {
+    let subject = "Your account was locked";
+    let text = format!(
+        r"
+(This is an automated message.)
+
+Hello,
+
+Someone tried to log in to your account with the wrong password too many times,
+so it has been locked until {locked_until}.
+
+If this wasn't you, consider changing your password once the lock expires.
+"
+    );
+    let html = format!(
+        r"
+<p>(This is an automated message.)</p>
+
+<p>Hello,</p>
+
+<p>Someone tried to log in to your account with the wrong password too many times,
+so it has been locked until {locked_until}.</p>
+
+<p>If this wasn't you, consider changing your password once the lock expires.</p>
+"
+    );
+
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
+}
@@ -0,0 +1,53 @@
+use crate::mailer::RenderedEmail;
+use crate::Mailer;
+use tera::Context;
+
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
+    let mut context = Context::new();
+    context.insert("link", link);
+    let email = mailer
+        .render("auth_email_change", &context)
+        .unwrap_or_else(|| built_in(link));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_email_change` mail template
+fn built_in(link: &str) -> RenderedEmail {
+    let subject = "Confirm your new email address";
+    let text = format!(
+        r#"
+(This is an automated message.)
+
+Hello,
+
+Someone asked to change the email address of their account to this one.
+Please visit this link to confirm the change:
+{link}
+
+Until then, the account keeps using its current email address.
+If this wasn't you, you can safely ignore this email.
+"#
+    );
+    let html = format!(
+        r#"
+<p>(This is an automated message.)</p>
+
+<p>Hello,</p>
+
+<p>Someone asked to change the email address of their account to this one.
+Please visit this link to confirm the change:</p>
+<p><a href="{link}">{link}</a></p>
+
+<p>Until then, the account keeps using its current email address.
+If this wasn't you, you can safely ignore this email.</p>
+"#
+    );
+
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
+}
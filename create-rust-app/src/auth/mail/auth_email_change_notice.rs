@@ -0,0 +1,49 @@
+use crate::mailer::RenderedEmail;
+use crate::Mailer;
+use tera::Context;
+
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, new_email: &str) {
+    let mut context = Context::new();
+    context.insert("new_email", new_email);
+    let email = mailer
+        .render("auth_email_change_notice", &context)
+        .unwrap_or_else(|| built_in(new_email));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_email_change_notice` mail template
+fn built_in(new_email: &str) -> RenderedEmail {
+    let subject = "Your email address is being changed";
+    let text = format!(
+        r"
+(This is an automated message.)
+
+Hello,
+
+Someone asked to change the email address of your account to {new_email}.
+The change only happens once it's confirmed from that address.
+
+If this wasn't you, change your password and log out of your other sessions.
+"
+    );
+    let html = format!(
+        r"
+<p>(This is an automated message.)</p>
+
+<p>Hello,</p>
+
+<p>Someone asked to change the email address of your account to {new_email}.
+The change only happens once it's confirmed from that address.</p>
+
+<p>If this wasn't you, change your password and log out of your other sessions.</p>
+"
+    );
+
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
+}
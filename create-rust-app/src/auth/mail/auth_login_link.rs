@@ -0,0 +1,53 @@
+use crate::mailer::RenderedEmail;
+use crate::Mailer;
+use tera::Context;
+
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
+    let mut context = Context::new();
+    context.insert("link", link);
+    let email = mailer
+        .render("auth_login_link", &context)
+        .unwrap_or_else(|| built_in(link));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_login_link` mail template
+fn built_in(link: &str) -> RenderedEmail {
+    let subject = "Your login link";
+    let text = format!(
+        r#"
+(This is an automated message.)
+
+Hello,
+
+Someone requested a link to log in to the account associated with this email.
+Please visit this link to log in:
+{link}
+(the link can only be used once, and expires soon)
+
+If this wasn't you, you can safely ignore this email.
+"#
+    );
+    let html = format!(
+        r#"
+<p>(This is an automated message.)</p>
+
+<p>Hello,</p>
+
+<p>Someone requested a link to log in to the account associated with this email.
+Please visit this link to log in:</p>
+<p><a href="{link}">{link}</a></p>
+<p>(the link can only be used once, and expires soon)</p>
+
+<p>If this wasn't you, you can safely ignore this email.</p>
+"#
+    );
+
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
+}
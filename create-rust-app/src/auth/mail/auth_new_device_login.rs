@@ -0,0 +1,50 @@
+use crate::mailer::RenderedEmail;
+use crate::Mailer;
+use tera::Context;
+
+#[allow(dead_code)]
+pub fn send(mailer: &Mailer, to_email: &str, device: &str, ip_address: &str) {
+    let mut context = Context::new();
+    context.insert("device", device);
+    context.insert("ip_address", ip_address);
+    let email = mailer
+        .render("auth_new_device_login", &context)
+        .unwrap_or_else(|| built_in(device, ip_address));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_new_device_login` mail template
+fn built_in(device: &str, ip_address: &str) -> RenderedEmail {
+    let subject = "New login to your account";
+    let text = format!(
+        r"
+(This is an automated message.)
+
+Hello,
+
+Your account was just logged in to from a device we didn't recognise:
+{device}, from {ip_address}.
+
+If this wasn't you, change your password and log out of your other sessions.
+"
+    );
+    let html = format!(
+        r"
+<p>(This is an automated message.)</p>
+
+<p>Hello,</p>
+
+<p>Your account was just logged in to from a device we didn't recognise:
+{device}, from {ip_address}.</p>
+
+<p>If this wasn't you, change your password and log out of your other sessions.</p>
+"
+    );
+
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
+}
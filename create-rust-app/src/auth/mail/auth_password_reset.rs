@@ -1,7 +1,19 @@
+use crate::mailer::RenderedEmail;
 use crate::Mailer;
+use tera::Context;
 
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str) {
+    let context = Context::new();
+    let email = mailer
+        .render("auth_password_reset", &context)
+        .unwrap_or_else(built_in);
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_password_reset` mail template
+fn built_in() -> RenderedEmail {
     let subject = "Your password was reset";
     let text = r"
 (This is an automated message.)
@@ -21,5 +33,9 @@ Your password was successfully reset!
 "
     .to_string();
 
-    mailer.send(to_email, subject, &text, &html);
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
 }
@@ -1,7 +1,20 @@
+use crate::mailer::RenderedEmail;
 use crate::Mailer;
+use tera::Context;
 
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
+    let mut context = Context::new();
+    context.insert("link", link);
+    let email = mailer
+        .render("auth_recover_existent_account", &context)
+        .unwrap_or_else(|| built_in(link));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_recover_existent_account` mail template
+fn built_in(link: &str) -> RenderedEmail {
     let subject = "Reset Password Instructions";
     let text = format!(
         r#"
@@ -28,5 +41,9 @@ Please visit this link to reset your password:</p>
 "#
     );
 
-    mailer.send(to_email, subject, &text, &html);
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
 }
@@ -1,7 +1,20 @@
+use crate::mailer::RenderedEmail;
 use crate::Mailer;
+use tera::Context;
 
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
+    let mut context = Context::new();
+    context.insert("link", link);
+    let email = mailer
+        .render("auth_recover_nonexistent_account", &context)
+        .unwrap_or_else(|| built_in(link));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_recover_nonexistent_account` mail template
+fn built_in(link: &str) -> RenderedEmail {
     let subject = "Reset Password Instructions";
     let text = format!(
         r#"
@@ -26,5 +39,9 @@ If this was intentional, you can register for a new account using the link below
 "#
     );
 
-    mailer.send(to_email, subject, &text, &html);
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
 }
@@ -1,7 +1,20 @@
+use crate::mailer::RenderedEmail;
 use crate::Mailer;
+use tera::Context;
 
 #[allow(dead_code)]
 pub fn send(mailer: &Mailer, to_email: &str, link: &str) {
+    let mut context = Context::new();
+    context.insert("link", link);
+    let email = mailer
+        .render("auth_register", &context)
+        .unwrap_or_else(|| built_in(link));
+
+    mailer.send(to_email, &email.subject, &email.text, &email.html);
+}
+
+/// the email sent when the project has no `auth_register` mail template
+fn built_in(link: &str) -> RenderedEmail {
     let subject = "Registration Confirmation";
     let text = format!(
         r#"
@@ -24,5 +37,9 @@ Please follow the link below to complete your registration:
 "#
     );
 
-    mailer.send(to_email, subject, &text, &html);
+    RenderedEmail {
+        subject: subject.to_string(),
+        text,
+        html,
+    }
 }
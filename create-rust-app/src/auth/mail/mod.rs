@@ -1,5 +1,10 @@
 // Mail
+pub mod auth_account_locked;
 pub mod auth_activated;
+pub mod auth_email_change;
+pub mod auth_email_change_notice;
+pub mod auth_login_link;
+pub mod auth_new_device_login;
 pub mod auth_password_changed;
 pub mod auth_password_reset;
 pub mod auth_recover_existent_account;
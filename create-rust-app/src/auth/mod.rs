@@ -1,3 +1,4 @@
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "plugin_utoipa")]
@@ -14,22 +15,62 @@ pub use extractors::*;
 pub mod controller;
 mod endpoints;
 pub use endpoints::*;
+pub mod error;
+pub use error::AuthError;
 
 #[cfg(feature = "plugin_auth-oidc")]
 pub mod oidc;
 
+pub mod account;
+pub mod admin;
+mod admin_audit_log;
+pub mod authorization_server;
+mod failed_login;
+pub mod guards;
+pub mod keys;
 pub(crate) mod mail;
+mod oauth_authorization_code;
+mod oauth_client;
+pub mod passkey;
+mod passkey_challenge;
+mod password_policy;
 mod permissions;
 mod schema;
+mod throttle;
+pub mod totp;
 mod user;
+pub mod user_agent;
+mod user_api_key;
+mod user_lockout;
+mod user_login_link;
+mod user_passkey;
 mod user_session;
+mod user_totp;
+
+pub use admin_audit_log::{AdminAuditLog, AdminAuditLogChangeset};
+pub use failed_login::{FailedLogin, FailedLoginChangeset};
+#[cfg(feature = "plugin_utoipa")]
+pub use guards::Guarded;
+pub use guards::{AuthGuard, RequirePermission, RequireRole};
+pub use oauth_authorization_code::{OAuthAuthorizationCode, OAuthAuthorizationCodeChangeset};
+pub use oauth_client::{OAuthClient, OAuthClientChangeset};
+pub use passkey_challenge::{PasskeyChallenge, PasskeyChallengeChangeset};
+pub use password_policy::{BreachedPasswords, PasswordPolicy, PasswordViolation};
 
 pub use permissions::{
-    Permission, Role, RolePermission, RolePermissionChangeset, UserPermission,
-    UserPermissionChangeset,
+    Permission, Role, RoleInheritance, RoleInheritanceChangeset, RolePermission,
+    RolePermissionChangeset, RoleResourcePermission, RoleResourcePermissionChangeset,
+    UserPermission, UserPermissionChangeset, UserResourcePermission,
+    UserResourcePermissionChangeset,
 };
+pub use throttle::LoginThrottleConfig;
 pub use user::{User, UserChangeset};
+pub use user_api_key::{UserApiKey, UserApiKeyChangeset, API_KEY_PREFIX};
+pub use user_lockout::{UserLockout, UserLockoutChangeset};
+pub use user_login_link::{UserLoginLink, UserLoginLinkChangeset};
+pub use user_passkey::{UserPasskey, UserPasskeyChangeset};
 pub use user_session::{UserSession, UserSessionChangeset};
+pub use user_totp::{UserTotp, UserTotpChangeset};
 
 #[tsync::tsync]
 type ID = i32;
@@ -40,6 +81,16 @@ type Utc = chrono::DateTime<chrono::Utc>;
 #[cfg(feature = "database_sqlite")]
 type Utc = chrono::NaiveDateTime;
 
+/// the current time, in the representation used by the database's timestamp columns
+#[cfg(not(feature = "database_sqlite"))]
+pub(crate) fn utc_now() -> Utc {
+    chrono::Utc::now()
+}
+#[cfg(feature = "database_sqlite")]
+pub(crate) fn utc_now() -> Utc {
+    chrono::Utc::now().naive_utc()
+}
+
 #[tsync::tsync]
 #[derive(Deserialize)]
 #[cfg_attr(feature = "plugin_utoipa", derive(utoipa::IntoParams))]
@@ -68,11 +119,34 @@ impl PaginationParams {
 pub struct UserSessionJson {
     pub id: ID,
     pub device: Option<String>,
+    /// named after the session's user agent, like "Firefox on Linux"
+    pub device_name: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    /// when the user logged in
+    pub logged_in_at: Utc,
+    /// when the session was last refreshed, every refresh continues the session in a new entry
     pub created_at: Utc,
     #[cfg(not(feature = "database_sqlite"))]
     pub updated_at: Utc,
 }
 
+impl From<&UserSession> for UserSessionJson {
+    fn from(session: &UserSession) -> Self {
+        Self {
+            id: session.id,
+            device: session.device.clone(),
+            device_name: session.user_agent.as_deref().map(user_agent::device_name),
+            ip_address: session.ip_address.clone(),
+            user_agent: session.user_agent.clone(),
+            logged_in_at: session.logged_in_at,
+            created_at: session.created_at,
+            #[cfg(not(feature = "database_sqlite"))]
+            updated_at: session.updated_at,
+        }
+    }
+}
+
 #[tsync::tsync]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
@@ -83,6 +157,78 @@ pub struct UserSessionResponse {
     pub num_pages: i64,
 }
 
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of a entry from the databases `user_passkeys` table
+/// serialized into Json, without the key material
+pub struct UserPasskeyJson {
+    pub id: ID,
+    pub name: Option<String>,
+    pub last_used_at: Option<Utc>,
+    pub created_at: Utc,
+}
+
+impl From<&UserPasskey> for UserPasskeyJson {
+    fn from(passkey: &UserPasskey) -> Self {
+        Self {
+            id: passkey.id,
+            name: passkey.name.clone(),
+            last_used_at: passkey.last_used_at,
+            created_at: passkey.created_at,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of the
+/// backends JSON response to a GET request at the /passkeys endpoint
+pub struct UserPasskeyResponse {
+    pub passkeys: Vec<UserPasskeyJson>,
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of a entry from the databases `user_api_keys` table
+/// serialized into Json, without the key's hash
+pub struct UserApiKeyJson {
+    pub id: ID,
+    pub name: String,
+    /// the first characters of the key, so users can recognize it
+    pub key_prefix: String,
+    /// the permissions the key is limited to, `None` if it has all of the user's permissions and roles
+    pub permissions: Option<Vec<String>>,
+    pub last_used_at: Option<Utc>,
+    pub expires_at: Option<Utc>,
+    pub created_at: Utc,
+}
+
+impl From<&UserApiKey> for UserApiKeyJson {
+    fn from(api_key: &UserApiKey) -> Self {
+        Self {
+            id: api_key.id,
+            name: api_key.name.clone(),
+            key_prefix: api_key.key_prefix.clone(),
+            permissions: api_key.permission_list(),
+            last_used_at: api_key.last_used_at,
+            expires_at: api_key.expires_at,
+            created_at: api_key.created_at,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of the
+/// backends JSON response to a GET request at the /api-keys endpoint
+pub struct UserApiKeyResponse {
+    pub api_keys: Vec<UserApiKeyJson>,
+}
+
 #[tsync::tsync]
 #[derive(Debug, Serialize, Deserialize)]
 /// TODO: documentation
@@ -121,6 +267,18 @@ pub struct AuthMessageResponse {
     pub message: String,
 }
 
+#[cfg(feature = "plugin_utoipa")]
+#[tsync::tsync]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+/// structure to help utoipa know what responses to a password which doesn't follow
+/// the [`PasswordPolicy`] contain, see [`error::AuthError::PasswordPolicy`]
+pub struct PasswordPolicyResponse {
+    /// always `password_policy`
+    pub code: String,
+    pub message: String,
+    pub violations: Vec<PasswordViolation>,
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[cfg(feature = "plugin_utoipa")]
 #[tsync::tsync]
@@ -130,9 +288,88 @@ pub struct AuthTokenResponse {
     pub access_token: String,
 }
 
+#[allow(clippy::module_name_repetitions)]
+#[cfg(feature = "plugin_utoipa")]
+#[tsync::tsync]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+/// structure to help utoipa know what a login response looks like
+/// when the user still has to provide their second factor
+pub struct AuthTwoFactorRequiredResponse {
+    pub two_factor_required: bool,
+    pub two_factor_token: String,
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone)]
 pub struct AuthConfig {
     #[cfg(feature = "plugin_auth-oidc")]
     pub oidc_providers: Vec<crate::auth::oidc::OIDCProvider>,
+    /// limits on failed login attempts, see [`LoginThrottleConfig`]
+    pub login_throttle: LoginThrottleConfig,
+    /// the keys JWTs are signed and verified with, see [`keys::KeyProvider`]
+    pub keys: Box<dyn keys::KeyProvider>,
+    /// how long access tokens are valid for
+    pub access_token_lifetime: chrono::Duration,
+    /// how long refresh tokens are valid for; every refresh issues a new one
+    pub refresh_token_lifetime: chrono::Duration,
+    /// how long emailed login links are valid for, see [`controller::request_login_link`]
+    pub login_link_lifetime: chrono::Duration,
+    /// how long users have to wait before another login link is emailed to them,
+    /// so [`controller::request_login_link`] can't be used to flood their inbox
+    pub login_link_cooldown: chrono::Duration,
+    /// users with this role can use the admin endpoints (see [`admin`]),
+    /// they are disabled when it's `None`
+    pub admin_role: Option<String>,
+    /// lets the apps registered as [`OAuthClient`]s sign users in through this backend,
+    /// see [`authorization_server`]
+    pub authorization_server: bool,
+    /// how long after a user asked for their account to be deleted it's actually deleted,
+    /// see [`account`]
+    pub account_deletion_grace_period: chrono::Duration,
+    /// emails users when they log in from a device none of their sessions use,
+    /// see [`EmailTemplates::send_new_device_login`](`crate::EmailTemplates::send_new_device_login`)
+    pub notify_new_device: bool,
+    /// rules new passwords have to follow, see [`PasswordPolicy`]
+    pub password_policy: PasswordPolicy,
+}
+
+impl AuthConfig {
+    /// the default configuration, signing tokens with the `SECRET_KEY` environment variable
+    /// (see [`keys::StaticKeyProvider::from_env`])
+    ///
+    /// # Errors
+    /// * could not get `SECRET_KEY` from environment
+    pub fn from_env() -> Result<Self, std::env::VarError> {
+        Ok(Self {
+            #[cfg(feature = "plugin_auth-oidc")]
+            oidc_providers: vec![],
+            login_throttle: LoginThrottleConfig::default(),
+            keys: Box::new(keys::StaticKeyProvider::from_env()?),
+            access_token_lifetime: chrono::Duration::minutes(15),
+            refresh_token_lifetime: chrono::Duration::hours(24),
+            login_link_lifetime: chrono::Duration::minutes(15),
+            login_link_cooldown: chrono::Duration::minutes(1),
+            admin_role: None,
+            authorization_server: false,
+            account_deletion_grace_period: chrono::Duration::days(30),
+            notify_new_device: false,
+            password_policy: PasswordPolicy::default(),
+        })
+    }
+
+    /// the configuration used when the app didn't register one, so apps which don't need to configure
+    /// the auth plugin keep working: [`AuthConfig::from_env`], `None` if `SECRET_KEY` isn't set
+    pub(crate) fn fallback() -> Option<&'static Self> {
+        static FALLBACK: OnceCell<Option<AuthConfig>> = OnceCell::new();
+
+        FALLBACK
+            .get_or_init(|| {
+                Self::from_env()
+                    .map_err(|_| {
+                        println!("Warning: no AuthConfig was registered and SECRET_KEY isn't set");
+                    })
+                    .ok()
+            })
+            .as_ref()
+    }
 }
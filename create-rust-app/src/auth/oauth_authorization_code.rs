@@ -0,0 +1,121 @@
+use super::schema::oauth_authorization_codes;
+use crate::diesel::{
+    insert_into, AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable, RunQueryDsl,
+};
+
+use super::{utc_now, Utc, ID};
+use crate::database::Connection;
+use diesel::{OptionalExtension, QueryResult};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name=oauth_authorization_codes)]
+/// Rust struct modeling an entry in the `oauth_authorization_codes` table
+///
+/// a code issued to a client after the user authorized it, which it can exchange for tokens once
+pub struct OAuthAuthorizationCode {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    /// see [`OAuthAuthorizationCode::hash_code`], the code itself is only sent to the client
+    pub code_hash: String,
+    /// the [`OAuthClient`](`crate::auth::OAuthClient`) the code was issued to
+    pub oauth_client_id: ID,
+    pub user_id: ID,
+    /// the client has to present the same redirect uri when it exchanges the code
+    pub redirect_uri: String,
+    /// space separated scopes the user authorized
+    pub scope: String,
+    /// echoed in the ID token
+    pub nonce: Option<String>,
+    /// the base64url encoded SHA-256 hash of the client's PKCE code verifier
+    pub code_challenge: String,
+    pub expires_at: Utc,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=oauth_authorization_codes)]
+/// Rust struct modeling mutable data in an entry in the `oauth_authorization_codes` table
+pub struct OAuthAuthorizationCodeChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub code_hash: String,
+    pub oauth_client_id: ID,
+    pub user_id: ID,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub nonce: Option<String>,
+    pub code_challenge: String,
+    pub expires_at: Utc,
+}
+
+impl OAuthAuthorizationCode {
+    /// 256 random bits, base64url encoded
+    #[must_use]
+    pub fn generate_code() -> String {
+        let code: [u8; 32] = rand::thread_rng().gen();
+        data_encoding::BASE64URL_NOPAD.encode(&code)
+    }
+
+    /// the hash stored in place of `code`
+    #[must_use]
+    pub fn hash_code(code: &str) -> String {
+        data_encoding::HEXLOWER.encode(&Sha256::digest(code.as_bytes()))
+    }
+
+    /// Create an entry in [`db`](`Connection`)'s `oauth_authorization_codes` table using the data in [`item`](`OAuthAuthorizationCodeChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(
+        db: &mut Connection,
+        item: &OAuthAuthorizationCodeChangeset,
+    ) -> QueryResult<Self> {
+        use super::schema::oauth_authorization_codes::dsl::oauth_authorization_codes;
+
+        insert_into(oauth_authorization_codes)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `oauth_authorization_codes` table for `code`,
+    /// returning it if it hadn't expired yet
+    ///
+    /// the delete makes sure a code can only be exchanged once
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn consume(db: &mut Connection, code: &str) -> QueryResult<Option<Self>> {
+        use super::schema::oauth_authorization_codes::dsl::{code_hash, oauth_authorization_codes};
+
+        let item =
+            diesel::delete(oauth_authorization_codes.filter(code_hash.eq(Self::hash_code(code))))
+                .get_result::<Self>(db)
+                .optional()?;
+
+        Ok(item.filter(|item| item.expires_at > utc_now()))
+    }
+
+    /// Delete every entry in [`db`](`Connection`)'s `oauth_authorization_codes` table which expired before `before`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_expired(db: &mut Connection, before: Utc) -> QueryResult<usize> {
+        use super::schema::oauth_authorization_codes::dsl::{
+            expires_at, oauth_authorization_codes,
+        };
+
+        diesel::delete(oauth_authorization_codes.filter(expires_at.lt(before))).execute(db)
+    }
+}
@@ -0,0 +1,186 @@
+use super::schema::oauth_clients;
+use crate::diesel::{
+    insert_into, AsChangeset, ExpressionMethods, Identifiable, Insertable, QueryDsl, Queryable,
+    RunQueryDsl,
+};
+
+use super::{UserSession, Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Identifiable, AsChangeset,
+)]
+#[diesel(table_name=oauth_clients)]
+/// Rust struct modeling an entry in the `oauth_clients` table
+///
+/// an app which may sign its users in through this backend, see [`crate::auth::authorization_server`]
+pub struct OAuthClient {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+    /// the public identifier the client sends in its requests
+    pub client_id: String,
+    /// see [`OAuthClient::hash_secret`], `None` for public clients (like SPAs and mobile apps)
+    /// which can't keep a secret and only rely on PKCE
+    #[serde(skip_serializing)]
+    pub client_secret_hash: Option<String>,
+    /// shown to users when they're asked for their consent
+    pub name: String,
+    /// newline separated urls the client may be redirected to, compared exactly
+    pub redirect_uris: String,
+    /// first-party clients are trusted, users aren't asked for their consent
+    pub first_party: bool,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=oauth_clients)]
+/// Rust struct modeling mutable data in an entry in the `oauth_clients` table
+pub struct OAuthClientChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub client_id: String,
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    pub redirect_uris: String,
+    pub first_party: bool,
+}
+
+impl OAuthClient {
+    /// registers a client, returning it with its secret (`None` if `confidential` is false)
+    ///
+    /// the secret can't be recovered later, it has to be handed to the client right away
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn register(
+        db: &mut Connection,
+        name: &str,
+        redirect_uris: &[&str],
+        first_party: bool,
+        confidential: bool,
+    ) -> QueryResult<(Self, Option<String>)> {
+        let client_secret = confidential.then(Self::generate_secret);
+
+        let client = Self::create(
+            db,
+            &OAuthClientChangeset {
+                client_id: Self::generate_secret(),
+                client_secret_hash: client_secret.as_deref().map(Self::hash_secret),
+                name: name.to_string(),
+                redirect_uris: redirect_uris.join("\n"),
+                first_party,
+            },
+        )?;
+
+        Ok((client, client_secret))
+    }
+
+    /// 256 random bits, base64url encoded
+    #[must_use]
+    pub fn generate_secret() -> String {
+        let secret: [u8; 32] = rand::thread_rng().gen();
+        data_encoding::BASE64URL_NOPAD.encode(&secret)
+    }
+
+    /// the hash stored in place of `secret`
+    ///
+    /// secrets are random rather than chosen by users, so a fast hash is enough
+    #[must_use]
+    pub fn hash_secret(secret: &str) -> String {
+        data_encoding::HEXLOWER.encode(&Sha256::digest(secret.as_bytes()))
+    }
+
+    /// whether `redirect_uri` is one of the client's registered redirect uris
+    #[must_use]
+    pub fn allows_redirect_uri(&self, redirect_uri: &str) -> bool {
+        self.redirect_uris.lines().any(|uri| uri == redirect_uri)
+    }
+
+    /// whether `client_secret` authenticates this client; public clients don't have a secret
+    #[must_use]
+    pub fn verify_secret(&self, client_secret: Option<&str>) -> bool {
+        match (&self.client_secret_hash, client_secret) {
+            (None, _) => true,
+            (Some(hash), Some(secret)) => *hash == Self::hash_secret(secret),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Create an entry in [`db`](`Connection`)'s `oauth_clients` table using the data in [`item`](`OAuthClientChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &OAuthClientChangeset) -> QueryResult<Self> {
+        use super::schema::oauth_clients::dsl::oauth_clients;
+
+        insert_into(oauth_clients)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `oauth_clients`
+    /// who's primary key matches [`item_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use super::schema::oauth_clients::dsl::{id, oauth_clients};
+
+        oauth_clients.filter(id.eq(item_id)).first::<Self>(db)
+    }
+
+    /// Query [`db`](`Connection`)'s `oauth_clients` table for an entry
+    /// who's `client_id` matches `item_client_id`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn find_by_client_id(db: &mut Connection, item_client_id: &str) -> QueryResult<Self> {
+        use super::schema::oauth_clients::dsl::{client_id, oauth_clients};
+
+        oauth_clients
+            .filter(client_id.eq(item_client_id))
+            .first::<Self>(db)
+    }
+
+    /// Query [`db`](`Connection`) for all entries in the `oauth_clients` table
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(db: &mut Connection) -> QueryResult<Vec<Self>> {
+        use super::schema::oauth_clients::dsl::{id, oauth_clients};
+
+        oauth_clients.order(id.asc()).load::<Self>(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `oauth_clients` table who's
+    /// primary key matches [`item_id`](`ID`), along with its pending authorization codes and
+    /// its sessions (so its refresh tokens stop working)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::oauth_authorization_codes::dsl::{
+            oauth_authorization_codes, oauth_client_id,
+        };
+        use super::schema::oauth_clients::dsl::{id, oauth_clients};
+
+        diesel::delete(oauth_authorization_codes.filter(oauth_client_id.eq(item_id)))
+            .execute(db)?;
+        UserSession::delete_all_for_client(db, item_id)?;
+        diesel::delete(oauth_clients.filter(id.eq(item_id))).execute(db)
+    }
+}
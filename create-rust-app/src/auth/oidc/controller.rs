@@ -1,7 +1,10 @@
 use crate::{
     auth::{
-        controller::{create_user_session, generate_salt, ARGON_CONFIG},
-        AuthConfig, User, UserChangeset,
+        controller::{create_user_session, hash_password, ClientInfo},
+        error::AuthError,
+        permissions::UserRole,
+        totp::percent_encode,
+        Auth, AuthConfig, Role, User, UserChangeset, UserPasskey, ID,
     },
     AppConfig, Database,
 };
@@ -11,40 +14,89 @@ use rand::{distributions::Alphanumeric, Rng};
 
 use super::{
     model::{CreateUserOauth2Link, UpdateUserOauth2Link, UserOauth2Link},
-    OIDCProvider,
+    ClaimMapping, OIDCProvider, RoleMapping, UserOauth2LinkJson, UserOauth2LinkResponse,
 };
+use data_encoding::BASE64URL_NOPAD;
+use std::collections::HashSet;
 
 use openidconnect::{
-    core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata},
+    core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata, CoreTokenResponse},
+    http::{
+        header::{ACCEPT, AUTHORIZATION, USER_AGENT},
+        HeaderMap, HeaderValue, Method,
+    },
     reqwest::async_http_client,
-    AccessTokenHash, AuthorizationCode, ClientId, ClientSecret, CsrfToken, IssuerUrl, Nonce,
-    OAuth2TokenResponse, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
+    url::Url,
+    AccessTokenHash, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, HttpRequest,
+    IssuerUrl, JsonWebKeySet, Nonce, OAuth2TokenResponse, PkceCodeChallenge, PkceCodeVerifier,
+    RedirectUrl, Scope, TokenResponse, TokenUrl, UserInfoUrl,
 };
 
-async fn create_oidc_client(provider: &OIDCProvider, app_url: String) -> Result<CoreClient> {
+type UserInfoEndpoint = Option<String>;
+type AuthorizationUrl = String;
+type LinkState = String;
+
+/// the cookie holding the state of the link flow a logged in user started in this browser,
+/// see [`oidc_link_url`]
+pub const LINK_STATE_COOKIE_NAME: &str = "oidc_link_state";
+
+/// creates a client for `provider`, and returns it alongside the provider's userinfo endpoint
+///
+/// OIDC providers are configured through discovery, plain OAuth2 providers through their
+/// [`oauth2_endpoints`](`OIDCProvider::oauth2_endpoints`)
+async fn create_oidc_client(
+    provider: &OIDCProvider,
+    app_url: String,
+) -> Result<(CoreClient, UserInfoEndpoint)> {
+    let redirect_url = RedirectUrl::new(provider.redirect_uri(&app_url))?;
+
+    if let Some(endpoints) = &provider.oauth2_endpoints {
+        let client = CoreClient::new(
+            ClientId::new(provider.client_id.clone()),
+            Some(ClientSecret::new(provider.client_secret.clone())),
+            IssuerUrl::new(provider.issuer_url.clone())?,
+            AuthUrl::new(endpoints.auth_url.clone())?,
+            Some(TokenUrl::new(endpoints.token_url.clone())?),
+            Some(UserInfoUrl::new(endpoints.userinfo_url.clone())?),
+            JsonWebKeySet::default(),
+        )
+        .disable_openid_scope()
+        .set_redirect_uri(redirect_url);
+
+        return Ok((client, Some(endpoints.userinfo_url.clone())));
+    }
+
     let provider_metadata = CoreProviderMetadata::discover_async(
         IssuerUrl::new(provider.clone().issuer_url)?,
         async_http_client,
     )
     .await?;
+    let userinfo_endpoint = provider_metadata
+        .userinfo_endpoint()
+        .map(|url| url.url().to_string());
 
-    Ok(CoreClient::from_provider_metadata(
+    let client = CoreClient::from_provider_metadata(
         provider_metadata,
         ClientId::new(provider.clone().client_id),
         Some(ClientSecret::new(provider.clone().client_secret)),
     )
-    .set_redirect_uri(RedirectUrl::new(provider.redirect_uri(&app_url))?))
+    .set_redirect_uri(redirect_url);
+
+    Ok((client, userinfo_endpoint))
 }
 
-/// # Errors
-/// * could not create the OIDC client
-pub async fn oidc_login_url(
+/// starts an authorization code flow with the provider named `provider_name`, returns the url
+/// the user should be redirected to and the flow's state, or `None` if the provider isn't configured
+///
+/// `user_id` is set when a logged in user links the provider to their account
+async fn authorization_url(
     db: &Database,
     app_config: &AppConfig,
     auth_config: &AuthConfig,
     provider_name: String,
-) -> Result<Option<String>> {
-    let mut db = db.get_connection().unwrap();
+    user_id: Option<i32>,
+) -> Result<Option<(AuthorizationUrl, LinkState)>> {
+    let mut db = db.get_connection()?;
 
     let Some(provider) = auth_config
         .clone()
@@ -55,21 +107,22 @@ pub async fn oidc_login_url(
         return Ok(None);
     };
 
-    let client = create_oidc_client(&provider, app_config.clone().app_url).await?;
+    let (client, _) = create_oidc_client(&provider, app_config.clone().app_url).await?;
 
     let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
 
-    // TODO: set redirect_uri from provider config
-    let (auth_url, csrf_token, nonce) = client
+    let mut request = client
         .authorize_url(
             CoreAuthenticationFlow::AuthorizationCode,
             CsrfToken::new_random,
             Nonce::new_random,
         )
-        .add_extra_param("access_type", "offline") // TODO: Cleanup: this line is Google-OIDC-specific for retrieving a refresh token
         .add_scopes(provider.scope.into_iter().map(Scope::new))
-        .set_pkce_challenge(pkce_challenge)
-        .url();
+        .set_pkce_challenge(pkce_challenge);
+    for (name, value) in provider.extra_params {
+        request = request.add_extra_param(name, value);
+    }
+    let (auth_url, csrf_token, nonce) = request.url();
 
     UserOauth2Link::create(
         &mut db,
@@ -78,32 +131,308 @@ pub async fn oidc_login_url(
             access_token: None,
             refresh_token: None,
             subject_id: None,
-            user_id: None,
+            user_id,
             csrf_token: csrf_token.secret().clone(),
             nonce: nonce.secret().clone(),
             pkce_secret: pkce_verifier.secret().clone(),
         },
     )?;
 
-    Ok(Some(auth_url.to_string()))
+    Ok(Some((auth_url.to_string(), csrf_token.secret().clone())))
+}
+
+/// # Errors
+/// * could not connect to the database
+/// * could not create the OIDC client
+pub async fn oidc_login_url(
+    db: &Database,
+    app_config: &AppConfig,
+    auth_config: &AuthConfig,
+    provider_name: String,
+) -> Result<Option<AuthorizationUrl>> {
+    authorization_url(db, app_config, auth_config, provider_name, None)
+        .await
+        .map(|url| url.map(|(url, _)| url))
+}
+
+/// returns the url which links the provider named `provider_name` to the user associated with
+/// [`auth`](`Auth`) once they've logged in there, see [`oauth_login`]
+///
+/// the returned state has to be stored in the [`LINK_STATE_COOKIE_NAME`] cookie, so the link
+/// can only be completed in the browser that asked for it
+///
+/// # Errors
+/// * could not connect to the database
+/// * could not create the OIDC client
+pub async fn oidc_link_url(
+    db: &Database,
+    app_config: &AppConfig,
+    auth_config: &AuthConfig,
+    auth: &Auth,
+    provider_name: String,
+) -> Result<Option<(AuthorizationUrl, LinkState)>> {
+    authorization_url(
+        db,
+        app_config,
+        auth_config,
+        provider_name,
+        Some(auth.user_id),
+    )
+    .await
 }
 
 type RefreshToken = String;
 type AccessToken = String;
-type StatusCode = u16;
-type Message = String;
 
-/// # Panics
-/// * Could not update the user oauth2 link
+/// who the user is at the provider
+struct ProviderIdentity {
+    subject: String,
+    /// only set if the provider verified it
+    email: Option<String>,
+    /// the ID token's claims, or the userinfo response of plain OAuth2 providers
+    claims: serde_json::Value,
+}
+
+/// a string (or numeric, like GitHub's user ids) claim of a userinfo response
+fn claim_string(userinfo: &serde_json::Value, claim: &str) -> Option<String> {
+    match userinfo.get(claim)? {
+        serde_json::Value::String(value) => Some(value.clone()),
+        serde_json::Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// whether a boolean claim is true, some providers (like Apple) send booleans as strings
+fn claim_is_true(userinfo: &serde_json::Value, claim: &str) -> bool {
+    match userinfo.get(claim) {
+        Some(serde_json::Value::Bool(value)) => *value,
+        Some(serde_json::Value::String(value)) => value == "true",
+        _ => false,
+    }
+}
+
+/// the email of a userinfo response, if it was verified
+fn verified_email(userinfo: &serde_json::Value, claims: &ClaimMapping) -> Option<String> {
+    let email = claim_string(userinfo, &claims.email)?;
+
+    match &claims.email_verified {
+        Some(claim) if !claim_is_true(userinfo, claim) => None,
+        _ => Some(email),
+    }
+}
+
+/// GETs a json document from one of the provider's apis on behalf of the user
+async fn fetch_json(url: &str, access_token: &str) -> Result<serde_json::Value> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {access_token}"))?,
+    );
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    // GitHub rejects requests without a user agent
+    headers.insert(USER_AGENT, HeaderValue::from_static("create-rust-app"));
+
+    let response = async_http_client(HttpRequest {
+        url: Url::parse(url)?,
+        method: Method::GET,
+        headers,
+        body: vec![],
+    })
+    .await?;
+
+    if !response.status_code.is_success() {
+        anyhow::bail!("{url} responded with {}", response.status_code);
+    }
+
+    Ok(serde_json::from_slice(&response.body)?)
+}
+
+/// reads the user's identity from the ID token, falling back to the provider's userinfo
+/// (and emails) endpoints for whatever the ID token doesn't contain
+async fn provider_identity(
+    provider: &OIDCProvider,
+    client: &CoreClient,
+    userinfo_endpoint: Option<String>,
+    token_response: &CoreTokenResponse,
+    nonce: String,
+) -> Result<ProviderIdentity, AuthError> {
+    let access_token = token_response.access_token().secret();
+    let mut subject = None;
+    let mut email = None;
+    let mut all_claims = serde_json::Value::Null;
+
+    if let Some(id_token) = token_response.id_token() {
+        let Ok(claims) = id_token.claims(&client.id_token_verifier(), &Nonce::new(nonce)) else {
+            return Err(AuthError::InvalidProviderResponse);
+        };
+
+        if let Some(expected_access_token_hash) = claims.access_token_hash() {
+            let Ok(signing_alg) = id_token.signing_alg() else {
+                return Err(AuthError::InvalidProviderResponse);
+            };
+
+            let Ok(actual_access_token_hash) =
+                AccessTokenHash::from_token(token_response.access_token(), &signing_alg)
+            else {
+                return Err(AuthError::InvalidProviderResponse);
+            };
+
+            if actual_access_token_hash != *expected_access_token_hash {
+                return Err(AuthError::InvalidProviderResponse);
+            }
+        }
+
+        // the claims were verified above, but custom claims (like `groups`) aren't exposed by them
+        let Some(payload) = id_token
+            .to_string()
+            .split('.')
+            .nth(1)
+            .and_then(|payload| BASE64URL_NOPAD.decode(payload.as_bytes()).ok())
+            .and_then(|payload| serde_json::from_slice(&payload).ok())
+        else {
+            return Err(AuthError::InvalidProviderResponse);
+        };
+        // read through the claim mapping, so providers can name their verification claim
+        email = verified_email(&payload, &provider.claims);
+        all_claims = payload;
+
+        subject = Some(claims.subject().to_string());
+    } else if provider.oauth2_endpoints.is_none() {
+        return Err(AuthError::InvalidProviderResponse);
+    }
+
+    if email.is_none() {
+        if let Some(userinfo_endpoint) = userinfo_endpoint {
+            let Ok(userinfo) = fetch_json(&userinfo_endpoint, access_token).await else {
+                return Err(AuthError::CouldNotFetchUserInfo);
+            };
+
+            email = verified_email(&userinfo, &provider.claims);
+            if subject.is_none() {
+                subject = claim_string(&userinfo, &provider.claims.subject);
+                all_claims = userinfo;
+            }
+        }
+    }
+
+    let emails_url = provider
+        .oauth2_endpoints
+        .as_ref()
+        .and_then(|endpoints| endpoints.emails_url.as_deref());
+    if let (None, Some(emails_url)) = (&email, emails_url) {
+        let Ok(serde_json::Value::Array(emails)) = fetch_json(emails_url, access_token).await
+        else {
+            return Err(AuthError::CouldNotFetchUserInfo);
+        };
+
+        // prefer the primary email, if it's verified
+        email = emails
+            .iter()
+            .filter_map(|entry| {
+                verified_email(entry, &provider.claims)
+                    .map(|email| (email, claim_is_true(entry, "primary")))
+            })
+            .max_by_key(|(_, primary)| *primary)
+            .map(|(email, _)| email);
+    }
+
+    let Some(subject) = subject else {
+        return Err(AuthError::InvalidProviderResponse);
+    };
+
+    Ok(ProviderIdentity {
+        subject,
+        email,
+        claims: all_claims,
+    })
+}
+
+/// assigns the User whose id is [`user_id`](`ID`) the roles `role_mapping` maps their `claims` to,
+/// and unassigns the other roles it manages
+fn sync_roles(
+    db: &mut crate::Connection,
+    user_id: ID,
+    role_mapping: &RoleMapping,
+    claims: &serde_json::Value,
+) -> Result<()> {
+    let granted = role_mapping.roles_for(claims);
+    let current = UserRole::read_all(db, user_id)?
+        .into_iter()
+        .map(|user_role| user_role.role)
+        .collect::<HashSet<_>>();
+
+    let to_assign = granted.difference(&current).cloned().collect::<Vec<_>>();
+    let to_unassign = role_mapping
+        .managed_roles()
+        .into_iter()
+        .filter(|role| current.contains(role) && !granted.contains(role))
+        .collect::<Vec<_>>();
+
+    if !to_assign.is_empty() && !Role::assign_many(db, user_id, to_assign)? {
+        anyhow::bail!("could not assign roles");
+    }
+    if !to_unassign.is_empty() && !Role::unassign_many(db, user_id, to_unassign)? {
+        anyhow::bail!("could not unassign roles");
+    }
+
+    Ok(())
+}
+
+/// stores the tokens of a completed flow, and clears its one-time secrets
+fn completed_link(
+    token_response: &CoreTokenResponse,
+    subject_id: Option<String>,
+    user_id: Option<i32>,
+) -> UpdateUserOauth2Link {
+    UpdateUserOauth2Link {
+        provider: None,
+        access_token: Some(Some(token_response.access_token().secret().to_string())),
+        // keep the previous refresh token if we didn't get a new one
+        refresh_token: token_response
+            .refresh_token()
+            .map(|token| Some(token.secret().to_string())),
+        csrf_token: Some(String::new()),
+        nonce: Some(String::new()),
+        pkce_secret: Some(String::new()),
+        user_id: user_id.map(Some),
+        subject_id: subject_id.map(Some),
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+/// the `error_uri` of `provider`, with the status code, `code` and `message` of the [`oauth_login`] `error`
+#[must_use]
+pub fn error_redirect_url(provider: &OIDCProvider, error: &AuthError) -> String {
+    let json = error.to_json();
+
+    format!(
+        "{}?status_code={}&code={}&message={}",
+        provider.error_uri,
+        error.status_code(),
+        json["code"].as_str().unwrap_or_default(),
+        percent_encode(error.message())
+    )
+}
+
+/// handles the provider's redirect back to us
+///
+/// if the flow was started by [`oidc_link_url`] (in the browser `link_state`, the value of its
+/// [`LINK_STATE_COOKIE_NAME`] cookie, came from), the provider is linked to that user and `None`
+/// is returned, otherwise the user is logged in (and registered if they're new), and the session
+/// records the [`client_info`](`ClientInfo`) the callback request came from
 ///
 /// # Errors
-/// * 501 - This oauth provider is not supported
-/// * 400 - Invalid code
-/// * 500 - Internal server error (could be a lot of things)
+/// * 501 - [`AuthError::ProviderNotSupported`]
+/// * 401 - [`AuthError::OidcProviderError`]
+/// * 400 - [`AuthError::InvalidCsrfToken`]
+/// * 401 - [`AuthError::InvalidCode`]
+/// * 409 - [`AuthError::LinkedToOtherUser`]
+/// * 400 - [`AuthError::EmailInUse`]
+/// * 500 - [`AuthError::Internal`] (could be a lot of things)
 ///
-/// TODO: don't panic
 /// TODO: this function is too long, break it up into smaller parts
-#[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_lines, clippy::too_many_arguments)]
 pub async fn oauth_login(
     db: &Database,
     app_config: &AppConfig,
@@ -112,8 +441,12 @@ pub async fn oauth_login(
     query_param_code: Option<String>,
     query_param_error: Option<String>,
     query_param_state: Option<String>,
-) -> Result<(AccessToken, RefreshToken), (StatusCode, Message)> {
-    let db = &mut db.get_connection().unwrap();
+    link_state: Option<String>,
+    client_info: &ClientInfo,
+) -> Result<Option<(AccessToken, RefreshToken)>, AuthError> {
+    let db = &mut db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
 
     // 1. Make sure this provider is setup
     let Some(provider) = auth_config
@@ -122,7 +455,7 @@ pub async fn oauth_login(
         .into_iter()
         .find(|provider_config| provider_config.name.eq(&provider_name))
     else {
-        return Err((501, "This oauth provider is not supported".into()));
+        return Err(AuthError::ProviderNotSupported);
     };
 
     // 2. make sure we haven't encountered an error
@@ -171,26 +504,43 @@ pub async fn oauth_login(
                The requested scope is invalid, unknown, malformed, or
                exceeds the scope granted by the resource owner.
         */
-        return Err((401, query_param_error));
+        return Err(AuthError::OidcProviderError {
+            error: query_param_error,
+        });
     }
 
     // 3. make sure the CSRF/state variable is what we expect (i.e. exists in our db)
     // later on, we'll use the pkce verifier associated with this csrf token
     let Some(state) = query_param_state else {
-        return Err((400, "Invalid CSRF token".into()));
+        return Err(AuthError::InvalidCsrfToken);
     };
-    let oauth_request = UserOauth2Link::read_by_csrf_token(db, provider_name.clone(), state)
-        .expect("Invalid oauth2 redirection");
+    let Ok(oauth_request) = UserOauth2Link::read_by_csrf_token(db, provider_name.clone(), state)
+    else {
+        return Err(AuthError::InvalidCsrfToken);
+    };
+    if oauth_request.subject_id.is_some() {
+        // this flow has already been completed
+        return Err(AuthError::InvalidCsrfToken);
+    }
+    // otherwise a link url could be sent to someone else, linking their provider account to
+    // the account of whoever asked for the url
+    if oauth_request.user_id.is_some()
+        && link_state.as_deref() != Some(oauth_request.csrf_token.as_str())
+    {
+        return Err(AuthError::InvalidCsrfToken);
+    }
 
-    let pkce_verifier = PkceCodeVerifier::new(oauth_request.pkce_secret);
+    let pkce_verifier = PkceCodeVerifier::new(oauth_request.pkce_secret.clone());
 
     // 4. exchange code for a token!
     let Some(code) = query_param_code else {
-        return Err((400, "Invalid code".into()));
+        return Err(AuthError::InvalidCode);
     };
 
-    let Ok(client) = create_oidc_client(&provider, app_config.clone().app_url).await else {
-        return Err((500, "Internal server error".into()));
+    let Ok((client, userinfo_endpoint)) =
+        create_oidc_client(&provider, app_config.clone().app_url).await
+    else {
+        return Err(AuthError::Internal);
     };
 
     let Ok(token_response) = client
@@ -199,148 +549,201 @@ pub async fn oauth_login(
         .request_async(async_http_client)
         .await
     else {
-        return Err((400, "Invalid code".into()));
+        return Err(AuthError::InvalidCode);
     };
 
-    let Some(id_token) = token_response.id_token() else {
-        return Err((500, "Server did not return an ID token".into()));
-    };
+    // 5. find out who the user is
+    let identity = provider_identity(
+        &provider,
+        &client,
+        userinfo_endpoint,
+        &token_response,
+        oauth_request.nonce.clone(),
+    )
+    .await?;
 
-    let Ok(claims) = id_token.claims(
-        &client.id_token_verifier(),
-        &Nonce::new(oauth_request.nonce),
-    ) else {
-        return Err((500, "Invalid ID token claims".into()));
+    let Ok(existing_link) =
+        UserOauth2Link::read_by_subject(db, provider_name.clone(), identity.subject.clone())
+            .optional()
+    else {
+        return Err(AuthError::Internal);
     };
 
-    if let Some(expected_access_token_hash) = claims.access_token_hash() {
-        let Ok(signing_alg) = id_token.signing_alg() else {
-            return Err((500, "Invalid signing algorithm".into()));
+    // A logged in user is linking this provider to their account
+    if let Some(user_id) = oauth_request.user_id {
+        let result = match existing_link {
+            Some(link) if link.user_id != Some(user_id) => {
+                let _ = UserOauth2Link::delete(db, oauth_request.id);
+                return Err(AuthError::LinkedToOtherUser);
+            }
+            Some(link) => {
+                UserOauth2Link::update(db, link.id, &completed_link(&token_response, None, None))
+                    .and_then(|_| UserOauth2Link::delete(db, oauth_request.id))
+            }
+            None => UserOauth2Link::update(
+                db,
+                oauth_request.id,
+                &completed_link(&token_response, Some(identity.subject), None),
+            )
+            .map(|_| 1),
         };
 
-        let Ok(actual_access_token_hash) =
-            AccessTokenHash::from_token(token_response.access_token(), &signing_alg)
-        else {
-            return Err((500, "Invalid access token".into()));
+        return match result {
+            Ok(_) => Ok(None),
+            Err(_) => Err(AuthError::Internal),
         };
-
-        if actual_access_token_hash != *expected_access_token_hash {
-            return Err((401, "Invalid access token".into()));
-        }
     }
 
-    let subject = claims.subject().to_string();
-
     // OAuth login can happen in 1 of two ways
     // 1. Check if the subject is already present and linked to an existing user
     // 2. Link the subject to a new user (unless the email is already claimed by a local account)
 
-    let user = match UserOauth2Link::read_by_subject(db, subject).optional() {
-        Ok(Some(oauth2_link)) => {
-            // subject is already present, let's check if it's linked to a user
-            if oauth2_link.user_id.is_none() {
-                return Err((500, "Internal server error".into()));
-            }
-            let Ok(user) = User::read(db, oauth2_link.user_id.unwrap()) else {
-                return Err((500, "Internal server error".into()));
-            };
-
-            // TODO: put this in a transaction because we'll create a session and if that fails, we need to rollback!
+    let user = if let Some(oauth2_link) = existing_link {
+        // subject is already present, let's check if it's linked to a user
+        let Some(user_id) = oauth2_link.user_id else {
+            return Err(AuthError::Internal);
+        };
+        let Ok(user) = User::read(db, user_id) else {
+            return Err(AuthError::Internal);
+        };
 
-            UserOauth2Link::update(
-                db,
-                oauth_request.id,
-                &UpdateUserOauth2Link {
-                    provider: None,
-                    access_token: Some(Some(token_response.access_token().secret().to_string())),
-                    refresh_token: token_response
-                        .refresh_token()
-                        .map(|token| Some(token.secret().to_string())),
-                    csrf_token: None,
-                    nonce: None,
-                    pkce_secret: None,
-                    user_id: None,
-                    subject_id: None,
-                    created_at: None,
-                    updated_at: None,
-                },
-            )
-            .unwrap();
+        // TODO: put this in a transaction because we'll create a session and if that fails, we need to rollback!
 
-            user
+        if UserOauth2Link::update(
+            db,
+            oauth2_link.id,
+            &completed_link(&token_response, None, None),
+        )
+        .and_then(|_| UserOauth2Link::delete(db, oauth_request.id))
+        .is_err()
+        {
+            return Err(AuthError::Internal);
         }
-        Ok(None) => {
-            // subject is not already present, let's create a new user!
-            let email = match (claims.email(), claims.email_verified()) {
-                (Some(email), Some(true)) => email.to_string(),
-                (None, _) => return Err((500, "No email returned".into())),
-                (_, Some(false) | None) => return Err((500, "Email not verified".into())),
-            };
 
-            match User::find_by_email(db, email.clone()).optional() {
-                Ok(Some(_)) => {
-                    return Err((500, "Email already registered".into()));
-                }
-                Err(_) => {
-                    return Err((500, "Internal server error".into()));
-                }
-                Ok(None) => {}
-            }
+        user
+    } else {
+        // subject is not already present, let's create a new user!
+        let Some(email) = identity.email else {
+            return Err(AuthError::NoVerifiedEmail);
+        };
 
-            // create a random password
-            let salt = generate_salt();
-            let random_password = rand::thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(64)
-                .map(char::from)
-                .collect::<String>();
-            let hash =
-                argon2::hash_encoded(random_password.as_bytes(), &salt, &ARGON_CONFIG).unwrap();
-            let Ok(new_user) = User::create(
-                db,
-                &UserChangeset {
-                    email,
-                    activated: false, // do not activate the account because it should not be allowed to login locally
-                    hash_password: hash,
-                },
-            ) else {
-                return Err((500, "Internal server error".into()));
-            };
+        match User::find_by_email(db, email.clone()).optional() {
+            Ok(Some(_)) => {
+                return Err(AuthError::EmailInUse);
+            }
+            Err(_) => {
+                return Err(AuthError::Internal);
+            }
+            Ok(None) => {}
+        }
 
-            // TODO: put this in a a transaction because we've created a user at this point and if this
-            // next step doesn't work, we need to rollback!
-            UserOauth2Link::update(
-                db,
-                oauth_request.id,
-                &UpdateUserOauth2Link {
-                    provider: None,
-                    access_token: Some(Some(token_response.access_token().secret().to_string())),
-                    refresh_token: Some(
-                        token_response
-                            .refresh_token()
-                            .map(|token| token.secret().into()),
-                    ),
-                    csrf_token: Some(String::new()),
-                    nonce: Some(String::new()),
-                    pkce_secret: Some(String::new()),
-                    user_id: Some(Some(new_user.id)),
-                    subject_id: Some(Some(claims.subject().to_string())),
-                    created_at: None,
-                    updated_at: None,
-                },
-            )
-            .unwrap();
+        // create a random password
+        let random_password = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(64)
+            .map(char::from)
+            .collect::<String>();
+        let hash = hash_password(&random_password)?;
+        let Ok(new_user) = User::create(
+            db,
+            &UserChangeset {
+                email,
+                activated: false, // do not activate the account because it should not be allowed to login locally
+                hash_password: hash,
+            },
+        ) else {
+            return Err(AuthError::Internal);
+        };
 
-            new_user
+        // TODO: put this in a a transaction because we've created a user at this point and if this
+        // next step doesn't work, we need to rollback!
+        if UserOauth2Link::update(
+            db,
+            oauth_request.id,
+            &completed_link(&token_response, Some(identity.subject), Some(new_user.id)),
+        )
+        .is_err()
+        {
+            return Err(AuthError::Internal);
         }
-        Err(_) => return Err((500, "Internal server error".into())),
+
+        new_user
     };
 
+    // the provider is the source of truth for the roles it maps
+    if let Some(role_mapping) = &provider.role_mapping {
+        if sync_roles(db, user.id, role_mapping, &identity.claims).is_err() {
+            return Err(AuthError::CouldNotSyncRoles);
+        }
+    }
+
     create_user_session(
         db,
+        auth_config,
+        None,
+        client_info,
         Some(format!("Oauth2 - {}", &provider_name)),
         None,
         user.id,
     )
-    .map_err(|error| (error.0, error.1.to_string()))
+    .map(Some)
+}
+
+/// /oidc/links
+///
+/// returns the providers linked to the User associated with [`auth`](`Auth`)
+///
+/// # Errors
+/// - 500: [`AuthError::CouldNotFetchLinks`]
+pub fn get_oidc_links(db: &Database, auth: &Auth) -> Result<UserOauth2LinkResponse, AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let Ok(links) = UserOauth2Link::read_all_linked(&mut db, auth.user_id) else {
+        return Err(AuthError::CouldNotFetchLinks);
+    };
+
+    Ok(UserOauth2LinkResponse {
+        links: links.iter().map(UserOauth2LinkJson::from).collect(),
+    })
+}
+
+/// /oidc/links/{id}
+///
+/// unlinks the provider with the specified `item_id` from the User associated with [`auth`](`Auth`),
+/// unless it's the only way they can log in
+///
+/// # Errors
+/// - 404: [`AuthError::LinkNotFound`]
+/// - 400: [`AuthError::CannotUnlinkOnlyLogin`]
+/// - 500: [`AuthError::Internal`]
+pub fn unlink_oidc_provider(db: &Database, auth: &Auth, item_id: i32) -> Result<(), AuthError> {
+    let mut db = db
+        .get_connection()
+        .map_err(|_| AuthError::DatabaseUnavailable)?;
+
+    let Ok(links) = UserOauth2Link::read_all_linked(&mut db, auth.user_id) else {
+        return Err(AuthError::Internal);
+    };
+    if !links.iter().any(|link| link.id == item_id) {
+        return Err(AuthError::LinkNotFound);
+    }
+
+    // accounts created through a provider have a random password and aren't activated
+    let Ok(user) = User::read(&mut db, auth.user_id) else {
+        return Err(AuthError::Internal);
+    };
+    let Ok(passkeys) = UserPasskey::read_all(&mut db, auth.user_id) else {
+        return Err(AuthError::Internal);
+    };
+    if !user.activated && passkeys.is_empty() && links.len() == 1 {
+        return Err(AuthError::CannotUnlinkOnlyLogin);
+    }
+
+    if UserOauth2Link::delete(&mut db, item_id).is_err() {
+        return Err(AuthError::Internal);
+    }
+
+    Ok(())
 }
@@ -1,7 +1,42 @@
 pub mod controller;
 mod model;
 
-mod schema;
+pub(crate) mod schema;
+
+pub use model::UserOauth2Link;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of a linked entry from the databases `user_oauth2_links` table
+/// serialized into Json, without the tokens
+pub struct UserOauth2LinkJson {
+    pub id: i32,
+    pub provider: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&UserOauth2Link> for UserOauth2LinkJson {
+    fn from(link: &UserOauth2Link) -> Self {
+        Self {
+            id: link.id,
+            provider: link.provider.clone(),
+            created_at: link.created_at,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// Rust struct representation of the
+/// backends JSON response to a GET request at the /oidc/links endpoint
+pub struct UserOauth2LinkResponse {
+    pub links: Vec<UserOauth2LinkJson>,
+}
 
 #[derive(Clone)]
 pub struct OIDCProvider {
@@ -15,6 +50,94 @@ pub struct OIDCProvider {
     pub success_uri: String,
     // URI to redirect to when OAuth fails
     pub error_uri: String,
+    /// extra query parameters for the authorization url, like Google's `access_type=offline`
+    pub extra_params: Vec<(String, String)>,
+    /// endpoints of plain OAuth2 providers (like GitHub) which don't support OIDC discovery or ID tokens,
+    /// `None` for OIDC providers
+    pub oauth2_endpoints: Option<OAuth2Endpoints>,
+    /// where to find the user's id and email when they aren't in the ID token
+    pub claims: ClaimMapping,
+    /// roles to sync from the ID token on every login, see [`OIDCProvider::with_role_mapping`]
+    pub role_mapping: Option<RoleMapping>,
+}
+
+/// the endpoints of a plain OAuth2 provider, see [`OIDCProvider::oauth2_endpoints`]
+#[derive(Clone)]
+pub struct OAuth2Endpoints {
+    pub auth_url: String,
+    pub token_url: String,
+    /// returns a json object describing the user, read according to [`OIDCProvider::claims`]
+    pub userinfo_url: String,
+    /// returns a json list of `{ "email", "verified", "primary" }` objects (like GitHub's `/user/emails`),
+    /// fetched when the userinfo response doesn't contain an email
+    pub emails_url: Option<String>,
+}
+
+/// the names of the claims (or userinfo fields) which identify a user
+///
+/// OIDC providers always identify users by the ID token's `sub` claim, the userinfo endpoint is only
+/// queried (and `subject` only used) when the ID token doesn't contain an email
+#[derive(Clone)]
+pub struct ClaimMapping {
+    /// the user's unique id at the provider
+    pub subject: String,
+    pub email: String,
+    /// whether the email was verified; `None` if the provider only returns verified emails
+    pub email_verified: Option<String>,
+}
+
+impl Default for ClaimMapping {
+    fn default() -> Self {
+        Self {
+            subject: "sub".to_string(),
+            email: "email".to_string(),
+            email_verified: Some("email_verified".to_string()),
+        }
+    }
+}
+
+/// maps the values of an ID token claim (like `groups` or `roles`) to roles
+///
+/// for plain OAuth2 providers, the claim is read from the userinfo response instead
+#[derive(Clone)]
+pub struct RoleMapping {
+    /// the claim's name, nested claims are separated by dots (like Keycloak's `realm_access.roles`)
+    pub claim: String,
+    /// (claim value, role) pairs, several values may map to the same role
+    pub roles: Vec<(String, String)>,
+}
+
+impl RoleMapping {
+    /// the roles a user whose ID token contains `claims` should have
+    #[must_use]
+    pub fn roles_for(&self, claims: &serde_json::Value) -> HashSet<String> {
+        let value = claims.get(&self.claim).or_else(|| {
+            self.claim
+                .split('.')
+                .try_fold(claims, |value, key| value.get(key))
+        });
+
+        let values = match value {
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .collect(),
+            Some(serde_json::Value::String(value)) => vec![value.as_str()],
+            _ => vec![],
+        };
+
+        self.roles
+            .iter()
+            .filter(|(claim_value, _)| values.contains(&claim_value.as_str()))
+            .map(|(_, role)| role.clone())
+            .collect()
+    }
+
+    /// every role this mapping assigns, roles which aren't in the mapping are never unassigned
+    #[must_use]
+    pub fn managed_roles(&self) -> HashSet<String> {
+        self.roles.iter().map(|(_, role)| role.clone()).collect()
+    }
 }
 
 type ClientId = String;
@@ -36,8 +159,198 @@ impl OIDCProvider {
             client_secret,
             success_uri,
             error_uri,
+            // Google only returns a refresh token when asked for offline access
+            extra_params: vec![("access_type".to_string(), "offline".to_string())],
+            oauth2_endpoints: None,
+            claims: ClaimMapping::default(),
+            role_mapping: None,
+        };
+
+    /// GitHub only supports plain OAuth2, the user is read from its REST API
+    pub const GITHUB: ProviderFactory =
+        |client_id: ClientId,
+         client_secret: ClientSecret,
+         success_uri: SuccessURI,
+         error_uri: ErrorURI| Self {
+            name: "github".to_string(),
+            scope: vec!["read:user".to_string(), "user:email".to_string()],
+            issuer_url: "https://github.com".to_string(),
+            client_id,
+            client_secret,
+            success_uri,
+            error_uri,
+            extra_params: vec![],
+            oauth2_endpoints: Some(OAuth2Endpoints {
+                auth_url: "https://github.com/login/oauth/authorize".to_string(),
+                token_url: "https://github.com/login/oauth/access_token".to_string(),
+                userinfo_url: "https://api.github.com/user".to_string(),
+                emails_url: Some("https://api.github.com/user/emails".to_string()),
+            }),
+            claims: ClaimMapping {
+                subject: "id".to_string(),
+                email: "email".to_string(),
+                // the public email of a profile isn't necessarily verified, only trust the emails endpoint
+                email_verified: Some("verified".to_string()),
+            },
+            role_mapping: None,
+        };
+
+    /// gitlab.com, see [`OIDCProvider::gitlab_self_managed`] for other instances
+    pub const GITLAB: ProviderFactory =
+        |client_id: ClientId,
+         client_secret: ClientSecret,
+         success_uri: SuccessURI,
+         error_uri: ErrorURI| {
+            Self::gitlab_self_managed(
+                "https://gitlab.com",
+                client_id,
+                client_secret,
+                success_uri,
+                error_uri,
+            )
         };
 
+    /// Sign in with Apple
+    ///
+    /// Apple's client secret is a short-lived JWT signed with your private key,
+    /// it has to be regenerated before it expires (after at most 6 months)
+    pub const APPLE: ProviderFactory =
+        |client_id: ClientId,
+         client_secret: ClientSecret,
+         success_uri: SuccessURI,
+         error_uri: ErrorURI| Self {
+            name: "apple".to_string(),
+            scope: vec!["email".to_string()],
+            issuer_url: "https://appleid.apple.com".to_string(),
+            client_id,
+            client_secret,
+            success_uri,
+            error_uri,
+            // Apple POSTs the result to the redirect uri when scopes are requested
+            extra_params: vec![("response_mode".to_string(), "form_post".to_string())],
+            oauth2_endpoints: None,
+            claims: ClaimMapping::default(),
+            role_mapping: None,
+        };
+
+    /// Microsoft Entra ID (Azure AD) for the directory `tenant_id`
+    ///
+    /// Entra ID's `email` claim can be set to any address by the tenant's users and administrators,
+    /// so emails are only trusted when the `xms_edov` (email domain owner verified) optional claim is
+    /// true; add it to the ID token in the app registration's token configuration, otherwise new
+    /// users can't sign up through this provider
+    #[must_use]
+    pub fn microsoft(
+        tenant_id: &str,
+        client_id: ClientId,
+        client_secret: ClientSecret,
+        success_uri: SuccessURI,
+        error_uri: ErrorURI,
+    ) -> Self {
+        Self {
+            name: "microsoft".to_string(),
+            scope: vec!["email".to_string(), "profile".to_string()],
+            issuer_url: format!("https://login.microsoftonline.com/{tenant_id}/v2.0"),
+            client_id,
+            client_secret,
+            success_uri,
+            error_uri,
+            extra_params: vec![],
+            oauth2_endpoints: None,
+            claims: ClaimMapping {
+                email_verified: Some("xms_edov".to_string()),
+                ..ClaimMapping::default()
+            },
+            role_mapping: None,
+        }
+    }
+
+    /// a self-managed GitLab instance at `instance_url` (like `https://gitlab.example.com`)
+    #[must_use]
+    pub fn gitlab_self_managed(
+        instance_url: &str,
+        client_id: ClientId,
+        client_secret: ClientSecret,
+        success_uri: SuccessURI,
+        error_uri: ErrorURI,
+    ) -> Self {
+        Self {
+            name: "gitlab".to_string(),
+            scope: vec!["email".to_string()],
+            issuer_url: instance_url.trim_end_matches('/').to_string(),
+            client_id,
+            client_secret,
+            success_uri,
+            error_uri,
+            extra_params: vec![],
+            oauth2_endpoints: None,
+            claims: ClaimMapping::default(),
+            role_mapping: None,
+        }
+    }
+
+    /// a Keycloak realm, `realm_url` looks like `https://keycloak.example.com/realms/my-realm`
+    #[must_use]
+    pub fn keycloak(
+        realm_url: &str,
+        client_id: ClientId,
+        client_secret: ClientSecret,
+        success_uri: SuccessURI,
+        error_uri: ErrorURI,
+    ) -> Self {
+        Self {
+            name: "keycloak".to_string(),
+            scope: vec!["email".to_string()],
+            issuer_url: realm_url.trim_end_matches('/').to_string(),
+            client_id,
+            client_secret,
+            success_uri,
+            error_uri,
+            extra_params: vec![],
+            oauth2_endpoints: None,
+            claims: ClaimMapping::default(),
+            role_mapping: None,
+        }
+    }
+
+    /// adds a query parameter to the authorization url, see [`OIDCProvider::extra_params`]
+    #[must_use]
+    pub fn with_extra_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_params.push((name.into(), value.into()));
+        self
+    }
+
+    /// makes the provider the source of truth for some roles: on every login, users are assigned
+    /// the roles their `claim`'s values map to, and unassigned the other roles in `roles`
+    ///
+    /// ```rust,ignore
+    /// OIDCProvider::keycloak(realm_url, client_id, client_secret, success_uri, error_uri)
+    ///     .with_role_mapping("realm_access.roles", [("app-admin", "admin"), ("app-user", "user")])
+    /// ```
+    #[must_use]
+    pub fn with_role_mapping<V: Into<String>, R: Into<String>>(
+        mut self,
+        claim: impl Into<String>,
+        roles: impl IntoIterator<Item = (V, R)>,
+    ) -> Self {
+        self.role_mapping = Some(RoleMapping {
+            claim: claim.into(),
+            roles: roles
+                .into_iter()
+                .map(|(value, role)| (value.into(), role.into()))
+                .collect(),
+        });
+        self
+    }
+
+    /// changes the name the provider is configured under (and its urls), which is useful
+    /// when a preset is used more than once, like for two Keycloak realms
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
     #[must_use]
     pub fn redirect_uri(&self, api_url: impl AsRef<str>) -> String {
         format!(
@@ -47,3 +360,38 @@ impl OIDCProvider {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_maps_claim_values_to_roles() {
+        let mapping = RoleMapping {
+            claim: "realm_access.roles".to_string(),
+            roles: vec![
+                ("app-admin".to_string(), "admin".to_string()),
+                ("app-owner".to_string(), "admin".to_string()),
+                ("app-user".to_string(), "user".to_string()),
+            ],
+        };
+
+        let claims = json!({ "realm_access": { "roles": ["app-owner", "offline_access"] } });
+        assert_eq!(
+            mapping.roles_for(&claims),
+            HashSet::from(["admin".to_string()])
+        );
+
+        let flat = json!({ "realm_access.roles": "app-user" });
+        assert_eq!(
+            mapping.roles_for(&flat),
+            HashSet::from(["user".to_string()])
+        );
+
+        assert!(mapping
+            .roles_for(&json!({ "groups": ["app-admin"] }))
+            .is_empty());
+        assert_eq!(mapping.managed_roles().len(), 2);
+    }
+}
@@ -103,14 +103,33 @@ impl UserOauth2Link {
             .first::<Self>(db)
     }
 
-    pub fn read_by_subject(db: &mut Connection, param_subject_id: String) -> QueryResult<Self> {
-        use crate::auth::oidc::schema::user_oauth2_links::dsl::{subject_id, user_oauth2_links};
+    pub fn read_by_subject(
+        db: &mut Connection,
+        param_provider: String,
+        param_subject_id: String,
+    ) -> QueryResult<Self> {
+        use crate::auth::oidc::schema::user_oauth2_links::dsl::{
+            provider, subject_id, user_oauth2_links,
+        };
 
         user_oauth2_links
+            .filter(provider.eq(param_provider))
             .filter(subject_id.eq(param_subject_id))
             .first::<Self>(db)
     }
 
+    pub fn read_all_linked(db: &mut Connection, param_user_id: i32) -> QueryResult<Vec<Self>> {
+        use crate::auth::oidc::schema::user_oauth2_links::dsl::{
+            id, subject_id, user_id, user_oauth2_links,
+        };
+
+        user_oauth2_links
+            .filter(user_id.eq(param_user_id))
+            .filter(subject_id.is_not_null())
+            .order(id.asc())
+            .load::<Self>(db)
+    }
+
     pub fn update(
         db: &mut Connection,
         param_id: i32,
@@ -0,0 +1,577 @@
+//! passkeys ([WebAuthn](https://www.w3.org/TR/webauthn-2/)), a phishing-resistant alternative to passwords
+//!
+//! only what's needed to register and log in with passkeys is implemented:
+//! attestation statements are not verified (we ask for "none" attestation),
+//! and ES256 and EdDSA credentials are supported
+use ciborium::value::Value;
+use data_encoding::BASE64URL_NOPAD;
+use rand::Rng;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+
+/// COSE algorithm identifier for ECDSA w/ SHA-256 on P-256
+pub const ES256: i64 = -7;
+
+/// COSE algorithm identifier for EdDSA (Ed25519)
+pub const EDDSA: i64 = -8;
+
+/// how long the browser should wait for the user, in milliseconds
+pub const TIMEOUT_MS: u32 = 300_000;
+
+/// the authenticator data flag set when the user was present
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+/// the authenticator data flag set when the authenticator verified the user (PIN, biometrics, ...)
+const FLAG_USER_VERIFIED: u8 = 0x04;
+
+/// the authenticator data flag set when attested credential data is included
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// why a passkey registration or assertion was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasskeyError {
+    /// a field isn't valid base64url, JSON, or CBOR, or is truncated
+    InvalidEncoding,
+    /// the client data is for a different kind of ceremony
+    WrongType,
+    /// the client data doesn't contain the challenge we issued
+    ChallengeMismatch,
+    /// the ceremony happened on another origin (phishing!)
+    OriginMismatch,
+    /// the authenticator data is scoped to another relying party
+    RelyingPartyMismatch,
+    /// the authenticator didn't confirm the user was present
+    UserNotPresent,
+    /// the authenticator didn't verify the user, so the passkey alone would be a single factor
+    UserNotVerified,
+    /// the credential uses a key type or algorithm we don't support
+    UnsupportedAlgorithm,
+    /// the assertion signature doesn't match the credential's public key
+    InvalidSignature,
+    /// the signature counter didn't increase, the credential may have been cloned
+    SignCountRegressed,
+}
+
+impl std::fmt::Display for PasskeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::InvalidEncoding => "malformed passkey response",
+            Self::WrongType => "wrong client data type",
+            Self::ChallengeMismatch => "challenge mismatch",
+            Self::OriginMismatch => "origin mismatch",
+            Self::RelyingPartyMismatch => "relying party mismatch",
+            Self::UserNotPresent => "user not present",
+            Self::UserNotVerified => "user not verified",
+            Self::UnsupportedAlgorithm => "unsupported credential algorithm",
+            Self::InvalidSignature => "invalid signature",
+            Self::SignCountRegressed => "signature counter did not increase",
+        };
+        write!(f, "{message}")
+    }
+}
+
+impl std::error::Error for PasskeyError {}
+
+/// the website passkeys are registered for
+///
+/// `id` is the domain credentials are scoped to, and `origin` is where the
+/// browser must be when it uses them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelyingParty {
+    pub id: String,
+    pub origin: String,
+}
+
+impl RelyingParty {
+    /// the relying party for an app served at `app_url`, for example
+    /// `https://example.com/` has the id `example.com` and the origin `https://example.com`
+    #[must_use]
+    pub fn from_app_url(app_url: &str) -> Option<Self> {
+        let (scheme, rest) = app_url.split_once("://")?;
+        let authority = rest.split(['/', '?', '#']).next()?;
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let id = match host.rsplit_once(':') {
+            Some((hostname, port)) if port.chars().all(|c| c.is_ascii_digit()) => hostname,
+            _ => host,
+        };
+
+        if id.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            id: id.to_lowercase(),
+            origin: format!("{scheme}://{host}").to_lowercase(),
+        })
+    }
+}
+
+/// a credential which passed [`verify_registration`], ready to be stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredCredential {
+    /// base64url encoded credential id
+    pub credential_id: String,
+    /// base64url encoded COSE public key
+    pub public_key: String,
+    pub sign_count: u32,
+}
+
+/// generates a random 256-bit challenge, base64url encoded (without padding)
+#[must_use]
+pub fn generate_challenge() -> String {
+    let challenge: [u8; 32] = rand::thread_rng().gen();
+    BASE64URL_NOPAD.encode(&challenge)
+}
+
+/// verifies the response of `navigator.credentials.create()` for the `challenge` we issued
+///
+/// `client_data_json` and `attestation_object` are base64url encoded, as sent by the browser
+///
+/// # Errors
+/// * see [`PasskeyError`]
+pub fn verify_registration(
+    rp: &RelyingParty,
+    challenge: &str,
+    client_data_json: &str,
+    attestation_object: &str,
+) -> Result<RegisteredCredential, PasskeyError> {
+    check_client_data(rp, challenge, client_data_json, "webauthn.create")?;
+
+    let attestation_object = decode(attestation_object)?;
+    let attestation: Value = ciborium::de::from_reader(attestation_object.as_slice())
+        .map_err(|_| PasskeyError::InvalidEncoding)?;
+    let auth_data = map_get(&attestation, &Value::Text("authData".to_string()))
+        .and_then(Value::as_bytes)
+        .ok_or(PasskeyError::InvalidEncoding)?;
+
+    let auth_data = AuthenticatorData::parse(auth_data)?;
+    auth_data.check(rp)?;
+
+    let (credential_id, public_key) = auth_data
+        .attested_credential
+        .ok_or(PasskeyError::InvalidEncoding)?;
+
+    // make sure we'll be able to verify this credential's signatures later on
+    PublicKey::parse(&public_key)?;
+
+    Ok(RegisteredCredential {
+        credential_id: BASE64URL_NOPAD.encode(&credential_id),
+        public_key: BASE64URL_NOPAD.encode(&public_key),
+        sign_count: auth_data.sign_count,
+    })
+}
+
+/// verifies the response of `navigator.credentials.get()` for the `challenge` we issued,
+/// using the stored (base64url encoded) `public_key` and `sign_count` of the credential
+///
+/// `client_data_json`, `authenticator_data` and `signature` are base64url encoded, as sent by the browser
+///
+/// returns the new signature counter, which should be stored
+///
+/// # Errors
+/// * see [`PasskeyError`]
+pub fn verify_assertion(
+    rp: &RelyingParty,
+    challenge: &str,
+    public_key: &str,
+    sign_count: u32,
+    client_data_json: &str,
+    authenticator_data: &str,
+    signature: &str,
+) -> Result<u32, PasskeyError> {
+    check_client_data(rp, challenge, client_data_json, "webauthn.get")?;
+
+    let raw_authenticator_data = decode(authenticator_data)?;
+    let auth_data = AuthenticatorData::parse(&raw_authenticator_data)?;
+    auth_data.check(rp)?;
+
+    // the signature covers the authenticator data and the hash of the client data
+    let mut message = raw_authenticator_data;
+    message.extend_from_slice(&Sha256::digest(decode(client_data_json)?));
+
+    PublicKey::parse(&decode(public_key)?)?.verify(&message, &decode(signature)?)?;
+
+    // authenticators which don't implement a counter always report 0
+    if (auth_data.sign_count != 0 || sign_count != 0) && auth_data.sign_count <= sign_count {
+        return Err(PasskeyError::SignCountRegressed);
+    }
+
+    Ok(auth_data.sign_count)
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, PasskeyError> {
+    // some clients pad their base64url
+    BASE64URL_NOPAD
+        .decode(value.trim_end_matches('=').as_bytes())
+        .map_err(|_| PasskeyError::InvalidEncoding)
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony: String,
+    challenge: String,
+    origin: String,
+}
+
+fn check_client_data(
+    rp: &RelyingParty,
+    challenge: &str,
+    client_data_json: &str,
+    ceremony: &str,
+) -> Result<(), PasskeyError> {
+    let client_data: ClientData = serde_json::from_slice(&decode(client_data_json)?)
+        .map_err(|_| PasskeyError::InvalidEncoding)?;
+
+    if client_data.ceremony != ceremony {
+        return Err(PasskeyError::WrongType);
+    }
+    if client_data.challenge.trim_end_matches('=') != challenge {
+        return Err(PasskeyError::ChallengeMismatch);
+    }
+    if !client_data.origin.eq_ignore_ascii_case(&rp.origin) {
+        return Err(PasskeyError::OriginMismatch);
+    }
+
+    Ok(())
+}
+
+struct AuthenticatorData {
+    rp_id_hash: Vec<u8>,
+    flags: u8,
+    sign_count: u32,
+    /// (credential id, COSE public key), only present when registering
+    attested_credential: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl AuthenticatorData {
+    fn parse(data: &[u8]) -> Result<Self, PasskeyError> {
+        if data.len() < 37 {
+            return Err(PasskeyError::InvalidEncoding);
+        }
+
+        let flags = data[32];
+        let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+        let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL_DATA == 0 {
+            None
+        } else {
+            // aaguid (16 bytes), credential id length (2 bytes), credential id, COSE key
+            let rest = data.get(37 + 16..).ok_or(PasskeyError::InvalidEncoding)?;
+            let id_length = usize::from(u16::from_be_bytes([
+                *rest.first().ok_or(PasskeyError::InvalidEncoding)?,
+                *rest.get(1).ok_or(PasskeyError::InvalidEncoding)?,
+            ]));
+            let credential_id = rest
+                .get(2..2 + id_length)
+                .ok_or(PasskeyError::InvalidEncoding)?;
+            let key_and_extensions = &rest[2 + id_length..];
+
+            // the key is followed by extensions, if any; only keep the bytes of the key
+            let mut remaining = key_and_extensions;
+            let _key: Value = ciborium::de::from_reader(&mut remaining)
+                .map_err(|_| PasskeyError::InvalidEncoding)?;
+            let key_length = key_and_extensions.len() - remaining.len();
+
+            Some((
+                credential_id.to_vec(),
+                key_and_extensions[..key_length].to_vec(),
+            ))
+        };
+
+        Ok(Self {
+            rp_id_hash: data[..32].to_vec(),
+            flags,
+            sign_count,
+            attested_credential,
+        })
+    }
+
+    fn check(&self, rp: &RelyingParty) -> Result<(), PasskeyError> {
+        if self.rp_id_hash != Sha256::digest(rp.id.as_bytes()).as_slice() {
+            return Err(PasskeyError::RelyingPartyMismatch);
+        }
+        if self.flags & FLAG_USER_PRESENT == 0 {
+            return Err(PasskeyError::UserNotPresent);
+        }
+        if self.flags & FLAG_USER_VERIFIED == 0 {
+            return Err(PasskeyError::UserNotVerified);
+        }
+
+        Ok(())
+    }
+}
+
+enum PublicKey {
+    Es256(p256::ecdsa::VerifyingKey),
+    EdDsa(ed25519_dalek::VerifyingKey),
+}
+
+impl PublicKey {
+    /// parses a COSE key ([RFC 8152 section 13](https://datatracker.ietf.org/doc/html/rfc8152#section-13))
+    fn parse(cose_key: &[u8]) -> Result<Self, PasskeyError> {
+        let key: Value =
+            ciborium::de::from_reader(cose_key).map_err(|_| PasskeyError::InvalidEncoding)?;
+
+        let int = |label: i64| {
+            map_get(&key, &Value::Integer(label.into()))
+                .and_then(Value::as_integer)
+                .and_then(|value| i64::try_from(value).ok())
+        };
+        let bytes =
+            |label: i64| map_get(&key, &Value::Integer(label.into())).and_then(Value::as_bytes);
+
+        // labels: 1 = kty, 3 = alg, -1 = crv, -2 = x, -3 = y
+        match (int(1), int(3), int(-1)) {
+            // EC2 key on P-256
+            (Some(2), Some(ES256), Some(1)) => {
+                let (Some(x), Some(y)) = (bytes(-2), bytes(-3)) else {
+                    return Err(PasskeyError::InvalidEncoding);
+                };
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(PasskeyError::InvalidEncoding);
+                }
+                let point = p256::EncodedPoint::from_affine_coordinates(
+                    p256::FieldBytes::from_slice(x),
+                    p256::FieldBytes::from_slice(y),
+                    false,
+                );
+                p256::ecdsa::VerifyingKey::from_encoded_point(&point)
+                    .map(Self::Es256)
+                    .map_err(|_| PasskeyError::InvalidEncoding)
+            }
+            // OKP key on Ed25519
+            (Some(1), Some(EDDSA), Some(6)) => {
+                let x = bytes(-2)
+                    .and_then(|x| <[u8; 32]>::try_from(x.as_slice()).ok())
+                    .ok_or(PasskeyError::InvalidEncoding)?;
+                ed25519_dalek::VerifyingKey::from_bytes(&x)
+                    .map(Self::EdDsa)
+                    .map_err(|_| PasskeyError::InvalidEncoding)
+            }
+            _ => Err(PasskeyError::UnsupportedAlgorithm),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), PasskeyError> {
+        use p256::ecdsa::signature::Verifier;
+
+        let verified = match self {
+            Self::Es256(key) => p256::ecdsa::Signature::from_der(signature)
+                .map(|signature| key.verify(message, &signature).is_ok()),
+            Self::EdDsa(key) => ed25519_dalek::Signature::from_slice(signature)
+                .map(|signature| key.verify(message, &signature).is_ok()),
+        };
+
+        match verified {
+            Ok(true) => Ok(()),
+            _ => Err(PasskeyError::InvalidSignature),
+        }
+    }
+}
+
+fn map_get<'a>(map: &'a Value, key: &Value) -> Option<&'a Value> {
+    map.as_map()?
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::{signature::Signer, SigningKey};
+
+    fn rp() -> RelyingParty {
+        RelyingParty::from_app_url("http://localhost:3000").unwrap()
+    }
+
+    fn encode(bytes: &[u8]) -> String {
+        BASE64URL_NOPAD.encode(bytes)
+    }
+
+    fn client_data(ceremony: &str, challenge: &str, origin: &str) -> String {
+        encode(
+            serde_json::json!({ "type": ceremony, "challenge": challenge, "origin": origin })
+                .to_string()
+                .as_bytes(),
+        )
+    }
+
+    fn cbor(value: &Value) -> Vec<u8> {
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn authenticator_data(rp_id: &str, flags: u8, sign_count: u32, attested: &[u8]) -> Vec<u8> {
+        let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        data.extend_from_slice(attested);
+        data
+    }
+
+    /// a software authenticator with a fixed P-256 key
+    fn register(signing_key: &SigningKey, challenge: &str) -> (String, String) {
+        let point = signing_key.verifying_key().to_encoded_point(false);
+        let cose_key = cbor(&Value::Map(vec![
+            (Value::Integer(1.into()), Value::Integer(2.into())),
+            (Value::Integer(3.into()), Value::Integer(ES256.into())),
+            (Value::Integer((-1).into()), Value::Integer(1.into())),
+            (
+                Value::Integer((-2).into()),
+                Value::Bytes(point.x().unwrap().to_vec()),
+            ),
+            (
+                Value::Integer((-3).into()),
+                Value::Bytes(point.y().unwrap().to_vec()),
+            ),
+        ]));
+
+        let credential_id = [7u8; 16];
+        let mut attested = vec![0u8; 16];
+        attested.extend_from_slice(&u16::try_from(credential_id.len()).unwrap().to_be_bytes());
+        attested.extend_from_slice(&credential_id);
+        attested.extend_from_slice(&cose_key);
+
+        let attestation_object = cbor(&Value::Map(vec![
+            (
+                Value::Text("fmt".to_string()),
+                Value::Text("none".to_string()),
+            ),
+            (Value::Text("attStmt".to_string()), Value::Map(vec![])),
+            (
+                Value::Text("authData".to_string()),
+                Value::Bytes(authenticator_data("localhost", 0x45, 0, &attested)),
+            ),
+        ]));
+
+        (
+            client_data("webauthn.create", challenge, "http://localhost:3000"),
+            encode(&attestation_object),
+        )
+    }
+
+    fn assert(
+        signing_key: &SigningKey,
+        challenge: &str,
+        origin: &str,
+        flags: u8,
+        sign_count: u32,
+    ) -> (String, String, String) {
+        let client_data = client_data("webauthn.get", challenge, origin);
+        let auth_data = authenticator_data("localhost", flags, sign_count, &[]);
+
+        let mut message = auth_data.clone();
+        message.extend_from_slice(&Sha256::digest(decode(&client_data).unwrap()));
+        let signature: p256::ecdsa::Signature = signing_key.sign(&message);
+
+        (
+            client_data,
+            encode(&auth_data),
+            encode(signature.to_der().as_bytes()),
+        )
+    }
+
+    #[test]
+    fn test_relying_party_from_app_url() {
+        assert_eq!(
+            RelyingParty::from_app_url("https://Example.com/app/"),
+            Some(RelyingParty {
+                id: "example.com".to_string(),
+                origin: "https://example.com".to_string(),
+            })
+        );
+        assert_eq!(rp().id, "localhost");
+        assert_eq!(rp().origin, "http://localhost:3000");
+        assert_eq!(RelyingParty::from_app_url("localhost:3000"), None);
+    }
+
+    #[test]
+    fn test_registers_and_asserts() {
+        let signing_key = SigningKey::from_bytes(&[42u8; 32].into()).unwrap();
+
+        let challenge = generate_challenge();
+        let (client_data, attestation_object) = register(&signing_key, &challenge);
+        let credential =
+            verify_registration(&rp(), &challenge, &client_data, &attestation_object).unwrap();
+        assert_eq!(credential.credential_id, encode(&[7u8; 16]));
+        assert_eq!(credential.sign_count, 0);
+
+        let challenge = generate_challenge();
+        let (client_data, auth_data, signature) =
+            assert(&signing_key, &challenge, "http://localhost:3000", 0x05, 1);
+        let verify = |challenge: &str, stored_count: u32, signature: &str| {
+            verify_assertion(
+                &rp(),
+                challenge,
+                &credential.public_key,
+                stored_count,
+                &client_data,
+                &auth_data,
+                signature,
+            )
+        };
+
+        assert_eq!(verify(&challenge, 0, &signature), Ok(1));
+        assert_eq!(
+            verify(&generate_challenge(), 0, &signature),
+            Err(PasskeyError::ChallengeMismatch)
+        );
+        assert_eq!(
+            verify(&challenge, 1, &signature),
+            Err(PasskeyError::SignCountRegressed)
+        );
+
+        let (_, _, other_signature) =
+            assert(&signing_key, &challenge, "http://localhost:3000", 0x05, 2);
+        assert_eq!(
+            verify(&challenge, 0, &other_signature),
+            Err(PasskeyError::InvalidSignature)
+        );
+
+        let (client_data, auth_data, signature) =
+            assert(&signing_key, &challenge, "http://localhost:3000", 0x01, 1);
+        assert_eq!(
+            verify_assertion(
+                &rp(),
+                &challenge,
+                &credential.public_key,
+                0,
+                &client_data,
+                &auth_data,
+                &signature,
+            ),
+            Err(PasskeyError::UserNotVerified)
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_origins() {
+        let signing_key = SigningKey::from_bytes(&[42u8; 32].into()).unwrap();
+
+        let challenge = generate_challenge();
+        let (client_data, attestation_object) = register(&signing_key, &challenge);
+        let credential =
+            verify_registration(&rp(), &challenge, &client_data, &attestation_object).unwrap();
+
+        let (client_data, auth_data, signature) =
+            assert(&signing_key, &challenge, "https://evil.example", 0x05, 1);
+        assert_eq!(
+            verify_assertion(
+                &rp(),
+                &challenge,
+                &credential.public_key,
+                0,
+                &client_data,
+                &auth_data,
+                &signature,
+            ),
+            Err(PasskeyError::OriginMismatch)
+        );
+    }
+}
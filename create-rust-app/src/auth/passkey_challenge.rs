@@ -0,0 +1,86 @@
+use super::schema::passkey_challenges;
+use crate::diesel::{
+    insert_into, AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable, RunQueryDsl,
+};
+
+use super::{utc_now, Utc, ID};
+use crate::database::Connection;
+use diesel::{OptionalExtension, QueryResult};
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name=passkey_challenges)]
+/// Rust struct modeling an entry in the `passkey_challenges` table
+///
+/// every passkey challenge which was issued and not answered yet, so each one can only be used once
+pub struct PasskeyChallenge {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    /// base64url encoded challenge
+    pub challenge: String,
+    /// the user registering a passkey, `None` for login challenges
+    pub user_id: Option<ID>,
+    pub expires_at: Utc,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=passkey_challenges)]
+/// Rust struct modeling mutable data in an entry in the `passkey_challenges` table
+pub struct PasskeyChallengeChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub challenge: String,
+    pub user_id: Option<ID>,
+    pub expires_at: Utc,
+}
+
+impl PasskeyChallenge {
+    /// Create an entry in [`db`](`Connection`)'s `passkey_challenges` table using the data in [`item`](`PasskeyChallengeChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &PasskeyChallengeChangeset) -> QueryResult<Self> {
+        use super::schema::passkey_challenges::dsl::passkey_challenges;
+
+        insert_into(passkey_challenges)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `passkey_challenges` table whose primary key is
+    /// `item_challenge`, returning it if it hadn't expired yet
+    ///
+    /// the delete makes sure two requests can't both answer the same challenge
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn consume(db: &mut Connection, item_challenge: &str) -> QueryResult<Option<Self>> {
+        use super::schema::passkey_challenges::dsl::{challenge, passkey_challenges};
+
+        let item = diesel::delete(passkey_challenges.filter(challenge.eq(item_challenge)))
+            .get_result::<Self>(db)
+            .optional()?;
+
+        Ok(item.filter(|item| item.expires_at > utc_now()))
+    }
+
+    /// Delete every entry in [`db`](`Connection`)'s `passkey_challenges` table which expired before `before`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_expired(db: &mut Connection, before: Utc) -> QueryResult<usize> {
+        use super::schema::passkey_challenges::dsl::{expires_at, passkey_challenges};
+
+        diesel::delete(passkey_challenges.filter(expires_at.lt(before))).execute(db)
+    }
+}
@@ -0,0 +1,239 @@
+use data_encoding::HEXUPPER;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::path::PathBuf;
+
+/// number of hex characters of a password's SHA-1 hash used to pick its [`BreachedPasswords`] file
+const PREFIX_LENGTH: usize = 5;
+
+#[derive(Debug, Clone)]
+/// rules new passwords have to follow when registering, changing or resetting a password
+///
+/// set through [`AuthConfig::password_policy`](`crate::auth::AuthConfig`)
+pub struct PasswordPolicy {
+    /// minimum number of characters
+    pub min_length: usize,
+    /// require at least one lowercase letter
+    pub require_lowercase: bool,
+    /// require at least one uppercase letter
+    pub require_uppercase: bool,
+    /// require at least one digit
+    pub require_digit: bool,
+    /// require at least one character which isn't a letter or a digit
+    pub require_symbol: bool,
+    /// reject passwords containing the user's email, or the part of it before the `@`
+    pub reject_email: bool,
+    /// reject passwords found in a data breach, see [`BreachedPasswords`]
+    pub breached_passwords: Option<BreachedPasswords>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            reject_email: true,
+            breached_passwords: None,
+        }
+    }
+}
+
+#[tsync::tsync]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+#[cfg_attr(feature = "plugin_utoipa", derive(utoipa::ToSchema))]
+/// a rule of the [`PasswordPolicy`] a password doesn't follow
+pub enum PasswordViolation {
+    TooShort {
+        min_length: usize,
+    },
+    MissingLowercase,
+    MissingUppercase,
+    MissingDigit,
+    MissingSymbol,
+    ContainsEmail,
+    /// the password appeared `count` times in [`BreachedPasswords`]
+    Breached {
+        count: u64,
+    },
+}
+
+impl std::fmt::Display for PasswordViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooShort { min_length } => {
+                write!(
+                    f,
+                    "The password must be at least {min_length} characters long."
+                )
+            }
+            Self::MissingLowercase => write!(f, "The password must contain a lowercase letter."),
+            Self::MissingUppercase => write!(f, "The password must contain an uppercase letter."),
+            Self::MissingDigit => write!(f, "The password must contain a digit."),
+            Self::MissingSymbol => write!(f, "The password must contain a symbol."),
+            Self::ContainsEmail => write!(f, "The password must not contain your email."),
+            Self::Breached { .. } => write!(f, "The password appeared in a data breach."),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// the rules `password`, chosen by the user with the given `email`, doesn't follow
+    ///
+    /// # Errors
+    /// * the [`BreachedPasswords`] file could not be read
+    pub fn check(&self, password: &str, email: &str) -> std::io::Result<Vec<PasswordViolation>> {
+        let mut violations = vec![];
+
+        if password.chars().count() < self.min_length {
+            violations.push(PasswordViolation::TooShort {
+                min_length: self.min_length,
+            });
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(PasswordViolation::MissingLowercase);
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(PasswordViolation::MissingUppercase);
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::MissingDigit);
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            violations.push(PasswordViolation::MissingSymbol);
+        }
+        if self.reject_email && contains_email(password, email) {
+            violations.push(PasswordViolation::ContainsEmail);
+        }
+        if let Some(breached_passwords) = &self.breached_passwords {
+            let count = breached_passwords.count(password)?;
+            if count > 0 {
+                violations.push(PasswordViolation::Breached { count });
+            }
+        }
+
+        Ok(violations)
+    }
+}
+
+/// the shortest part of an email [`contains_email`] looks for, shorter ones (like the "j" of
+/// "j.doe@example.com") would reject too many passwords
+const MIN_EMAIL_PART_LENGTH: usize = 4;
+
+/// whether `password` contains the local part of `email`, or one of its parts (like "alice" and
+/// "smith" in "alice.smith+news"), ignoring the short ones
+fn contains_email(password: &str, email: &str) -> bool {
+    let password = password.to_lowercase();
+    let local_part = email.split('@').next().unwrap_or_default().to_lowercase();
+
+    std::iter::once(local_part.as_str())
+        .chain(local_part.split(|c: char| !c.is_alphanumeric()))
+        .filter(|part| part.chars().count() >= MIN_EMAIL_PART_LENGTH)
+        .any(|part| password.contains(part))
+}
+
+#[derive(Debug, Clone)]
+/// an offline copy of the [Pwned Passwords](https://haveibeenpwned.com/Passwords) range files
+///
+/// `directory` contains a file per 5 character prefix of the uppercase hex SHA-1 hashes, named like
+/// `21BD1.txt`, with a `SUFFIX:COUNT` line per breached password (the format of the range API,
+/// which is what `haveibeenpwned-downloader --single false` produces);
+/// only the prefix of a password's hash is used to find its file, so they can also be served from
+/// a shared location without revealing the passwords being checked
+pub struct BreachedPasswords {
+    pub directory: PathBuf,
+}
+
+impl BreachedPasswords {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// how many times `password` appeared in a breach
+    ///
+    /// # Errors
+    /// * the file for the password's prefix could not be read; a complete copy has a file for every
+    ///   prefix, so a missing one means the copy is incomplete (or `directory` is wrong)
+    pub fn count(&self, password: &str) -> std::io::Result<u64> {
+        let hash = HEXUPPER.encode(&Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(PREFIX_LENGTH);
+
+        let contents = std::fs::read_to_string(self.directory.join(format!("{prefix}.txt")))?;
+
+        Ok(contents
+            .lines()
+            .filter_map(|line| line.trim().split_once(':'))
+            .find(|(line_suffix, _)| line_suffix.eq_ignore_ascii_case(suffix))
+            .and_then(|(_, count)| count.parse().ok())
+            .unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_reports_every_violation() {
+        let policy = PasswordPolicy {
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..PasswordPolicy::default()
+        };
+
+        assert_eq!(
+            policy.check("alice", "Alice@example.com").unwrap(),
+            vec![
+                PasswordViolation::TooShort { min_length: 8 },
+                PasswordViolation::MissingUppercase,
+                PasswordViolation::MissingDigit,
+                PasswordViolation::MissingSymbol,
+                PasswordViolation::ContainsEmail,
+            ]
+        );
+        assert!(policy
+            .check("correct Horse battery 5taple", "alice@example.com")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_contains_email_ignores_short_parts() {
+        assert!(contains_email("xAlice.Smith1!", "alice.smith@example.com"));
+        assert!(contains_email("smith-2024", "alice.smith@example.com"));
+        assert!(!contains_email("a strong password", "j.a@example.com"));
+        assert!(!contains_email("bob's password", "bo@example.com"));
+    }
+
+    #[test]
+    fn test_breached_passwords_are_found_by_prefix() {
+        let directory = std::env::temp_dir().join(format!("cra-breached-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        // SHA-1 of "password" is 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        std::fs::write(
+            directory.join("5BAA6.txt"),
+            "003D68EB55068C33ACE09247EE4C639306B:3\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:9659365\r\n",
+        )
+        .unwrap();
+
+        let breached_passwords = BreachedPasswords::new(&directory);
+        assert_eq!(breached_passwords.count("password").unwrap(), 9_659_365);
+        assert!(breached_passwords.count("not in the file").is_err());
+
+        let hash = HEXUPPER.encode(&Sha1::digest(b"not in the file"));
+        std::fs::write(
+            directory.join(format!("{}.txt", &hash[..PREFIX_LENGTH])),
+            "003D68EB55068C33ACE09247EE4C639306B:3\r\n",
+        )
+        .unwrap();
+        assert_eq!(breached_passwords.count("not in the file").unwrap(), 0);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
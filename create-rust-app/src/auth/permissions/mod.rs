@@ -1,17 +1,25 @@
+mod role_inheritance;
 mod role_permission;
+mod role_resource_permission;
 mod user_permission;
+mod user_resource_permission;
 mod user_role;
 
+pub use role_inheritance::{RoleInheritance, RoleInheritanceChangeset};
 pub use role_permission::{RolePermission, RolePermissionChangeset};
+pub use role_resource_permission::{RoleResourcePermission, RoleResourcePermissionChangeset};
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 pub use user_permission::{UserPermission, UserPermissionChangeset};
+pub use user_resource_permission::{UserResourcePermission, UserResourcePermissionChangeset};
 pub use user_role::{UserRole, UserRoleChangeset};
 
 use crate::database::Connection;
 use anyhow::Result;
 use diesel::{
+    connection::Connection as _,
     sql_query,
-    sql_types::{Integer, Text},
+    sql_types::{BigInt, Integer, Text},
     RunQueryDsl,
 };
 use serde::{Deserialize, Serialize};
@@ -20,6 +28,41 @@ use crate::auth::ID;
 
 pub struct Role;
 
+/// recursive CTE resolving `effective_roles`: every role assigned to the User bound to `$1`,
+/// plus every role inherited (transitively) through `role_inheritances`
+const EFFECTIVE_ROLES: &str = r"
+      WITH RECURSIVE effective_roles(role) AS (
+        SELECT role FROM user_roles WHERE user_id = $1
+
+        UNION
+
+        SELECT role_inheritances.inherited_role
+        FROM role_inheritances
+        INNER JOIN effective_roles ON role_inheritances.role = effective_roles.role
+      )";
+
+/// returns true if adding the edge `role` -> `inherited_role` to `edges` would create a cycle,
+/// that is, if `role` is already reachable from `inherited_role`
+fn creates_cycle(edges: &[(String, String)], role: &str, inherited_role: &str) -> bool {
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (from, to) in edges {
+        graph.entry(from.as_str()).or_default().push(to.as_str());
+    }
+
+    let mut visited = HashSet::new();
+    let mut stack = vec![inherited_role];
+    while let Some(current) = stack.pop() {
+        if current == role {
+            return true;
+        }
+        if visited.insert(current) {
+            stack.extend(graph.get(current).into_iter().flatten());
+        }
+    }
+
+    false
+}
+
 #[derive(Debug, Serialize, Deserialize, QueryableByName, Clone)]
 struct RoleQueryRow {
     #[diesel(sql_type=Text)]
@@ -95,12 +138,60 @@ impl Role {
         Ok(unassigned.is_ok())
     }
 
-    /// returns a vector containing every role assigned to the User whose id is [`user_id`](`ID`)
+    /// makes `role` inherit `inherited_role`: users with `role` will also have `inherited_role`
+    /// (and every role it inherits), along with all of their permissions
+    ///
+    /// returns `Ok(())`, if successful
+    ///
+    /// # Errors
+    /// * if the new edge would make the role hierarchy cyclic (this includes a role inheriting itself)
+    /// * if `RoleInheritance::create` fails, returns the error
+    pub fn inherit(db: &mut Connection, role: &str, inherited_role: &str) -> Result<()> {
+        db.transaction(|db| {
+            let edges = RoleInheritance::read_graph(db)?
+                .into_iter()
+                .map(|edge| (edge.role, edge.inherited_role))
+                .collect::<Vec<_>>();
+
+            if creates_cycle(&edges, role, inherited_role) {
+                return Err(anyhow::anyhow!(
+                    "Role '{role}' cannot inherit '{inherited_role}': the role hierarchy would contain a cycle."
+                ));
+            }
+
+            RoleInheritance::create(
+                db,
+                &RoleInheritanceChangeset {
+                    role: role.to_string(),
+                    inherited_role: inherited_role.to_string(),
+                },
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// removes the inheritance edge from `role` to `inherited_role`
+    ///
+    /// returns `Ok(())`, if successful
+    ///
+    /// # Errors
+    /// * if `RoleInheritance::delete` fails, returns the error
+    pub fn disinherit(db: &mut Connection, role: &str, inherited_role: &str) -> Result<()> {
+        let _deleted = RoleInheritance::delete(db, role, inherited_role)?;
+
+        Ok(())
+    }
+
+    /// returns a vector containing every role the User whose id is [`user_id`](`ID`) has,
+    /// either because it was assigned to them directly or because an assigned role inherits it
     ///
     /// # Errors
     /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
     pub fn fetch_all(db: &mut Connection, user_id: ID) -> Result<Vec<String>> {
-        let roles = sql_query("SELECT role FROM user_roles WHERE user_id = $1");
+        let roles = sql_query(format!(
+            "{EFFECTIVE_ROLES} SELECT role FROM effective_roles"
+        ));
 
         let roles = roles
             .bind::<Integer, _>(user_id)
@@ -112,6 +203,18 @@ impl Role {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, QueryableByName, Clone)]
+struct CountQueryRow {
+    #[diesel(sql_type=BigInt)]
+    count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, QueryableByName, Clone)]
+struct ResourceIdQueryRow {
+    #[diesel(sql_type=Integer)]
+    resource_id: ID,
+}
+
 #[tsync::tsync]
 #[derive(Debug, Serialize, Deserialize, QueryableByName, Clone)]
 pub struct Permission {
@@ -313,16 +416,227 @@ impl Permission {
         Ok(())
     }
 
-    /// returns every permission granted to the User whose id is [`user_id`](`ID`)
+    /// grants `permission` to the User whose id is [`user_id`](`ID`), but only on the record
+    /// identified by (`resource_type`, [`resource_id`](`ID`))
+    ///
+    /// returns `Ok(())`, if successful
+    ///
+    /// # Errors
+    /// * if `UserResourcePermission::create` fails, returns the error
+    pub fn grant_to_user_on(
+        db: &mut Connection,
+        user_id: ID,
+        permission: &str,
+        resource_type: &str,
+        resource_id: ID,
+    ) -> Result<()> {
+        let _granted = UserResourcePermission::create(
+            db,
+            &UserResourcePermissionChangeset {
+                user_id,
+                permission: permission.to_string(),
+                resource_type: resource_type.to_string(),
+                resource_id,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// grants `permission` to `role`, but only on the record identified by (`resource_type`, [`resource_id`](`ID`))
+    ///
+    /// returns `Ok(())`, if successful
+    ///
+    /// # Errors
+    /// * if `RoleResourcePermission::create` fails, returns the error
+    pub fn grant_to_role_on(
+        db: &mut Connection,
+        role: &str,
+        permission: &str,
+        resource_type: &str,
+        resource_id: ID,
+    ) -> Result<()> {
+        let _granted = RoleResourcePermission::create(
+            db,
+            &RoleResourcePermissionChangeset {
+                role: role.to_string(),
+                permission: permission.to_string(),
+                resource_type: resource_type.to_string(),
+                resource_id,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// revokes `permission` on the record identified by (`resource_type`, [`resource_id`](`ID`))
+    /// from the User whose id is [`user_id`](`ID`)
+    ///
+    /// returns `Ok(())`, if successful
+    ///
+    /// # Errors
+    /// * if `UserResourcePermission::delete` fails, returns the error
+    pub fn revoke_from_user_on(
+        db: &mut Connection,
+        user_id: ID,
+        permission: &str,
+        resource_type: &str,
+        resource_id: ID,
+    ) -> Result<()> {
+        let _deleted = UserResourcePermission::delete(
+            db,
+            &UserResourcePermissionChangeset {
+                user_id,
+                permission: permission.to_string(),
+                resource_type: resource_type.to_string(),
+                resource_id,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// revokes `permission` on the record identified by (`resource_type`, [`resource_id`](`ID`)) from `role`
+    ///
+    /// returns `Ok(())`, if successful
+    ///
+    /// # Errors
+    /// * if `RoleResourcePermission::delete` fails, returns the error
+    pub fn revoke_from_role_on(
+        db: &mut Connection,
+        role: &str,
+        permission: &str,
+        resource_type: &str,
+        resource_id: ID,
+    ) -> Result<()> {
+        let _deleted = RoleResourcePermission::delete(
+            db,
+            &RoleResourcePermissionChangeset {
+                role: role.to_string(),
+                permission: permission.to_string(),
+                resource_type: resource_type.to_string(),
+                resource_id,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// revokes every resource-scoped permission on the record identified by (`resource_type`, [`resource_id`](`ID`)),
+    /// call this when the record is deleted
+    ///
+    /// returns `Ok(())`, if successful
+    ///
+    /// # Errors
+    /// * if `UserResourcePermission::delete_all_for_resource` or `RoleResourcePermission::delete_all_for_resource` fails, returns the error
+    pub fn revoke_all_on(db: &mut Connection, resource_type: &str, resource_id: ID) -> Result<()> {
+        let _deleted =
+            UserResourcePermission::delete_all_for_resource(db, resource_type, resource_id)?;
+        let _deleted =
+            RoleResourcePermission::delete_all_for_resource(db, resource_type, resource_id)?;
+
+        Ok(())
+    }
+
+    /// is `permission` granted on the record identified by (`resource_type`, [`resource_id`](`ID`))
+    /// to the User whose id is [`user_id`](`ID`), either directly or through one of their (inherited) roles
+    ///
+    /// this only looks at resource-scoped grants, see [`Auth::can`](`crate::auth::Auth::can`)
+    /// which also accepts global permissions
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn is_granted_on(
+        db: &mut Connection,
+        user_id: ID,
+        permission: &str,
+        resource_type: &str,
+        resource_id: ID,
+    ) -> Result<bool> {
+        let grants = sql_query(format!(
+            r"
+      {EFFECTIVE_ROLES}
+
+      SELECT COUNT(*) AS count FROM (
+        SELECT permission
+        FROM user_resource_permissions
+        WHERE user_id = $1 AND permission = $2 AND resource_type = $3 AND resource_id = $4
+
+        UNION ALL
+
+        SELECT permission
+        FROM effective_roles
+        INNER JOIN role_resource_permissions ON effective_roles.role = role_resource_permissions.role
+        WHERE permission = $2 AND resource_type = $3 AND resource_id = $4
+      ) AS grants
+      "
+        ));
+
+        let grants = grants
+            .bind::<Integer, _>(user_id)
+            .bind::<Text, _>(permission)
+            .bind::<Text, _>(resource_type)
+            .bind::<Integer, _>(resource_id)
+            .get_result::<CountQueryRow>(db)?;
+
+        Ok(grants.count > 0)
+    }
+
+    /// returns the id of every `resource_type` record on which `permission` is granted to the User
+    /// whose id is [`user_id`](`ID`), either directly or through one of their (inherited) roles
+    ///
+    /// useful for listing, for example, every project a user can edit
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn fetch_resource_ids(
+        db: &mut Connection,
+        user_id: ID,
+        permission: &str,
+        resource_type: &str,
+    ) -> Result<Vec<ID>> {
+        let resource_ids = sql_query(format!(
+            r"
+      {EFFECTIVE_ROLES}
+
+      SELECT resource_id
+      FROM user_resource_permissions
+      WHERE user_id = $1 AND permission = $2 AND resource_type = $3
+
+      UNION
+
+      SELECT resource_id
+      FROM effective_roles
+      INNER JOIN role_resource_permissions ON effective_roles.role = role_resource_permissions.role
+      WHERE permission = $2 AND resource_type = $3
+      "
+        ));
+
+        let resource_ids = resource_ids
+            .bind::<Integer, _>(user_id)
+            .bind::<Text, _>(permission)
+            .bind::<Text, _>(resource_type)
+            .get_results::<ResourceIdQueryRow>(db)?;
+
+        Ok(resource_ids.into_iter().map(|r| r.resource_id).collect())
+    }
+
+    /// returns every permission granted to the User whose id is [`user_id`](`ID`),
+    /// including those granted to roles inherited by the User's roles
+    ///
+    /// `from_role` is the role which grants the permission, or an empty string if it was granted
+    /// to the User directly
     ///
     /// # Errors
     /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
     pub fn fetch_all(db: &mut Connection, user_id: ID) -> Result<Vec<Self>> {
-        let permissions = sql_query(
+        let permissions = sql_query(format!(
             r"
-      SELECT 
+      {EFFECTIVE_ROLES}
+
+      SELECT
         permission AS permission,
-        NULL AS from_role
+        '' AS from_role
       FROM user_permissions
       WHERE user_permissions.user_id = $1
 
@@ -330,12 +644,11 @@ impl Permission {
 
       SELECT
         permission AS permission,
-        user_roles.role AS form_role
-      FROM user_roles
-      INNER JOIN role_permissions ON user_roles.role = role_permissions.role
-      WHERE user_roles.user_id = $1
-      ",
-        );
+        role_permissions.role AS from_role
+      FROM effective_roles
+      INNER JOIN role_permissions ON effective_roles.role = role_permissions.role
+      "
+        ));
 
         let permissions = permissions
             .bind::<Integer, _>(user_id)
@@ -344,3 +657,26 @@ impl Permission {
         Ok(permissions)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::creates_cycle;
+
+    fn edges(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, b)| ((*a).to_string(), (*b).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_cycles() {
+        let graph = edges(&[("admin", "editor"), ("editor", "viewer")]);
+
+        assert!(creates_cycle(&graph, "viewer", "admin"));
+        assert!(creates_cycle(&graph, "editor", "admin"));
+        assert!(creates_cycle(&graph, "viewer", "viewer"));
+        assert!(!creates_cycle(&graph, "admin", "viewer"));
+        assert!(!creates_cycle(&graph, "owner", "admin"));
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{schema::role_inheritances, Utc};
+use crate::database::Connection;
+use crate::diesel::{
+    insert_into, AsChangeset, BoolExpressionMethods, ExpressionMethods, Insertable, QueryDsl,
+    QueryResult, Queryable, RunQueryDsl,
+};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = role_inheritances)]
+/// Rust struct modeling an entry in the `role_inheritances` table
+///
+/// users with `role` also have `inherited_role`, and every permission granted to it
+pub struct RoleInheritance {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub role: String,
+    pub inherited_role: String,
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = role_inheritances)]
+/// Rust struct modeling mutable data in an entry in the `role_inheritances` table
+pub struct RoleInheritanceChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub role: String,
+    pub inherited_role: String,
+}
+
+/// CRUD functions for [`RoleInheritance`]
+impl RoleInheritance {
+    /// Create an entry in [`db`](`Connection`)'s `role_inheritances` table that has the data stored in [`item`](`RoleInheritanceChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &RoleInheritanceChangeset) -> QueryResult<Self> {
+        use crate::auth::schema::role_inheritances::dsl::role_inheritances;
+
+        insert_into(role_inheritances)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for every entry in the `role_inheritances` table that has
+    /// `item_role` as its `role`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(db: &mut Connection, item_role: &str) -> QueryResult<Vec<Self>> {
+        use crate::auth::schema::role_inheritances::dsl::{created_at, role, role_inheritances};
+
+        role_inheritances
+            .filter(role.eq(item_role))
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
+    /// Read every entry in [`db`](`Connection`)'s `role_inheritances` table
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_graph(db: &mut Connection) -> QueryResult<Vec<Self>> {
+        use crate::auth::schema::role_inheritances::dsl::role_inheritances;
+
+        role_inheritances.load::<Self>(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `role_inheritances` table that has
+    /// (`item_role`,`item_inherited_role`) as it's primary keys
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(
+        db: &mut Connection,
+        item_role: &str,
+        item_inherited_role: &str,
+    ) -> QueryResult<usize> {
+        use crate::auth::schema::role_inheritances::dsl::{
+            inherited_role, role, role_inheritances,
+        };
+
+        diesel::delete(
+            role_inheritances.filter(
+                role.eq(item_role)
+                    .and(inherited_role.eq(item_inherited_role)),
+            ),
+        )
+        .execute(db)
+    }
+
+    /// Delete every entry in [`db`](`Connection`)'s `role_inheritances` table that `item_role` is part of,
+    /// either as the inheriting or the inherited role
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_all(db: &mut Connection, item_role: &str) -> QueryResult<usize> {
+        use crate::auth::schema::role_inheritances::dsl::{
+            inherited_role, role, role_inheritances,
+        };
+
+        diesel::delete(
+            role_inheritances.filter(role.eq(item_role).or(inherited_role.eq(item_role))),
+        )
+        .execute(db)
+    }
+}
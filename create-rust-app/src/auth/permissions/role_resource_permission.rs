@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{schema::role_resource_permissions, Utc, ID};
+use crate::database::Connection;
+use crate::diesel::{
+    insert_into, AsChangeset, BoolExpressionMethods, ExpressionMethods, Insertable, QueryDsl,
+    QueryResult, Queryable, RunQueryDsl,
+};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name=role_resource_permissions)]
+/// Rust struct modeling an entry in the `role_resource_permissions` table
+///
+/// grants `permission` to every user with `role` (or a role inheriting it),
+/// but only on the record identified by (`resource_type`, `resource_id`)
+pub struct RoleResourcePermission {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub role: String,
+    pub permission: String,
+    pub resource_type: String,
+    pub resource_id: ID,
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=role_resource_permissions)]
+/// Rust struct modeling mutable data in an entry in the `role_resource_permissions` table
+pub struct RoleResourcePermissionChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub role: String,
+    pub permission: String,
+    pub resource_type: String,
+    pub resource_id: ID,
+}
+
+/// CRUD functions for [`RoleResourcePermission`]
+impl RoleResourcePermission {
+    /// Create an entry in [`db`](`Connection`)'s `role_resource_permissions` table that has the data stored in [`item`](`RoleResourcePermissionChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(
+        db: &mut Connection,
+        item: &RoleResourcePermissionChangeset,
+    ) -> QueryResult<Self> {
+        use crate::auth::schema::role_resource_permissions::dsl::role_resource_permissions;
+
+        insert_into(role_resource_permissions)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for every entry in the `role_resource_permissions` table that has
+    /// `item_role` as its `role`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(db: &mut Connection, item_role: &str) -> QueryResult<Vec<Self>> {
+        use crate::auth::schema::role_resource_permissions::dsl::{
+            created_at, role, role_resource_permissions,
+        };
+
+        role_resource_permissions
+            .filter(role.eq(item_role))
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for every entry in the `role_resource_permissions` table that is
+    /// scoped to the record identified by (`item_resource_type`, `item_resource_id`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all_for_resource(
+        db: &mut Connection,
+        item_resource_type: &str,
+        item_resource_id: ID,
+    ) -> QueryResult<Vec<Self>> {
+        use crate::auth::schema::role_resource_permissions::dsl::{
+            created_at, resource_id, resource_type, role_resource_permissions,
+        };
+
+        role_resource_permissions
+            .filter(
+                resource_type
+                    .eq(item_resource_type)
+                    .and(resource_id.eq(item_resource_id)),
+            )
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `role_resource_permissions` table that has
+    /// (`item.role`,`item.permission`,`item.resource_type`,`item.resource_id`) as it's primary keys
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(
+        db: &mut Connection,
+        item: &RoleResourcePermissionChangeset,
+    ) -> QueryResult<usize> {
+        use crate::auth::schema::role_resource_permissions::dsl::{
+            permission, resource_id, resource_type, role, role_resource_permissions,
+        };
+
+        diesel::delete(
+            role_resource_permissions.filter(
+                role.eq(&item.role)
+                    .and(permission.eq(&item.permission))
+                    .and(resource_type.eq(&item.resource_type))
+                    .and(resource_id.eq(item.resource_id)),
+            ),
+        )
+        .execute(db)
+    }
+
+    /// Delete every entry in [`db`](`Connection`)'s `role_resource_permissions` table that is
+    /// scoped to the record identified by (`item_resource_type`, `item_resource_id`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_all_for_resource(
+        db: &mut Connection,
+        item_resource_type: &str,
+        item_resource_id: ID,
+    ) -> QueryResult<usize> {
+        use crate::auth::schema::role_resource_permissions::dsl::{
+            resource_id, resource_type, role_resource_permissions,
+        };
+
+        diesel::delete(
+            role_resource_permissions.filter(
+                resource_type
+                    .eq(item_resource_type)
+                    .and(resource_id.eq(item_resource_id)),
+            ),
+        )
+        .execute(db)
+    }
+}
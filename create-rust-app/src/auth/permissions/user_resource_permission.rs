@@ -0,0 +1,155 @@
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::schema::user_resource_permissions;
+use crate::diesel::{
+    insert_into, AsChangeset, Associations, BoolExpressionMethods, ExpressionMethods, Insertable,
+    QueryDsl, Queryable, RunQueryDsl,
+};
+use crate::{
+    auth::{user::User, Utc, ID},
+    database::Connection,
+};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Associations, AsChangeset,
+)]
+#[diesel(table_name=user_resource_permissions,belongs_to(User))]
+/// Rust struct modeling an entry in the `user_resource_permissions` table
+///
+/// grants `permission` to the user, but only on the record identified by (`resource_type`, `resource_id`)
+pub struct UserResourcePermission {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub permission: String,
+    pub resource_type: String,
+    pub resource_id: ID,
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=user_resource_permissions)]
+/// Rust struct modeling mutable data in an entry in the `user_resource_permissions` table
+pub struct UserResourcePermissionChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub permission: String,
+    pub resource_type: String,
+    pub resource_id: ID,
+}
+
+/// CRUD functions for [`UserResourcePermission`]
+impl UserResourcePermission {
+    /// Create an entry in [`db`](`Connection`)'s `user_resource_permissions` table that has the data stored in [`item`](`UserResourcePermissionChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(
+        db: &mut Connection,
+        item: &UserResourcePermissionChangeset,
+    ) -> QueryResult<Self> {
+        use crate::auth::schema::user_resource_permissions::dsl::user_resource_permissions;
+
+        insert_into(user_resource_permissions)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for every entry in the `user_resource_permissions` table that has
+    /// `item_user_id` as its `user_id`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use crate::auth::schema::user_resource_permissions::dsl::{
+            created_at, user_id, user_resource_permissions,
+        };
+
+        user_resource_permissions
+            .filter(user_id.eq(item_user_id))
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for every entry in the `user_resource_permissions` table that is
+    /// scoped to the record identified by (`item_resource_type`, `item_resource_id`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all_for_resource(
+        db: &mut Connection,
+        item_resource_type: &str,
+        item_resource_id: ID,
+    ) -> QueryResult<Vec<Self>> {
+        use crate::auth::schema::user_resource_permissions::dsl::{
+            created_at, resource_id, resource_type, user_resource_permissions,
+        };
+
+        user_resource_permissions
+            .filter(
+                resource_type
+                    .eq(item_resource_type)
+                    .and(resource_id.eq(item_resource_id)),
+            )
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `user_resource_permissions` table that has
+    /// (`item.user_id`,`item.permission`,`item.resource_type`,`item.resource_id`) as it's primary keys
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(
+        db: &mut Connection,
+        item: &UserResourcePermissionChangeset,
+    ) -> QueryResult<usize> {
+        use crate::auth::schema::user_resource_permissions::dsl::{
+            permission, resource_id, resource_type, user_id, user_resource_permissions,
+        };
+
+        diesel::delete(
+            user_resource_permissions.filter(
+                user_id
+                    .eq(item.user_id)
+                    .and(permission.eq(&item.permission))
+                    .and(resource_type.eq(&item.resource_type))
+                    .and(resource_id.eq(item.resource_id)),
+            ),
+        )
+        .execute(db)
+    }
+
+    /// Delete every entry in [`db`](`Connection`)'s `user_resource_permissions` table that is
+    /// scoped to the record identified by (`item_resource_type`, `item_resource_id`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_all_for_resource(
+        db: &mut Connection,
+        item_resource_type: &str,
+        item_resource_id: ID,
+    ) -> QueryResult<usize> {
+        use crate::auth::schema::user_resource_permissions::dsl::{
+            resource_id, resource_type, user_resource_permissions,
+        };
+
+        diesel::delete(
+            user_resource_permissions.filter(
+                resource_type
+                    .eq(item_resource_type)
+                    .and(resource_id.eq(item_resource_id)),
+            ),
+        )
+        .execute(db)
+    }
+}
@@ -1,3 +1,76 @@
+table! {
+  admin_audit_logs (id) {
+      id -> Int4,
+      admin_user_id -> Int4,
+      action -> Text,
+      target_user_id -> Nullable<Int4>,
+      details -> Nullable<Text>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  failed_logins (id) {
+      id -> Int4,
+      user_id -> Nullable<Int4>,
+      ip_address -> Nullable<Text>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  oauth_authorization_codes (code_hash) {
+      code_hash -> Text,
+      oauth_client_id -> Int4,
+      user_id -> Int4,
+      redirect_uri -> Text,
+      scope -> Text,
+      nonce -> Nullable<Text>,
+      code_challenge -> Text,
+      expires_at -> Timestamptz,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  oauth_clients (id) {
+      id -> Int4,
+      client_id -> Text,
+      client_secret_hash -> Nullable<Text>,
+      name -> Text,
+      redirect_uris -> Text,
+      first_party -> Bool,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  passkey_challenges (challenge) {
+      challenge -> Text,
+      user_id -> Nullable<Int4>,
+      expires_at -> Timestamptz,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  role_inheritances (role, inherited_role) {
+      role -> Text,
+      inherited_role -> Text,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  role_resource_permissions (role, permission, resource_type, resource_id) {
+      role -> Text,
+      permission -> Text,
+      resource_type -> Text,
+      resource_id -> Int4,
+      created_at -> Timestamptz,
+  }
+}
+
 table! {
   role_permissions (role) {
       role -> Text,
@@ -6,6 +79,33 @@ table! {
   }
 }
 
+table! {
+  user_passkeys (id) {
+      id -> Int4,
+      user_id -> Int4,
+      credential_id -> Text,
+      public_key -> Text,
+      sign_count -> Int8,
+      name -> Nullable<Text>,
+      last_used_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  user_api_keys (id) {
+      id -> Int4,
+      user_id -> Int4,
+      name -> Text,
+      key_prefix -> Text,
+      key_hash -> Text,
+      permissions -> Nullable<Text>,
+      last_used_at -> Nullable<Timestamptz>,
+      expires_at -> Nullable<Timestamptz>,
+      created_at -> Timestamptz,
+  }
+}
+
 table! {
   user_permissions (user_id, permission) {
       user_id -> Int4,
@@ -14,6 +114,35 @@ table! {
   }
 }
 
+table! {
+  user_login_links (token_id) {
+      token_id -> Text,
+      user_id -> Int4,
+      expires_at -> Timestamptz,
+      created_at -> Timestamptz,
+  }
+}
+
+table! {
+  user_lockouts (user_id) {
+      user_id -> Int4,
+      lockout_count -> Int4,
+      locked_until -> Timestamptz,
+      created_at -> Timestamptz,
+      updated_at -> Timestamptz,
+  }
+}
+
+table! {
+  user_resource_permissions (user_id, permission, resource_type, resource_id) {
+      user_id -> Int4,
+      permission -> Text,
+      resource_type -> Text,
+      resource_id -> Int4,
+      created_at -> Timestamptz,
+  }
+}
+
 table! {
   user_roles (user_id, role) {
       user_id -> Int4,
@@ -28,6 +157,24 @@ table! {
       user_id -> Int4,
       refresh_token -> Text,
       device -> Nullable<Text>,
+      family -> Text,
+      rotated -> Bool,
+      ip_address -> Nullable<Text>,
+      user_agent -> Nullable<Text>,
+      oauth_client_id -> Nullable<Int4>,
+      logged_in_at -> Timestamptz,
+      created_at -> Timestamptz,
+      updated_at -> Timestamptz,
+  }
+}
+
+table! {
+  user_totps (user_id) {
+      user_id -> Int4,
+      secret -> Text,
+      confirmed -> Bool,
+      recovery_codes -> Text,
+      last_used_step -> Nullable<Int8>,
       created_at -> Timestamptz,
       updated_at -> Timestamptz,
   }
@@ -39,19 +186,46 @@ table! {
       email -> Text,
       hash_password -> Text,
       activated -> Bool,
+      deletion_scheduled_at -> Nullable<Timestamptz>,
+      deleted_at -> Nullable<Timestamptz>,
+      locale -> Nullable<Text>,
       created_at -> Timestamptz,
       updated_at -> Timestamptz,
   }
 }
 
+joinable!(admin_audit_logs -> users (admin_user_id));
+joinable!(failed_logins -> users (user_id));
+joinable!(oauth_authorization_codes -> oauth_clients (oauth_client_id));
+joinable!(oauth_authorization_codes -> users (user_id));
+joinable!(passkey_challenges -> users (user_id));
+joinable!(user_api_keys -> users (user_id));
+joinable!(user_lockouts -> users (user_id));
+joinable!(user_login_links -> users (user_id));
+joinable!(user_passkeys -> users (user_id));
 joinable!(user_permissions -> users (user_id));
+joinable!(user_resource_permissions -> users (user_id));
 joinable!(user_roles -> users (user_id));
 joinable!(user_sessions -> users (user_id));
+joinable!(user_totps -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    admin_audit_logs,
+    failed_logins,
+    oauth_authorization_codes,
+    oauth_clients,
+    passkey_challenges,
+    role_inheritances,
     role_permissions,
+    role_resource_permissions,
+    user_api_keys,
+    user_lockouts,
+    user_login_links,
+    user_passkeys,
     user_permissions,
+    user_resource_permissions,
     user_roles,
     user_sessions,
+    user_totps,
     users,
 );
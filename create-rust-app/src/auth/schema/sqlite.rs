@@ -1,5 +1,78 @@
 // We don't include the `updated_at` column in the SQLite schema
 
+table! {
+  admin_audit_logs (id) {
+      id -> Integer,
+      admin_user_id -> Integer,
+      action -> Text,
+      target_user_id -> Nullable<Integer>,
+      details -> Nullable<Text>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  failed_logins (id) {
+      id -> Integer,
+      user_id -> Nullable<Integer>,
+      ip_address -> Nullable<Text>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  oauth_authorization_codes (code_hash) {
+      code_hash -> Text,
+      oauth_client_id -> Integer,
+      user_id -> Integer,
+      redirect_uri -> Text,
+      scope -> Text,
+      nonce -> Nullable<Text>,
+      code_challenge -> Text,
+      expires_at -> Timestamp,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  oauth_clients (id) {
+      id -> Integer,
+      client_id -> Text,
+      client_secret_hash -> Nullable<Text>,
+      name -> Text,
+      redirect_uris -> Text,
+      first_party -> Bool,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  passkey_challenges (challenge) {
+      challenge -> Text,
+      user_id -> Nullable<Integer>,
+      expires_at -> Timestamp,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  role_inheritances (role, inherited_role) {
+      role -> Text,
+      inherited_role -> Text,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  role_resource_permissions (role, permission, resource_type, resource_id) {
+      role -> Text,
+      permission -> Text,
+      resource_type -> Text,
+      resource_id -> Integer,
+      created_at -> Timestamp,
+  }
+}
+
 table! {
   role_permissions (role, permission) {
       role -> Text,
@@ -8,6 +81,33 @@ table! {
   }
 }
 
+table! {
+  user_passkeys (id) {
+      id -> Integer,
+      user_id -> Integer,
+      credential_id -> Text,
+      public_key -> Text,
+      sign_count -> BigInt,
+      name -> Nullable<Text>,
+      last_used_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  user_api_keys (id) {
+      id -> Integer,
+      user_id -> Integer,
+      name -> Text,
+      key_prefix -> Text,
+      key_hash -> Text,
+      permissions -> Nullable<Text>,
+      last_used_at -> Nullable<Timestamp>,
+      expires_at -> Nullable<Timestamp>,
+      created_at -> Timestamp,
+  }
+}
+
 table! {
   user_permissions (user_id, permission) {
       user_id -> Integer,
@@ -16,6 +116,34 @@ table! {
   }
 }
 
+table! {
+  user_login_links (token_id) {
+      token_id -> Text,
+      user_id -> Integer,
+      expires_at -> Timestamp,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  user_lockouts (user_id) {
+      user_id -> Integer,
+      lockout_count -> Integer,
+      locked_until -> Timestamp,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  user_resource_permissions (user_id, permission, resource_type, resource_id) {
+      user_id -> Integer,
+      permission -> Text,
+      resource_type -> Text,
+      resource_id -> Integer,
+      created_at -> Timestamp,
+  }
+}
+
 table! {
   user_roles (user_id, role) {
       user_id -> Integer,
@@ -30,6 +158,23 @@ table! {
       user_id -> Integer,
       refresh_token -> Text,
       device -> Nullable<Text>,
+      family -> Text,
+      rotated -> Bool,
+      ip_address -> Nullable<Text>,
+      user_agent -> Nullable<Text>,
+      oauth_client_id -> Nullable<Integer>,
+      logged_in_at -> Timestamp,
+      created_at -> Timestamp,
+  }
+}
+
+table! {
+  user_totps (user_id) {
+      user_id -> Integer,
+      secret -> Text,
+      confirmed -> Bool,
+      recovery_codes -> Text,
+      last_used_step -> Nullable<BigInt>,
       created_at -> Timestamp,
   }
 }
@@ -40,18 +185,45 @@ table! {
       email -> Text,
       hash_password -> Text,
       activated -> Bool,
+      deletion_scheduled_at -> Nullable<Timestamp>,
+      deleted_at -> Nullable<Timestamp>,
+      locale -> Nullable<Text>,
       created_at -> Timestamp,
   }
 }
 
+joinable!(admin_audit_logs -> users (admin_user_id));
+joinable!(failed_logins -> users (user_id));
+joinable!(oauth_authorization_codes -> oauth_clients (oauth_client_id));
+joinable!(oauth_authorization_codes -> users (user_id));
+joinable!(passkey_challenges -> users (user_id));
+joinable!(user_api_keys -> users (user_id));
+joinable!(user_lockouts -> users (user_id));
+joinable!(user_login_links -> users (user_id));
+joinable!(user_passkeys -> users (user_id));
 joinable!(user_permissions -> users (user_id));
+joinable!(user_resource_permissions -> users (user_id));
 joinable!(user_roles -> users (user_id));
 joinable!(user_sessions -> users (user_id));
+joinable!(user_totps -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    admin_audit_logs,
+    failed_logins,
+    oauth_authorization_codes,
+    oauth_clients,
+    passkey_challenges,
+    role_inheritances,
     role_permissions,
+    role_resource_permissions,
+    user_api_keys,
+    user_lockouts,
+    user_login_links,
+    user_passkeys,
     user_permissions,
+    user_resource_permissions,
     user_roles,
     user_sessions,
+    user_totps,
     users,
 );
@@ -0,0 +1,62 @@
+use chrono::Duration;
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone)]
+/// limits on failed login attempts, used to slow down credential stuffing
+///
+/// set through [`AuthConfig::login_throttle`](`crate::auth::AuthConfig`)
+pub struct LoginThrottleConfig {
+    /// failed attempts for an account, within [`window`](`Self::window`), before it is locked
+    pub max_failed_attempts_per_account: i64,
+    /// failed attempts from a single IP address, within [`window`](`Self::window`),
+    /// before further attempts from it are rejected
+    pub max_failed_attempts_per_ip: i64,
+    /// how far back failed attempts are counted
+    pub window: Duration,
+    /// how long an account is locked the first time; every consecutive lockout doubles it
+    pub base_lockout: Duration,
+    /// upper bound on how long an account is locked
+    pub max_lockout: Duration,
+}
+
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        Self {
+            max_failed_attempts_per_account: 5,
+            max_failed_attempts_per_ip: 20,
+            window: Duration::minutes(15),
+            base_lockout: Duration::minutes(5),
+            max_lockout: Duration::hours(24),
+        }
+    }
+}
+
+impl LoginThrottleConfig {
+    /// how long an account is locked for its `lockout_count`-th consecutive lockout (starting at 1)
+    #[must_use]
+    pub fn lockout_duration(&self, lockout_count: i32) -> Duration {
+        let doublings = u32::try_from(lockout_count.saturating_sub(1)).unwrap_or(0);
+
+        2i32.checked_pow(doublings)
+            .and_then(|factor| self.base_lockout.checked_mul(factor))
+            .map_or(self.max_lockout, |duration| {
+                std::cmp::min(duration, self.max_lockout)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_duration_doubles_up_to_max() {
+        let config = LoginThrottleConfig::default();
+
+        assert_eq!(config.lockout_duration(1), Duration::minutes(5));
+        assert_eq!(config.lockout_duration(2), Duration::minutes(10));
+        assert_eq!(config.lockout_duration(4), Duration::minutes(40));
+        assert_eq!(config.lockout_duration(10), Duration::hours(24));
+        assert_eq!(config.lockout_duration(100), Duration::hours(24));
+    }
+}
@@ -0,0 +1,173 @@
+//! time-based one-time passwords ([RFC 6238](https://datatracker.ietf.org/doc/html/rfc6238))
+//! used as a second authentication factor
+use data_encoding::{BASE32_NOPAD, HEXLOWER};
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, Rng};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+
+/// number of digits in a generated code
+pub const DIGITS: u32 = 6;
+
+/// number of seconds a code is valid for
+pub const STEP: u64 = 30;
+
+/// number of steps before and after the current one that are still accepted,
+/// to allow for clock drift between the server and the authenticator app
+const SKEW: u64 = 1;
+
+/// number of recovery codes generated when two-factor authentication is confirmed
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// generates a random 160-bit secret, base32 encoded (without padding)
+/// so it can be typed into or scanned by authenticator apps
+#[must_use]
+pub fn generate_secret() -> String {
+    let secret: [u8; 20] = rand::thread_rng().gen();
+    BASE32_NOPAD.encode(&secret)
+}
+
+/// computes the code for the given time `step` (HOTP, RFC 4226)
+///
+/// # Panics
+/// * never; HMAC accepts keys of any length
+#[must_use]
+pub fn code_at(secret: &[u8], step: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC can take a key of any size");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    binary % 10u32.pow(DIGITS)
+}
+
+/// checks `code` against the base32 encoded `secret` at unix time `now`
+///
+/// codes for steps at or before `last_used_step` are rejected so a code can't be replayed
+///
+/// returns the step that matched, which should be stored as the new `last_used_step`
+#[must_use]
+pub fn verify(secret: &str, code: &str, now: u64, last_used_step: Option<i64>) -> Option<u64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let code = code.parse::<u32>().ok()?;
+    let secret = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+
+    let current = now / STEP;
+    let earliest = match last_used_step.and_then(|last| u64::try_from(last).ok()) {
+        Some(last) => current.saturating_sub(SKEW).max(last + 1),
+        None => current.saturating_sub(SKEW),
+    };
+
+    (earliest..=current + SKEW).find(|step| code_at(&secret, *step) == code)
+}
+
+/// builds the `otpauth://` url which authenticator apps read from a QR code
+#[must_use]
+pub fn otpauth_url(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{label}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP}",
+        label = percent_encode(&format!("{issuer}:{account}")),
+        issuer = percent_encode(issuer),
+    )
+}
+
+/// generates a fresh set of single-use recovery codes
+#[must_use]
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(|c| char::from(c).to_ascii_lowercase())
+                .collect::<String>();
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect()
+}
+
+/// recovery codes are random, so a fast hash is enough to keep them from being read back
+#[must_use]
+pub fn hash_recovery_code(code: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(code.trim().to_ascii_lowercase().as_bytes()))
+}
+
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // test vectors from RFC 6238, appendix B (SHA1, truncated to 6 digits)
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_code_at_matches_rfc_vectors() {
+        assert_eq!(code_at(RFC_SECRET, 59 / STEP), 287_082);
+        assert_eq!(code_at(RFC_SECRET, 1_111_111_109 / STEP), 81_804);
+        assert_eq!(code_at(RFC_SECRET, 1_234_567_890 / STEP), 5_924);
+        assert_eq!(code_at(RFC_SECRET, 20_000_000_000 / STEP), 353_130);
+    }
+
+    #[test]
+    fn test_verify_accepts_adjacent_steps_once() {
+        let secret = BASE32_NOPAD.encode(RFC_SECRET);
+
+        assert_eq!(
+            verify(&secret, "081804", 1_111_111_109, None),
+            Some(37_037_036)
+        );
+        assert_eq!(
+            verify(&secret, "081804", 1_111_111_109 + STEP, None),
+            Some(37_037_036)
+        );
+        assert_eq!(
+            verify(&secret, "081804", 1_111_111_109, Some(37_037_036)),
+            None
+        );
+        assert_eq!(
+            verify(&secret, "081804", 1_111_111_109 + 3 * STEP, None),
+            None
+        );
+        assert_eq!(verify(&secret, "81804", 1_111_111_109, None), None);
+    }
+
+    #[test]
+    fn test_recovery_codes_hash_case_insensitively() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert_eq!(
+            hash_recovery_code(&codes[0]),
+            hash_recovery_code(&codes[0].to_ascii_uppercase())
+        );
+    }
+
+    #[test]
+    fn test_otpauth_url_encodes_label() {
+        assert_eq!(
+            otpauth_url("My App", "a@b.c", "ABC"),
+            "otpauth://totp/My%20App%3Aa%40b.c?secret=ABC&issuer=My%20App&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
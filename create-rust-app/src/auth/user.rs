@@ -24,6 +24,13 @@ pub struct User {
     pub email: String,
     pub hash_password: String,
     pub activated: bool,
+    /// when the user asked for their account to be deleted, it's deleted once this has passed,
+    /// see [`account`](`crate::auth::account`)
+    pub deletion_scheduled_at: Option<Utc>,
+    /// set once the account was deleted; the row is kept, emptied, so ids stay unique
+    pub deleted_at: Option<Utc>,
+    /// the language tag (like `fr-CA`) the user's emails are written in, see [`Mailer::render`](`crate::Mailer::render`)
+    pub locale: Option<String>,
 
     pub created_at: Utc,
     #[cfg(not(feature = "database_sqlite"))]
@@ -40,6 +47,8 @@ pub struct UserChangeset {
     Don't include non-mutable columns
     (ex: id, created_at/updated_at)
     -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    /// users change it through [`request_email_change`](`crate::auth::controller::request_email_change`),
+    /// which verifies the new address first
     pub email: String,
     pub hash_password: String,
     pub activated: bool,
@@ -99,6 +108,51 @@ impl User {
             .load::<Self>(db)
     }
 
+    /// Read from [`db`](`Connection`), return entries of the `users` table whose email contains
+    /// `query` (all of them if it's `None`), paginated according to [`pagination`](`PaginationParams`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn search(
+        db: &mut Connection,
+        query: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> QueryResult<Vec<Self>> {
+        use super::schema::users::dsl::{created_at, users};
+
+        let page_size = std::cmp::min(
+            pagination.page_size,
+            i64::from(PaginationParams::MAX_PAGE_SIZE),
+        );
+
+        let mut results = users.into_boxed();
+        if let Some(query) = query {
+            results = results.filter(email_matches(query));
+        }
+
+        results
+            .order(created_at)
+            .limit(page_size)
+            .offset(pagination.page * page_size)
+            .load::<Self>(db)
+    }
+
+    /// Count the entries in [`db`](`Connection`)'s `users` table whose email contains
+    /// `query` (all of them if it's `None`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn count_search(db: &mut Connection, query: Option<&str>) -> QueryResult<i64> {
+        use super::schema::users::dsl::users;
+
+        let mut results = users.into_boxed();
+        if let Some(query) = query {
+            results = results.filter(email_matches(query));
+        }
+
+        results.count().get_result(db)
+    }
+
     /// Update the entry in [`db`](`Connection`)'s `users` table who's primary key matches
     /// [`item_id`](`ID`), with the data in [`item`](`UserChangeset`)
     ///
@@ -112,9 +166,127 @@ impl User {
             .get_result(db)
     }
 
+    /// Query [`db`](`Connection`)'s `users` table for the ids of the entries whose deletion
+    /// was scheduled before `before` and which weren't deleted yet
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all_due_for_deletion(db: &mut Connection, before: Utc) -> QueryResult<Vec<ID>> {
+        use super::schema::users::dsl::{deleted_at, deletion_scheduled_at, id, users};
+
+        users
+            .filter(deletion_scheduled_at.le(before))
+            .filter(deleted_at.is_null())
+            .select(id)
+            .load::<ID>(db)
+    }
+
+    /// Set (or clear, if `at` is `None`) the time the entry in [`db`](`Connection`)'s `users` table
+    /// who's primary key matches [`item_id`](`ID`) will be deleted at
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn schedule_deletion(
+        db: &mut Connection,
+        item_id: ID,
+        at: Option<Utc>,
+    ) -> QueryResult<Self> {
+        use super::schema::users::dsl::{deleted_at, deletion_scheduled_at, id, users};
+
+        diesel::update(users.filter(id.eq(item_id)).filter(deleted_at.is_null()))
+            .set(deletion_scheduled_at.eq(at))
+            .get_result(db)
+    }
+
+    /// Set (or clear, if `locale` is `None`) the locale of the entry in [`db`](`Connection`)'s `users` table
+    /// who's primary key matches [`item_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn set_locale(db: &mut Connection, item_id: ID, locale: Option<&str>) -> QueryResult<Self> {
+        use super::schema::users::dsl::{id, users};
+
+        diesel::update(users.filter(id.eq(item_id)))
+            .set(super::schema::users::locale.eq(locale))
+            .get_result(db)
+    }
+
+    /// Soft-delete the entry in [`db`](`Connection`)'s `users` table who's primary key matches
+    /// [`item_id`](`ID`): everything stored about the user in the other auth tables is deleted,
+    /// and the row itself is emptied and marked as deleted
+    ///
+    /// `hash_password` should be the hash of a random password, so the account can't be logged into
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if a query fails, nothing is deleted then
+    pub fn erase(db: &mut Connection, item_id: ID, hash_password: &str) -> QueryResult<Self> {
+        use super::schema::{
+            failed_logins, oauth_authorization_codes, passkey_challenges, user_api_keys,
+            user_lockouts, user_login_links, user_passkeys, user_permissions,
+            user_resource_permissions, user_roles, user_sessions, user_totps, users,
+        };
+
+        diesel::connection::Connection::transaction(db, |db| {
+            diesel::delete(user_sessions::table.filter(user_sessions::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(user_roles::table.filter(user_roles::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(user_permissions::table.filter(user_permissions::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(
+                user_resource_permissions::table
+                    .filter(user_resource_permissions::user_id.eq(item_id)),
+            )
+            .execute(db)?;
+            diesel::delete(user_totps::table.filter(user_totps::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(user_passkeys::table.filter(user_passkeys::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(
+                passkey_challenges::table.filter(passkey_challenges::user_id.eq(item_id)),
+            )
+            .execute(db)?;
+            diesel::delete(user_api_keys::table.filter(user_api_keys::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(user_login_links::table.filter(user_login_links::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(user_lockouts::table.filter(user_lockouts::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(failed_logins::table.filter(failed_logins::user_id.eq(item_id)))
+                .execute(db)?;
+            diesel::delete(
+                oauth_authorization_codes::table
+                    .filter(oauth_authorization_codes::user_id.eq(item_id)),
+            )
+            .execute(db)?;
+            #[cfg(feature = "plugin_auth-oidc")]
+            {
+                use crate::auth::oidc::schema::user_oauth2_links;
+
+                diesel::delete(
+                    user_oauth2_links::table.filter(user_oauth2_links::user_id.eq(item_id)),
+                )
+                .execute(db)?;
+            }
+
+            diesel::update(users::table.filter(users::id.eq(item_id)))
+                .set((
+                    users::email.eq(format!("deleted-user-{item_id}")),
+                    users::hash_password.eq(hash_password),
+                    users::activated.eq(false),
+                    users::deletion_scheduled_at.eq(None::<Utc>),
+                    users::locale.eq(None::<String>),
+                    users::deleted_at.eq(Some(super::utc_now())),
+                ))
+                .get_result(db)
+        })
+    }
+
     /// Delete the entry in [`db`](`Connection`)'s `users` table who's
     /// primary key matches [`item_id`](`ID`)
     ///
+    /// this fails if other tables still reference the user, see [`User::erase`] to delete an account
+    ///
     /// # Errors
     /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
     pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
@@ -123,3 +295,35 @@ impl User {
         diesel::delete(users.filter(id.eq(item_id))).execute(db)
     }
 }
+
+/// case-insensitive "`email` contains `query`" filter, `%` and `_` in `query` are matched literally
+#[cfg(not(feature = "database_sqlite"))]
+fn email_matches(
+    query: &str,
+) -> diesel::dsl::Escape<diesel::dsl::ILike<super::schema::users::email, String>> {
+    use super::schema::users::dsl::email;
+    use diesel::{EscapeExpressionMethods, PgTextExpressionMethods};
+
+    email.ilike(like_pattern(query)).escape('\\')
+}
+
+/// case-insensitive "`email` contains `query`" filter, `%` and `_` in `query` are matched literally
+#[cfg(feature = "database_sqlite")]
+fn email_matches(
+    query: &str,
+) -> diesel::dsl::Escape<diesel::dsl::Like<super::schema::users::email, String>> {
+    use super::schema::users::dsl::email;
+    use diesel::{EscapeExpressionMethods, TextExpressionMethods};
+
+    // sqlite's LIKE is already case-insensitive for ASCII
+    email.like(like_pattern(query)).escape('\\')
+}
+
+fn like_pattern(query: &str) -> String {
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{escaped}%")
+}
@@ -0,0 +1,101 @@
+//! human-readable names for the devices sessions are used from, derived from their `User-Agent` header
+//!
+//! this is only meant to help users recognise their own sessions, it doesn't try to identify
+//! every browser or to be accurate for clients lying about themselves
+
+/// longest `User-Agent` header stored on a session, longer ones are truncated
+pub const MAX_LENGTH: usize = 512;
+
+/// browsers, in the order they're checked: most browsers also claim to be the ones they're based on
+const BROWSERS: &[(&str, &str)] = &[
+    ("Edg/", "Edge"),
+    ("EdgiOS/", "Edge"),
+    ("OPR/", "Opera"),
+    ("SamsungBrowser/", "Samsung Internet"),
+    ("Firefox/", "Firefox"),
+    ("FxiOS/", "Firefox"),
+    ("CriOS/", "Chrome"),
+    ("Chrome/", "Chrome"),
+    ("Safari/", "Safari"),
+];
+
+/// operating systems, in the order they're checked
+const OPERATING_SYSTEMS: &[(&str, &str)] = &[
+    ("iPhone", "iOS"),
+    ("iPad", "iPadOS"),
+    ("Android", "Android"),
+    ("CrOS", "ChromeOS"),
+    ("Windows", "Windows"),
+    ("Mac OS X", "macOS"),
+    ("Macintosh", "macOS"),
+    ("Linux", "Linux"),
+];
+
+/// names the device a request came from, like "Firefox on Linux"
+///
+/// clients that aren't browsers are named after their first product token, like "curl"
+#[must_use]
+pub fn device_name(user_agent: &str) -> String {
+    let browser = BROWSERS
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+    let operating_system = OPERATING_SYSTEMS
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+
+    match (browser, operating_system) {
+        (Some(browser), Some(operating_system)) => format!("{browser} on {operating_system}"),
+        (Some(browser), None) => browser.to_string(),
+        (None, _) => {
+            let product = user_agent
+                .split(|c: char| c == '/' || c.is_whitespace())
+                .next()
+                .unwrap_or_default();
+
+            if product.is_empty() {
+                "Unknown device".to_string()
+            } else {
+                product.chars().take(64).collect()
+            }
+        }
+    }
+}
+
+/// truncates a `User-Agent` header to [`MAX_LENGTH`] characters before it's stored
+#[must_use]
+pub fn truncate(user_agent: &str) -> String {
+    user_agent.chars().take(MAX_LENGTH).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_name_of_browsers() {
+        assert_eq!(
+            device_name("Mozilla/5.0 (X11; Linux x86_64; rv:126.0) Gecko/20100101 Firefox/126.0"),
+            "Firefox on Linux"
+        );
+        assert_eq!(
+            device_name("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0.0.0 Safari/537.36 Edg/125.0.0.0"),
+            "Edge on Windows"
+        );
+        assert_eq!(
+            device_name("Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1"),
+            "Safari on iOS"
+        );
+        assert_eq!(
+            device_name("Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0.0.0 Mobile Safari/537.36"),
+            "Chrome on Android"
+        );
+    }
+
+    #[test]
+    fn test_device_name_of_other_clients() {
+        assert_eq!(device_name("curl/8.7.1"), "curl");
+        assert_eq!(device_name(""), "Unknown device");
+    }
+}
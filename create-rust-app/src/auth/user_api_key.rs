@@ -0,0 +1,178 @@
+use super::schema::user_api_keys;
+use crate::diesel::{
+    insert_into, AsChangeset, Associations, ExpressionMethods, Identifiable, Insertable, QueryDsl,
+    Queryable, RunQueryDsl,
+};
+
+use super::{user::User, utc_now, Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// every API key starts with this, so they can be told apart from access tokens (and found by secret scanners)
+pub const API_KEY_PREFIX: &str = "cra_";
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name=user_api_keys,belongs_to(User))]
+/// Rust struct modeling an entry in the `user_api_keys` table
+///
+/// a long-lived key a user created for machine clients, accepted in place of an access token
+pub struct UserApiKey {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+    pub user_id: ID,
+    pub name: String,
+    /// the first characters of the key, so users can recognize it
+    pub key_prefix: String,
+    /// see [`UserApiKey::hash_key`], the key itself is only shown once
+    pub key_hash: String,
+    /// newline separated permissions the key is limited to,
+    /// `None` if it has all of the user's permissions and roles
+    pub permissions: Option<String>,
+    pub last_used_at: Option<Utc>,
+    pub expires_at: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=user_api_keys)]
+/// Rust struct modeling mutable data in an entry in the `user_api_keys` table
+pub struct UserApiKeyChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub name: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub permissions: Option<String>,
+    pub last_used_at: Option<Utc>,
+    pub expires_at: Option<Utc>,
+}
+
+impl UserApiKey {
+    /// generates a new API key: [`API_KEY_PREFIX`] followed by 256 random bits, base64url encoded
+    #[must_use]
+    pub fn generate_key() -> String {
+        let secret: [u8; 32] = rand::thread_rng().gen();
+        format!(
+            "{API_KEY_PREFIX}{}",
+            data_encoding::BASE64URL_NOPAD.encode(&secret)
+        )
+    }
+
+    /// the hash stored in place of `key`
+    ///
+    /// keys are random rather than chosen by users, so a fast hash is enough
+    #[must_use]
+    pub fn hash_key(key: &str) -> String {
+        data_encoding::HEXLOWER.encode(&Sha256::digest(key.as_bytes()))
+    }
+
+    /// the permissions the key is limited to, `None` if it isn't limited
+    #[must_use]
+    pub fn permission_list(&self) -> Option<Vec<String>> {
+        self.permissions.as_ref().map(|permissions| {
+            permissions
+                .lines()
+                .filter(|permission| !permission.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        })
+    }
+
+    /// Create an entry in [`db`](`Connection`)'s `user_api_keys` table using the data in [`item`](`UserApiKeyChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &UserApiKeyChangeset) -> QueryResult<Self> {
+        use super::schema::user_api_keys::dsl::user_api_keys;
+
+        insert_into(user_api_keys)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `user_api_keys`
+    /// who's primary key matches [`item_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use super::schema::user_api_keys::dsl::{id, user_api_keys};
+
+        user_api_keys.filter(id.eq(item_id)).first::<Self>(db)
+    }
+
+    /// Query [`db`](`Connection`)'s `user_api_keys` table for an entry
+    /// who's `key_hash` matches the hash of `key`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn find_by_key(db: &mut Connection, key: &str) -> QueryResult<Self> {
+        use super::schema::user_api_keys::dsl::{key_hash, user_api_keys};
+
+        user_api_keys
+            .filter(key_hash.eq(Self::hash_key(key)))
+            .first::<Self>(db)
+    }
+
+    /// Query [`db`](`Connection`) for all entries in the `user_api_keys` table
+    /// who's `user_id` matches the given [`item_user_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::user_api_keys::dsl::{created_at, user_api_keys, user_id};
+
+        user_api_keys
+            .filter(user_id.eq(item_user_id))
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
+    /// Set the `last_used_at` column of the entry in [`db`](`Connection`)'s `user_api_keys` table
+    /// who's primary key matches [`item_id`](`ID`) to the current time
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn mark_used(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_api_keys::dsl::{id, last_used_at, user_api_keys};
+
+        diesel::update(user_api_keys.filter(id.eq(item_id)))
+            .set(last_used_at.eq(Some(utc_now())))
+            .execute(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `user_api_keys` table who's
+    /// primary key matches [`item_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_api_keys::dsl::{id, user_api_keys};
+
+        diesel::delete(user_api_keys.filter(id.eq(item_id))).execute(db)
+    }
+}
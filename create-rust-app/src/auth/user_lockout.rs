@@ -0,0 +1,112 @@
+use super::schema::user_lockouts;
+use crate::diesel::{
+    insert_into, AsChangeset, Associations, ExpressionMethods, Identifiable, Insertable, QueryDsl,
+    Queryable, RunQueryDsl,
+};
+
+use super::user::User;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name=user_lockouts, primary_key(user_id), belongs_to(User))]
+/// Rust struct modeling an entry in the `user_lockouts` table
+pub struct UserLockout {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+
+    /// number of consecutive lockouts, used to grow the lockout duration
+    pub lockout_count: i32,
+    pub locked_until: Utc,
+
+    pub created_at: Utc,
+    #[cfg(not(feature = "database_sqlite"))]
+    pub updated_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=user_lockouts)]
+/// Rust struct modeling mutable data in an entry in the `user_lockouts` table
+pub struct UserLockoutChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub lockout_count: i32,
+    pub locked_until: Utc,
+}
+
+impl UserLockout {
+    /// Create an entry in [`db`](`Connection`)'s `user_lockouts` table using the data in [`item`](`UserLockoutChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &UserLockoutChangeset) -> QueryResult<Self> {
+        use super::schema::user_lockouts::dsl::user_lockouts;
+
+        insert_into(user_lockouts)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for the entry in the `user_lockouts`
+    /// table belonging to the User whose id is [`item_user_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read(db: &mut Connection, item_user_id: ID) -> QueryResult<Self> {
+        use super::schema::user_lockouts::dsl::{user_id, user_lockouts};
+
+        user_lockouts
+            .filter(user_id.eq(item_user_id))
+            .first::<Self>(db)
+    }
+
+    /// Update the entry in [`db`](`Connection`)'s `user_lockouts` table belonging to the User whose id is
+    /// [`item_user_id`](`ID`), with the data in [`item`](`UserLockoutChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn update(
+        db: &mut Connection,
+        item_user_id: ID,
+        item: &UserLockoutChangeset,
+    ) -> QueryResult<Self> {
+        use super::schema::user_lockouts::dsl::{user_id, user_lockouts};
+
+        diesel::update(user_lockouts.filter(user_id.eq(item_user_id)))
+            .set(item)
+            .get_result(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `user_lockouts` table belonging to the
+    /// User whose id is [`item_user_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::user_lockouts::dsl::{user_id, user_lockouts};
+
+        diesel::delete(user_lockouts.filter(user_id.eq(item_user_id))).execute(db)
+    }
+}
@@ -0,0 +1,113 @@
+use super::schema::user_login_links;
+use crate::diesel::{
+    insert_into, AsChangeset, Associations, BoolExpressionMethods, ExpressionMethods, Insertable,
+    QueryDsl, Queryable, RunQueryDsl,
+};
+
+use super::{user::User, utc_now, Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Associations, AsChangeset,
+)]
+#[diesel(table_name=user_login_links,belongs_to(User))]
+/// Rust struct modeling an entry in the `user_login_links` table
+///
+/// every login link which was emailed and not used yet, so each one can only be used once
+pub struct UserLoginLink {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    /// the `jti` claim of the login link token
+    pub token_id: String,
+    pub user_id: ID,
+    pub expires_at: Utc,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=user_login_links)]
+/// Rust struct modeling mutable data in an entry in the `user_login_links` table
+pub struct UserLoginLinkChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub token_id: String,
+    pub user_id: ID,
+    pub expires_at: Utc,
+}
+
+impl UserLoginLink {
+    /// Create an entry in [`db`](`Connection`)'s `user_login_links` table using the data in [`item`](`UserLoginLinkChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &UserLoginLinkChangeset) -> QueryResult<Self> {
+        use super::schema::user_login_links::dsl::user_login_links;
+
+        insert_into(user_login_links)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Delete the unexpired entry in [`db`](`Connection`)'s `user_login_links` table whose primary key is
+    /// `item_token_id` and which belongs to the User whose id is [`item_user_id`](`ID`)
+    ///
+    /// returns the number of deleted entries: a login link may only be used if this is 1,
+    /// the delete makes sure two requests can't both use the same link
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn consume(
+        db: &mut Connection,
+        item_token_id: &str,
+        item_user_id: ID,
+    ) -> QueryResult<usize> {
+        use super::schema::user_login_links::dsl::{
+            expires_at, token_id, user_id, user_login_links,
+        };
+
+        diesel::delete(
+            user_login_links.filter(
+                token_id
+                    .eq(item_token_id)
+                    .and(user_id.eq(item_user_id))
+                    .and(expires_at.gt(utc_now())),
+            ),
+        )
+        .execute(db)
+    }
+
+    /// when the newest entry in [`db`](`Connection`)'s `user_login_links` table which belongs to the User
+    /// whose id is [`item_user_id`](`ID`) was created, `None` if there isn't any
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn last_created_at(db: &mut Connection, item_user_id: ID) -> QueryResult<Option<Utc>> {
+        use super::schema::user_login_links::dsl::{created_at, user_id, user_login_links};
+
+        user_login_links
+            .filter(user_id.eq(item_user_id))
+            .select(diesel::dsl::max(created_at))
+            .first::<Option<Utc>>(db)
+    }
+
+    /// Delete every entry in [`db`](`Connection`)'s `user_login_links` table which expired before `before`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_expired(db: &mut Connection, before: Utc) -> QueryResult<usize> {
+        use super::schema::user_login_links::dsl::{expires_at, user_login_links};
+
+        diesel::delete(user_login_links.filter(expires_at.lt(before))).execute(db)
+    }
+}
@@ -0,0 +1,146 @@
+use super::schema::user_passkeys;
+use crate::diesel::{
+    insert_into, AsChangeset, Associations, ExpressionMethods, Identifiable, Insertable, QueryDsl,
+    Queryable, RunQueryDsl,
+};
+
+use super::{user::User, utc_now, Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name=user_passkeys,belongs_to(User))]
+/// Rust struct modeling an entry in the `user_passkeys` table
+///
+/// a passkey (WebAuthn credential) the user registered, see [`crate::auth::passkey`]
+pub struct UserPasskey {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub id: ID,
+    pub user_id: ID,
+    /// base64url encoded credential id
+    pub credential_id: String,
+    /// base64url encoded COSE public key
+    pub public_key: String,
+    pub sign_count: i64,
+    /// a name the user gave the passkey, so they can tell them apart
+    pub name: Option<String>,
+    pub last_used_at: Option<Utc>,
+
+    pub created_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=user_passkeys)]
+/// Rust struct modeling mutable data in an entry in the `user_passkeys` table
+pub struct UserPasskeyChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub credential_id: String,
+    pub public_key: String,
+    pub sign_count: i64,
+    pub name: Option<String>,
+    pub last_used_at: Option<Utc>,
+}
+
+impl UserPasskey {
+    /// Create an entry in [`db`](`Connection`)'s `user_passkeys` table using the data in [`item`](`UserPasskeyChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &UserPasskeyChangeset) -> QueryResult<Self> {
+        use super::schema::user_passkeys::dsl::user_passkeys;
+
+        insert_into(user_passkeys)
+            .values(item)
+            .get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `user_passkeys`
+    /// who's primary key matches [`item_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        use super::schema::user_passkeys::dsl::{id, user_passkeys};
+
+        user_passkeys.filter(id.eq(item_id)).first::<Self>(db)
+    }
+
+    /// Query [`db`](`Connection`)'s `user_passkeys` table for an entry
+    /// who's `credential_id` matches the given `item_credential_id`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn find_by_credential_id(
+        db: &mut Connection,
+        item_credential_id: &str,
+    ) -> QueryResult<Self> {
+        use super::schema::user_passkeys::dsl::{credential_id, user_passkeys};
+
+        user_passkeys
+            .filter(credential_id.eq(item_credential_id))
+            .first::<Self>(db)
+    }
+
+    /// Query [`db`](`Connection`) for all entries in the `user_passkeys` table
+    /// who's `user_id` matches the given [`item_user_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::user_passkeys::dsl::{created_at, user_id, user_passkeys};
+
+        user_passkeys
+            .filter(user_id.eq(item_user_id))
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
+    /// Record a successful login with the entry in [`db`](`Connection`)'s `user_passkeys` table
+    /// who's primary key matches [`item_id`](`ID`), storing the authenticator's new signature counter
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn mark_used(db: &mut Connection, item_id: ID, item_sign_count: i64) -> QueryResult<usize> {
+        use super::schema::user_passkeys::dsl::{id, last_used_at, sign_count, user_passkeys};
+
+        diesel::update(user_passkeys.filter(id.eq(item_id)))
+            .set((
+                sign_count.eq(item_sign_count),
+                last_used_at.eq(Some(utc_now())),
+            ))
+            .execute(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `user_passkeys` table who's
+    /// primary key matches [`item_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_passkeys::dsl::{id, user_passkeys};
+
+        diesel::delete(user_passkeys.filter(id.eq(item_id))).execute(db)
+    }
+}
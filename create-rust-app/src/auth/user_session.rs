@@ -33,6 +33,21 @@ pub struct UserSession {
     pub user_id: ID,
     pub refresh_token: String,
     pub device: Option<String>,
+    /// shared by all sessions created by refreshing the same login
+    pub family: String,
+    /// set once the refresh token has been exchanged for a new one,
+    /// presenting it again revokes the whole family
+    pub rotated: bool,
+    /// the client the session was started or last refreshed from
+    pub ip_address: Option<String>,
+    /// the `User-Agent` header the session was started or last refreshed with,
+    /// see [`user_agent::device_name`](`super::user_agent::device_name`)
+    pub user_agent: Option<String>,
+    /// set when the session belongs to an [`OAuthClient`](`super::OAuthClient`) (and not to the
+    /// app's own frontend), only that client can refresh it
+    pub oauth_client_id: Option<ID>,
+    /// when the user logged in, kept across refreshes
+    pub logged_in_at: Utc,
 
     pub created_at: Utc,
     #[cfg(not(feature = "database_sqlite"))]
@@ -52,6 +67,12 @@ pub struct UserSessionChangeset {
     pub user_id: ID,
     pub refresh_token: String,
     pub device: Option<String>,
+    pub family: String,
+    pub rotated: bool,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub oauth_client_id: Option<ID>,
+    pub logged_in_at: Utc,
 }
 
 impl UserSession {
@@ -94,8 +115,8 @@ impl UserSession {
             .first::<Self>(db)
     }
 
-    /// Read from [`db`](`Connection`), return entries of the `user_sessions` table,
-    /// paginated according to [`pagination`](`PaginationParams`)
+    /// Read from [`db`](`Connection`), return the entries of the `user_sessions` table
+    /// which have not been rotated, paginated according to [`pagination`](`PaginationParams`)
     ///
     /// # Errors
     /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
@@ -104,10 +125,11 @@ impl UserSession {
         pagination: &PaginationParams,
         item_user_id: ID,
     ) -> QueryResult<Vec<Self>> {
-        use super::schema::user_sessions::dsl::{created_at, user_id, user_sessions};
+        use super::schema::user_sessions::dsl::{created_at, rotated, user_id, user_sessions};
 
         user_sessions
             .filter(user_id.eq(item_user_id))
+            .filter(rotated.eq(false))
             .order(created_at)
             .limit(pagination.page_size)
             .offset(
@@ -120,16 +142,32 @@ impl UserSession {
             .load::<Self>(db)
     }
 
+    /// Read from [`db`](`Connection`), return all the entries of the `user_sessions` table
+    /// which belong to [`item_user_id`](`ID`) and have not been rotated
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all_for_user(db: &mut Connection, item_user_id: ID) -> QueryResult<Vec<Self>> {
+        use super::schema::user_sessions::dsl::{created_at, rotated, user_id, user_sessions};
+
+        user_sessions
+            .filter(user_id.eq(item_user_id))
+            .filter(rotated.eq(false))
+            .order(created_at)
+            .load::<Self>(db)
+    }
+
     /// Query [`db`](`Connection`) for all entries in the `user_sessions` table
-    /// who's `user_id` matches the given [`item_user_id`]
+    /// who's `user_id` matches the given [`item_user_id`] and which have not been rotated
     ///
     /// # Errors
     /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
     pub fn count_all(db: &mut Connection, item_user_id: ID) -> QueryResult<i64> {
-        use super::schema::user_sessions::dsl::{user_id, user_sessions};
+        use super::schema::user_sessions::dsl::{rotated, user_id, user_sessions};
 
         user_sessions
             .filter(user_id.eq(item_user_id))
+            .filter(rotated.eq(false))
             .count()
             .get_result(db)
     }
@@ -151,6 +189,25 @@ impl UserSession {
             .get_result(db)
     }
 
+    /// Mark the entry in [`db`](`Connection`)'s `user_sessions` table who's primary key matches
+    /// [`item_id`](`ID`) as rotated, unless it already is
+    ///
+    /// returns the number of updated entries, 0 means the session was already rotated
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn mark_rotated(db: &mut Connection, item_id: ID) -> QueryResult<usize> {
+        use super::schema::user_sessions::dsl::{id, rotated, user_sessions};
+
+        diesel::update(
+            user_sessions
+                .filter(id.eq(item_id))
+                .filter(rotated.eq(false)),
+        )
+        .set(rotated.eq(true))
+        .execute(db)
+    }
+
     /// Delete the entry in [`db`](`Connection`)'s `user_sessions` table who's
     /// primary key matches [`item_id`](`ID`)
     ///
@@ -172,4 +229,65 @@ impl UserSession {
 
         diesel::delete(user_sessions.filter(user_id.eq(item_user_id))).execute(db)
     }
+
+    /// Delete all entries in [`db`](`Connection`)'s `user_sessions` table who's
+    /// '`user_id`' matches [`item_user_id`](`ID`), except those in the family `keep_family`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_all_for_user_except_family(
+        db: &mut Connection,
+        item_user_id: ID,
+        keep_family: &str,
+    ) -> QueryResult<usize> {
+        use super::schema::user_sessions::dsl::{family, user_id, user_sessions};
+
+        diesel::delete(
+            user_sessions
+                .filter(user_id.eq(item_user_id))
+                .filter(family.ne(keep_family)),
+        )
+        .execute(db)
+    }
+
+    /// Delete all entries in [`db`](`Connection`)'s `user_sessions` table who's
+    /// '`oauth_client_id`' matches [`item_oauth_client_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_all_for_client(
+        db: &mut Connection,
+        item_oauth_client_id: ID,
+    ) -> QueryResult<usize> {
+        use super::schema::user_sessions::dsl::{oauth_client_id, user_sessions};
+
+        diesel::delete(user_sessions.filter(oauth_client_id.eq(item_oauth_client_id))).execute(db)
+    }
+
+    /// Delete all entries in [`db`](`Connection`)'s `user_sessions` table who's
+    /// '`family`' matches `item_family`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_family(db: &mut Connection, item_family: &str) -> QueryResult<usize> {
+        use super::schema::user_sessions::dsl::{family, user_sessions};
+
+        diesel::delete(user_sessions.filter(family.eq(item_family))).execute(db)
+    }
+
+    /// Delete all rotated entries in [`db`](`Connection`)'s `user_sessions` table created before `before`,
+    /// their refresh tokens have expired so they can't be presented again
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete_rotated_before(db: &mut Connection, before: Utc) -> QueryResult<usize> {
+        use super::schema::user_sessions::dsl::{created_at, rotated, user_sessions};
+
+        diesel::delete(
+            user_sessions
+                .filter(rotated.eq(true))
+                .filter(created_at.lt(before)),
+        )
+        .execute(db)
+    }
 }
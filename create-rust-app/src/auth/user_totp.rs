@@ -0,0 +1,127 @@
+use super::schema::user_totps;
+use crate::diesel::{
+    insert_into, AsChangeset, Associations, ExpressionMethods, Identifiable, Insertable, QueryDsl,
+    Queryable, RunQueryDsl,
+};
+
+use super::user::User;
+use super::{Utc, ID};
+use crate::database::Connection;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name=user_totps, primary_key(user_id), belongs_to(User))]
+/// Rust struct modeling an entry in the `user_totps` table,
+/// the TOTP second factor of a User
+pub struct UserTotp {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+
+    /// base32 encoded shared secret
+    pub secret: String,
+    /// false until the user proves their authenticator app works by submitting a code
+    pub confirmed: bool,
+    /// newline-separated hashes of the unused recovery codes
+    pub recovery_codes: String,
+    /// the last accepted time step, used to prevent replaying a code
+    pub last_used_step: Option<i64>,
+
+    pub created_at: Utc,
+    #[cfg(not(feature = "database_sqlite"))]
+    pub updated_at: Utc,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[tsync::tsync]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable, AsChangeset)]
+#[diesel(table_name=user_totps, treat_none_as_null = true)]
+/// Rust struct modeling mutable data in an entry in the `user_totps` table
+pub struct UserTotpChangeset {
+    /* -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+    Add columns here in the same order as the schema
+    Don't include non-mutable columns
+    (ex: id, created_at/updated_at)
+    -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=- */
+    pub user_id: ID,
+    pub secret: String,
+    pub confirmed: bool,
+    pub recovery_codes: String,
+    pub last_used_step: Option<i64>,
+}
+
+impl UserTotp {
+    /// Create an entry in [`db`](`Connection`)'s `user_totps` table using the data in [`item`](`UserTotpChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn create(db: &mut Connection, item: &UserTotpChangeset) -> QueryResult<Self> {
+        use super::schema::user_totps::dsl::user_totps;
+
+        insert_into(user_totps).values(item).get_result::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), querying for the entry in the `user_totps`
+    /// table belonging to the User whose id is [`item_user_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read(db: &mut Connection, item_user_id: ID) -> QueryResult<Self> {
+        use super::schema::user_totps::dsl::{user_id, user_totps};
+
+        user_totps
+            .filter(user_id.eq(item_user_id))
+            .first::<Self>(db)
+    }
+
+    /// Update the entry in [`db`](`Connection`)'s `user_totps` table belonging to the User whose id is
+    /// [`item_user_id`](`ID`), with the data in [`item`](`UserTotpChangeset`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn update(
+        db: &mut Connection,
+        item_user_id: ID,
+        item: &UserTotpChangeset,
+    ) -> QueryResult<Self> {
+        use super::schema::user_totps::dsl::{user_id, user_totps};
+
+        diesel::update(user_totps.filter(user_id.eq(item_user_id)))
+            .set(item)
+            .get_result(db)
+    }
+
+    /// Delete the entry in [`db`](`Connection`)'s `user_totps` table belonging to the
+    /// User whose id is [`item_user_id`](`ID`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn delete(db: &mut Connection, item_user_id: ID) -> QueryResult<usize> {
+        use super::schema::user_totps::dsl::{user_id, user_totps};
+
+        diesel::delete(user_totps.filter(user_id.eq(item_user_id))).execute(db)
+    }
+
+    /// the hashes of the recovery codes which have not been used yet
+    #[must_use]
+    pub fn recovery_code_hashes(&self) -> Vec<&str> {
+        self.recovery_codes
+            .lines()
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
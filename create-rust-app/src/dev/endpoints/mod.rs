@@ -7,3 +7,8 @@ pub use service_poem::api;
 mod service_actixweb;
 #[cfg(feature = "backend_actix-web")]
 pub use service_actixweb::endpoints;
+
+#[cfg(feature = "backend_axum")]
+mod service_axum;
+#[cfg(feature = "backend_axum")]
+pub use service_axum::router;
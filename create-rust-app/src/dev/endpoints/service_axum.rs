@@ -0,0 +1,16 @@
+use axum::{http::StatusCode, routing::post, Extension, Json, Router};
+
+use crate::dev::{controller, controller::MySqlQuery};
+
+use crate::Database;
+
+async fn query(
+    Extension(db): Extension<Database>,
+    Json(body): Json<MySqlQuery>,
+) -> Result<String, StatusCode> {
+    controller::query_db(&db, &body).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub fn router() -> Router {
+    Router::new().route("/db/query", post(query))
+}
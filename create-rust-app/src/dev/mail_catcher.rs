@@ -0,0 +1,275 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::OutgoingEmail;
+
+/// how many emails the development server keeps, older ones are dropped
+const MAX_CAUGHT_MAILS: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// an email the app sent while running under the development server, see [`forward`]
+pub struct CaughtMail {
+    pub id: u64,
+    /// when the development server received it, in seconds since the unix epoch
+    pub received_at: u64,
+    #[serde(flatten)]
+    pub email: OutgoingEmail,
+}
+
+/// the development server's inbox, kept in memory so it survives backend restarts (but not dev server restarts)
+#[derive(Debug, Default)]
+pub struct MailCatcher {
+    last_id: AtomicU64,
+    mails: Mutex<VecDeque<CaughtMail>>,
+}
+
+impl MailCatcher {
+    /// adds `email` to the inbox
+    ///
+    /// # Panics
+    /// * if the inbox's lock was poisoned
+    pub fn catch(&self, email: OutgoingEmail) -> CaughtMail {
+        let mail = CaughtMail {
+            id: self.last_id.fetch_add(1, Ordering::Relaxed) + 1,
+            received_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
+            email,
+        };
+
+        let mut mails = self.mails.lock().unwrap();
+        mails.push_front(mail.clone());
+        mails.truncate(MAX_CAUGHT_MAILS);
+
+        mail
+    }
+
+    /// the emails in the inbox, newest first
+    ///
+    /// # Panics
+    /// * if the inbox's lock was poisoned
+    #[must_use]
+    pub fn list(&self) -> Vec<CaughtMail> {
+        self.mails.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// # Panics
+    /// * if the inbox's lock was poisoned
+    #[must_use]
+    pub fn get(&self, id: u64) -> Option<CaughtMail> {
+        self.mails
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|mail| mail.id == id)
+            .cloned()
+    }
+
+    /// empties the inbox
+    ///
+    /// # Panics
+    /// * if the inbox's lock was poisoned
+    pub fn clear(&self) {
+        self.mails.lock().unwrap().clear();
+    }
+}
+
+/// sends `email` to the development server's inbox on a background thread, if the app was started by it
+/// (`DEV_SERVER_PORT` is set), then prints `summary` with the url of the inbox page showing it
+///
+/// returns `false` if there is no development server to send it to
+pub(crate) fn forward(email: &OutgoingEmail, summary: String) -> bool {
+    let Ok(port) = std::env::var("DEV_SERVER_PORT") else {
+        return false;
+    };
+
+    let email = email.clone();
+    std::thread::spawn(move || {
+        let caught = ureq::post(&format!("http://localhost:{port}/mail"))
+            .timeout(Duration::from_secs(5))
+            .send_json(&email)
+            .map_err(|error| error.to_string())
+            .and_then(|response| {
+                response
+                    .into_json::<CaughtMail>()
+                    .map_err(|error| error.to_string())
+            });
+
+        match caught {
+            Ok(caught) => println!("{summary}, see http://localhost:{port}/inbox/{}", caught.id),
+            Err(error) => println!(
+                "{summary}\nWARNING: Could not send the email to the dev server's inbox: {error}"
+            ),
+        }
+    });
+
+    true
+}
+
+/// escapes `text` so it can be embedded in html
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// escapes `text` and turns the `http(s)://` urls in it into links opening in a new tab,
+/// so activation and reset links in plain text emails can be clicked
+fn linkify(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let (before, from_url) = rest.split_at(start);
+        let end = from_url
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\''))
+            .unwrap_or(from_url.len());
+        let (url, after) = from_url.split_at(end);
+
+        html.push_str(&escape(before));
+        html.push_str(&format!(
+            r#"<a href="{url}" target="_blank" rel="noopener">{url}</a>"#,
+            url = escape(url)
+        ));
+        rest = after;
+    }
+    html.push_str(&escape(rest));
+
+    html
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        r#"<html style="background-color: #171717; color: white; font-family: sans-serif;">
+    <head>
+        <title>{title} | Create Rust App: Development Server</title>
+        <style>
+            a {{ color: skyblue; }}
+            table {{ width: 100%; border-collapse: collapse; }}
+            td, th {{ text-align: left; padding: 6px; border-bottom: 1px solid #333; }}
+            pre {{ white-space: pre-wrap; background-color: #222; padding: 12px; }}
+            iframe {{ width: 100%; height: 60vh; border: none; background-color: white; }}
+        </style>
+    </head>
+    <body style="padding: 20px 50px;">
+        {body}
+    </body>
+</html>"#,
+        title = escape(title),
+    )
+}
+
+/// the html page listing the emails in the inbox
+#[must_use]
+pub fn inbox_page(mails: &[CaughtMail]) -> String {
+    let rows = mails
+        .iter()
+        .map(|mail| {
+            format!(
+                r#"<tr><td>{to}</td><td><a href="/inbox/{id}">{subject}</a></td><td>{from}</td></tr>"#,
+                id = mail.id,
+                to = escape(&mail.email.to),
+                subject = escape(&mail.email.subject),
+                from = escape(&mail.email.from),
+            )
+        })
+        .collect::<String>();
+
+    let body = if mails.is_empty() {
+        "<p>No emails yet, the emails your app sends will show up here.</p>".to_string()
+    } else {
+        format!(
+            r#"<form method="post" action="/inbox/clear"><button type="submit">Clear inbox</button></form>
+        <table><tr><th>To</th><th>Subject</th><th>From</th></tr>{rows}</table>"#
+        )
+    };
+
+    page(
+        "Inbox",
+        &format!(r#"<h3><a href="/">Development Server</a> / Inbox</h3>{body}"#),
+    )
+}
+
+/// the html page showing `mail`, with a preview of its html version
+#[must_use]
+pub fn mail_page(mail: &CaughtMail) -> String {
+    page(
+        &mail.email.subject,
+        &format!(
+            r#"<h3><a href="/">Development Server</a> / <a href="/inbox">Inbox</a> / {subject}</h3>
+        <p>From: {from}<br />To: {to}</p>
+        <h4>HTML</h4>
+        <iframe sandbox="allow-popups allow-popups-to-escape-sandbox" src="/mail/{id}/html"></iframe>
+        <h4>Text</h4>
+        <pre>{text}</pre>"#,
+            id = mail.id,
+            subject = escape(&mail.email.subject),
+            from = escape(&mail.email.from),
+            to = escape(&mail.email.to),
+            text = linkify(&mail.email.text),
+        ),
+    )
+}
+
+/// the html version of `mail`, with its links opening in a new tab instead of the preview's frame
+#[must_use]
+pub fn mail_html(mail: &CaughtMail) -> String {
+    format!(r#"<base target="_blank">{}"#, mail.email.html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(subject: &str) -> OutgoingEmail {
+        OutgoingEmail {
+            from: "app@example.com".to_string(),
+            to: "alice@example.com".to_string(),
+            subject: subject.to_string(),
+            text: "Hello".to_string(),
+            html: "<p>Hello</p>".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_catch_keeps_the_newest_mails_first() {
+        let catcher = MailCatcher::default();
+        let first = catcher.catch(email("first"));
+        let second = catcher.catch(email("second"));
+
+        assert_eq!(
+            catcher
+                .list()
+                .iter()
+                .map(|mail| mail.email.subject.as_str())
+                .collect::<Vec<_>>(),
+            vec!["second", "first"]
+        );
+        assert_eq!(catcher.get(first.id).unwrap().email.subject, "first");
+        assert_ne!(first.id, second.id);
+
+        catcher.clear();
+        assert!(catcher.list().is_empty());
+        assert!(catcher.get(second.id).is_none());
+    }
+
+    #[test]
+    fn test_linkify_escapes_and_links_urls() {
+        assert_eq!(
+            linkify("Activate <here>: http://localhost:3000/activate?token=a&b=1\nthanks"),
+            "Activate &lt;here&gt;: <a href=\"http://localhost:3000/activate?token=a&amp;b=1\" target=\"_blank\" rel=\"noopener\">http://localhost:3000/activate?token=a&amp;b=1</a>\nthanks"
+        );
+        assert_eq!(linkify("no links"), "no links");
+    }
+}
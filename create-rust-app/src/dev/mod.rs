@@ -1,6 +1,7 @@
 mod backend_compiling_server;
 mod dev_server;
 mod frontend_dev_server;
+pub(crate) mod mail_catcher;
 
 pub mod controller;
 use cargo_metadata::CompilerMessage;
@@ -11,6 +12,7 @@ mod endpoints;
 use crate::util::net::find_free_port;
 use async_priority_channel as priority;
 pub use endpoints::*;
+pub use mail_catcher::CaughtMail;
 use std::iter::FromIterator;
 use std::path::PathBuf;
 use std::process::exit;
@@ -97,6 +99,8 @@ pub enum DevServerEvent {
     BackendStatus(bool),
     CompileSuccess(bool),
     CompileMessages(Vec<CompilerMessage>),
+    /// sent when the app sends an email, which the dev server keeps in its inbox (`/inbox`)
+    MailCaught(CaughtMail),
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -179,6 +183,11 @@ impl DevServerEvent {
                 })
                 .to_string()
             }
+            Self::MailCaught(mail) => json!({
+                "type": "mailCaught",
+                "mail": mail
+            })
+            .to_string(),
         }
     }
 }
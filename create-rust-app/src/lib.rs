@@ -3,6 +3,16 @@ compile_error!(
     "feature \"backend_actix-web\" and feature \"backend_poem\" cannot be enabled at the same time"
 );
 
+#[cfg(all(feature = "backend_axum", feature = "backend_actix-web"))]
+compile_error!(
+    "feature \"backend_axum\" and feature \"backend_actix-web\" cannot be enabled at the same time"
+);
+
+#[cfg(all(feature = "backend_axum", feature = "backend_poem"))]
+compile_error!(
+    "feature \"backend_axum\" and feature \"backend_poem\" cannot be enabled at the same time"
+);
+
 #[cfg(all(feature = "database_sqlite", feature = "database_postgres"))]
 compile_error!(
     "feature \"database_sqlite\" and feature \"database_postgres\" cannot be enabled at the same time"
@@ -10,7 +20,7 @@ compile_error!(
 
 // #[cfg(not(any(feature = "backend_poem", feature = "backend_actix-web")))]
 // compile_error!(
-//     "Please enable one of the backend features (options: 'backend_actix-web', 'backend-poem')"
+//     "Please enable one of the backend features (options: 'backend_actix-web', 'backend-poem', 'backend_axum')"
 // );
 
 mod util;
@@ -45,7 +55,11 @@ mod storage;
 pub use storage::{Attachment, AttachmentBlob, AttachmentData, Storage};
 
 mod mailer;
-pub use mailer::Mailer;
+pub use mailer::{
+    is_valid_locale, EmailAddress, FileMailTransport, HttpMailTransport, MailError, MailTransport,
+    MailTransportError, Mailer, OutgoingEmail, RenderedEmail, SendmailMailTransport, SmtpConfig,
+    SmtpMailTransport, SmtpTlsMode, StubMailTransport,
+};
 #[cfg(feature = "plugin_auth")]
 pub use mailer::{DefaultMailTemplates, EmailTemplates};
 
@@ -153,3 +167,17 @@ pub async fn not_found(_: poem::error::NotFoundError) -> poem::Response {
         .header("Content-Type", "application/json")
         .body(json.to_string())
 }
+
+#[cfg(feature = "backend_axum")]
+/// fallback for requests which didn't match any route,
+/// responds with the same json payload as poem's [`not_found`] handler
+pub async fn not_found() -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    let json = serde_json::json!({
+        "success": false,
+        "message": "Invalid endpoint"
+    });
+
+    (axum::http::StatusCode::NOT_FOUND, axum::Json(json)).into_response()
+}
@@ -0,0 +1,36 @@
+use super::MailError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// a syntactically valid email address, like `alice@example.com`
+///
+/// parse one with `"alice@example.com".parse::<EmailAddress>()`
+pub struct EmailAddress(lettre::Address);
+
+impl EmailAddress {
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+impl std::str::FromStr for EmailAddress {
+    type Err = MailError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self)
+            .map_err(|_| MailError::InvalidAddress(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for EmailAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for EmailAddress {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
@@ -0,0 +1,37 @@
+use super::MailTransportError;
+
+#[derive(Debug)]
+/// why an email could not be sent, see [`Mailer::send_async`](`crate::Mailer::send_async`)
+pub enum MailError {
+    /// the recipient, or `SMTP_FROM_ADDRESS`, isn't a valid email address
+    InvalidAddress(String),
+    /// the [`MailTransport`](`crate::MailTransport`) could not deliver the email
+    Transport(MailTransportError),
+    /// the background thread delivering emails stopped before it could deliver the email
+    QueueClosed,
+}
+
+impl std::fmt::Display for MailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidAddress(address) => write!(f, "invalid email address `{address}`"),
+            Self::Transport(error) => write!(f, "could not deliver the email: {error}"),
+            Self::QueueClosed => write!(f, "the mail queue was closed"),
+        }
+    }
+}
+
+impl std::error::Error for MailError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(error) => Some(error.as_ref()),
+            Self::InvalidAddress(_) | Self::QueueClosed => None,
+        }
+    }
+}
+
+impl From<MailTransportError> for MailError {
+    fn from(error: MailTransportError) -> Self {
+        Self::Transport(error)
+    }
+}
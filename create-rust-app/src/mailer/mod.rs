@@ -0,0 +1,527 @@
+#[cfg(feature = "plugin_auth")]
+use crate::auth::mail::{
+    auth_account_locked, auth_activated, auth_email_change, auth_email_change_notice,
+    auth_login_link, auth_new_device_login, auth_password_changed, auth_password_reset,
+    auth_recover_existent_account, auth_recover_nonexistent_account, auth_register,
+};
+#[cfg(feature = "plugin_auth")]
+use dyn_clone::{clone_trait_object, DynClone};
+
+use std::sync::Arc;
+
+mod address;
+mod error;
+mod queue;
+mod templates;
+mod transport;
+pub use address::EmailAddress;
+pub use error::MailError;
+pub use templates::{is_valid_locale, RenderedEmail};
+pub use transport::{
+    FileMailTransport, HttpMailTransport, MailTransport, MailTransportError, OutgoingEmail,
+    SendmailMailTransport, SmtpConfig, SmtpMailTransport, SmtpTlsMode, StubMailTransport,
+};
+
+// the DyncClone trait bound is for cloning, and the
+// Send trait bound is for thread-safety
+#[cfg(feature = "plugin_auth")]
+/// A trait that defines the behavior of an email template
+pub trait EmailTemplates: DynClone + Sync + Send {
+    fn send_activated(&self, mailer: &Mailer, to_email: &str);
+    fn send_password_changed(&self, mailer: &Mailer, to_email: &str);
+    fn send_password_reset(&self, mailer: &Mailer, to_email: &str);
+    fn send_recover_existent_account(&self, mailer: &Mailer, to_email: &str, link: &str);
+    fn send_recover_nonexistent_account(&self, mailer: &Mailer, to_email: &str, link: &str);
+    fn send_register(&self, mailer: &Mailer, to_email: &str, link: &str);
+    /// defaults to the built-in email, see [`DefaultMailTemplates`]
+    fn send_account_locked(&self, mailer: &Mailer, to_email: &str, locked_until: &str) {
+        auth_account_locked::send(mailer, to_email, locked_until);
+    }
+    /// defaults to the built-in email, linking to `link` on the [`default`](`DefaultMailTemplates::default`)
+    /// base url; override it if the app is served from elsewhere
+    fn send_login_link(&self, mailer: &Mailer, to_email: &str, link: &str) {
+        DefaultMailTemplates::default().send_login_link(mailer, to_email, link);
+    }
+    /// defaults to the built-in email, linking to `link` on the [`default`](`DefaultMailTemplates::default`)
+    /// base url; override it if the app is served from elsewhere
+    fn send_email_change(&self, mailer: &Mailer, to_email: &str, link: &str) {
+        DefaultMailTemplates::default().send_email_change(mailer, to_email, link);
+    }
+    /// defaults to the built-in email, see [`DefaultMailTemplates`]
+    fn send_email_change_notice(&self, mailer: &Mailer, to_email: &str, new_email: &str) {
+        auth_email_change_notice::send(mailer, to_email, new_email);
+    }
+    /// defaults to the built-in email, see [`DefaultMailTemplates`]
+    fn send_new_device_login(
+        &self,
+        mailer: &Mailer,
+        to_email: &str,
+        device: &str,
+        ip_address: &str,
+    ) {
+        auth_new_device_login::send(mailer, to_email, device, ip_address);
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+clone_trait_object!(EmailTemplates);
+
+#[derive(Clone)]
+/// struct used to handle sending emails
+pub struct Mailer {
+    /// the email address emails should be sent from
+    ///
+    /// set by the `SMTP_FROM_ADDRESS` environment variable
+    pub from_address: String,
+    /// the smtp server to connect to for purposes of sending emails
+    ///
+    /// set by the `SMTP_SERVER` environment variable
+    pub smtp_server: String,
+    /// username used to log into `SMTP_SERVER`
+    ///
+    /// set by the `SMTP_USERNAME` environment variable
+    pub smtp_username: String,
+    /// the password used to log into `SMTP_SERVER`
+    ///
+    /// set by the `SMTP_PASSWORD` environment variable
+    pub smtp_password: String,
+    /// whether or not emails should actually be sent when requested
+    ///
+    /// it may be useful to set this to false in some devolopment environments
+    /// while setting it to true in production
+    ///
+    /// set by the `SEND_MAIL` environment variable
+    pub actually_send: bool,
+    /// delivers the emails, see [`Mailer::with_transport`]
+    ///
+    /// chosen by the `MAIL_TRANSPORT` environment variable (`smtp`, `file`, `sendmail` or `http`),
+    /// a [`StubMailTransport`] when `SEND_MAIL` isn't `true`;
+    /// with the tasks plugin, the outbox when `MAIL_OUTBOX` is `true` (see [`Mailer::with_outbox`])
+    pub transport: Arc<dyn MailTransport>,
+    /// whether [`Mailer::send`] queues emails (see [`Mailer::enqueue`]) instead of waiting for them to be delivered,
+    /// `false` by default, see [`Mailer::with_fire_and_forget`]
+    pub fire_and_forget: bool,
+    /// the recipient's locale (like `fr-CA`), picks the mail templates [`Mailer::render`] uses,
+    /// see [`Mailer::with_locale`]
+    pub locale: Option<String>,
+    #[cfg(feature = "plugin_auth")]
+    // Structure containing email templates to be used for various purposes
+    pub templates: Box<dyn EmailTemplates + Sync + Send>,
+}
+
+impl Default for Mailer {
+    #[cfg(feature = "plugin_auth")]
+    fn default() -> Self {
+        Self::new(Box::<DefaultMailTemplates>::default())
+    }
+    #[cfg(not(feature = "plugin_auth"))]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mailer {
+    /// using information stored in the `SMTP_FROM_ADDRESS`, `SMTP_SERVER`, `SMTP_USERNAME`, `SMTP_PASSWORD`, and `SEND_MAIL`
+    /// environment variables to connect to a remote SMTP server,
+    ///
+    /// allows webservers to send emails to users for purposes
+    /// like marketing, user authentification, etc.
+    #[cfg(not(feature = "plugin_auth"))]
+    pub fn new() -> Self {
+        Mailer::check_environment_variables();
+
+        let from_address: String = std::env::var("SMTP_FROM_ADDRESS")
+            .unwrap_or_else(|_| "create-rust-app@localhost".to_string());
+        let smtp_server: String = std::env::var("SMTP_SERVER").unwrap_or_else(|_| "".to_string());
+        let smtp_username: String =
+            std::env::var("SMTP_USERNAME").unwrap_or_else(|_| "".to_string());
+        let smtp_password: String =
+            std::env::var("SMTP_PASSWORD").unwrap_or_else(|_| "".to_string());
+        let actually_send: bool = std::env::var("SEND_MAIL")
+            .unwrap_or_else(|_| "false".to_string())
+            .eq_ignore_ascii_case("true");
+        let transport =
+            Self::transport_from_env(actually_send, &smtp_server, &smtp_username, &smtp_password);
+        #[cfg(all(feature = "plugin_tasks", feature = "database_postgres"))]
+        let transport = Self::outbox_from_env(transport);
+        Mailer {
+            from_address,
+            smtp_server,
+            smtp_username,
+            smtp_password,
+            actually_send,
+            transport,
+            fire_and_forget: false,
+            locale: None,
+        }
+    }
+
+    #[cfg(feature = "plugin_auth")]
+    #[must_use]
+    pub fn new(templates: Box<dyn EmailTemplates + Sync + Send>) -> Self {
+        Self::check_environment_variables();
+
+        let from_address: String = std::env::var("SMTP_FROM_ADDRESS")
+            .unwrap_or_else(|_| "create-rust-app@localhost".to_string());
+        let smtp_server: String = std::env::var("SMTP_SERVER").unwrap_or_else(|_| String::new());
+        let smtp_username: String =
+            std::env::var("SMTP_USERNAME").unwrap_or_else(|_| String::new());
+        let smtp_password: String =
+            std::env::var("SMTP_PASSWORD").unwrap_or_else(|_| String::new());
+        let actually_send: bool = std::env::var("SEND_MAIL")
+            .unwrap_or_else(|_| "false".to_string())
+            .eq_ignore_ascii_case("true");
+        let transport =
+            Self::transport_from_env(actually_send, &smtp_server, &smtp_username, &smtp_password);
+        #[cfg(all(feature = "plugin_tasks", feature = "database_postgres"))]
+        let transport = Self::outbox_from_env(transport);
+        Self {
+            from_address,
+            smtp_server,
+            smtp_username,
+            smtp_password,
+            actually_send,
+            transport,
+            fire_and_forget: false,
+            locale: None,
+            templates,
+        }
+    }
+
+    /// delivers emails with `transport` instead of the one chosen by the environment variables,
+    /// regardless of `SEND_MAIL`
+    #[must_use]
+    pub fn with_transport(mut self, transport: impl MailTransport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// saves emails to the outbox instead of delivering them right away,
+    /// they're delivered (and retried) by the tasks queue, see [`tasks::outbox`](`crate::tasks::outbox`)
+    #[cfg(all(feature = "plugin_tasks", feature = "database_postgres"))]
+    #[must_use]
+    pub fn with_outbox(self) -> Self {
+        self.with_transport(crate::tasks::outbox::OutboxMailTransport)
+    }
+
+    /// renders emails for a recipient using `locale`, the auth plugin sets it to the user's stored locale
+    #[must_use]
+    pub fn with_locale(mut self, locale: Option<String>) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// makes [`Mailer::send`] queue emails instead of waiting for them to be delivered,
+    /// so request handlers don't block on the [`transport`](`Mailer::transport`)
+    #[must_use]
+    pub const fn with_fire_and_forget(mut self, fire_and_forget: bool) -> Self {
+        self.fire_and_forget = fire_and_forget;
+        self
+    }
+
+    /// the transport selected by the `MAIL_TRANSPORT` environment variable:
+    /// * `smtp` (the default): [`SmtpMailTransport`], also configured by `SMTP_PORT` and `SMTP_TLS`
+    ///   (`tls`, `starttls`, `opportunistic` or `none`, see [`SmtpTlsMode`])
+    /// * `file`: [`FileMailTransport`], writing to `MAIL_FILE_DIRECTORY` (`./mail` by default)
+    /// * `sendmail`: [`SendmailMailTransport`], running `SENDMAIL_COMMAND` if it's set
+    /// * `http`: [`HttpMailTransport`], posting to `MAIL_HTTP_URL` with the `MAIL_HTTP_API_KEY` bearer token
+    ///
+    /// falls back to a [`StubMailTransport`] (printing a warning) if the transport could not be set up
+    fn transport_from_env(
+        actually_send: bool,
+        smtp_server: &str,
+        smtp_username: &str,
+        smtp_password: &str,
+    ) -> Arc<dyn MailTransport> {
+        if !actually_send {
+            return Arc::new(StubMailTransport);
+        }
+
+        let transport: Result<Arc<dyn MailTransport>, MailTransportError> =
+            match std::env::var("MAIL_TRANSPORT")
+                .unwrap_or_else(|_| "smtp".to_string())
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "smtp" => Self::smtp_config_from_env(smtp_server, smtp_username, smtp_password)
+                    .and_then(|config| SmtpMailTransport::new(&config))
+                    .map(|transport| Arc::new(transport) as Arc<dyn MailTransport>),
+                "file" => {
+                    let directory = std::env::var("MAIL_FILE_DIRECTORY")
+                        .unwrap_or_else(|_| "./mail".to_string());
+                    std::fs::create_dir_all(&directory)
+                        .map(|()| {
+                            Arc::new(FileMailTransport::new(directory)) as Arc<dyn MailTransport>
+                        })
+                        .map_err(Into::into)
+                }
+                "sendmail" => Ok(Arc::new(std::env::var("SENDMAIL_COMMAND").map_or_else(
+                    |_| SendmailMailTransport::new(),
+                    SendmailMailTransport::with_command,
+                ))),
+                "http" => std::env::var("MAIL_HTTP_URL")
+                    .map(|url| {
+                        Arc::new(HttpMailTransport::new(
+                            url,
+                            std::env::var("MAIL_HTTP_API_KEY").ok(),
+                        )) as Arc<dyn MailTransport>
+                    })
+                    .map_err(|_| "MAIL_HTTP_URL must be set".into()),
+                other => Err(format!(
+                "unknown MAIL_TRANSPORT `{other}`, expected `smtp`, `file`, `sendmail` or `http`"
+            )
+                .into()),
+            };
+
+        transport.unwrap_or_else(|error| {
+            println!("Warning: Mailing disabled; {error}");
+            Arc::new(StubMailTransport)
+        })
+    }
+
+    /// an [`OutboxMailTransport`](`crate::tasks::outbox::OutboxMailTransport`) instead of `transport`
+    /// when the `MAIL_OUTBOX` environment variable is `true`
+    #[cfg(all(feature = "plugin_tasks", feature = "database_postgres"))]
+    fn outbox_from_env(transport: Arc<dyn MailTransport>) -> Arc<dyn MailTransport> {
+        if std::env::var("MAIL_OUTBOX").is_ok_and(|outbox| outbox.eq_ignore_ascii_case("true")) {
+            Arc::new(crate::tasks::outbox::OutboxMailTransport)
+        } else {
+            transport
+        }
+    }
+
+    /// the transport the environment variables select, ignoring `MAIL_OUTBOX`,
+    /// which delivers the emails saved to the outbox
+    #[cfg(all(feature = "plugin_tasks", feature = "database_postgres"))]
+    pub(crate) fn delivery_transport_from_env() -> Arc<dyn MailTransport> {
+        let env = |name| std::env::var(name).unwrap_or_default();
+
+        Self::transport_from_env(
+            env("SEND_MAIL").eq_ignore_ascii_case("true"),
+            &env("SMTP_SERVER"),
+            &env("SMTP_USERNAME"),
+            &env("SMTP_PASSWORD"),
+        )
+    }
+
+    fn smtp_config_from_env(
+        smtp_server: &str,
+        smtp_username: &str,
+        smtp_password: &str,
+    ) -> Result<SmtpConfig, MailTransportError> {
+        Ok(SmtpConfig {
+            server: smtp_server.to_string(),
+            port: std::env::var("SMTP_PORT")
+                .ok()
+                .map(|port| port.parse())
+                .transpose()?,
+            tls: std::env::var("SMTP_TLS").map_or(Ok(SmtpTlsMode::Tls), |tls| tls.parse())?,
+            username: smtp_username.to_string(),
+            password: smtp_password.to_string(),
+            ..SmtpConfig::default()
+        })
+    }
+
+    /// checks that the required environment variables are set
+    ///
+    /// prints messages denoting which, if any, of the required
+    /// environment variables were not set
+    ///
+    /// # Panics
+    ///
+    /// panics if any of the required environment variables aren't set properly
+    ///
+    /// TODO: it'd be better to return a Result and let the user handle the error
+    pub fn check_environment_variables() {
+        let vars = vec![
+            "SMTP_FROM_ADDRESS",
+            "SMTP_SERVER",
+            "SMTP_USERNAME",
+            "SMTP_PASSWORD",
+            "SEND_MAIL",
+        ];
+
+        let unset_vars = vars
+            .into_iter()
+            .filter(|v| std::env::var(v).is_err())
+            .collect::<Vec<_>>();
+
+        if !unset_vars.is_empty() {
+            println!(
+                "Warning: Mailing disabled; the following variables must be set: {}",
+                unset_vars.join(", ")
+            );
+        }
+
+        let send_mail_value = std::env::var("SEND_MAIL").unwrap_or_default();
+        if !send_mail_value.eq_ignore_ascii_case("true")
+            && !send_mail_value.eq_ignore_ascii_case("false")
+        {
+            println!("Warning: SEND_MAIL must be `true` or `false`");
+        }
+    }
+
+    /// send an email with the specifified content and subject to the specified user
+    ///
+    /// the email is delivered by the [`transport`](`Mailer::transport`), which only sends it
+    /// if the `SEND_MAIL` environment variable was set to true when this mailer was initialized
+    /// (or another transport was set with [`Mailer::with_transport`]);
+    /// in [`fire_and_forget`](`Mailer::fire_and_forget`) mode, it's queued instead
+    ///
+    /// failures, including an invalid `to` address, are printed, see [`Mailer::send_async`] to handle them
+    ///
+    /// # Arguments
+    /// * `to` - a string slice that holds the email address of the intended recipient
+    /// * `subject` - subject field of the email
+    /// * `text` - text content of the email
+    /// * `html` - html content of the email
+    pub fn send(&self, to: &str, subject: &str, text: &str, html: &str) {
+        let to = match to.parse::<EmailAddress>() {
+            Ok(to) => to,
+            Err(error) => {
+                println!("Could not send email: {error}");
+                return;
+            }
+        };
+
+        if self.fire_and_forget {
+            self.enqueue(&to, subject, text, html);
+        } else {
+            let _ = queue::deliver(
+                self.transport.as_ref(),
+                &self.email(&to, subject, text, html),
+            );
+        }
+    }
+
+    /// sends an email like [`Mailer::send`], on a background thread so it doesn't block the async runtime
+    ///
+    /// # Errors
+    /// * [`MailError::InvalidAddress`] if `SMTP_FROM_ADDRESS` isn't a valid email address
+    /// * [`MailError::Transport`] if the [`transport`](`Mailer::transport`) could not deliver the email
+    pub async fn send_async(
+        &self,
+        to: &EmailAddress,
+        subject: &str,
+        text: &str,
+        html: &str,
+    ) -> Result<(), MailError> {
+        self.from_address.parse::<EmailAddress>()?;
+
+        queue::deliver_async(self.transport.clone(), self.email(to, subject, text, html)).await
+    }
+
+    /// queues an email to be sent on a background thread and returns right away,
+    /// failures are only printed
+    pub fn enqueue(&self, to: &EmailAddress, subject: &str, text: &str, html: &str) {
+        queue::enqueue(self.transport.clone(), self.email(to, subject, text, html));
+    }
+
+    /// renders the `name` email from the project's mail templates, in the [`locale`](`Mailer::locale`):
+    /// `{name}.subject.txt`, `{name}.txt` and `{name}.html`, found with the `CRA_MAIL_TEMPLATES_GLOB`
+    /// environment variable (`backend/mail/templates/**/*` by default)
+    ///
+    /// each of them is read from the `{locale}/` directory (like `fr-CA/`) if it's there,
+    /// then from the `{language}/` one (like `fr/`), and then from the templates directory itself,
+    /// so templates can also extend a shared layout (`{% extends "layout.html" %}`)
+    ///
+    /// returns `None`, so callers can fall back to a built-in email, if one of them is missing
+    /// or could not be rendered (which is printed)
+    #[must_use]
+    pub fn render(&self, name: &str, context: &tera::Context) -> Option<RenderedEmail> {
+        templates::render(name, self.locale.as_deref(), context)
+    }
+
+    fn email(&self, to: &EmailAddress, subject: &str, text: &str, html: &str) -> OutgoingEmail {
+        OutgoingEmail {
+            from: self.from_address.clone(),
+            to: to.to_string(),
+            subject: subject.to_string(),
+            text: text.to_string(),
+            html: html.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "plugin_auth")]
+#[derive(Clone)]
+pub struct DefaultMailTemplates {
+    pub base_url: String,
+}
+#[cfg(feature = "plugin_auth")]
+impl DefaultMailTemplates {
+    #[must_use]
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+        }
+    }
+}
+#[cfg(feature = "plugin_auth")]
+impl Default for DefaultMailTemplates {
+    fn default() -> Self {
+        Self::new("http://localhost:3000/")
+    }
+}
+#[cfg(feature = "plugin_auth")]
+impl EmailTemplates for DefaultMailTemplates {
+    fn send_activated(&self, mailer: &Mailer, to_email: &str) {
+        auth_activated::send(mailer, to_email);
+    }
+    fn send_password_changed(&self, mailer: &Mailer, to_email: &str) {
+        auth_password_changed::send(mailer, to_email);
+    }
+    fn send_password_reset(&self, mailer: &Mailer, to_email: &str) {
+        auth_password_reset::send(mailer, to_email);
+    }
+    fn send_recover_existent_account(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_recover_existent_account::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_recover_nonexistent_account(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_recover_nonexistent_account::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_register(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_register::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_account_locked(&self, mailer: &Mailer, to_email: &str, locked_until: &str) {
+        auth_account_locked::send(mailer, to_email, locked_until);
+    }
+    fn send_login_link(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_login_link::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_email_change(&self, mailer: &Mailer, to_email: &str, url_path: &str) {
+        auth_email_change::send(
+            mailer,
+            to_email,
+            format!("{base_url}{url_path}", base_url = self.base_url).as_str(),
+        );
+    }
+    fn send_email_change_notice(&self, mailer: &Mailer, to_email: &str, new_email: &str) {
+        auth_email_change_notice::send(mailer, to_email, new_email);
+    }
+    fn send_new_device_login(
+        &self,
+        mailer: &Mailer,
+        to_email: &str,
+        device: &str,
+        ip_address: &str,
+    ) {
+        auth_new_device_login::send(mailer, to_email, device, ip_address);
+    }
+}
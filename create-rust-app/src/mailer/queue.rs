@@ -0,0 +1,171 @@
+use super::{MailError, MailTransport, OutgoingEmail};
+use futures_channel::oneshot;
+use lazy_static::lazy_static;
+use std::sync::{mpsc, Arc, Mutex};
+
+/// how many background threads deliver emails
+const WORKERS: usize = 4;
+
+/// an email waiting to be delivered by one of the [`WORKERS`]
+struct Delivery {
+    transport: Arc<dyn MailTransport>,
+    email: OutgoingEmail,
+    /// where the result is sent, `None` when nobody waits for it
+    reply: Option<oneshot::Sender<Result<(), MailError>>>,
+}
+
+lazy_static! {
+    static ref QUEUE: Mutex<mpsc::Sender<Delivery>> = Mutex::new(start_workers());
+}
+
+fn start_workers() -> mpsc::Sender<Delivery> {
+    let (sender, receiver) = mpsc::channel::<Delivery>();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for _ in 0..WORKERS {
+        let receiver = receiver.clone();
+        std::thread::spawn(move || loop {
+            let Ok(delivery) = receiver.lock().map(|receiver| receiver.recv()) else {
+                return;
+            };
+            let Ok(delivery) = delivery else {
+                return;
+            };
+
+            let result = deliver(delivery.transport.as_ref(), &delivery.email);
+            if let Some(reply) = delivery.reply {
+                // the caller may have stopped waiting, that's fine
+                let _ = reply.send(result);
+            }
+        });
+    }
+
+    sender
+}
+
+fn push(delivery: Delivery) -> Result<(), MailError> {
+    QUEUE
+        .lock()
+        .map_err(|_| MailError::QueueClosed)?
+        .send(delivery)
+        .map_err(|_| MailError::QueueClosed)
+}
+
+/// delivers `email` with `transport` on the current thread, and prints it
+///
+/// when running under the development server, it's put in the dev server's inbox (from a background thread)
+/// instead of being printed in full
+pub(super) fn deliver(
+    transport: &dyn MailTransport,
+    email: &OutgoingEmail,
+) -> Result<(), MailError> {
+    let result = transport.send(email).map_err(MailError::from);
+    let action = if transport.queues() { "Queued" } else { "Sent" };
+
+    #[cfg(all(feature = "plugin_dev", debug_assertions))]
+    if crate::dev::mail_catcher::forward(
+        email,
+        format!("{action} email {result:?} to {:?}", email.to),
+    ) {
+        return result;
+    }
+
+    println!(
+        r#"====================
+{} email {:#?}
+--------------------
+to: {:?}
+from: {}
+message:
+{}
+===================="#,
+        action, result, email.to, email.from, email.text
+    );
+
+    result
+}
+
+/// delivers `email` with `transport` on a background thread, waiting for the result without blocking
+pub(super) async fn deliver_async(
+    transport: Arc<dyn MailTransport>,
+    email: OutgoingEmail,
+) -> Result<(), MailError> {
+    let (reply, result) = oneshot::channel();
+    push(Delivery {
+        transport,
+        email,
+        reply: Some(reply),
+    })?;
+
+    result.await.map_err(|_| MailError::QueueClosed)?
+}
+
+/// delivers `email` with `transport` on a background thread, failures are only printed
+pub(super) fn enqueue(transport: Arc<dyn MailTransport>, email: OutgoingEmail) {
+    if let Err(error) = push(Delivery {
+        transport,
+        email,
+        reply: None,
+    }) {
+        println!("Could not queue email: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MailTransportError;
+
+    struct RecordingTransport(Mutex<Vec<String>>);
+
+    impl MailTransport for RecordingTransport {
+        fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError> {
+            if email.to.starts_with("fail") {
+                return Err("rejected".into());
+            }
+            self.0.lock().unwrap().push(email.to.clone());
+            Ok(())
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        struct ThreadWaker(std::thread::Thread);
+        impl std::task::Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut context = std::task::Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    fn email(to: &str) -> OutgoingEmail {
+        OutgoingEmail {
+            from: "app@example.com".to_string(),
+            to: to.to_string(),
+            subject: "Welcome".to_string(),
+            text: "Hello".to_string(),
+            html: "<p>Hello</p>".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_deliver_async_reports_the_result() {
+        let transport = Arc::new(RecordingTransport(Mutex::new(vec![])));
+
+        block_on(deliver_async(transport.clone(), email("alice@example.com"))).unwrap();
+        assert!(matches!(
+            block_on(deliver_async(transport.clone(), email("fail@example.com"))),
+            Err(MailError::Transport(_))
+        ));
+        assert_eq!(*transport.0.lock().unwrap(), vec!["alice@example.com"]);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::util::workspace_utils::mail_templates_glob;
+use once_cell::sync::OnceCell;
+use tera::{Context, Tera};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// an email rendered from the project's mail templates, see [`Mailer::render`](`crate::Mailer::render`)
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// the project's mail templates, `None` if it doesn't have any (or they could not be parsed)
+///
+/// they're read from `CRA_MAIL_TEMPLATES_GLOB` (`backend/mail/templates/**/*` by default) the first time an email is rendered
+fn templates() -> Option<&'static Tera> {
+    static TEMPLATES: OnceCell<Option<Tera>> = OnceCell::new();
+
+    TEMPLATES
+        .get_or_init(|| {
+            let glob = mail_templates_glob();
+            match Tera::new(glob) {
+                Ok(tera) if tera.get_template_names().next().is_some() => Some(tera),
+                Ok(_) => None,
+                Err(error) => {
+                    println!(
+                        "Warning: using the built-in mail templates; could not parse `{glob}`: {error}"
+                    );
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// whether `locale` looks like a language tag (`en`, `fr-CA`, `pt_BR`, ...), so it's safe to use in template names
+#[must_use]
+pub fn is_valid_locale(locale: &str) -> bool {
+    (1..=35).contains(&locale.len())
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// the directories a template is looked up in for `locale`, most specific first:
+/// `fr-CA/`, then `fr/`, then the templates directory itself
+fn locale_prefixes(locale: Option<&str>) -> Vec<String> {
+    let mut prefixes = vec![];
+
+    if let Some(locale) = locale.filter(|locale| is_valid_locale(locale)) {
+        prefixes.push(format!("{locale}/"));
+        if let Some((language, _)) = locale.split_once(['-', '_']) {
+            prefixes.push(format!("{language}/"));
+        }
+    }
+    prefixes.push(String::new());
+
+    prefixes
+}
+
+/// renders `{name}.subject.txt`, `{name}.txt` and `{name}.html` from `tera`, each from the most specific
+/// [locale directory](`locale_prefixes`) that has it; `None` if one of them is missing or fails to render
+fn render_with(
+    tera: &Tera,
+    name: &str,
+    locale: Option<&str>,
+    context: &Context,
+) -> Option<RenderedEmail> {
+    let prefixes = locale_prefixes(locale);
+    let render = |file: String| {
+        let template = prefixes
+            .iter()
+            .map(|prefix| format!("{prefix}{file}"))
+            .find(|template| tera.get_template(template).is_ok())?;
+
+        tera.render(&template, context)
+            .map_err(|error| {
+                println!("Warning: could not render the `{template}` mail template: {error:?}")
+            })
+            .ok()
+    };
+
+    Some(RenderedEmail {
+        subject: render(format!("{name}.subject.txt"))?.trim().to_string(),
+        text: render(format!("{name}.txt"))?,
+        html: render(format!("{name}.html"))?,
+    })
+}
+
+/// renders the `name` email from the project's mail templates, see [`render_with`]
+pub(crate) fn render(name: &str, locale: Option<&str>, context: &Context) -> Option<RenderedEmail> {
+    render_with(templates()?, name, locale, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_picks_the_most_specific_locale() {
+        let mut tera = Tera::default();
+        tera.add_raw_templates(vec![
+            (
+                "layout.html",
+                "<main>{% block content %}{% endblock %}</main>",
+            ),
+            ("welcome.subject.txt", "Welcome\n"),
+            ("welcome.txt", "Hello {{ name }}"),
+            (
+                "welcome.html",
+                r#"{% extends "layout.html" %}{% block content %}Hello {{ name }}{% endblock %}"#,
+            ),
+            ("fr/welcome.subject.txt", "Bienvenue"),
+            (
+                "fr/welcome.html",
+                r#"{% extends "layout.html" %}{% block content %}Bonjour {{ name }}{% endblock %}"#,
+            ),
+        ])
+        .unwrap();
+        let mut context = Context::new();
+        context.insert("name", "<Alice>");
+
+        let email = render_with(&tera, "welcome", Some("fr-CA"), &context).unwrap();
+        assert_eq!(email.subject, "Bienvenue");
+        assert_eq!(email.text, "Hello <Alice>");
+        assert_eq!(email.html, "<main>Bonjour &lt;Alice&gt;</main>");
+
+        let email = render_with(&tera, "welcome", Some("../fr"), &context).unwrap();
+        assert_eq!(email.subject, "Welcome");
+
+        assert_eq!(render_with(&tera, "goodbye", None, &context), None);
+    }
+}
@@ -0,0 +1,358 @@
+use lettre::message::{Message, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::transport::smtp::PoolConfig;
+use lettre::{FileTransport, SendmailTransport, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// why a [`MailTransport`] could not deliver an email
+pub type MailTransportError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// an email, as handed to a [`MailTransport`] by [`Mailer::send`](`crate::Mailer::send`)
+pub struct OutgoingEmail {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+impl OutgoingEmail {
+    /// the email as a multipart (plain text and html) [`Message`]
+    ///
+    /// # Errors
+    /// * `from` or `to` isn't a valid email address
+    pub fn to_message(&self) -> Result<Message, MailTransportError> {
+        Ok(Message::builder()
+            .to(self.to.parse()?)
+            .from(self.from.parse()?)
+            .subject(&self.subject)
+            .multipart(MultiPart::alternative_plain_html(
+                self.text.clone(),
+                self.html.clone(),
+            ))?)
+    }
+}
+
+/// delivers the emails sent with a [`Mailer`](`crate::Mailer`), see [`Mailer::with_transport`](`crate::Mailer::with_transport`)
+///
+/// implemented by [`SmtpMailTransport`], [`FileMailTransport`], [`SendmailMailTransport`],
+/// [`HttpMailTransport`] and [`StubMailTransport`]
+pub trait MailTransport: Send + Sync {
+    /// delivers `email`
+    ///
+    /// # Errors
+    /// * the email could not be delivered
+    fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError>;
+
+    /// whether [`send`](`MailTransport::send`) only saves the email to be delivered later,
+    /// like the tasks plugin's outbox, instead of delivering it
+    fn queues(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// how [`SmtpMailTransport`] secures its connections
+pub enum SmtpTlsMode {
+    /// TLS from the start of the connection ("SMTPS"), port 465 by default
+    Tls,
+    /// upgrade the connection with `STARTTLS`, failing if the server doesn't support it, port 587 by default
+    StartTls,
+    /// upgrade the connection with `STARTTLS` if the server supports it, port 587 by default
+    Opportunistic,
+    /// plain text, only use this for servers on the local network, port 25 by default
+    None,
+}
+
+impl std::str::FromStr for SmtpTlsMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "tls" => Ok(Self::Tls),
+            "starttls" => Ok(Self::StartTls),
+            "opportunistic" => Ok(Self::Opportunistic),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "unknown TLS mode `{s}`, expected `tls`, `starttls`, `opportunistic` or `none`"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// where and how [`SmtpMailTransport`] connects
+pub struct SmtpConfig {
+    pub server: String,
+    /// overrides the default port of the [`tls`](`SmtpConfig::tls`) mode
+    pub port: Option<u16>,
+    pub tls: SmtpTlsMode,
+    /// no authentication is attempted when empty
+    pub username: String,
+    pub password: String,
+    /// how many connections are kept open and reused
+    pub max_pool_size: u32,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            server: String::new(),
+            port: None,
+            tls: SmtpTlsMode::Tls,
+            username: String::new(),
+            password: String::new(),
+            max_pool_size: 10,
+        }
+    }
+}
+
+#[derive(Clone)]
+/// sends emails through an SMTP server, reusing a pool of connections
+pub struct SmtpMailTransport {
+    transport: SmtpTransport,
+}
+
+impl SmtpMailTransport {
+    /// no connection is made until the first email is sent
+    ///
+    /// # Errors
+    /// * the TLS parameters for [`config.server`](`SmtpConfig::server`) could not be set up
+    pub fn new(config: &SmtpConfig) -> Result<Self, MailTransportError> {
+        let mut builder = match config.tls {
+            SmtpTlsMode::Tls => SmtpTransport::relay(&config.server)?,
+            SmtpTlsMode::StartTls => SmtpTransport::starttls_relay(&config.server)?,
+            SmtpTlsMode::Opportunistic => SmtpTransport::builder_dangerous(&config.server)
+                .port(587)
+                .tls(Tls::Opportunistic(TlsParameters::new(
+                    config.server.clone(),
+                )?)),
+            SmtpTlsMode::None => SmtpTransport::builder_dangerous(&config.server),
+        };
+
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if !config.username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                config.username.clone(),
+                config.password.clone(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder
+                .pool_config(PoolConfig::new().max_size(config.max_pool_size))
+                .build(),
+        })
+    }
+}
+
+impl MailTransport for SmtpMailTransport {
+    fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError> {
+        self.transport.send(&email.to_message()?)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+/// writes each email to a `.eml` file in a directory instead of sending it, useful for local debugging
+pub struct FileMailTransport {
+    directory: PathBuf,
+    transport: FileTransport,
+}
+
+impl FileMailTransport {
+    /// `directory` must exist, see [`FileMailTransport::directory`]
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        let directory = directory.into();
+
+        Self {
+            transport: FileTransport::new(&directory),
+            directory,
+        }
+    }
+
+    /// where the `.eml` files are written
+    #[must_use]
+    pub fn directory(&self) -> &std::path::Path {
+        &self.directory
+    }
+}
+
+impl MailTransport for FileMailTransport {
+    fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError> {
+        self.transport.send(&email.to_message()?)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+/// hands emails to the local `sendmail` command
+pub struct SendmailMailTransport {
+    transport: SendmailTransport,
+}
+
+impl SendmailMailTransport {
+    /// uses the `sendmail` command found in the `PATH`
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            transport: SendmailTransport::new(),
+        }
+    }
+
+    /// uses the given command instead of `sendmail`, like `/usr/sbin/sendmail`
+    pub fn with_command(command: impl Into<std::ffi::OsString>) -> Self {
+        Self {
+            transport: SendmailTransport::new_with_command(command),
+        }
+    }
+}
+
+impl Default for SendmailMailTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MailTransport for SendmailMailTransport {
+    fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError> {
+        self.transport.send(&email.to_message()?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// sends emails by POSTing them as Json to the API of a mail provider (or a proxy in front of it)
+///
+/// the body is the [`OutgoingEmail`]: `{"from": ..., "to": ..., "subject": ..., "text": ..., "html": ...}`,
+/// any non-2xx response is an error
+pub struct HttpMailTransport {
+    pub url: String,
+    /// sent as a bearer token in the `Authorization` header, if set
+    pub api_key: Option<String>,
+    /// how long to wait for the API to respond
+    pub timeout: std::time::Duration,
+}
+
+impl HttpMailTransport {
+    pub fn new(url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            api_key,
+            timeout: std::time::Duration::from_secs(30),
+        }
+    }
+}
+
+impl MailTransport for HttpMailTransport {
+    fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError> {
+        let mut request = ureq::post(&self.url).timeout(self.timeout);
+        if let Some(api_key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {api_key}"));
+        }
+
+        request.send_json(email)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// doesn't deliver emails, the [`Mailer`](`crate::Mailer`) uses it when `SEND_MAIL` isn't `true`
+pub struct StubMailTransport;
+
+impl MailTransport for StubMailTransport {
+    fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError> {
+        email.to_message()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    fn email() -> OutgoingEmail {
+        OutgoingEmail {
+            from: "app@example.com".to_string(),
+            to: "alice@example.com".to_string(),
+            subject: "Welcome".to_string(),
+            text: "Hello Alice".to_string(),
+            html: "<p>Hello Alice</p>".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_file_transport_writes_eml_files() {
+        let directory = std::env::temp_dir().join(format!("cra-mail-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        FileMailTransport::new(&directory).send(&email()).unwrap();
+
+        let files = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].extension().unwrap(), "eml");
+        assert!(std::fs::read_to_string(&files[0])
+            .unwrap()
+            .contains("Subject: Welcome"));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_http_transport_posts_json() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/send", listener.local_addr().unwrap());
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut headers = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                headers.push(line.trim().to_string());
+            }
+            let content_length = headers
+                .iter()
+                .find_map(|header| header.strip_prefix("Content-Length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+
+            (
+                headers,
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            )
+        });
+
+        HttpMailTransport::new(url, Some("secret".to_string()))
+            .send(&email())
+            .unwrap();
+
+        let (headers, body) = server.join().unwrap();
+        assert_eq!(headers[0], "POST /send HTTP/1.1");
+        assert!(headers.contains(&"Authorization: Bearer secret".to_string()));
+        assert_eq!(body["to"], "alice@example.com");
+        assert_eq!(body["html"], "<p>Hello Alice</p>");
+    }
+}
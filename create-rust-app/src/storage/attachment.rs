@@ -134,12 +134,12 @@ impl Attachment {
         upload_result
     }
 
-    /// in poem, we need to pass in the pool itself because the Connection is not Send+Sync which poem handlers require
+    /// in poem and axum, we need to pass in the pool itself because the Connection is not Send+Sync which their handlers require
     ///
     /// # Errors
     /// * Diesel error
     #[allow(clippy::too_many_arguments)]
-    #[cfg(feature = "backend_poem")]
+    #[cfg(any(feature = "backend_poem", feature = "backend_axum"))]
     pub async fn attach(
         pool: std::sync::Arc<&crate::database::Pool>,
         storage: &Storage,
@@ -150,7 +150,7 @@ impl Attachment {
         allow_multiple: bool,
         overwrite_existing: bool,
     ) -> Result<String, String> {
-        let mut db = pool.clone().get().unwrap();
+        let mut db = diesel_logger::LoggingConnection::new(pool.clone().get().unwrap());
 
         let checksum = format!("{:x}", md5::compute(&data.data));
         let file_name = data.file_name.clone();
@@ -253,7 +253,7 @@ impl Attachment {
         Ok(())
     }
 
-    /// in poem, we need to pass in the pool itself because the Connection is not Send+Sync which poem handlers require
+    /// in poem and axum, we need to pass in the pool itself because the Connection is not Send+Sync which their handlers require
     ///
     /// # Errors
     /// * Diesel error
@@ -261,13 +261,13 @@ impl Attachment {
     /// # Panics
     /// * If the pool is unable to get a connection
     #[allow(clippy::too_many_arguments)]
-    #[cfg(feature = "backend_poem")]
+    #[cfg(any(feature = "backend_poem", feature = "backend_axum"))]
     pub async fn detach(
         pool: std::sync::Arc<&crate::database::Pool>,
         storage: &Storage,
         item_id: ID,
     ) -> Result<(), String> {
-        let mut db = pool.get().unwrap();
+        let mut db = diesel_logger::LoggingConnection::new(pool.get().unwrap());
 
         let attached =
             Self::find_by_id(&mut db, item_id).map_err(|_| "Could not load attachment")?;
@@ -306,6 +306,31 @@ impl Attachment {
     ) -> Result<(), String> {
         let attached = Self::find_all_for_record(db, name, record_type, record_id)
             .map_err(|_| "Could not load attachments")?;
+
+        Self::detach_many(db, storage, attached).await
+    }
+
+    /// detaches every attachment of the record, whatever its name
+    ///
+    /// # Errors
+    /// * Diesel error
+    pub async fn detach_all_for_record(
+        db: &mut Connection,
+        storage: &Storage,
+        record_type: String,
+        record_id: ID,
+    ) -> Result<(), String> {
+        let attached = Self::find_all_for_record_any_name(db, record_type, record_id)
+            .map_err(|_| "Could not load attachments")?;
+
+        Self::detach_many(db, storage, attached).await
+    }
+
+    async fn detach_many(
+        db: &mut Connection,
+        storage: &Storage,
+        attached: Vec<Self>,
+    ) -> Result<(), String> {
         let attached_ids = attached
             .iter()
             .map(|attached| attached.id)
@@ -388,6 +413,21 @@ impl Attachment {
             .get_results::<Self>(db)
     }
 
+    /// Find all attachments for a given record type and record id, whatever their name
+    ///
+    /// # Errors
+    /// * Diesel error
+    pub fn find_all_for_record_any_name(
+        db: &mut Connection,
+        item_record_type: String,
+        item_record_id: ID,
+    ) -> QueryResult<Vec<Self>> {
+        schema::attachments::table
+            .filter(schema::attachments::record_type.eq(item_record_type))
+            .filter(schema::attachments::record_id.eq(item_record_id))
+            .get_results::<Self>(db)
+    }
+
     /// Find all attachments for a given record type and record ids
     ///
     /// # Errors
@@ -0,0 +1,89 @@
+//! completes the account deletions whose grace period is over (see [`account`](`crate::auth::account`))
+//!
+//! call [`schedule`] once at the start of the app, the [`CompleteAccountDeletions`] task then runs every
+//! hour in the `sync` queue (see [`queue`](`super::queue`)); apps without the tasks plugin have to run
+//! [`complete_deletions`](`crate::auth::account::complete_deletions`) periodically themselves
+use fang::serde::{Deserialize as TaskDeserialize, Serialize as TaskSerialize};
+use fang::typetag;
+use fang::{FangError, Queueable, Runnable, Scheduled};
+
+use crate::auth::account::{self, CompletedDeletions};
+use crate::Database;
+
+/// when [`CompleteAccountDeletions`] runs: at the start of every hour
+//                     sec  min  hour  day of month  month  day of week  year
+const SCHEDULE: &str = "0 0 * * * * *";
+
+#[derive(Debug, Default, TaskSerialize, TaskDeserialize)]
+#[serde(crate = "fang::serde")]
+/// deletes every account whose grace period is over, and their attachments when the storage plugin is enabled
+pub struct CompleteAccountDeletions {}
+
+#[typetag::serde]
+impl Runnable for CompleteAccountDeletions {
+    fn run(&self, _queue: &dyn Queueable) -> Result<(), FangError> {
+        let deletions = complete_deletions().map_err(|description| FangError { description })?;
+
+        if deletions.failed.is_empty() {
+            return Ok(());
+        }
+
+        // the other accounts were deleted, the failed ones are tried again on the next run
+        Err(FangError {
+            description: deletions
+                .failed
+                .iter()
+                .map(|(user_id, error)| format!("could not delete account {user_id}: {error}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+    }
+
+    fn task_type(&self) -> String {
+        "sync".to_string()
+    }
+
+    // it's rescheduled after every run, so there's only ever one of it in the queue
+    fn uniq(&self) -> bool {
+        true
+    }
+
+    fn cron(&self) -> Option<Scheduled> {
+        Some(Scheduled::CronPattern(SCHEDULE.to_string()))
+    }
+
+    // the next run retries the accounts which could not be deleted
+    fn max_retries(&self) -> i32 {
+        0
+    }
+}
+
+fn complete_deletions() -> Result<CompletedDeletions, String> {
+    let mut db = Database::new()
+        .get_connection()
+        .map_err(|error| error.to_string())?;
+
+    #[cfg(feature = "plugin_storage")]
+    return tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|error| error.to_string())?
+        .block_on(account::complete_deletions_with_attachments(
+            &mut db,
+            &crate::Storage::new(),
+        ))
+        .map_err(|error| error.to_string());
+
+    #[cfg(not(feature = "plugin_storage"))]
+    account::complete_deletions(&mut db).map_err(|error| error.to_string())
+}
+
+/// adds [`CompleteAccountDeletions`] to the `sync` queue, unless it's already there
+///
+/// # Errors
+/// * [`fang::QueueError`] if no connection is available, or the task could not be saved
+pub fn schedule() -> Result<(), fang::QueueError> {
+    super::queue().schedule_task(&CompleteAccountDeletions {})?;
+
+    Ok(())
+}
@@ -5,6 +5,13 @@ use std::sync::Mutex;
 // re-export setup for tasks
 pub use crate::setup;
 
+#[cfg(feature = "plugin_auth")]
+pub mod account_deletions;
+#[cfg(feature = "database_postgres")]
+pub mod outbox;
+#[cfg(feature = "database_postgres")]
+mod schema;
+
 /// Returns a reference to the synchronous queue.
 ///
 /// Tasks scheduled in this queue will be executed by the synchronous queue process.
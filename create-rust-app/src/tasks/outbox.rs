@@ -0,0 +1,354 @@
+//! a durable outbox for the emails sent with a [`Mailer`](`crate::Mailer`)
+//!
+//! with the outbox enabled (`MAIL_OUTBOX=true`, or [`Mailer::with_outbox`](`crate::Mailer::with_outbox`)),
+//! emails are saved to the `mail_outbox` table and delivered by a [`DeliverOutboxMail`] task
+//! running in the `sync` queue (see [`queue`](`super::queue`)), which retries failed deliveries with
+//! an exponential backoff; an email that still could not be delivered after [`MAX_RETRIES`] retries
+//! is marked as [`OutboxStatus::Failed`], and can be inspected with [`OutboxMail::read_all`]
+//! and sent again with [`OutboxMail::requeue`]; generated projects wrap these in an `outbox` binary
+//! (`cargo run --bin outbox -- list failed`)
+use std::sync::Arc;
+
+use chrono::Utc;
+use diesel::Connection as _;
+use fang::serde::{Deserialize as TaskDeserialize, Serialize as TaskSerialize};
+use fang::typetag;
+use fang::{FangError, Queue, QueueError, Queueable, Runnable};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use super::schema::mail_outbox;
+use crate::diesel::{
+    insert_into, update, ExpressionMethods, Identifiable, Insertable, QueryDsl, QueryResult,
+    Queryable, RunQueryDsl,
+};
+use crate::{Connection, Database, MailTransport, MailTransportError, Mailer, OutgoingEmail};
+
+type ID = i32;
+
+/// how many times delivering an email is retried before it's marked as [`OutboxStatus::Failed`]
+pub const MAX_RETRIES: i32 = 5;
+
+/// the largest page [`OutboxMail::read_all`] returns
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// where an [`OutboxMail`] is in its delivery
+pub enum OutboxStatus {
+    /// waiting to be delivered, or to be retried
+    Pending,
+    Sent,
+    /// could not be delivered after [`MAX_RETRIES`] retries, see [`OutboxMail::requeue`]
+    Failed,
+}
+
+impl OutboxStatus {
+    /// the value stored in the `status` column
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl std::str::FromStr for OutboxStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pending" => Ok(Self::Pending),
+            "sent" => Ok(Self::Sent),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!(
+                "unknown outbox status `{s}`, expected pending, sent or failed"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OutboxStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Identifiable)]
+#[diesel(table_name = mail_outbox)]
+/// Rust struct modeling an entry in the `mail_outbox` table
+pub struct OutboxMail {
+    pub id: ID,
+
+    pub from_address: String,
+    pub to_address: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+    /// one of the [`OutboxStatus`]es
+    pub status: String,
+    /// how many times delivering the email was tried
+    pub attempts: i32,
+    /// why the last attempt failed
+    pub last_error: Option<String>,
+    pub sent_at: Option<chrono::DateTime<Utc>>,
+
+    pub created_at: chrono::DateTime<Utc>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Serialize, Deserialize, Clone, Insertable)]
+#[diesel(table_name = mail_outbox)]
+/// Rust struct modeling the data needed to create an entry in the `mail_outbox` table
+pub struct OutboxMailChangeset {
+    pub from_address: String,
+    pub to_address: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+}
+
+impl From<&OutgoingEmail> for OutboxMailChangeset {
+    fn from(email: &OutgoingEmail) -> Self {
+        Self {
+            from_address: email.from.clone(),
+            to_address: email.to.clone(),
+            subject: email.subject.clone(),
+            text_body: email.text.clone(),
+            html_body: email.html.clone(),
+        }
+    }
+}
+
+impl OutboxMail {
+    /// saves `item` to the outbox and queues a [`DeliverOutboxMail`] task for it,
+    /// in a single transaction on one of the [`queue`](`super::queue`)'s connections
+    ///
+    /// # Errors
+    /// * [`QueueError`] if no connection is available, or either insert fails
+    pub fn create(item: &OutboxMailChangeset) -> Result<Self, QueueError> {
+        let mut db = super::queue().get_connection()?;
+
+        db.transaction(|db| {
+            let mail = insert_into(mail_outbox::table)
+                .values(item)
+                .get_result::<Self>(db)?;
+            Queue::insert_query(db, &DeliverOutboxMail { id: mail.id }, Utc::now())?;
+
+            Ok(mail)
+        })
+    }
+
+    /// Read from [`db`](`Connection`), querying for an entry in the `mail_outbox` table who's primary key matches `item_id`
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read(db: &mut Connection, item_id: ID) -> QueryResult<Self> {
+        mail_outbox::table
+            .filter(mail_outbox::id.eq(item_id))
+            .first::<Self>(db)
+    }
+
+    /// Read from [`db`](`Connection`), return entries of the `mail_outbox` table with the given `status` (all of them if `None`),
+    /// most recent first, `page_size` (at most 100) at a time
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn read_all(
+        db: &mut Connection,
+        status: Option<OutboxStatus>,
+        page: i64,
+        page_size: i64,
+    ) -> QueryResult<Vec<Self>> {
+        let page_size = std::cmp::min(page_size, MAX_PAGE_SIZE);
+
+        let mut query = mail_outbox::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(mail_outbox::status.eq(status.as_str()));
+        }
+
+        query
+            .order(mail_outbox::id.desc())
+            .limit(page_size)
+            .offset(page * page_size)
+            .load::<Self>(db)
+    }
+
+    /// Count the entries in [`db`](`Connection`)'s `mail_outbox` table with the given `status` (all of them if `None`)
+    ///
+    /// # Errors
+    /// * [`diesel::result::Error`](`diesel::result::Error`) if the query fails
+    pub fn count_all(db: &mut Connection, status: Option<OutboxStatus>) -> QueryResult<i64> {
+        let mut query = mail_outbox::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(mail_outbox::status.eq(status.as_str()));
+        }
+
+        query.count().get_result(db)
+    }
+
+    /// resets a [`failed`](`OutboxStatus::Failed`) email to [`pending`](`OutboxStatus::Pending`),
+    /// with no attempts, and queues a new [`DeliverOutboxMail`] task for it
+    ///
+    /// # Errors
+    /// * [`QueueError`] if no connection is available, or the email doesn't exist or didn't fail
+    pub fn requeue(item_id: ID) -> Result<Self, QueueError> {
+        let mut db = super::queue().get_connection()?;
+
+        db.transaction(|db| {
+            let mail = update(
+                mail_outbox::table
+                    .filter(mail_outbox::id.eq(item_id))
+                    .filter(mail_outbox::status.eq(OutboxStatus::Failed.as_str())),
+            )
+            .set((
+                mail_outbox::status.eq(OutboxStatus::Pending.as_str()),
+                mail_outbox::attempts.eq(0),
+                mail_outbox::last_error.eq(None::<String>),
+            ))
+            .get_result::<Self>(db)?;
+            Queue::insert_query(db, &DeliverOutboxMail { id: mail.id }, Utc::now())?;
+
+            Ok(mail)
+        })
+    }
+
+    /// [`requeue`](`OutboxMail::requeue`)s every [`failed`](`OutboxStatus::Failed`) email, returning how many there were
+    ///
+    /// # Errors
+    /// * [`QueueError`] if no connection is available, or a query fails
+    pub fn requeue_all_failed() -> Result<usize, QueueError> {
+        let mut db = super::queue().get_connection()?;
+
+        db.transaction(|db| {
+            let ids = update(
+                mail_outbox::table.filter(mail_outbox::status.eq(OutboxStatus::Failed.as_str())),
+            )
+            .set((
+                mail_outbox::status.eq(OutboxStatus::Pending.as_str()),
+                mail_outbox::attempts.eq(0),
+                mail_outbox::last_error.eq(None::<String>),
+            ))
+            .returning(mail_outbox::id)
+            .get_results::<ID>(db)?;
+
+            for id in &ids {
+                Queue::insert_query(db, &DeliverOutboxMail { id: *id }, Utc::now())?;
+            }
+
+            Ok(ids.len())
+        })
+    }
+
+    /// the email to hand to a [`MailTransport`]
+    #[must_use]
+    pub fn email(&self) -> OutgoingEmail {
+        OutgoingEmail {
+            from: self.from_address.clone(),
+            to: self.to_address.clone(),
+            subject: self.subject.clone(),
+            text: self.text_body.clone(),
+            html: self.html_body.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// saves emails to the outbox instead of delivering them, see [`OutboxMail::create`]
+///
+/// used by the [`Mailer`] when `MAIL_OUTBOX` is `true`, or with [`Mailer::with_outbox`](`crate::Mailer::with_outbox`)
+pub struct OutboxMailTransport;
+
+impl MailTransport for OutboxMailTransport {
+    fn send(&self, email: &OutgoingEmail) -> Result<(), MailTransportError> {
+        email.to_message()?;
+        OutboxMail::create(&email.into())?;
+        Ok(())
+    }
+
+    fn queues(&self) -> bool {
+        true
+    }
+}
+
+/// sets the transport [`DeliverOutboxMail`] tasks deliver emails with, in this process
+///
+/// by default, it's the one the `MAIL_TRANSPORT` and `SMTP_*` environment variables select (see [`Mailer`]),
+/// call this at the start of the queue process if the app uses [`Mailer::with_transport`](`crate::Mailer::with_transport`)
+///
+/// returns `false` if it was already set, or an email was already delivered
+pub fn set_delivery_transport(transport: impl MailTransport + 'static) -> bool {
+    DELIVERY_TRANSPORT.set(Arc::new(transport)).is_ok()
+}
+
+static DELIVERY_TRANSPORT: OnceCell<Arc<dyn MailTransport>> = OnceCell::new();
+
+fn delivery_transport() -> &'static Arc<dyn MailTransport> {
+    DELIVERY_TRANSPORT.get_or_init(Mailer::delivery_transport_from_env)
+}
+
+#[derive(TaskSerialize, TaskDeserialize)]
+#[serde(crate = "fang::serde")]
+/// delivers the [`OutboxMail`] with the given `id`, queued by [`OutboxMail::create`] and [`OutboxMail::requeue`]
+///
+/// runs in the `sync` queue, retried up to [`MAX_RETRIES`] times, waiting 1, 2, 4, 8 and then 16 minutes
+pub struct DeliverOutboxMail {
+    pub id: ID,
+}
+
+#[typetag::serde]
+impl Runnable for DeliverOutboxMail {
+    fn run(&self, _queue: &dyn Queueable) -> Result<(), FangError> {
+        deliver(self.id).map_err(|error| FangError {
+            description: error.to_string(),
+        })
+    }
+
+    fn task_type(&self) -> String {
+        "sync".to_string()
+    }
+
+    fn max_retries(&self) -> i32 {
+        MAX_RETRIES
+    }
+
+    fn backoff(&self, attempt: u32) -> u32 {
+        60 * 2u32.pow(attempt)
+    }
+}
+
+/// delivers a [`pending`](`OutboxStatus::Pending`) email, recording the attempt
+fn deliver(item_id: ID) -> Result<(), MailTransportError> {
+    let mut db = Database::new().get_connection()?;
+
+    let mail = OutboxMail::read(&mut db, item_id)?;
+    if mail.status != OutboxStatus::Pending.as_str() {
+        return Ok(());
+    }
+
+    let result = delivery_transport().send(&mail.email());
+
+    let attempts = mail.attempts + 1;
+    let (status, last_error, sent_at) = match &result {
+        Ok(()) => (OutboxStatus::Sent, None, Some(Utc::now())),
+        Err(error) if attempts > MAX_RETRIES => {
+            (OutboxStatus::Failed, Some(error.to_string()), None)
+        }
+        Err(error) => (OutboxStatus::Pending, Some(error.to_string()), None),
+    };
+
+    update(mail_outbox::table.filter(mail_outbox::id.eq(item_id)))
+        .set((
+            mail_outbox::status.eq(status.as_str()),
+            mail_outbox::attempts.eq(attempts),
+            mail_outbox::last_error.eq(last_error),
+            mail_outbox::sent_at.eq(sent_at),
+        ))
+        .execute(&mut db)?;
+
+    result
+}
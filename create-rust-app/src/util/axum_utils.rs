@@ -0,0 +1,190 @@
+#[cfg(debug_assertions)]
+use std::sync::Mutex;
+
+use super::template_utils::SinglePageApplication;
+use super::workspace_utils::frontend_dir;
+use crate::util::template_utils::{to_template_name, DEFAULT_TEMPLATE, TEMPLATES};
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Router};
+use tera::Context;
+
+///  view: the template which renders the app
+///
+///  Full example (to render the `views/spa.html` template):
+/// ```
+///  app = app.nest("/my-spa", create_rust_app::render_single_page_application("spa.html"));
+/// ```
+pub fn render_single_page_application(view: &str) -> Router {
+    let view = view.strip_prefix('/').unwrap_or(view);
+
+    Router::new()
+        .fallback(render_spa_handler)
+        .layer(Extension(SinglePageApplication {
+            view_name: view.to_string(),
+        }))
+}
+
+async fn render_spa_handler(
+    headers: HeaderMap,
+    Extension(spa_info): Extension<SinglePageApplication>,
+) -> Response {
+    let content = TEMPLATES
+        .render(spa_info.view_name.as_str(), &Context::new())
+        .unwrap();
+    template_response(&headers, content)
+}
+
+// used to count number of refresh requests sent when viteJS dev server is down
+#[allow(clippy::mutex_integer)] // TODO: can we use an atomic integer here?
+#[cfg(debug_assertions)]
+static REQUEST_REFRESH_COUNT: Mutex<i32> = Mutex::new(0);
+
+/// takes a request to, say, `www.you_webapp.com/foo/bar` and looks in the ./backend/views folder
+/// for a html file/template at the matching path (in this case, ./foo/bar.html),
+/// defaults to index.html
+///
+/// then, your frontend (all the css files, scripts, etc. in your frontend's vite manifest (at ./frontend/dist/manifest.json))
+/// will be compiled and injected into the template wherever `{{ bundle(name="index.tsx") }}` is (the `index.tsx` can be any .tsx file in ./frontend/bundles)
+///
+/// then, that compiled html is sent to the client
+///
+/// use it as the fallback of your router: `app = app.fallback(create_rust_app::render_views);`
+///
+/// # Panics
+/// - the mutex lock is poisoned
+pub async fn render_views(uri: Uri, headers: HeaderMap) -> Response {
+    let path = uri.path();
+
+    #[cfg(debug_assertions)]
+    {
+        if path.eq("/__vite_ping") {
+            println!("The vite dev server seems to be down...");
+        }
+
+        // Catch viteJS ping requests and try to handle them gracefully
+        // Request the browser to refresh the page (maybe the server is up but the browser just can't reconnect)
+
+        if path.eq("/__vite_ping") {
+            #[cfg(feature = "plugin_dev")]
+            {
+                crate::dev::vitejs_ping_down().await;
+            }
+            #[allow(clippy::mutex_integer)]
+            let mut count = REQUEST_REFRESH_COUNT.lock().unwrap();
+            if *count < 3 {
+                *count += 1;
+                println!("The vite dev server seems to be down... refreshing page ({count}).");
+                drop(count);
+                return axum::response::Redirect::temporary(".").into_response();
+            }
+            println!("The vite dev server is down.");
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        // If this is a non-viteJS ping request, let's reset the refresh attempt count
+        #[cfg(feature = "plugin_dev")]
+        {
+            crate::dev::vitejs_ping_up().await;
+        }
+        #[allow(clippy::mutex_integer)]
+        let mut count = REQUEST_REFRESH_COUNT.lock().unwrap();
+        *count = 0;
+    }
+
+    let mut template_path = to_template_name(path);
+    // try and render from your ./backend/views
+    let mut content_result = TEMPLATES.render(template_path, &Context::new());
+
+    // if that fails, then
+    //  if in debug mode look for views in ./frontend/... or ./frontend/public/...
+    //  else default to ./backend/views/index.html
+    if content_result.is_err() {
+        #[cfg(debug_assertions)]
+        {
+            // dev asset serving
+            let asset_path = &format!("{frontend_dir}{path}", frontend_dir = frontend_dir());
+            if std::path::PathBuf::from(asset_path).is_file() {
+                println!("ASSET_FILE {path} => {asset_path}");
+                return file_response(asset_path).await;
+            }
+
+            let public_path =
+                &format!("{frontend_dir}/public{path}", frontend_dir = frontend_dir());
+            if std::path::PathBuf::from(public_path).is_file() {
+                println!("PUBLIC_FILE {path} => {public_path}");
+                return file_response(public_path).await;
+            }
+        }
+
+        #[cfg(not(debug_assertions))]
+        {
+            // production asset serving
+            let static_path = &format!("{frontend_dir}/dist{path}", frontend_dir = frontend_dir());
+            if std::path::PathBuf::from(static_path).is_file() {
+                return file_response(static_path).await;
+            }
+        }
+
+        content_result = TEMPLATES.render(DEFAULT_TEMPLATE, &Context::new());
+        template_path = DEFAULT_TEMPLATE;
+        if content_result.is_err() {
+            // default template doesn't exist -- return 404 not found
+            return StatusCode::NOT_FOUND.into_response();
+        }
+    }
+
+    println!("TEMPLATE_FILE {path} => {template_path}");
+
+    let content = content_result.unwrap();
+
+    template_response(&headers, content)
+}
+
+#[allow(unused_variables)]
+fn template_response(headers: &HeaderMap, content: String) -> Response {
+    #[cfg(not(debug_assertions))]
+    let content = content;
+    #[cfg(debug_assertions)]
+    let mut content = content;
+    #[cfg(debug_assertions)]
+    {
+        let hostname = headers
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .and_then(|host| host.parse::<Uri>().ok())
+            .and_then(|uri| uri.host().map(ToString::to_string))
+            .unwrap_or_else(|| "localhost".to_string());
+
+        let inject: &str = &format!(
+            r##"
+        <!-- development mode -->
+        <script type="module">
+            import RefreshRuntime from 'http://{hostname}:21012/@react-refresh'
+            RefreshRuntime.injectIntoGlobalHook(window)
+            window.$RefreshReg$ = () => {{}}
+            window.$RefreshSig$ = () => (type) => type
+            window.__vite_plugin_react_preamble_installed__ = true
+        </script>
+        <script type="module" src="http://{hostname}:21012/src/dev.tsx"></script>
+        "##
+        );
+
+        if content.contains("<body>") {
+            content = content.replace("<body>", &format!("<body>{inject}"));
+        } else {
+            content = format!("{inject}{content}");
+        }
+    }
+
+    ([(header::CONTENT_TYPE, "text/html")], content).into_response()
+}
+
+async fn file_response(path: &str) -> Response {
+    match tokio::fs::read(path).await {
+        Ok(file) => {
+            let content_type = mime_guess::from_path(path).first_or_octet_stream();
+            ([(header::CONTENT_TYPE, content_type.to_string())], file).into_response()
+        }
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
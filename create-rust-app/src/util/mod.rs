@@ -18,6 +18,12 @@ mod poem_utils;
 #[cfg(feature = "backend_poem")]
 pub use poem_utils::*;
 
+#[cfg(feature = "backend_axum")]
+mod axum_utils;
+
+#[cfg(feature = "backend_axum")]
+pub use axum_utils::*;
+
 /// expose `template_utils` for all backends.
 mod template_utils;
 
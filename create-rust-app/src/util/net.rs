@@ -1,5 +1,9 @@
+use once_cell::sync::OnceCell;
 use std::{
-    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6, TcpListener, ToSocketAddrs},
+    net::{
+        IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, TcpListener,
+        ToSocketAddrs,
+    },
     ops::Range,
 };
 
@@ -24,3 +28,118 @@ pub fn is_port_free(port: u16) -> bool {
 pub fn find_free_port(mut range: Range<u16>) -> Option<u16> {
     range.find(|port| is_port_free(*port))
 }
+
+/// strips the port, if any, from a client address such as `127.0.0.1:54321` or `[::1]:54321`
+#[must_use]
+pub fn client_ip(addr: &str) -> String {
+    addr.parse::<SocketAddr>()
+        .map_or_else(|_| addr.to_string(), |addr| addr.ip().to_string())
+}
+
+/// the proxies allowed to report the client's address, from the comma separated `TRUSTED_PROXIES`
+/// environment variable (like `127.0.0.1,10.0.0.2`), none by default
+fn trusted_proxies() -> &'static [IpAddr] {
+    static TRUSTED_PROXIES: OnceCell<Vec<IpAddr>> = OnceCell::new();
+
+    TRUSTED_PROXIES.get_or_init(|| {
+        std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .filter_map(|proxy| {
+                proxy
+                    .parse()
+                    .map_err(|_| {
+                        println!("Warning: ignoring invalid TRUSTED_PROXIES entry `{proxy}`")
+                    })
+                    .ok()
+            })
+            .collect()
+    })
+}
+
+/// the address of the client that sent a request, given the `peer` address of its connection
+/// and its `X-Forwarded-For` and `X-Real-IP` headers
+///
+/// anyone can set those headers, so they're only used when `peer` is one of the [`TRUSTED_PROXIES`](`trusted_proxies`);
+/// the client is then the right-most `X-Forwarded-For` address that isn't a trusted proxy itself
+#[must_use]
+pub fn request_client_ip(
+    peer: Option<IpAddr>,
+    x_forwarded_for: Option<&str>,
+    x_real_ip: Option<&str>,
+) -> Option<String> {
+    resolve_client_ip(peer, x_forwarded_for, x_real_ip, trusted_proxies()).map(|ip| ip.to_string())
+}
+
+fn resolve_client_ip(
+    peer: Option<IpAddr>,
+    x_forwarded_for: Option<&str>,
+    x_real_ip: Option<&str>,
+    trusted: &[IpAddr],
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted.contains(&peer) {
+        return Some(peer);
+    }
+
+    let parse = |addr: &str| {
+        let addr = client_ip(addr.trim());
+        addr.parse::<IpAddr>().ok()
+    };
+
+    let forwarded = x_forwarded_for.map_or_else(Vec::new, |header| {
+        header.split(',').map(parse).collect::<Vec<_>>()
+    });
+    if !forwarded.is_empty() {
+        // an unparseable entry could have been written by anyone, so we stop there
+        return forwarded
+            .into_iter()
+            .rev()
+            .map_while(|addr| addr)
+            .find(|addr| !trusted.contains(addr))
+            .or(Some(peer));
+    }
+
+    Some(x_real_ip.and_then(parse).unwrap_or(peer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_client_ip_only_trusts_proxies() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let trusted = [proxy];
+
+        // a client connecting directly can't pick its address
+        assert_eq!(
+            resolve_client_ip(Some(client), Some("1.2.3.4"), Some("1.2.3.4"), &trusted),
+            Some(client)
+        );
+        // a spoofed first entry is ignored, the proxy appended the real one
+        assert_eq!(
+            resolve_client_ip(Some(proxy), Some("1.2.3.4, 203.0.113.7"), None, &trusted),
+            Some(client)
+        );
+        assert_eq!(
+            resolve_client_ip(Some(proxy), Some("203.0.113.7, 10.0.0.2"), None, &trusted),
+            Some(client)
+        );
+        assert_eq!(
+            resolve_client_ip(Some(proxy), None, Some("203.0.113.7"), &trusted),
+            Some(client)
+        );
+        assert_eq!(
+            resolve_client_ip(Some(proxy), None, None, &trusted),
+            Some(proxy)
+        );
+        assert_eq!(
+            resolve_client_ip(None, Some("1.2.3.4"), None, &trusted),
+            None
+        );
+    }
+}
@@ -130,6 +130,22 @@ pub(crate) fn views_glob() -> &'static str {
         })
     })
 }
+/// fn for the glob matching the project's mail templates, see [`Mailer::render`](`crate::Mailer::render`)
+pub(crate) fn mail_templates_glob() -> &'static str {
+    static MAIL_TEMPLATES_GLOB: OnceLock<String> = OnceLock::new();
+    MAIL_TEMPLATES_GLOB.get_or_init(|| {
+        std::env::var("CRA_MAIL_TEMPLATES_GLOB").unwrap_or_else(|_| {
+            fallback(
+                cargo_locate_project_workspace(),
+                cargo_locate_project(),
+                env!("CARGO_MANIFEST_DIR"),
+                "mail/templates/**/*",
+                "backend/mail/templates/**/*",
+            )
+            .to_string()
+        })
+    })
+}
 
 #[cfg(test)]
 mod fallback_logic_tests {
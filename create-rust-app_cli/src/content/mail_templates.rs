@@ -0,0 +1,33 @@
+use anyhow::Result;
+use rust_embed::RustEmbed;
+use std::path::Path;
+
+use crate::utils::logger;
+
+#[derive(RustEmbed)]
+#[folder = "template-mail"]
+struct Asset;
+
+/// copies the default mail templates (the auth plugin's emails and their shared layout) into `directory`,
+/// where `create_rust_app::Mailer::render` picks them up instead of the built-in emails
+///
+/// existing files are kept, unless `force` is set
+pub fn eject(directory: &Path, force: bool) -> Result<()> {
+    std::fs::create_dir_all(directory)?;
+
+    for filename in Asset::iter() {
+        let file_path = directory.join(filename.as_ref());
+        if file_path.exists() && !force {
+            logger::message(&format!(
+                "Skipping {file_path:#?}, it already exists (use --force to overwrite it)"
+            ));
+            continue;
+        }
+
+        let file_contents = Asset::get(filename.as_ref()).unwrap();
+        logger::add_file_msg(&format!("{file_path:#?}"));
+        std::fs::write(file_path, file_contents.data)?;
+    }
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ pub mod cargo_toml;
 ///
 /// This package contains helper functions which create content in create-rust-app projects.
 ///
+pub mod mail_templates;
 pub mod migration;
 pub mod model;
 pub mod project;
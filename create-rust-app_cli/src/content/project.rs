@@ -129,6 +129,10 @@ fn add_bins_to_cargo_toml(
             name: "async_queue",
             path: "backend/async_queue.rs",
         });
+        project_binaries.push(ProjectBinary {
+            name: "outbox",
+            path: "backend/outbox.rs",
+        });
     };
 
     let binaries_cargo_toml_string = project_binaries
@@ -225,6 +229,21 @@ pub fn remove_non_framework_files(
                 logger::rename_file_msg(&format!("{:#?}", &file), &format!("{:#?}", &dest));
                 std::fs::rename(file, dest)?;
             };
+        } else if path.ends_with("+axum") {
+            if framework != BackendFramework::Axum {
+                logger::remove_file_msg(&format!("{:#?}", &file));
+                std::fs::remove_file(file)?;
+            };
+            if framework == BackendFramework::Axum {
+                let dest = file.with_extension(
+                    file.extension()
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace("+axum", ""),
+                );
+                logger::rename_file_msg(&format!("{:#?}", &file), &format!("{:#?}", &dest));
+                std::fs::rename(file, dest)?;
+            };
         }
     }
 
@@ -413,6 +432,28 @@ pub fn create(project_name: &str, creation_options: CreationOptions) -> Result<(
                 r#"tracing-subscriber = "0.3.7""#,
             )?;
         }
+        BackendFramework::Axum => {
+            add_dependency(
+                &project_dir,
+                "axum",
+                r#"axum = { version = "0.7.5", features = ["multipart", "ws"] }"#,
+            )?;
+            add_dependency(
+                &project_dir,
+                "tower-http",
+                r#"tower-http = { version = "0.5", features = ["fs", "trace"] }"#,
+            )?;
+            add_dependency(
+                &project_dir,
+                "tokio",
+                r#"tokio = { version = "1", features = ["full"] }"#,
+            )?;
+            add_dependency(
+                &project_dir,
+                "tracing_subscriber",
+                r#"tracing-subscriber = "0.3.7""#,
+            )?;
+        }
     }
     add_dependency(&project_dir, "simple_logger", r#"simple_logger = "5.0""#)?;
     add_dependency(&project_dir, "futures-util", r#"futures-util = "0.3.30""#)?;
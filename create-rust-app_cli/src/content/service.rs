@@ -25,6 +25,7 @@ pub fn create(
     let resource = match backend {
         BackendFramework::ActixWeb => generate_actix(resource_name, include_qsync_attr),
         BackendFramework::Poem => generate_poem(resource_name),
+        BackendFramework::Axum => generate_axum(resource_name),
     };
 
     crate::fs::add_rust_file(
@@ -45,6 +46,11 @@ pub fn create(
             service_api_fn,
             base_endpoint_path,
         )?,
+        BackendFramework::Axum => {
+            let name = resource.config.file_name.as_str();
+            let service_entry = &format!("services::{name}::router()");
+            register_axum(name, service_entry, base_endpoint_path)?;
+        }
     };
 
     Ok(())
@@ -151,6 +157,109 @@ fn generate_poem(service_name: &str) -> Service {
     }
 }
 
+fn generate_axum(service_name: &str) -> Service {
+    let config = config(service_name);
+    let contents_template: &str = indoc! {r#"
+    use axum::{
+        extract::{Path, Query},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::get,
+        Extension, Json, Router,
+    };
+    use create_rust_app::Database;
+    use diesel::OptionalExtension;
+    use serde::Deserialize;
+    use tsync::tsync;
+
+    use crate::models::$TABLE_NAME::{$MODEL_NAME, Create$MODEL_NAME, Update$MODEL_NAME};
+
+    #[tsync]
+    #[derive(Deserialize)]
+    struct List$MODEL_NAMERequest {
+        page: i64,
+        page_size: i64,
+    }
+
+    async fn list(
+        Extension(db): Extension<Database>,
+        Query(info): Query<List$MODEL_NAMERequest>,
+    ) -> Response {
+        let mut db = db.get_connection().unwrap();
+
+        match $MODEL_NAME::paginate(&mut db, info.page, info.page_size) {
+            Ok(results) => Json(results).into_response(),
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+
+    async fn read(
+        Extension(db): Extension<Database>,
+        Path(item_id): Path<i32>,
+    ) -> Response {
+        let mut db = db.get_connection().unwrap();
+
+        match $MODEL_NAME::read(&mut db, item_id).optional() {
+            Ok(Some(item)) => Json(item).into_response(),
+            Ok(None) => StatusCode::NOT_FOUND.into_response(),
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+
+    async fn create(
+        Extension(db): Extension<Database>,
+        Json(item): Json<Create$MODEL_NAME>,
+    ) -> Response {
+        let mut db = db.get_connection().unwrap();
+
+        match $MODEL_NAME::create(&mut db, &item) {
+            Ok(result) => Json(result).into_response(),
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+
+    async fn update(
+        Extension(db): Extension<Database>,
+        Path(item_id): Path<i32>,
+        Json(item): Json<Update$MODEL_NAME>,
+    ) -> Response {
+        let mut db = db.get_connection().unwrap();
+
+        match $MODEL_NAME::update(&mut db, item_id, &item) {
+            Ok(result) => Json(result).into_response(),
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+
+    async fn destroy(
+        Extension(db): Extension<Database>,
+        Path(item_id): Path<i32>,
+    ) -> StatusCode {
+        let mut db = db.get_connection().unwrap();
+
+        match $MODEL_NAME::delete(&mut db, item_id) {
+            Ok(_) => StatusCode::OK,
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn router() -> Router {
+        Router::new()
+            .route("/", get(list).post(create))
+            .route("/:id", get(read).put(update).delete(destroy))
+    }
+    "#};
+
+    let contents = String::from(contents_template)
+        .replace("$MODEL_NAME", config.model_name.as_str())
+        .replace("$TABLE_NAME", config.file_name.to_plural().as_str());
+
+    Service {
+        config,
+        file_contents: format!("{}\n", contents.trim()),
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 fn generate_actix(service_name: &str, include_qsync_attr: bool) -> Service {
     let config = config(service_name);
@@ -344,6 +453,30 @@ pub fn register_poem(
     Ok(())
 }
 
+/// use `fs::replace` instead and also `fs::append` for the services/mod.rs entry
+// #[deprecated]
+pub fn register_axum(
+    name: &str,
+    service_router_fn: &str,
+    service_base_endpoint_path: &str,
+) -> Result<()> {
+    register_service_msg(name);
+    let main_file_path = PathBuf::from("backend/main.rs");
+    if main_file_path.exists() && main_file_path.is_file() {
+        let mut main_file_contents = std::fs::read_to_string(&main_file_path)?;
+
+        main_file_contents = main_file_contents.replace(
+            "let mut api_routes = Router::new();",
+            &format!(
+                "let mut api_routes = Router::new();\n    api_routes = api_routes.nest(\"{service_base_endpoint_path}\", {service_router_fn});",
+            ),
+        );
+        std::fs::write(main_file_path, main_file_contents)?;
+    }
+
+    Ok(())
+}
+
 /// use `fs::replace` instead and also `fs::append` for the services/mod.rs entry
 // #[deprecated]
 pub fn register_actix(name: &str, service: &str) -> Result<()> {
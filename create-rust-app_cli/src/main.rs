@@ -22,6 +22,7 @@ use utils::{fs, logger};
 pub enum BackendFramework {
     ActixWeb,
     Poem,
+    Axum,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
@@ -170,6 +171,19 @@ enum Commands {
         )]
         add_new_service: bool,
     },
+    /// Copy the default email templates into an existing project, to customise or translate them
+    EjectMailTemplates {
+        #[arg(
+            long = "dir",
+            help = "Where to write the templates, matching the CRA_MAIL_TEMPLATES_GLOB environment variable",
+            default_value = "backend/mail/templates",
+            value_hint = ValueHint::DirPath,
+        )]
+        directory: PathBuf,
+
+        #[arg(long = "force", help = "Overwrite templates that already exist")]
+        force: bool,
+    },
 }
 
 /// CREATE RUST APP
@@ -205,6 +219,9 @@ fn main() -> Result<()> {
                     qsync_debug,
                     add_new_service,
                 )?,
+                Commands::EjectMailTemplates { directory, force } => {
+                    content::mail_templates::eject(&directory, force)?;
+                }
             };
         }
         None => {
@@ -276,7 +293,7 @@ fn create_project(
         assert!(!cli_mode, "Fatal: No backend database specified");
         logger::message("Select a rust backend framework to use:");
         logger::message("Use UP/DOWN arrows to navigate and SPACE or ENTER to confirm.");
-        let items = vec!["actix-web", "poem"];
+        let items = vec!["actix-web", "poem", "axum"];
         let selection = Select::with_theme(&ColorfulTheme::default())
             .items(&items)
             .default(0)
@@ -285,6 +302,7 @@ fn create_project(
         match selection {
             Some(0) => BackendFramework::ActixWeb,
             Some(1) => BackendFramework::Poem,
+            Some(2) => BackendFramework::Axum,
             _ => panic!("Fatal: Unknown backend framework specified."),
         }
     };
@@ -374,6 +392,7 @@ fn create_project(
     cra_enabled_features.push(match backend_framework {
         BackendFramework::ActixWeb => "backend_actix-web".to_string(),
         BackendFramework::Poem => "backend_poem".to_string(),
+        BackendFramework::Axum => "backend_axum".to_string(),
     });
 
     project::create(
@@ -543,7 +562,7 @@ fn configure_project(
                 // TODO: maybe obtain this programmatically by parsing the users cargo.toml file?
                 logger::message("Which backend framework are you using?");
                 logger::message("Use UP/DOWN arrows to navigate and SPACE or ENTER to confirm.");
-                let items = vec!["actix_web", "poem"];
+                let items = vec!["actix_web", "poem", "axum"];
                 let selection = Select::with_theme(&ColorfulTheme::default())
                     .items(&items)
                     .default(0)
@@ -552,6 +571,7 @@ fn configure_project(
                 match selection {
                     Some(0) => BackendFramework::ActixWeb,
                     Some(1) => panic!("Fatal: this feature is not yet implemented for `poem`"),
+                    Some(2) => panic!("Fatal: this feature is not yet implemented for `axum`"),
                     _ => panic!("Fatal: Unknown backend framework specified."),
                 };
 
@@ -583,7 +603,7 @@ fn configure_project(
 
                 logger::message("Which backend framework are you using?");
                 logger::message("Use UP/DOWN arrows to navigate and SPACE or ENTER to confirm.");
-                let items = vec!["actix_web", "poem"];
+                let items = vec!["actix_web", "poem", "axum"];
                 let selection = Select::with_theme(&ColorfulTheme::default())
                     .items(&items)
                     .default(0)
@@ -592,6 +612,7 @@ fn configure_project(
                 let backend_framework: BackendFramework = match selection {
                     Some(0) => BackendFramework::ActixWeb,
                     Some(1) => BackendFramework::Poem,
+                    Some(2) => BackendFramework::Axum,
                     _ => panic!("Fatal: Unknown backend framework specified."),
                 };
 
@@ -42,7 +42,10 @@ impl Plugin for Auth {
             "frontend/src/App.tsx",
             r"import { useAuth, useAuthCheck } from './hooks/useAuth'
 import { AccountPage } from './containers/AccountPage'
+import { EmailChangePage } from './containers/EmailChangePage'
 import { LoginPage } from './containers/LoginPage'
+import { LoginLinkPage } from './containers/LoginLinkPage'
+import { OAuthAuthorizePage } from './containers/OAuthAuthorizePage'
 import { ActivationPage } from './containers/ActivationPage'
 import { RegistrationPage } from './containers/RegistrationPage'
 import { RecoveryPage } from './containers/RecoveryPage'
@@ -65,11 +68,14 @@ import { ResetPage } from './containers/ResetPage'",
             r"{/* CRA: routes */}",
             r#"{/* CRA: routes */}
           <Route path="/login" element={<LoginPage />} />
+          <Route path="/login/link" element={<LoginLinkPage />} />
           <Route path="/recovery" element={<RecoveryPage />} />
           <Route path="/reset" element={<ResetPage />} />
           <Route path="/activate" element={<ActivationPage />} />
           <Route path="/register" element={<RegistrationPage />} />
           <Route path="/account" element={<AccountPage />} />
+          <Route path="/email/confirm" element={<EmailChangePage />} />
+          <Route path="/oauth/authorize" element={<OAuthAuthorizePage />} />
     "#,
         )?;
         fs::replace(
@@ -96,6 +102,37 @@ import { ResetPage } from './containers/ResetPage'",
             "{/* CRA: Unwrap */}\n</AuthProvider>",
         )?;
 
+        // ===============================
+        // PATCH BACKEND
+        // ===============================
+
+        match install_config.backend_framework {
+            BackendFramework::ActixWeb => fs::replace(
+                "backend/main.rs",
+                "app = app.app_data(Data::new(app_data.mailer.clone()));",
+                r#"app = app.app_data(Data::new(app_data.mailer.clone()));
+        app = app.app_data(Data::new(
+            create_rust_app::auth::AuthConfig::from_env().expect("SECRET_KEY must be set"),
+        ));"#,
+            )?,
+            BackendFramework::Poem => fs::replace(
+                "backend/main.rs",
+                ".with(AddData::new(data.database))",
+                r#".with(AddData::new(data.database))
+                .with(AddData::new(
+                    create_rust_app::auth::AuthConfig::from_env().expect("SECRET_KEY must be set"),
+                ))"#,
+            )?,
+            BackendFramework::Axum => fs::replace(
+                "backend/main.rs",
+                ".layer(Extension(data.database))",
+                r#".layer(Extension(data.database))
+        .layer(Extension(
+            create_rust_app::auth::AuthConfig::from_env().expect("SECRET_KEY must be set"),
+        ))"#,
+            )?,
+        }
+
         crate::content::migration::create(
             "plugin_auth",
             match install_config.backend_database {
@@ -105,6 +142,9 @@ import { ResetPage } from './containers/ResetPage'",
         email TEXT NOT NULL,
         hash_password TEXT NOT NULL,
         activated BOOL NOT NULL DEFAULT FALSE,
+        deletion_scheduled_at TIMESTAMPTZ,
+        deleted_at TIMESTAMPTZ,
+        locale TEXT,
         created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
         updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
       );
@@ -116,10 +156,18 @@ import { ResetPage } from './containers/ResetPage'",
         user_id SERIAL NOT NULL REFERENCES users(id),
         refresh_token TEXT NOT NULL,
         device TEXT,
+        family TEXT NOT NULL,
+        rotated BOOL NOT NULL DEFAULT FALSE,
+        ip_address TEXT,
+        user_agent TEXT,
+        oauth_client_id INTEGER,
+        logged_in_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
         created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
         updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
       );
 
+      CREATE INDEX user_sessions_family ON user_sessions (family);
+
       SELECT manage_updated_at('user_sessions');
 
       CREATE TABLE user_permissions (
@@ -142,6 +190,142 @@ import { ResetPage } from './containers/ResetPage'",
         created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
         PRIMARY KEY (role, permission)
       );
+
+      CREATE TABLE role_inheritances (
+        role TEXT NOT NULL,
+        inherited_role TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (role, inherited_role),
+        CHECK (role <> inherited_role)
+      );
+
+      CREATE TABLE user_resource_permissions (
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        permission TEXT NOT NULL,
+        resource_type TEXT NOT NULL,
+        resource_id INTEGER NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (user_id, permission, resource_type, resource_id)
+      );
+
+      CREATE INDEX user_resource_permissions_resource ON user_resource_permissions (resource_type, resource_id);
+
+      CREATE TABLE role_resource_permissions (
+        role TEXT NOT NULL,
+        permission TEXT NOT NULL,
+        resource_type TEXT NOT NULL,
+        resource_id INTEGER NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (role, permission, resource_type, resource_id)
+      );
+
+      CREATE INDEX role_resource_permissions_resource ON role_resource_permissions (resource_type, resource_id);
+
+      CREATE TABLE user_totps (
+        user_id INTEGER PRIMARY KEY REFERENCES users(id),
+        secret TEXT NOT NULL,
+        confirmed BOOL NOT NULL DEFAULT FALSE,
+        recovery_codes TEXT NOT NULL DEFAULT '',
+        last_used_step BIGINT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      SELECT manage_updated_at('user_totps');
+
+      CREATE TABLE failed_logins (
+        id SERIAL PRIMARY KEY,
+        user_id INTEGER REFERENCES users(id),
+        ip_address TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX failed_logins_user_id_created_at ON failed_logins (user_id, created_at);
+      CREATE INDEX failed_logins_ip_address_created_at ON failed_logins (ip_address, created_at);
+
+      CREATE TABLE user_lockouts (
+        user_id INTEGER PRIMARY KEY REFERENCES users(id),
+        lockout_count INTEGER NOT NULL DEFAULT 0,
+        locked_until TIMESTAMPTZ NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      SELECT manage_updated_at('user_lockouts');
+
+      CREATE TABLE user_login_links (
+        token_id TEXT PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        expires_at TIMESTAMPTZ NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE admin_audit_logs (
+        id SERIAL PRIMARY KEY,
+        admin_user_id INTEGER NOT NULL REFERENCES users(id),
+        action TEXT NOT NULL,
+        target_user_id INTEGER REFERENCES users(id),
+        details TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX admin_audit_logs_target_user_id ON admin_audit_logs (target_user_id);
+
+      CREATE TABLE user_passkeys (
+        id SERIAL PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        credential_id TEXT NOT NULL UNIQUE,
+        public_key TEXT NOT NULL,
+        sign_count BIGINT NOT NULL DEFAULT 0,
+        name TEXT,
+        last_used_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX user_passkeys_user_id ON user_passkeys (user_id);
+
+      CREATE TABLE passkey_challenges (
+        challenge TEXT PRIMARY KEY,
+        user_id INTEGER REFERENCES users(id),
+        expires_at TIMESTAMPTZ NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE user_api_keys (
+        id SERIAL PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        name TEXT NOT NULL,
+        key_prefix TEXT NOT NULL,
+        key_hash TEXT NOT NULL UNIQUE,
+        permissions TEXT,
+        last_used_at TIMESTAMPTZ,
+        expires_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX user_api_keys_user_id ON user_api_keys (user_id);
+
+      CREATE TABLE oauth_clients (
+        id SERIAL PRIMARY KEY,
+        client_id TEXT NOT NULL UNIQUE,
+        client_secret_hash TEXT,
+        name TEXT NOT NULL,
+        redirect_uris TEXT NOT NULL,
+        first_party BOOLEAN NOT NULL DEFAULT FALSE,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE oauth_authorization_codes (
+        code_hash TEXT PRIMARY KEY,
+        oauth_client_id INTEGER NOT NULL REFERENCES oauth_clients(id),
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        redirect_uri TEXT NOT NULL,
+        scope TEXT NOT NULL,
+        nonce TEXT,
+        code_challenge TEXT NOT NULL,
+        expires_at TIMESTAMPTZ NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
     "},
                 BackendDatabase::Sqlite => indoc! {r"
       CREATE TABLE users (
@@ -149,6 +333,9 @@ import { ResetPage } from './containers/ResetPage'",
         email TEXT NOT NULL,
         hash_password TEXT NOT NULL,
         activated BOOLEAN NOT NULL DEFAULT FALSE,
+        deletion_scheduled_at DATETIME,
+        deleted_at DATETIME,
+        locale TEXT,
         created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
       );
 
@@ -157,9 +344,17 @@ import { ResetPage } from './containers/ResetPage'",
         user_id INTEGER NOT NULL REFERENCES users(id),
         refresh_token TEXT NOT NULL,
         device TEXT,
+        family TEXT NOT NULL,
+        rotated BOOLEAN NOT NULL DEFAULT FALSE,
+        ip_address TEXT,
+        user_agent TEXT,
+        oauth_client_id INTEGER,
+        logged_in_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
         created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
       );
 
+      CREATE INDEX user_sessions_family ON user_sessions (family);
+
       CREATE TABLE user_permissions (
         user_id INTEGER NOT NULL REFERENCES users(id),
         permission TEXT NOT NULL,
@@ -180,10 +375,153 @@ import { ResetPage } from './containers/ResetPage'",
         created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
         PRIMARY KEY (role, permission)
       );
+
+      CREATE TABLE role_inheritances (
+        role TEXT NOT NULL,
+        inherited_role TEXT NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (role, inherited_role),
+        CHECK (role <> inherited_role)
+      );
+
+      CREATE TABLE user_resource_permissions (
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        permission TEXT NOT NULL,
+        resource_type TEXT NOT NULL,
+        resource_id INTEGER NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (user_id, permission, resource_type, resource_id)
+      );
+
+      CREATE INDEX user_resource_permissions_resource ON user_resource_permissions (resource_type, resource_id);
+
+      CREATE TABLE role_resource_permissions (
+        role TEXT NOT NULL,
+        permission TEXT NOT NULL,
+        resource_type TEXT NOT NULL,
+        resource_id INTEGER NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (role, permission, resource_type, resource_id)
+      );
+
+      CREATE INDEX role_resource_permissions_resource ON role_resource_permissions (resource_type, resource_id);
+
+      CREATE TABLE user_totps (
+        user_id INTEGER PRIMARY KEY NOT NULL REFERENCES users(id),
+        secret TEXT NOT NULL,
+        confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+        recovery_codes TEXT NOT NULL DEFAULT '',
+        last_used_step BIGINT,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE failed_logins (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER REFERENCES users(id),
+        ip_address TEXT,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX failed_logins_user_id_created_at ON failed_logins (user_id, created_at);
+      CREATE INDEX failed_logins_ip_address_created_at ON failed_logins (ip_address, created_at);
+
+      CREATE TABLE user_lockouts (
+        user_id INTEGER PRIMARY KEY NOT NULL REFERENCES users(id),
+        lockout_count INTEGER NOT NULL DEFAULT 0,
+        locked_until DATETIME NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE user_login_links (
+        token_id TEXT PRIMARY KEY NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        expires_at DATETIME NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE admin_audit_logs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        admin_user_id INTEGER NOT NULL REFERENCES users(id),
+        action TEXT NOT NULL,
+        target_user_id INTEGER REFERENCES users(id),
+        details TEXT,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX admin_audit_logs_target_user_id ON admin_audit_logs (target_user_id);
+
+      CREATE TABLE user_passkeys (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        credential_id TEXT NOT NULL UNIQUE,
+        public_key TEXT NOT NULL,
+        sign_count BIGINT NOT NULL DEFAULT 0,
+        name TEXT,
+        last_used_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX user_passkeys_user_id ON user_passkeys (user_id);
+
+      CREATE TABLE passkey_challenges (
+        challenge TEXT PRIMARY KEY NOT NULL,
+        user_id INTEGER REFERENCES users(id),
+        expires_at DATETIME NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE user_api_keys (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        name TEXT NOT NULL,
+        key_prefix TEXT NOT NULL,
+        key_hash TEXT NOT NULL UNIQUE,
+        permissions TEXT,
+        last_used_at DATETIME,
+        expires_at DATETIME,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE INDEX user_api_keys_user_id ON user_api_keys (user_id);
+
+      CREATE TABLE oauth_clients (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        client_id TEXT NOT NULL UNIQUE,
+        client_secret_hash TEXT,
+        name TEXT NOT NULL,
+        redirect_uris TEXT NOT NULL,
+        first_party BOOLEAN NOT NULL DEFAULT FALSE,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
+
+      CREATE TABLE oauth_authorization_codes (
+        code_hash TEXT PRIMARY KEY NOT NULL,
+        oauth_client_id INTEGER NOT NULL REFERENCES oauth_clients(id),
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        redirect_uri TEXT NOT NULL,
+        scope TEXT NOT NULL,
+        nonce TEXT,
+        code_challenge TEXT NOT NULL,
+        expires_at DATETIME NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+      );
     "},
             },
             indoc! {r"
+      DROP TABLE oauth_authorization_codes;
+      DROP TABLE oauth_clients;
+      DROP TABLE user_api_keys;
+      DROP TABLE passkey_challenges;
+      DROP TABLE user_passkeys;
+      DROP TABLE admin_audit_logs;
+      DROP TABLE user_login_links;
+      DROP TABLE user_lockouts;
+      DROP TABLE failed_logins;
+      DROP TABLE user_totps;
+      DROP TABLE role_resource_permissions;
+      DROP TABLE user_resource_permissions;
       DROP TABLE user_permissions;
+      DROP TABLE role_inheritances;
       DROP TABLE role_permissions;
       DROP TABLE user_roles;
       DROP TABLE user_sessions;
@@ -201,6 +539,11 @@ import { ResetPage } from './containers/ResetPage'",
                 "create_rust_app::auth::api()",
                 "/auth",
             )?,
+            BackendFramework::Axum => crate::content::service::register_axum(
+                "auth",
+                "create_rust_app::auth::router()",
+                "/auth",
+            )?,
         };
 
         Ok(())
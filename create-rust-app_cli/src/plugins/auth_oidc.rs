@@ -2,7 +2,7 @@ use crate::plugins::InstallConfig;
 use crate::plugins::Plugin;
 use crate::utils::logger::add_file_msg;
 use crate::utils::{fs, logger};
-use crate::{BackendDatabase, BackendFramework};
+use crate::BackendDatabase;
 use anyhow::Result;
 use indoc::indoc;
 use rust_embed::RustEmbed;
@@ -51,54 +51,11 @@ impl Plugin for AuthOIDC {
         // ===============================
         // Backend changes
         // ===============================
-        match install_config.backend_framework {
-            BackendFramework::ActixWeb => {
-                fs::replace(
-                    "backend/main.rs",
-                    r#"app = app.app_data(Data::new(AppConfig {
-            app_url: std::env::var("APP_URL").unwrap(),
-        }));"#,
-                    r#"app = app.app_data(Data::new(AppConfig {
-            app_url: std::env::var("APP_URL").unwrap(),
-        }));
-        app = app.app_data(Data::new(create_rust_app::auth::AuthConfig {
-            oidc_providers: vec![create_rust_app::auth::oidc::OIDCProvider::GOOGLE(
-                std::env::var("GOOGLE_OAUTH2_CLIENT_ID").unwrap(),
-                std::env::var("GOOGLE_OAUTH2_CLIENT_SECRET").unwrap(),
-                format!(
-                    "{app_url}/oauth/success",
-                    app_url = std::env::var("APP_URL").unwrap()
-                ),
-                format!(
-                    "{app_url}/oauth/error",
-                    app_url = std::env::var("APP_URL").unwrap()
-                ),
-            )],
-        }));
-"#,
-                )?;
-            }
-            BackendFramework::Poem => {
-                fs::replace(
-                    "backend/main.rs",
-                    r#"app = app.app_data(Data::new(AppConfig {
-            app_url: std::env::var("APP_URL").unwrap(),
-        }));"#,
-                    r#"app = app.app_data(Data::new(AppConfig {
-            app_url: std::env::var("APP_URL").unwrap(),
-        }));
-"#,
-                )?;
-
-                fs::replace(
-                    "backend/main.rs",
-                    r#".with(AddData::new(AppConfig {
-                    app_url: std::env::var("APP_URL").unwrap(),
-                 })"#,
-                    r#".with(AddData::new(AppConfig {
-                    app_url: std::env::var("APP_URL").unwrap(),
-                 })
-                 .with(AddData::new(create_rust_app::auth::AuthConfig {
+        // the auth plugin registers a default `AuthConfig`, fill in the providers
+        fs::replace(
+            "backend/main.rs",
+            r#"create_rust_app::auth::AuthConfig::from_env().expect("SECRET_KEY must be set")"#,
+            r#"create_rust_app::auth::AuthConfig {
             oidc_providers: vec![create_rust_app::auth::oidc::OIDCProvider::GOOGLE(
                 std::env::var("GOOGLE_OAUTH2_CLIENT_ID").unwrap(),
                 std::env::var("GOOGLE_OAUTH2_CLIENT_SECRET").unwrap(),
@@ -111,10 +68,9 @@ impl Plugin for AuthOIDC {
                     app_url = std::env::var("APP_URL").unwrap()
                 ),
             )],
-        })"#,
-                )?;
-            }
-        }
+            ..create_rust_app::auth::AuthConfig::from_env().expect("SECRET_KEY must be set")
+        }"#,
+        )?;
 
         // ===============================
         // MIGRATIONS
@@ -88,6 +88,19 @@ if (import.meta.env.DEV_SERVER_PORT) {
         app = app.at("/admin", StaticFilesEndpoint::new(".cargo/admin/dist").index_file("admin.html"));"#,
                 )?;
             }
+            BackendFramework::Axum => {
+                register_service_msg("(dev-only) /development");
+                register_service_msg("(dev-only) /admin");
+                fs::replace(
+                    "backend/main.rs",
+                    r"/* Development-only routes */",
+                    r#"/* Development-only routes */
+        // Mount development-only API routes
+        api_routes = api_routes.nest("/development", create_rust_app::dev::router());
+        // Mount the admin dashboard on /admin
+        app = app.nest_service("/admin", ServeDir::new(".cargo/admin/dist").fallback(tower_http::services::ServeFile::new(".cargo/admin/dist/admin.html")));"#,
+                )?;
+            }
         }
 
         Ok(())
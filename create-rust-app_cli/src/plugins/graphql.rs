@@ -25,6 +25,14 @@ impl Plugin for GraphQL {
             std::process::exit(1);
         }
 
+        // TODO: remove when the GraphQL plugin is implemented for Axum
+        if install_config.backend_framework == BackendFramework::Axum {
+            crate::logger::error(
+                "Currently, the GraphQL plugin does not support the Axum backend!",
+            );
+            std::process::exit(1);
+        }
+
         for filename in Asset::iter() {
             let file_contents = Asset::get(filename.as_ref()).unwrap();
             let mut file_path = std::path::PathBuf::from(&install_config.project_dir);
@@ -62,6 +70,9 @@ impl Plugin for GraphQL {
                     r#"async-graphql-poem = "3.0.38""#,
                 )?;
             }
+            BackendFramework::Axum => {
+                panic!("plugin_graphql not yet implemented for the axum backend");
+            }
         }
 
         fs::prepend(
@@ -251,6 +262,9 @@ use actix_web::guard;",
                 .with(AddData::new(schema))",
                 )?;
             }
+            BackendFramework::Axum => {
+                panic!("plugin_graphql not yet implemented for the axum backend");
+            }
         };
 
         Ok(())
@@ -152,6 +152,20 @@ DROP TABLE attachments;
                 .with(AddData::new(data.storage))",
                 )?;
             }
+            BackendFramework::Axum => {
+                crate::content::service::register_axum(
+                    "file",
+                    "services::file::router()",
+                    "/files",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    ".layer(Extension(data.database))",
+                    ".layer(Extension(data.database))
+        .layer(Extension(data.storage))",
+                )?;
+            }
         };
 
         fs::append("backend/services/mod.rs", "pub mod file;")?;
@@ -76,8 +76,40 @@ CREATE INDEX fang_tasks_state_index ON fang_tasks(state);
 CREATE INDEX fang_tasks_type_index ON fang_tasks(task_type);
 CREATE INDEX fang_tasks_scheduled_at_index ON fang_tasks(scheduled_at);
 CREATE INDEX fang_tasks_uniq_hash ON fang_tasks(uniq_hash);
+
+CREATE TABLE mail_outbox (
+    id SERIAL PRIMARY KEY,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    html_body TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX mail_outbox_status ON mail_outbox(status);
+
+SELECT manage_updated_at('mail_outbox');
 "#},
-            r"DROP TABLE fang_tasks;",
+            r"DROP TABLE mail_outbox;
+DROP TABLE fang_tasks;",
+        )?;
+
+        // ===============================
+        // Save emails to the outbox
+        // ===============================
+
+        fs::append(
+            ".env.example",
+            r"
+# emails are saved to the mail_outbox table and delivered by the `queue` binary
+MAIL_OUTBOX=true
+",
         )?;
 
         // ===============================
@@ -112,6 +144,17 @@ CREATE INDEX fang_tasks_uniq_hash ON fang_tasks(uniq_hash);
 
 "#.trim();
 
+        // the auth plugin's account deletions are completed by a periodic task
+        let example_tasks = if install_config.plugin_auth {
+            format!(
+                "{example_tasks}
+
+    create_rust_app::tasks::account_deletions::schedule().expect(\"Failed to schedule account deletions\");"
+            )
+        } else {
+            example_tasks.to_string()
+        };
+
         match install_config.backend_framework {
             BackendFramework::ActixWeb => {
                 fs::replace(
@@ -130,6 +173,23 @@ CREATE INDEX fang_tasks_uniq_hash ON fang_tasks(uniq_hash);
                     ),
                 )?;
             }
+            BackendFramework::Axum => {
+                fs::replace(
+                    "backend/main.rs",
+                    "use tower_http::services::ServeDir;",
+                    "use tower_http::services::ServeDir;\nuse fang::{AsyncQueueable, AsyncRunnable, NoTls, Queueable};",
+                )?;
+
+                fs::replace(
+                    "backend/main.rs",
+                    "let data = create_rust_app::setup();",
+                    &format!(
+                        "{example_tasks}
+
+    let data = create_rust_app::setup();"
+                    ),
+                )?;
+            }
             BackendFramework::Poem => {
                 fs::replace(
                     "backend/main.rs",
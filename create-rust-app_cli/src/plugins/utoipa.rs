@@ -13,7 +13,7 @@ impl Plugin for Utoipa {
     }
 
     fn install(&self, install_config: InstallConfig) -> Result<()> {
-        // TODO: remove when utoipa plugin is implemented for Poem
+        // TODO: remove when utoipa plugin is implemented for Poem and Axum
         if install_config.backend_framework != BackendFramework::ActixWeb {
             crate::logger::error("Currently, the Utoipa plugin requires the Actix plugin!");
             std::process::exit(1);
@@ -41,7 +41,7 @@ impl Plugin for Utoipa {
                     r#"utoipa-swagger-ui = { version="4", features=["actix-web"]}"#,
                 )?;
             }
-            BackendFramework::Poem => {
+            BackendFramework::Poem | BackendFramework::Axum => {
                 crate::logger::error(
                     "plugin_utoipa not yet implemented for backends other than actix-web",
                 );
@@ -70,7 +70,7 @@ impl Plugin for Utoipa {
             ]));"#,
                 )?;
             }
-            BackendFramework::Poem => {
+            BackendFramework::Poem | BackendFramework::Axum => {
                 panic!("plugin_utoipa not yet implemented for backends other than actix-web");
             }
         };
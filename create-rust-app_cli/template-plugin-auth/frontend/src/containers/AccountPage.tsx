@@ -9,6 +9,11 @@ export const AccountPage = () => {
   const [processing, setProcessing] = useState<boolean>(false)
   const [originalPassword, setOriginalPassword] = useState<string>('')
   const [password, setPassword] = useState<string>('')
+  const [emailPassword, setEmailPassword] = useState<string>('')
+  const [newEmail, setNewEmail] = useState<string>('')
+  const [emailMessage, setEmailMessage] = useState<string>('')
+  const [deletionPassword, setDeletionPassword] = useState<string>('')
+  const [deletionMessage, setDeletionMessage] = useState<string>('')
 
   const [page, setPage] = useState<number>(0)
   const [pageSize, setPageSize] = useState<number>(10)
@@ -105,6 +110,78 @@ export const AccountPage = () => {
     setProcessing(false)
   }
 
+  const changeEmail = async () => {
+    setProcessing(true)
+    const response = await (
+      await fetch('/api/auth/email/change', {
+        method: 'POST',
+        headers: {
+          'Content-Type': 'application/json',
+          Authorization: `Bearer ${auth.accessToken}`,
+        },
+        body: JSON.stringify({
+          password: emailPassword,
+          new_email: newEmail,
+        }),
+      })
+    ).json()
+    setEmailMessage(response.message)
+    setEmailPassword('')
+    setNewEmail('')
+    setProcessing(false)
+  }
+
+  const requestDeletion = async () => {
+    setProcessing(true)
+    const response = await fetch('/api/auth/account/deletion', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ password: deletionPassword }),
+    })
+    const json = await response.json()
+    setDeletionMessage(
+      response.ok
+        ? `Your account will be deleted on ${new Date(
+            json.deletion_scheduled_at
+          ).toLocaleString()}.`
+        : json.message
+    )
+    setDeletionPassword('')
+    setProcessing(false)
+  }
+
+  const cancelDeletion = async () => {
+    setProcessing(true)
+    const response = await (
+      await fetch('/api/auth/account/deletion', {
+        method: 'DELETE',
+        headers: {
+          Authorization: `Bearer ${auth.accessToken}`,
+        },
+      })
+    ).json()
+    setDeletionMessage(response.message)
+    setProcessing(false)
+  }
+
+  const exportData = async () => {
+    const data = await (
+      await fetch('/api/auth/account/export', {
+        headers: {
+          Authorization: `Bearer ${auth.accessToken}`,
+        },
+      })
+    ).blob()
+    const link = document.createElement('a')
+    link.href = URL.createObjectURL(data)
+    link.download = 'account.json'
+    link.click()
+    URL.revokeObjectURL(link.href)
+  }
+
   return (
     <div style={{ textAlign: 'left' }}>
       <h1>Account</h1>
@@ -151,6 +228,59 @@ export const AccountPage = () => {
               </button>
             </div>
           </div>
+          <div className="Form" style={{ textAlign: 'left' }}>
+            <h1>Change email</h1>
+            <br />
+            {emailMessage && <p>{emailMessage}</p>}
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <label>New Email</label>
+              <input
+                value={newEmail}
+                onChange={(e) => setNewEmail(e.target.value)}
+              />
+            </div>
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <label>Password</label>
+              <input
+                type="password"
+                value={emailPassword}
+                onChange={(e) => setEmailPassword(e.target.value)}
+              />
+            </div>
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <button disabled={processing} onClick={changeEmail}>
+                Send confirmation link
+              </button>
+            </div>
+          </div>
+          <div className="Form" style={{ textAlign: 'left' }}>
+            <h1>Your data</h1>
+            <br />
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <button onClick={exportData}>Download my data</button>
+            </div>
+          </div>
+          <div className="Form" style={{ textAlign: 'left' }}>
+            <h1>Delete account</h1>
+            <br />
+            {deletionMessage && <p>{deletionMessage}</p>}
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <label>Password</label>
+              <input
+                type="password"
+                value={deletionPassword}
+                onChange={(e) => setDeletionPassword(e.target.value)}
+              />
+            </div>
+            <div style={{ display: 'flex', flexFlow: 'column' }}>
+              <button disabled={processing} onClick={requestDeletion}>
+                Delete my account
+              </button>
+              <button disabled={processing} onClick={cancelDeletion}>
+                Cancel deletion
+              </button>
+            </div>
+          </div>
           <div>
             <h1>Sessions</h1>
             <button disabled={isDeleting} onClick={() => deleteAllSessions()}>
@@ -158,6 +288,13 @@ export const AccountPage = () => {
             </button>
             {sessions.sessions.map((session) => (
               <div>
+                <div>
+                  {session.device_name ?? session.device ?? 'Unknown device'}
+                  {' from '}
+                  {session.ip_address ?? 'an unknown address'}
+                  {', last refreshed '}
+                  {new Date(session.created_at).toLocaleString()}
+                </div>
                 {JSON.stringify(session, null, 2)}
                 <button
                   disabled={isDeleting}
@@ -0,0 +1,42 @@
+import React, { useEffect, useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { useQueryParam } from '../hooks/useQueryParam'
+
+export const EmailChangePage = () => {
+  const navigate = useNavigate()
+  const token = useQueryParam('token')
+  const [message, setMessage] = useState<string>('Confirming your new email...')
+
+  // the user followed the link emailed to their new address
+  useEffect(() => {
+    if (!token) {
+      setMessage('This link is invalid.')
+      return
+    }
+    fetch('/api/auth/email/change/confirm', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ email_change_token: token }),
+    }).then(async (response) => {
+      const json = await response.json()
+      setMessage(
+        response.ok
+          ? 'Your email was changed, and your other sessions were logged out.'
+          : json.message
+      )
+    })
+  }, [token])
+
+  return (
+    <div className="Form" style={{ textAlign: 'left' }}>
+      <h1>Change email</h1>
+      <br />
+      <p>{message}</p>
+      <div style={{ display: 'flex', flexFlow: 'column' }}>
+        <button onClick={() => navigate('/account')}>Back to your account</button>
+      </div>
+    </div>
+  )
+}
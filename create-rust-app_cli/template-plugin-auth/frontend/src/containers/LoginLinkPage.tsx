@@ -0,0 +1,69 @@
+import React, { useEffect, useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { useAuth } from '../hooks/useAuth'
+import { useQueryParam } from '../hooks/useQueryParam'
+
+export const LoginLinkPage = () => {
+  const auth = useAuth()
+  const navigate = useNavigate()
+  const loginToken = useQueryParam('token')
+  const [email, setEmail] = useState<string>('')
+  const [processing, setProcessing] = useState<boolean>(false)
+  const [message, setMessage] = useState<string>('')
+
+  // the user followed the emailed link
+  useEffect(() => {
+    if (!loginToken) return
+    setProcessing(true)
+    auth.loginWithLink(loginToken).then((success) => {
+      setProcessing(false)
+      if (success) {
+        navigate('/')
+      } else {
+        setMessage('This login link is invalid or has expired.')
+      }
+    })
+  }, [loginToken])
+
+  const sendLink = async () => {
+    setProcessing(true)
+    const response = await (
+      await fetch('/api/auth/login/link', {
+        method: 'POST',
+        headers: {
+          'Content-Type': 'application/json',
+        },
+        body: JSON.stringify({ email }),
+      })
+    ).json()
+    setMessage(response.message)
+    setProcessing(false)
+    setEmail('')
+  }
+
+  if (auth.isAuthenticated) {
+    navigate('/')
+    return <div>Already logged in. Redirecting you to the home page...</div>
+  }
+
+  if (loginToken && processing) {
+    return <div>Logging you in...</div>
+  }
+
+  return (
+    <div className="Form" style={{ textAlign: 'left' }}>
+      <h1>Email me a login link</h1>
+      <br />
+      {message && <p>{message}</p>}
+      <div style={{ display: 'flex', flexFlow: 'column' }}>
+        <label>Email</label>
+        <input value={email} onChange={(e) => setEmail(e.target.value)} />
+      </div>
+      <div style={{ display: 'flex', flexFlow: 'column' }}>
+        <button disabled={processing} onClick={sendLink}>
+          Send link
+        </button>
+      </div>
+    </div>
+  )
+}
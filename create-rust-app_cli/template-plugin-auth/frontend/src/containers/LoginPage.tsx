@@ -55,6 +55,13 @@ export const LoginPage = () => {
       >
         Forgot your password? Click here to recover your account.
       </a>
+      <a
+        style={{ marginTop: '30px' }}
+        href="#"
+        onClick={() => navigate('/login/link')}
+      >
+        Rather not type your password? Click here to get a login link by email.
+      </a>
     </div>
   )
 }
@@ -0,0 +1,87 @@
+import React, { useEffect, useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { useAuth } from '../hooks/useAuth'
+
+interface ConsentRequest {
+  client_name: string
+  scopes: string[]
+}
+
+export const OAuthAuthorizePage = () => {
+  const auth = useAuth()
+  const navigate = useNavigate()
+  const [processing, setProcessing] = useState<boolean>(false)
+  const [consent, setConsent] = useState<ConsentRequest | undefined>()
+  const [message, setMessage] = useState<string>('')
+
+  // the client app sent the user here with its request in the query string
+  const authorize = async (consent?: boolean) => {
+    setProcessing(true)
+    const params = Object.fromEntries(new URLSearchParams(window.location.search))
+    const response = await fetch('/api/auth/oauth/authorize', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Authorization: `Bearer ${auth.accessToken}`,
+      },
+      body: JSON.stringify({ ...params, consent }),
+    })
+    const json = await response.json()
+    if (response.ok && json.redirect_uri) {
+      window.location.href = json.redirect_uri
+      return
+    }
+    if (response.ok && json.consent_required) {
+      setConsent({ client_name: json.client_name, scopes: json.scopes })
+    } else {
+      setMessage(json.message || 'This authorization request is invalid.')
+    }
+    setProcessing(false)
+  }
+
+  useEffect(() => {
+    if (auth.isAuthenticated) authorize()
+  }, [auth.isAuthenticated])
+
+  if (!auth.isAuthenticated) {
+    return (
+      <div className="Form" style={{ textAlign: 'left' }}>
+        <h1>Sign in</h1>
+        <br />
+        <p>Log in first, then come back to this page to continue.</p>
+        <div style={{ display: 'flex', flexFlow: 'column' }}>
+          <button onClick={() => navigate('/login')}>Login</button>
+        </div>
+      </div>
+    )
+  }
+
+  if (message) {
+    return <div>{message}</div>
+  }
+
+  if (!consent) {
+    return <div>Signing you in...</div>
+  }
+
+  return (
+    <div className="Form" style={{ textAlign: 'left' }}>
+      <h1>Allow {consent.client_name}?</h1>
+      <br />
+      <p>{consent.client_name} would like to access your:</p>
+      <ul>
+        {consent.scopes.map((scope) => (
+          <li key={scope}>{scope}</li>
+        ))}
+      </ul>
+      <div style={{ display: 'flex', flexFlow: 'column' }}>
+        <button disabled={processing} onClick={() => authorize(true)}>
+          Allow
+        </button>
+        <button disabled={processing} onClick={() => authorize(false)}>
+          Deny
+        </button>
+      </div>
+    </div>
+  )
+}
@@ -2,26 +2,45 @@ import React, { useState } from 'react'
 import { useNavigate } from 'react-router-dom'
 import { useAuth } from '../hooks/useAuth'
 
+// the Json body of an error response of the auth endpoints
+type AuthErrorResponse = AuthError & { message: string }
+
+// the message of an error response, followed by the password policy violations it lists, if any
+export const describePasswordError = (json: AuthErrorResponse) =>
+  [
+    json.message,
+    ...(json.code === 'password_policy' ? json.violations : []).map(
+      (violation) =>
+        violation.code === 'too_short'
+          ? `It must be at least ${violation.min_length} characters long.`
+          : `(${violation.code.replaceAll('_', ' ')})`,
+    ),
+  ].join(' ')
+
 export const RegistrationPage = () => {
   const auth = useAuth()
   const navigate = useNavigate()
   const [email, setEmail] = useState<string>('')
   const [password, setPassword] = useState<string>('')
   const [processing, setProcessing] = useState<boolean>(false)
+  const [error, setError] = useState<string>('')
 
   const register = async () => {
     setProcessing(true)
-    const response = (
-      await fetch('/api/auth/register', {
-        method: 'POST',
-        headers: {
-          'Content-Type': 'application/json',
-        },
-        body: JSON.stringify({ email, password }),
-      })
-    ).json()
-    console.log(response)
+    const response = await fetch('/api/auth/register', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ email, password, locale: navigator.language }),
+    })
+    const json = await response.json()
+    console.log(json)
     setProcessing(false)
+    if (!response.ok) {
+      setError(describePasswordError(json))
+      return
+    }
     navigate('/activate')
   }
 
@@ -41,6 +60,7 @@ export const RegistrationPage = () => {
           onChange={(e) => setPassword(e.target.value)}
         />
       </div>
+      {error && <div>{error}</div>}
       <div style={{ display: 'flex', flexFlow: 'column' }}>
         <button disabled={processing} onClick={register}>
           Register
@@ -2,6 +2,7 @@ import React, { useState } from 'react'
 import { useNavigate } from 'react-router-dom'
 import { useAuth } from '../hooks/useAuth'
 import { useQueryParam } from '../hooks/useQueryParam'
+import { describePasswordError } from './RegistrationPage'
 
 export const ResetPage = () => {
   const auth = useAuth()
@@ -11,23 +12,27 @@ export const ResetPage = () => {
   const [newPasswordConfirmation, setNewPasswordConfirmation] =
     useState<string>('')
   const [processing, setProcessing] = useState<boolean>(false)
+  const [error, setError] = useState<string>('')
 
   const reset = async () => {
     setProcessing(true)
     try {
-      const response = await (
-        await fetch('/api/auth/reset', {
-          method: 'POST',
-          headers: {
-            'Content-Type': 'application/json',
-          },
-          body: JSON.stringify({
-            reset_token: resetToken,
-            new_password: newPassword,
-          }),
-        })
-      ).json()
-      console.log(response)
+      const response = await fetch('/api/auth/reset', {
+        method: 'POST',
+        headers: {
+          'Content-Type': 'application/json',
+        },
+        body: JSON.stringify({
+          reset_token: resetToken,
+          new_password: newPassword,
+        }),
+      })
+      const json = await response.json()
+      console.log(json)
+      if (!response.ok) {
+        setError(describePasswordError(json))
+        return
+      }
       navigate('/login')
       setNewPassword('')
       setNewPasswordConfirmation('')
@@ -61,6 +66,7 @@ export const ResetPage = () => {
           onChange={(e) => setNewPasswordConfirmation(e.target.value)}
         />
       </div>
+      {error && <div>{error}</div>}
       <div style={{ display: 'flex', flexFlow: 'column' }}>
         <button disabled={processing} onClick={reset}>
           Recover
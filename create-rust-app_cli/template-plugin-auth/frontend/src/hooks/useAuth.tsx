@@ -90,6 +90,22 @@ export const useAuth = () => {
       body: JSON.stringify({ email, password }),
     })
 
+    return startSession(response)
+  }
+
+  const loginWithLink = async (loginToken: string): Promise<boolean> => {
+    const response = await fetch('/api/auth/login/link/verify', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ login_token: loginToken }),
+    })
+
+    return startSession(response)
+  }
+
+  const startSession = async (response: Response): Promise<boolean> => {
     if (response.ok) {
       const responseJson = await response.json()
       const parsedToken = parseJwt(responseJson.access_token) as AccessTokenClaims
@@ -131,6 +147,7 @@ export const useAuth = () => {
     isCheckingAuth: context.isCheckingAuth,
     isAuthenticated: !!context.accessToken,
     login,
+    loginWithLink,
     logout,
   }
 }
@@ -1,81 +1,1052 @@
+/* This file is generated and managed by tsync */
+
+/** when an account will be deleted, see [`request_deletion`] */
+interface AccountDeletionResponse {
+  deletion_scheduled_at: Utc;
+}
+
+/** query parameters for the admin user search */
+interface UserSearchParams {
+  page: number;
+  page_size: number;
+  /** only return users whose email contains this (case-insensitive) */
+  query?: string;
+}
+
+/** a user, as seen by an admin */
+interface AdminUserJson {
+  id: ID;
+  email: string;
+  activated: boolean;
+  created_at: Utc;
+  /** roles assigned directly to the user (inherited roles are not included) */
+  roles: Array<string>;
+  /** permissions granted directly to the user (permissions granted through roles are not included) */
+  permissions: Array<string>;
+}
+
+/** a page of users matching an admin search */
+interface AdminUserResponse {
+  users: Array<AdminUserJson>;
+  num_pages: number;
+}
+
+/** a page of the admin audit trail, most recent first */
+interface AdminAuditLogResponse {
+  entries: Array<AdminAuditLog>;
+  num_pages: number;
+}
+
+interface ActivationInput {
+  activated: boolean;
+}
+
+interface RoleInput {
+  role: string;
+}
+
+interface PermissionInput {
+  permission: string;
+}
+
+/**
+ * Rust struct modeling an entry in the `admin_audit_logs` table
+ * 
+ * every change an admin makes through the admin endpoints is recorded here
+ */
+interface AdminAuditLog {
+  id: ID;
+  /** the admin who made the change */
+  admin_user_id: ID;
+  /** what was done, for example `role.assign` */
+  action: string;
+  /** the user the change was made to, if any */
+  target_user_id?: ID;
+  /** action specific details, for example the role that was assigned */
+  details?: string;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `admin_audit_logs` table */
+interface AdminAuditLogChangeset {
+  admin_user_id: ID;
+  action: string;
+  target_user_id?: ID;
+  details?: string;
+}
+
+/** the query parameters of an authorization request, posted by the frontend on behalf of the user */
+interface AuthorizeInput {
+  response_type: string;
+  client_id: string;
+  redirect_uri: string;
+  scope?: string;
+  state?: string;
+  nonce?: string;
+  code_challenge?: string;
+  code_challenge_method?: string;
+  /** the user's answer to [`AuthorizeOutcome::ConsentRequired`], not needed for first-party clients */
+  consent?: boolean;
+}
+
+/**
+ * the [RFC 6749](https://datatracker.ietf.org/doc/html/rfc6749#section-5.2) error codes of [`token`],
+ * see [`AuthError::InvalidTokenRequest`]
+ */
+type TokenError =
+  | "invalid_client" | "invalid_grant" | "invalid_request" | "unsupported_grant_type" | "server_error";
+
+/**
+ * why a request to the auth endpoints (see [`controller`](`crate::auth::controller`)) failed
+ * 
+ * the variant names are stable and sent as the snake-cased `code` of error responses,
+ * alongside an english `message`, so frontends can show their own (localised) messages
+ */
+type AuthError =
+  | AuthError__AccountNotActivated
+  | AuthError__AlreadyActivated
+  | AuthError__AlreadyRegistered
+  | AuthError__CannotDeactivateSelf
+  | AuthError__CannotUnlinkOnlyLogin
+  | AuthError__DeletionNotScheduled
+  | AuthError__DeviceTooLong
+  | AuthError__EmailInUse
+  | AuthError__EmailUnchanged
+  | AuthError__ExpiryInPast
+  | AuthError__InvalidChallenge
+  | AuthError__InvalidCsrfToken
+  | AuthError__InvalidLocale
+  | AuthError__InvalidName
+  | AuthError__InvalidPagination
+  | AuthError__InvalidPasskeyRegistration
+  | AuthError__InvalidPermission
+  | AuthError__InvalidRedirectUri
+  | AuthError__InvalidTokenRequest
+  | AuthError__MissingEmail
+  | AuthError__MissingPassword
+  | AuthError__NameTooLong
+  | AuthError__PasskeyAlreadyRegistered
+  | AuthError__PasswordUnchanged
+  | AuthError__PasswordPolicy
+  | AuthError__TwoFactorAlreadyEnabled
+  | AuthError__TwoFactorNotEnabled
+  | AuthError__TwoFactorNotEnrolled
+  | AuthError__UnknownClient
+  | AuthError__InvalidCode
+  | AuthError__InvalidCredentials
+  | AuthError__InvalidPasskey
+  | AuthError__InvalidSession
+  | AuthError__InvalidToken
+  | AuthError__OidcProviderError
+  | AuthError__ApiKeyCannotAuthorize
+  | AuthError__ApiKeyNotAllowed
+  | AuthError__Forbidden
+  | AuthError__ApiKeyNotFound
+  | AuthError__LinkNotFound
+  | AuthError__NotFound
+  | AuthError__PasskeyNotFound
+  | AuthError__SessionNotFound
+  | AuthError__UserNotFound
+  | AuthError__LinkedToOtherUser
+  | AuthError__TooManyFailedLogins
+  | AuthError__CouldNotActivateUser
+  | AuthError__CouldNotAssignRole
+  | AuthError__CouldNotCancelDeletion
+  | AuthError__CouldNotCheckPassword
+  | AuthError__CouldNotCreateApiKey
+  | AuthError__CouldNotCreateAuthorizationCode
+  | AuthError__CouldNotCreateSession
+  | AuthError__CouldNotDeleteApiKey
+  | AuthError__CouldNotDeletePasskey
+  | AuthError__CouldNotDeleteSession
+  | AuthError__CouldNotDeleteSessions
+  | AuthError__CouldNotDisableTwoFactor
+  | AuthError__CouldNotEnableTwoFactor
+  | AuthError__CouldNotEnrollTwoFactor
+  | AuthError__CouldNotExportAccount
+  | AuthError__CouldNotFetchApiKeys
+  | AuthError__CouldNotFetchAuditLog
+  | AuthError__CouldNotFetchLinks
+  | AuthError__CouldNotFetchPasskeys
+  | AuthError__CouldNotFetchSessions
+  | AuthError__CouldNotFetchUser
+  | AuthError__CouldNotFetchUserInfo
+  | AuthError__CouldNotFetchUsers
+  | AuthError__CouldNotFindUser
+  | AuthError__CouldNotGrantPermission
+  | AuthError__CouldNotRecordAdminAction
+  | AuthError__CouldNotRegisterPasskey
+  | AuthError__CouldNotRevokePermission
+  | AuthError__CouldNotScheduleDeletion
+  | AuthError__CouldNotSyncRoles
+  | AuthError__CouldNotUnassignRole
+  | AuthError__CouldNotUpdateEmail
+  | AuthError__CouldNotUpdatePassword
+  | AuthError__CouldNotUpdateSession
+  | AuthError__CouldNotUpdateUser
+  | AuthError__InvalidProviderResponse
+  | AuthError__NoVerifiedEmail
+  | AuthError__DatabaseUnavailable
+  | AuthError__Internal
+  | AuthError__ProviderNotSupported;
+
+type AuthError__AccountNotActivated = {
+  code: "account_not_activated";
+};
+/** the user already activated their account */
+type AuthError__AlreadyActivated = {
+  code: "already_activated";
+};
+type AuthError__AlreadyRegistered = {
+  code: "already_registered";
+};
+type AuthError__CannotDeactivateSelf = {
+  code: "cannot_deactivate_self";
+};
+/** unlinking the provider would leave the user without a way to log in */
+type AuthError__CannotUnlinkOnlyLogin = {
+  code: "cannot_unlink_only_login";
+};
+type AuthError__DeletionNotScheduled = {
+  code: "deletion_not_scheduled";
+};
+type AuthError__DeviceTooLong = {
+  code: "device_too_long";
+};
+type AuthError__EmailInUse = {
+  code: "email_in_use";
+};
+type AuthError__EmailUnchanged = {
+  code: "email_unchanged";
+};
+type AuthError__ExpiryInPast = {
+  code: "expiry_in_past";
+};
+type AuthError__InvalidChallenge = {
+  code: "invalid_challenge";
+};
+type AuthError__InvalidCsrfToken = {
+  code: "invalid_csrf_token";
+};
+type AuthError__InvalidLocale = {
+  code: "invalid_locale";
+};
+type AuthError__InvalidName = {
+  code: "invalid_name";
+};
+/** a negative `page`, or a `page_size` below 1 */
+type AuthError__InvalidPagination = {
+  code: "invalid_pagination";
+};
+type AuthError__InvalidPasskeyRegistration = {
+  code: "invalid_passkey_registration";
+};
+type AuthError__InvalidPermission = {
+  code: "invalid_permission";
+};
+type AuthError__InvalidRedirectUri = {
+  code: "invalid_redirect_uri";
+};
+/**
+ * a request to the authorization server's token endpoint failed, `error` is its
+ * [RFC 6749](https://datatracker.ietf.org/doc/html/rfc6749#section-5.2) error code (400, or 401 for `invalid_client`)
+ */
+type AuthError__InvalidTokenRequest = {
+  code: "invalid_token_request";
+  error: TokenError;
+};
+type AuthError__MissingEmail = {
+  code: "missing_email";
+};
+type AuthError__MissingPassword = {
+  code: "missing_password";
+};
+type AuthError__NameTooLong = {
+  code: "name_too_long";
+};
+type AuthError__PasskeyAlreadyRegistered = {
+  code: "passkey_already_registered";
+};
+type AuthError__PasswordUnchanged = {
+  code: "password_unchanged";
+};
+/** the new password doesn't follow [`AuthConfig::password_policy`](`crate::auth::AuthConfig`) */
+type AuthError__PasswordPolicy = {
+  code: "password_policy";
+  violations: Array<PasswordViolation>;
+};
+type AuthError__TwoFactorAlreadyEnabled = {
+  code: "two_factor_already_enabled";
+};
+type AuthError__TwoFactorNotEnabled = {
+  code: "two_factor_not_enabled";
+};
+type AuthError__TwoFactorNotEnrolled = {
+  code: "two_factor_not_enrolled";
+};
+type AuthError__UnknownClient = {
+  code: "unknown_client";
+};
+type AuthError__InvalidCode = {
+  code: "invalid_code";
+};
+type AuthError__InvalidCredentials = {
+  code: "invalid_credentials";
+};
+type AuthError__InvalidPasskey = {
+  code: "invalid_passkey";
+};
+type AuthError__InvalidSession = {
+  code: "invalid_session";
+};
+type AuthError__InvalidToken = {
+  code: "invalid_token";
+};
+/** the OIDC provider redirected back with an `error` */
+type AuthError__OidcProviderError = {
+  code: "oidc_provider_error";
+  error: string;
+};
+type AuthError__ApiKeyCannotAuthorize = {
+  code: "api_key_cannot_authorize";
+};
+type AuthError__ApiKeyNotAllowed = {
+  code: "api_key_not_allowed";
+};
+type AuthError__Forbidden = {
+  code: "forbidden";
+};
+type AuthError__ApiKeyNotFound = {
+  code: "api_key_not_found";
+};
+type AuthError__LinkNotFound = {
+  code: "link_not_found";
+};
+/** the endpoint is disabled, like the admin endpoints without [`AuthConfig::admin_role`](`crate::auth::AuthConfig`) */
+type AuthError__NotFound = {
+  code: "not_found";
+};
+type AuthError__PasskeyNotFound = {
+  code: "passkey_not_found";
+};
+type AuthError__SessionNotFound = {
+  code: "session_not_found";
+};
+type AuthError__UserNotFound = {
+  code: "user_not_found";
+};
+/** the provider's account is already linked to another user */
+type AuthError__LinkedToOtherUser = {
+  code: "linked_to_other_user";
+};
+type AuthError__TooManyFailedLogins = {
+  code: "too_many_failed_logins";
+};
+type AuthError__CouldNotActivateUser = {
+  code: "could_not_activate_user";
+};
+type AuthError__CouldNotAssignRole = {
+  code: "could_not_assign_role";
+};
+type AuthError__CouldNotCancelDeletion = {
+  code: "could_not_cancel_deletion";
+};
+type AuthError__CouldNotCheckPassword = {
+  code: "could_not_check_password";
+};
+type AuthError__CouldNotCreateApiKey = {
+  code: "could_not_create_api_key";
+};
+type AuthError__CouldNotCreateAuthorizationCode = {
+  code: "could_not_create_authorization_code";
+};
+type AuthError__CouldNotCreateSession = {
+  code: "could_not_create_session";
+};
+type AuthError__CouldNotDeleteApiKey = {
+  code: "could_not_delete_api_key";
+};
+type AuthError__CouldNotDeletePasskey = {
+  code: "could_not_delete_passkey";
+};
+type AuthError__CouldNotDeleteSession = {
+  code: "could_not_delete_session";
+};
+type AuthError__CouldNotDeleteSessions = {
+  code: "could_not_delete_sessions";
+};
+type AuthError__CouldNotDisableTwoFactor = {
+  code: "could_not_disable_two_factor";
+};
+type AuthError__CouldNotEnableTwoFactor = {
+  code: "could_not_enable_two_factor";
+};
+type AuthError__CouldNotEnrollTwoFactor = {
+  code: "could_not_enroll_two_factor";
+};
+type AuthError__CouldNotExportAccount = {
+  code: "could_not_export_account";
+};
+type AuthError__CouldNotFetchApiKeys = {
+  code: "could_not_fetch_api_keys";
+};
+type AuthError__CouldNotFetchAuditLog = {
+  code: "could_not_fetch_audit_log";
+};
+type AuthError__CouldNotFetchLinks = {
+  code: "could_not_fetch_links";
+};
+type AuthError__CouldNotFetchPasskeys = {
+  code: "could_not_fetch_passkeys";
+};
+type AuthError__CouldNotFetchSessions = {
+  code: "could_not_fetch_sessions";
+};
+type AuthError__CouldNotFetchUser = {
+  code: "could_not_fetch_user";
+};
+type AuthError__CouldNotFetchUserInfo = {
+  code: "could_not_fetch_user_info";
+};
+type AuthError__CouldNotFetchUsers = {
+  code: "could_not_fetch_users";
+};
+type AuthError__CouldNotFindUser = {
+  code: "could_not_find_user";
+};
+type AuthError__CouldNotGrantPermission = {
+  code: "could_not_grant_permission";
+};
+type AuthError__CouldNotRecordAdminAction = {
+  code: "could_not_record_admin_action";
+};
+type AuthError__CouldNotRegisterPasskey = {
+  code: "could_not_register_passkey";
+};
+type AuthError__CouldNotRevokePermission = {
+  code: "could_not_revoke_permission";
+};
+type AuthError__CouldNotScheduleDeletion = {
+  code: "could_not_schedule_deletion";
+};
+type AuthError__CouldNotSyncRoles = {
+  code: "could_not_sync_roles";
+};
+type AuthError__CouldNotUnassignRole = {
+  code: "could_not_unassign_role";
+};
+type AuthError__CouldNotUpdateEmail = {
+  code: "could_not_update_email";
+};
+type AuthError__CouldNotUpdatePassword = {
+  code: "could_not_update_password";
+};
+type AuthError__CouldNotUpdateSession = {
+  code: "could_not_update_session";
+};
+type AuthError__CouldNotUpdateUser = {
+  code: "could_not_update_user";
+};
+/** the OIDC provider's tokens or claims couldn't be verified */
+type AuthError__InvalidProviderResponse = {
+  code: "invalid_provider_response";
+};
+/** the OIDC provider didn't tell us a verified email for a new user */
+type AuthError__NoVerifiedEmail = {
+  code: "no_verified_email";
+};
+/** the database connection pool couldn't hand out a connection, any controller function can fail with this */
+type AuthError__DatabaseUnavailable = {
+  code: "database_unavailable";
+};
+type AuthError__Internal = {
+  code: "internal";
+};
+/** there's no OIDC provider with the requested name */
+type AuthError__ProviderNotSupported = {
+  code: "provider_not_supported";
+};
+
+/** Rust struct modeling an entry in the `failed_logins` table */
+interface FailedLogin {
+  id: ID;
+  /** the account the attempt was for, if the email belonged to one */
+  user_id?: ID;
+  ip_address?: string;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `failed_logins` table */
+interface FailedLoginChangeset {
+  user_id?: ID;
+  ip_address?: string;
+}
+
 type ID = number
 
-type UTC = Date
+type Utc = Date
 
+/**
+ * Rust struct that provides the information needed to allow
+ * pagination of results for requests that have a lot of results
+ * 
+ * often times, GET requests to a REST API will have a lot of
+ * results to return, pagination allows the server to break up
+ * those results into smaller chunks that can be more easily
+ * sent to, and used by, the client
+ */
 interface PaginationParams {
-    page: number
-    page_size: number
+  page: number;
+  page_size: number;
+}
+
+/**
+ * Rust struct representation of a entry from the databases `user_session` table
+ * serialized into Json
+ */
+interface UserSessionJson {
+  id: ID;
+  device?: string;
+  /** named after the session's user agent, like \"Firefox on Linux\" */
+  device_name?: string;
+  ip_address?: string;
+  user_agent?: string;
+  /** when the user logged in */
+  logged_in_at: Utc;
+  /** when the session was last refreshed, every refresh continues the session in a new entry */
+  created_at: Utc;
+  updated_at: Utc;
+}
+
+/**
+ * Rust struct representation of the
+ * backends JSON response to a GET request at the /sessions endpoint
+ */
+interface UserSessionResponse {
+  sessions: Array<UserSessionJson>;
+  num_pages: number;
+}
+
+/**
+ * Rust struct representation of a entry from the databases `user_passkeys` table
+ * serialized into Json, without the key material
+ */
+interface UserPasskeyJson {
+  id: ID;
+  name?: string;
+  last_used_at?: Utc;
+  created_at: Utc;
 }
 
+/**
+ * Rust struct representation of the
+ * backends JSON response to a GET request at the /passkeys endpoint
+ */
+interface UserPasskeyResponse {
+  passkeys: Array<UserPasskeyJson>;
+}
+
+/**
+ * Rust struct representation of a entry from the databases `user_api_keys` table
+ * serialized into Json, without the key's hash
+ */
+interface UserApiKeyJson {
+  id: ID;
+  name: string;
+  /** the first characters of the key, so users can recognize it */
+  key_prefix: string;
+  /** the permissions the key is limited to, `None` if it has all of the user's permissions and roles */
+  permissions?: Array<string>;
+  last_used_at?: Utc;
+  expires_at?: Utc;
+  created_at: Utc;
+}
+
+/**
+ * Rust struct representation of the
+ * backends JSON response to a GET request at the /api-keys endpoint
+ */
+interface UserApiKeyResponse {
+  api_keys: Array<UserApiKeyJson>;
+}
+
+/** TODO: documentation */
 interface AccessTokenClaims {
-    exp: number
-    sub: number
-    token_type: string
-    roles: Array<string>
-    permissions: Array<Permission>
+  exp: number;
+  sub: ID;
+  token_type: string;
+  roles: Array<string>;
+  permissions: Array<Permission>;
 }
 
-interface UserSessionJson {
-    id: number
-    device: string | undefined
-    created_atDate
-    updated_atDate
+/** structure to help utoipa know what responses that contain a message */
+interface AuthMessageResponse {
+  message: string;
 }
 
-interface UserSessionResponse {
-    sessions: Array<UserSessionJson>
-    num_pages: number
+/**
+ * structure to help utoipa know what responses to a password which doesn't follow
+ * the [`PasswordPolicy`] contain, see [`error::AuthError::PasswordPolicy`]
+ */
+interface PasswordPolicyResponse {
+  /** always `password_policy` */
+  code: string;
+  message: string;
+  violations: Array<PasswordViolation>;
+}
+
+/** structure to help utoipa know what responses that contain the `access_token` should look like */
+interface AuthTokenResponse {
+  access_token: string;
+}
+
+/**
+ * structure to help utoipa know what a login response looks like
+ * when the user still has to provide their second factor
+ */
+interface AuthTwoFactorRequiredResponse {
+  two_factor_required: boolean;
+  two_factor_token: string;
 }
 
+/**
+ * Rust struct modeling an entry in the `oauth_authorization_codes` table
+ * 
+ * a code issued to a client after the user authorized it, which it can exchange for tokens once
+ */
+interface OAuthAuthorizationCode {
+  /** see [`OAuthAuthorizationCode::hash_code`], the code itself is only sent to the client */
+  code_hash: string;
+  /** the [`OAuthClient`](`crate::auth::OAuthClient`) the code was issued to */
+  oauth_client_id: ID;
+  user_id: ID;
+  /** the client has to present the same redirect uri when it exchanges the code */
+  redirect_uri: string;
+  /** space separated scopes the user authorized */
+  scope: string;
+  /** echoed in the ID token */
+  nonce?: string;
+  /** the base64url encoded SHA-256 hash of the client's PKCE code verifier */
+  code_challenge: string;
+  expires_at: Utc;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `oauth_authorization_codes` table */
+interface OAuthAuthorizationCodeChangeset {
+  code_hash: string;
+  oauth_client_id: ID;
+  user_id: ID;
+  redirect_uri: string;
+  scope: string;
+  nonce?: string;
+  code_challenge: string;
+  expires_at: Utc;
+}
+
+/**
+ * Rust struct modeling an entry in the `oauth_clients` table
+ * 
+ * an app which may sign its users in through this backend, see [`crate::auth::authorization_server`]
+ */
+interface OAuthClient {
+  id: ID;
+  /** the public identifier the client sends in its requests */
+  client_id: string;
+  /**
+   * see [`OAuthClient::hash_secret`], `None` for public clients (like SPAs and mobile apps)
+   * which can't keep a secret and only rely on PKCE
+   */
+  client_secret_hash?: string;
+  /** shown to users when they're asked for their consent */
+  name: string;
+  /** newline separated urls the client may be redirected to, compared exactly */
+  redirect_uris: string;
+  /** first-party clients are trusted, users aren't asked for their consent */
+  first_party: boolean;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `oauth_clients` table */
+interface OAuthClientChangeset {
+  client_id: string;
+  client_secret_hash?: string;
+  name: string;
+  redirect_uris: string;
+  first_party: boolean;
+}
+
+/**
+ * Rust struct representation of a linked entry from the databases `user_oauth2_links` table
+ * serialized into Json, without the tokens
+ */
+interface UserOauth2LinkJson {
+  id: number;
+  provider: string;
+  created_at: Date;
+}
+
+/**
+ * Rust struct representation of the
+ * backends JSON response to a GET request at the /oidc/links endpoint
+ */
+interface UserOauth2LinkResponse {
+  links: Array<UserOauth2LinkJson>;
+}
+
+interface UserOauth2Link {
+  id: number;
+  provider: string;
+  csrf_token: string;
+  nonce: string;
+  pkce_secret: string;
+  refresh_token?: string;
+  access_token?: string;
+  subject_id?: string;
+  user_id?: number;
+  created_at: Date;
+  updated_at: Date;
+}
+
+interface CreateUserOauth2Link {
+  provider: string;
+  csrf_token: string;
+  nonce: string;
+  pkce_secret: string;
+  refresh_token?: string;
+  access_token?: string;
+  subject_id?: string;
+  user_id?: number;
+}
+
+interface UpdateUserOauth2Link {
+  provider?: string;
+  csrf_token?: string;
+  nonce?: string;
+  pkce_secret?: string;
+  refresh_token?: string;
+  access_token?: string;
+  subject_id?: string;
+  user_id?: number;
+  created_at?: Date;
+  updated_at?: Date;
+}
+
+/**
+ * Rust struct modeling an entry in the `passkey_challenges` table
+ * 
+ * every passkey challenge which was issued and not answered yet, so each one can only be used once
+ */
+interface PasskeyChallenge {
+  /** base64url encoded challenge */
+  challenge: string;
+  /** the user registering a passkey, `None` for login challenges */
+  user_id?: ID;
+  expires_at: Utc;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `passkey_challenges` table */
+interface PasskeyChallengeChangeset {
+  challenge: string;
+  user_id?: ID;
+  expires_at: Utc;
+}
+
+/** a rule of the [`PasswordPolicy`] a password doesn't follow */
+type PasswordViolation =
+  | PasswordViolation__TooShort
+  | PasswordViolation__MissingLowercase
+  | PasswordViolation__MissingUppercase
+  | PasswordViolation__MissingDigit
+  | PasswordViolation__MissingSymbol
+  | PasswordViolation__ContainsEmail
+  | PasswordViolation__Breached;
+
+type PasswordViolation__TooShort = {
+  code: "too_short";
+  min_length: number;
+};
+type PasswordViolation__MissingLowercase = {
+  code: "missing_lowercase";
+};
+type PasswordViolation__MissingUppercase = {
+  code: "missing_uppercase";
+};
+type PasswordViolation__MissingDigit = {
+  code: "missing_digit";
+};
+type PasswordViolation__MissingSymbol = {
+  code: "missing_symbol";
+};
+type PasswordViolation__ContainsEmail = {
+  code: "contains_email";
+};
+/** the password appeared `count` times in [`BreachedPasswords`] */
+type PasswordViolation__Breached = {
+  code: "breached";
+  count: number;
+};
+
 interface Permission {
-    from_role: string
-    permission: string
+  /** the role this permission is coming from */
+  from_role: string;
+  /** the permission itself */
+  permission: string;
+}
+
+/**
+ * Rust struct modeling an entry in the `role_inheritances` table
+ * 
+ * users with `role` also have `inherited_role`, and every permission granted to it
+ */
+interface RoleInheritance {
+  role: string;
+  inherited_role: string;
+  created_at: Utc;
 }
 
+/** Rust struct modeling an entry in the `role_permissions` table */
 interface RolePermission {
-    role: string
-    permission: string
-    created_atDate
+  role: string;
+  permission: string;
+  created_at: Utc;
+}
+
+/**
+ * Rust struct modeling an entry in the `role_resource_permissions` table
+ * 
+ * grants `permission` to every user with `role` (or a role inheriting it),
+ * but only on the record identified by (`resource_type`, `resource_id`)
+ */
+interface RoleResourcePermission {
+  role: string;
+  permission: string;
+  resource_type: string;
+  resource_id: ID;
+  created_at: Utc;
 }
 
+/** Rust struct modeling an entry in the `user_permissions` table */
 interface UserPermission {
-    user_id: number
-    permission: string
-    created_atDate
+  user_id: ID;
+  permission: string;
+  created_at: Utc;
 }
 
+/**
+ * Rust struct modeling an entry in the `user_resource_permissions` table
+ * 
+ * grants `permission` to the user, but only on the record identified by (`resource_type`, `resource_id`)
+ */
+interface UserResourcePermission {
+  user_id: ID;
+  permission: string;
+  resource_type: string;
+  resource_id: ID;
+  created_at: Utc;
+}
+
+/** Rust struct modeling an entry in the `user_roles` table */
 interface UserRole {
-    user_id: number
-    role: string
-    created_atDate
+  user_id: ID;
+  role: string;
+  created_at: Utc;
 }
 
 interface User {
-    id: number
-    email: string
-    hash_password: string
-    activated: boolean
-    created_atDate
-    updated_atDate
+  id: ID;
+  email: string;
+  hash_password: string;
+  activated: boolean;
+  /**
+   * when the user asked for their account to be deleted, it's deleted once this has passed,
+   * see [`account`](`crate::auth::account`)
+   */
+  deletion_scheduled_at?: Utc;
+  /** set once the account was deleted; the row is kept, emptied, so ids stay unique */
+  deleted_at?: Utc;
+  /** the language tag (like `fr-CA`) the user's emails are written in, see [`Mailer::render`](`crate::Mailer::render`) */
+  locale?: string;
+  created_at: Utc;
+  updated_at: Utc;
 }
 
 interface UserChangeset {
-    email: string
-    hash_password: string
-    activated: boolean
+  /**
+   * users change it through [`request_email_change`](`crate::auth::controller::request_email_change`),
+   * which verifies the new address first
+   */
+  email: string;
+  hash_password: string;
+  activated: boolean;
+}
+
+/**
+ * Rust struct modeling an entry in the `user_api_keys` table
+ * 
+ * a long-lived key a user created for machine clients, accepted in place of an access token
+ */
+interface UserApiKey {
+  id: ID;
+  user_id: ID;
+  name: string;
+  /** the first characters of the key, so users can recognize it */
+  key_prefix: string;
+  /** see [`UserApiKey::hash_key`], the key itself is only shown once */
+  key_hash: string;
+  /**
+   * newline separated permissions the key is limited to,
+   * `None` if it has all of the user's permissions and roles
+   */
+  permissions?: string;
+  last_used_at?: Utc;
+  expires_at?: Utc;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `user_api_keys` table */
+interface UserApiKeyChangeset {
+  user_id: ID;
+  name: string;
+  key_prefix: string;
+  key_hash: string;
+  permissions?: string;
+  last_used_at?: Utc;
+  expires_at?: Utc;
+}
+
+/** Rust struct modeling an entry in the `user_lockouts` table */
+interface UserLockout {
+  user_id: ID;
+  /** number of consecutive lockouts, used to grow the lockout duration */
+  lockout_count: number;
+  locked_until: Utc;
+  created_at: Utc;
+  updated_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `user_lockouts` table */
+interface UserLockoutChangeset {
+  user_id: ID;
+  lockout_count: number;
+  locked_until: Utc;
+}
+
+/**
+ * Rust struct modeling an entry in the `user_login_links` table
+ * 
+ * every login link which was emailed and not used yet, so each one can only be used once
+ */
+interface UserLoginLink {
+  /** the `jti` claim of the login link token */
+  token_id: string;
+  user_id: ID;
+  expires_at: Utc;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `user_login_links` table */
+interface UserLoginLinkChangeset {
+  token_id: string;
+  user_id: ID;
+  expires_at: Utc;
+}
+
+/**
+ * Rust struct modeling an entry in the `user_passkeys` table
+ * 
+ * a passkey (WebAuthn credential) the user registered, see [`crate::auth::passkey`]
+ */
+interface UserPasskey {
+  id: ID;
+  user_id: ID;
+  /** base64url encoded credential id */
+  credential_id: string;
+  /** base64url encoded COSE public key */
+  public_key: string;
+  sign_count: number;
+  /** a name the user gave the passkey, so they can tell them apart */
+  name?: string;
+  last_used_at?: Utc;
+  created_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `user_passkeys` table */
+interface UserPasskeyChangeset {
+  user_id: ID;
+  credential_id: string;
+  public_key: string;
+  sign_count: number;
+  name?: string;
+  last_used_at?: Utc;
 }
 
 interface UserSession {
-    id: number
-    user_id: number
-    refresh_token: string
-    device: string | undefined
-    created_atDate
-    updated_atDate
+  id: ID;
+  user_id: ID;
+  refresh_token: string;
+  device?: string;
+  /** shared by all sessions created by refreshing the same login */
+  family: string;
+  /**
+   * set once the refresh token has been exchanged for a new one,
+   * presenting it again revokes the whole family
+   */
+  rotated: boolean;
+  /** the client the session was started or last refreshed from */
+  ip_address?: string;
+  /**
+   * the `User-Agent` header the session was started or last refreshed with,
+   * see [`user_agent::device_name`](`super::user_agent::device_name`)
+   */
+  user_agent?: string;
+  /**
+   * set when the session belongs to an [`OAuthClient`](`super::OAuthClient`) (and not to the
+   * app's own frontend), only that client can refresh it
+   */
+  oauth_client_id?: ID;
+  /** when the user logged in, kept across refreshes */
+  logged_in_at: Utc;
+  created_at: Utc;
+  updated_at: Utc;
 }
 
 interface UserSessionChangeset {
-    user_id: number
-    refresh_token: string
-    device: string | undefined
+  user_id: ID;
+  refresh_token: string;
+  device?: string;
+  family: string;
+  rotated: boolean;
+  ip_address?: string;
+  user_agent?: string;
+  oauth_client_id?: ID;
+  logged_in_at: Utc;
+}
+
+/**
+ * Rust struct modeling an entry in the `user_totps` table,
+ * the TOTP second factor of a User
+ */
+interface UserTotp {
+  user_id: ID;
+  /** base32 encoded shared secret */
+  secret: string;
+  /** false until the user proves their authenticator app works by submitting a code */
+  confirmed: boolean;
+  /** newline-separated hashes of the unused recovery codes */
+  recovery_codes: string;
+  /** the last accepted time step, used to prevent replaying a code */
+  last_used_step?: number;
+  created_at: Utc;
+  updated_at: Utc;
+}
+
+/** Rust struct modeling mutable data in an entry in the `user_totps` table */
+interface UserTotpChangeset {
+  user_id: ID;
+  secret: string;
+  confirmed: boolean;
+  recovery_codes: string;
+  last_used_step?: number;
 }
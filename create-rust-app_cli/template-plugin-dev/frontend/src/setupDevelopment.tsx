@@ -15,6 +15,16 @@ interface Migration {
   version: string
 }
 
+interface CaughtMail {
+  id: number
+  receivedAt: number
+  from: string
+  to: string
+  subject: string
+  text: string
+  html: string
+}
+
 const useWebsocketConnection = () => {
   const [features, setFeaturesList] = useState<Set<String>>(new Set())
   const [backendCompileState, setBackendCompileState] = useState(true)
@@ -29,6 +39,7 @@ const useWebsocketConnection = () => {
   const [migrationSuccess, setMigrationSuccess] = useState(true)
   const [migrationError, setMigrationError] = useState()
   const [migrations, setMigrations] = useState<Migration[]>([])
+  const [caughtMail, setCaughtMail] = useState<CaughtMail>()
 
   const wsRef = useRef<ReconnectingWebsocket>()
 
@@ -74,6 +85,8 @@ const useWebsocketConnection = () => {
           setMigrationSuccess(data.status)
           setMigrationError(data.error)
           setMigrating(false)
+        } else if (data.type === 'mailCaught') {
+          setCaughtMail(data.mail)
         }
       }
     }
@@ -127,6 +140,8 @@ const useWebsocketConnection = () => {
     migrationError,
     migrating,
     migrations,
+    caughtMail,
+    setCaughtMail,
     send
   }
 }
@@ -147,6 +162,7 @@ const DevBox = () => {
     || state.migrationSuccess === false
     || state.migrating === true
     || isWeirdMigrationState
+    || state.caughtMail !== undefined
 
   useEffect(() => {
     if (state.migrations?.find(m => m.status === 'AppliedButMissingLocally')) {
@@ -179,6 +195,20 @@ const DevBox = () => {
         window.location.reload()
       }}>refresh?</a></div>}
       
+      {/*
+        MAIL
+      */}
+      {state.caughtMail && <div>
+        📬 Sent "{state.caughtMail.subject}" to {state.caughtMail.to}. <a
+          href={`http://localhost:${import.meta.env.DEV_SERVER_PORT}/inbox/${state.caughtMail.id}`}
+          target="_blank"
+          onClick={() => state.setCaughtMail(undefined)}
+        >open?</a> <a href="#" onClick={(e) => {
+          e.preventDefault()
+          state.setCaughtMail(undefined)
+        }}>dismiss</a>
+      </div>}
+
       {/* 
         MIGRATIONS
       */}
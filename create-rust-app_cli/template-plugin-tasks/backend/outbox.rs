@@ -0,0 +1,84 @@
+///
+/// This binary inspects and requeues the emails saved to the outbox (see `MAIL_OUTBOX` in `.env`)
+///
+/// Use `cargo run --bin outbox -- list [pending|sent|failed] [page]` to list the emails, most recent first
+/// Use `cargo run --bin outbox -- requeue <id>` to send a failed email again
+/// Use `cargo run --bin outbox -- requeue-failed` to send every failed email again
+///
+
+use create_rust_app::tasks::outbox::{OutboxMail, OutboxStatus};
+use create_rust_app::Database;
+
+const PAGE_SIZE: i64 = 50;
+
+const USAGE: &str = "usage: outbox list [pending|sent|failed] [page] | requeue <id> | requeue-failed";
+
+pub fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    if let Err(error) = run(&args) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        ["list", rest @ ..] => {
+            let status = rest
+                .first()
+                .map(|status| status.parse::<OutboxStatus>())
+                .transpose()?;
+            let page = rest
+                .get(1)
+                .map(|page| page.parse::<i64>().map_err(|error| error.to_string()))
+                .transpose()?
+                .unwrap_or(0);
+
+            list(status, page)
+        }
+        ["requeue", id] => {
+            let id = id.parse::<i32>().map_err(|error| error.to_string())?;
+            let mail = OutboxMail::requeue(id).map_err(|error| format!("{error:?}"))?;
+
+            println!("Requeued email #{} to {}", mail.id, mail.to_address);
+            Ok(())
+        }
+        ["requeue-failed"] => {
+            let count = OutboxMail::requeue_all_failed().map_err(|error| format!("{error:?}"))?;
+
+            println!("Requeued {count} failed email(s)");
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn list(status: Option<OutboxStatus>, page: i64) -> Result<(), String> {
+    let mut db = Database::new()
+        .get_connection()
+        .map_err(|error| error.to_string())?;
+
+    let mails = OutboxMail::read_all(&mut db, status, page, PAGE_SIZE).map_err(|error| error.to_string())?;
+    let total = OutboxMail::count_all(&mut db, status).map_err(|error| error.to_string())?;
+
+    for mail in &mails {
+        println!(
+            "#{} [{}] {} attempt(s), created {}: {} to {}{}",
+            mail.id,
+            mail.status,
+            mail.attempts,
+            mail.created_at,
+            mail.subject,
+            mail.to_address,
+            mail.last_error
+                .as_ref()
+                .map(|error| format!(" ({error})"))
+                .unwrap_or_default()
+        );
+    }
+    println!("{} of {total} email(s), page {page}", mails.len());
+
+    Ok(())
+}